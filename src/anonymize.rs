@@ -0,0 +1,108 @@
+//! ## Rare pattern suppression
+//! K-anonymity style sanitizing of bit-packed categorical data: the bits are
+//! read as consecutive fields of a fixed width, and any field holding a
+//! value too few others share is cleared, so rare categories cannot single
+//! out the records they belong to before the data is shared.
+
+use alloc::{vec, vec::Vec};
+
+use crate::symbols::TailPolicy;
+use crate::BitVec;
+
+impl BitVec {
+    /// Counts each value of the consecutive, non-overlapping fields of
+    /// `width` bits, read MSB first, indexed by value. A trailing field
+    /// shorter than the width is not counted. Panics if the width is not 1
+    /// to 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("01 01 11 00 0").unwrap();
+    /// assert_eq!(bv.symbol_histogram(2), vec![1, 2, 0, 1]);
+    /// ```
+    pub fn symbol_histogram(&self, width: usize) -> Vec<usize> {
+        let (symbols, _) = self.to_symbols(width, TailPolicy::Keep).unwrap();
+        let mut counts = vec![0; 1 << width];
+        for symbol in symbols {
+            counts[symbol as usize] += 1;
+        }
+        counts
+    }
+
+    /// Clears every field of `window` bits, as `symbol_histogram` reads
+    /// them, whose value occurs fewer than `min_count` times, returning the
+    /// number of fields cleared. A trailing field shorter than the window is
+    /// left alone. Panics if the window is not 1 to 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bit_str("01 01 11 01 10").unwrap();
+    /// assert_eq!(bv.suppress_rare_windows(2, 2), 2);
+    /// assert_eq!(bv.to_bit_string(), "0101000100");
+    /// ```
+    pub fn suppress_rare_windows(&mut self, window: usize, min_count: usize) -> usize {
+        let (symbols, _) = self.to_symbols(window, TailPolicy::Keep).unwrap();
+        let mut counts = vec![0; 1 << window];
+        for &symbol in &symbols {
+            counts[symbol as usize] += 1;
+        }
+
+        let mut suppressed = 0;
+        for (index, &symbol) in symbols.iter().enumerate() {
+            if symbol != 0 && counts[symbol as usize] < min_count {
+                self.slice_mut(index * window..(index + 1) * window).fill(false);
+                suppressed += 1;
+            }
+        }
+        suppressed
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histograms() {
+        let bv = BitVec::from(&[0x12, 0x34, 0x12]);
+        let counts = bv.symbol_histogram(8);
+        assert_eq!(counts.len(), 256);
+        assert_eq!((counts[0x12], counts[0x34]), (2, 1));
+        assert_eq!(bv.symbol_histogram(1), vec![17, 7]);
+        assert_eq!(BitVec::new().symbol_histogram(3), vec![0; 8]);
+    }
+
+    #[test]
+    fn suppresses_rare_fields() {
+        // Fields of 3 bits: 101 twice, 011 and 110 once, then a partial 11
+        let mut bv = BitVec::from_bit_str("101 011 101 110 11").unwrap();
+        assert_eq!(bv.suppress_rare_windows(3, 2), 2);
+        assert_eq!(bv.to_bit_string(), "10100010100011");
+
+        // Already clear fields are not counted as suppressed
+        let mut zeros = BitVec::from_bit_str("0000 1111").unwrap();
+        assert_eq!(zeros.suppress_rare_windows(4, 5), 1);
+        assert_eq!(zeros.count_ones(), 0);
+        assert_eq!(zeros.suppress_rare_windows(4, 5), 0);
+
+        // Nothing is rarer than 0 or 1 occurrence
+        let mut bv = BitVec::from(&[0xAB, 0xCD]);
+        assert_eq!(bv.suppress_rare_windows(8, 1), 0);
+        assert_eq!(bv, BitVec::from(&[0xAB, 0xCD]));
+    }
+
+    #[test]
+    #[should_panic(expected = "1 to 16 bits")]
+    fn window_too_wide() {
+        BitVec::from(&[0; 4]).suppress_rare_windows(17, 2);
+    }
+}
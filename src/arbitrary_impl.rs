@@ -0,0 +1,63 @@
+//! ## Fuzzing support
+//! An `Arbitrary` implementation, behind the `arbitrary` feature, so fuzzers
+//! and property tests can build BitVecs from raw input. The first byte of
+//! input picks how many bits of the last byte to drop, so lengths that are
+//! not a whole number of bytes come up as often as those that are, along
+//! with the empty vector and vectors as long as the input allows.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::BitVec;
+
+impl<'a> Arbitrary<'a> for BitVec {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let trim = u.int_in_range(0..=7)?;
+        let bytes: Vec<u8> = Vec::arbitrary(u)?;
+        Ok(from_bytes_trimmed(bytes, trim))
+    }
+
+    fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+        let trim = u.int_in_range(0..=7)?;
+        Ok(from_bytes_trimmed(u.take_rest().to_vec(), trim))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, None)
+    }
+}
+
+/// Builds a BitVec from bytes, dropping up to 7 bits from the end
+fn from_bytes_trimmed(bytes: Vec<u8>, trim: usize) -> BitVec {
+    let mut bv = BitVec::from(bytes);
+    bv.truncate(bv.len.saturating_sub(trim));
+    bv
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_from_input() {
+        // Drop 3 bits from the two bytes which make up the rest
+        let bv = BitVec::arbitrary_take_rest(Unstructured::new(&[3, 0xFF, 0xFF])).unwrap();
+        assert_eq!(bv.len_bits(), 13);
+        assert_eq!(bv.as_bytes(), &[0xFF, 0xF8]);
+
+        assert_eq!(BitVec::arbitrary_take_rest(Unstructured::new(&[5])).unwrap().len_bits(), 0);
+        assert_eq!(BitVec::arbitrary(&mut Unstructured::new(&[])).unwrap().len_bits(), 0);
+
+        let input: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let mut u = Unstructured::new(&input);
+        while let Ok(bv) = BitVec::arbitrary(&mut u) {
+            // Padding is always clear
+            assert_eq!(bv.count_ones(), bv.iter().filter(|&bit| bit).count());
+            if u.is_empty() {
+                break;
+            }
+        }
+    }
+}
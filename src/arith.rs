@@ -0,0 +1,293 @@
+//! ## Arithmetic
+//! Addition and subtraction treating a BitVec as an unsigned big-endian
+//! integer of `len` bits, for counters and masks wider than any primitive.
+//! The padding bits at the end of the data are clear, so the bytes read as
+//! a big-endian integer are the value shifted up by the padding, and two
+//! vectors of the same length add a word at a time with no shifting.
+//!
+//! An operand of a different length is taken at its own value: a shorter
+//! one is zero-extended, and a longer one overflows if any of its extra high
+//! bits are set. Arithmetic is separate from `+`, which concatenates.
+
+use alloc::borrow::Cow;
+
+use crate::BitVec;
+
+/// Adds or subtracts big-endian bytes of the same length, a word at a time
+/// from the least significant end, returning the carry or borrow out
+fn add_bytes(dst: &mut [u8], src: &[u8], subtract: bool) -> bool {
+    let mut carry = false;
+    let mut dst_words = dst.rchunks_exact_mut(8);
+    let mut src_words = src.rchunks_exact(8);
+    for (dst_word, src_word) in dst_words.by_ref().zip(src_words.by_ref()) {
+        let a = u64::from_be_bytes(dst_word.try_into().unwrap());
+        let b = u64::from_be_bytes(src_word.try_into().unwrap());
+        let (result, carry_a, carry_b) = if subtract {
+            let (result, borrow_a) = a.overflowing_sub(b);
+            let (result, borrow_b) = result.overflowing_sub(carry as u64);
+            (result, borrow_a, borrow_b)
+        } else {
+            let (result, carry_a) = a.overflowing_add(b);
+            let (result, carry_b) = result.overflowing_add(carry as u64);
+            (result, carry_a, carry_b)
+        };
+        dst_word.copy_from_slice(&result.to_be_bytes());
+        carry = carry_a || carry_b;
+    }
+
+    // The leftover bytes at the front, fewer than a word, as one short word
+    let (dst, src) = (dst_words.into_remainder(), src_words.remainder());
+    if dst.is_empty() {
+        return carry;
+    }
+    let bits = 8 * dst.len() as u32;
+    let load = |bytes: &[u8]| bytes.iter().fold(0u64, |word, &byte| word << 8 | byte as u64);
+    let (a, b) = (load(dst), load(src) + carry as u64);
+    let result = if subtract { a.wrapping_sub(b) } else { a + b };
+    for (index, byte) in dst.iter_mut().rev().enumerate() {
+        *byte = (result >> (8 * index)) as u8;
+    }
+    if subtract { a < b } else { result >> bits != 0 }
+}
+
+impl BitVec {
+    /// Returns another vector's value as an operand of this vector's length,
+    /// and whether it had set bits that do not fit
+    fn operand<'a>(&self, other: &'a BitVec) -> (Cow<'a, BitVec>, bool) {
+        if other.len == self.len {
+            return (Cow::Borrowed(other), false);
+        }
+
+        if other.len > self.len {
+            let extra = other.len - self.len;
+            let low = other.slice(extra..).to_bitvec();
+            return (Cow::Owned(low), other.count_ones_in(..extra) > 0);
+        }
+
+        let mut operand = BitVec::with_capacity(self.len);
+        operand.resize(self.len - other.len, false);
+        operand.append_bits(other);
+        (Cow::Owned(operand), false)
+    }
+
+    /// Adds another vector's value in place, wrapping at `len` bits, and
+    /// returns true if the sum overflowed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut counter = BitVec::from_bit_str("1111 1111 1111").unwrap();
+    /// assert!(counter.overflowing_add(&BitVec::from_bit_str("10").unwrap()));
+    /// assert_eq!(counter.to_bit_string(), "000000000001");
+    /// ```
+    pub fn overflowing_add(&mut self, other: &BitVec) -> bool {
+        let (operand, high) = self.operand(other);
+        let bytes = self.len.div_ceil(8);
+        add_bytes(&mut self.data[..bytes], &operand.data[..bytes], false) || high
+    }
+
+    /// Adds another vector's value in place, wrapping at `len` bits
+    pub fn wrapping_add(&mut self, other: &BitVec) {
+        self.overflowing_add(other);
+    }
+
+    /// Returns the sum with another vector's value, or None if it overflows
+    /// `len` bits
+    pub fn checked_add(&self, other: &BitVec) -> Option<BitVec> {
+        let mut sum = self.clone();
+        (!sum.overflowing_add(other)).then_some(sum)
+    }
+
+    /// Adds another vector's value in place, stopping at all 1s if the sum
+    /// overflows
+    pub fn saturating_add(&mut self, other: &BitVec) {
+        if self.overflowing_add(other) {
+            self.set_range(.., true);
+        }
+    }
+
+    /// Subtracts another vector's value in place, wrapping at `len` bits,
+    /// and returns true if the difference underflowed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bit_str("1 0000 0000").unwrap();
+    /// assert!(!bv.overflowing_sub(&BitVec::from_bit_str("1").unwrap()));
+    /// assert_eq!(bv.to_bit_string(), "011111111");
+    /// ```
+    pub fn overflowing_sub(&mut self, other: &BitVec) -> bool {
+        let (operand, high) = self.operand(other);
+        let bytes = self.len.div_ceil(8);
+        add_bytes(&mut self.data[..bytes], &operand.data[..bytes], true) || high
+    }
+
+    /// Subtracts another vector's value in place, wrapping at `len` bits
+    pub fn wrapping_sub(&mut self, other: &BitVec) {
+        self.overflowing_sub(other);
+    }
+
+    /// Returns the difference with another vector's value, or None if it
+    /// underflows
+    pub fn checked_sub(&self, other: &BitVec) -> Option<BitVec> {
+        let mut difference = self.clone();
+        (!difference.overflowing_sub(other)).then_some(difference)
+    }
+
+    /// Subtracts another vector's value in place, stopping at 0 if the
+    /// difference underflows
+    pub fn saturating_sub(&mut self, other: &BitVec) {
+        if self.overflowing_sub(other) {
+            self.set_range(.., false);
+        }
+    }
+
+    /// Adds or subtracts 1 in the last bit, returning the carry or borrow
+    /// out of the first byte
+    fn step(&mut self, down: bool) -> bool {
+        let mut amount = 1 << ((8 - self.len % 8) % 8);
+        for byte in self.data[..self.len.div_ceil(8)].iter_mut().rev() {
+            let (result, carry) = if down { byte.overflowing_sub(amount) } else { byte.overflowing_add(amount) };
+            *byte = result;
+            if !carry {
+                return false;
+            }
+            amount = 1;
+        }
+        true
+    }
+
+    /// Adds 1 in place, wrapping to 0, and returns true if it wrapped. Since
+    /// bit 0 is the most significant, counting up from all 0s visits every
+    /// pattern of the length in lexicographic order, for exhaustive tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut counter = BitVec::from_bit_str("0111").unwrap();
+    /// assert!(!counter.increment());
+    /// assert_eq!(counter.to_bit_string(), "1000");
+    ///
+    /// let mut pattern = BitVec::repeat_bit(false, 2);
+    /// let mut patterns = vec![pattern.to_bit_string()];
+    /// while !pattern.increment() {
+    ///     patterns.push(pattern.to_bit_string());
+    /// }
+    /// assert_eq!(patterns, ["00", "01", "10", "11"]);
+    /// ```
+    pub fn increment(&mut self) -> bool {
+        self.step(false)
+    }
+
+    /// Subtracts 1 in place, wrapping to all 1s, and returns true if it
+    /// wrapped. Counting down visits the patterns in reverse lexicographic
+    /// order.
+    pub fn decrement(&mut self) -> bool {
+        self.step(true)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a vector holding the low `len` bits of a value
+    fn number(value: u128, len: usize) -> BitVec {
+        let mut bv = BitVec::new();
+        for shift in (0..len).rev() {
+            bv.push_bit(value >> shift & 1 == 1);
+        }
+        bv
+    }
+
+    #[test]
+    fn matches_u128() {
+        let values = [0, 1, 0xFF, 0x1_0000_0000_0000_0000, 0xDEAD_BEEF_0123_4567_89AB, u128::MAX >> 30];
+        // Lengths with and without padding, and with and without a short
+        // leading word
+        for len in [1, 7, 8, 13, 64, 65, 98] {
+            let modulus = 1u128 << len;
+            for &a in &values {
+                for &b in &values {
+                    let (a, b) = (a % modulus, b % modulus);
+                    let mut sum = number(a, len);
+                    assert_eq!(sum.overflowing_add(&number(b, len)), a + b >= modulus);
+                    assert_eq!(sum, number((a + b) % modulus, len));
+
+                    let mut difference = number(a, len);
+                    assert_eq!(difference.overflowing_sub(&number(b, len)), a < b);
+                    assert_eq!(difference, number(a.wrapping_sub(b) % modulus, len));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_lengths() {
+        let mut bv = number(0x0FFF, 16);
+        assert!(!bv.overflowing_add(&number(1, 1)));
+        assert_eq!(bv, number(0x1000, 16));
+
+        // A longer operand with only clear extra bits fits
+        assert_eq!(bv.checked_add(&number(5, 40)), Some(number(0x1005, 16)));
+        assert_eq!(bv.checked_add(&number(1 << 16, 40)), None);
+        assert_eq!(bv.checked_sub(&number(0x1001, 16)), None);
+        assert_eq!(bv.checked_sub(&number(0x0001, 3)), Some(number(0x0FFF, 16)));
+
+        bv.saturating_add(&number(0xF000, 16));
+        assert_eq!(bv, number(0xFFFF, 16));
+        bv.saturating_sub(&number(0x1_0000, 17));
+        assert_eq!(bv, number(0, 16));
+    }
+
+    #[test]
+    fn increment_and_decrement() {
+        let mut counter = number(0, 10);
+        for expected in 1..1024 {
+            assert!(!counter.increment());
+            assert_eq!(counter, number(expected, 10));
+        }
+        assert!(counter.increment());
+        assert_eq!(counter, number(0, 10));
+        assert!(counter.decrement());
+        assert_eq!(counter, number(1023, 10));
+        assert!(!counter.decrement());
+        assert_eq!(counter, number(1022, 10));
+
+        // A 0-bit counter always wraps
+        assert!(BitVec::new().increment());
+    }
+
+    #[test]
+    fn lexicographic_patterns() {
+        // Every pattern of 9 bits, each after the last as strings
+        let mut pattern = BitVec::repeat_bit(false, 9);
+        let mut previous = pattern.to_bit_string();
+        let mut count = 1;
+        while !pattern.increment() {
+            let next = pattern.to_bit_string();
+            assert!(next > previous);
+            assert_eq!(pattern.len_bits(), 9);
+            previous = next;
+            count += 1;
+        }
+        assert_eq!(count, 512);
+        assert_eq!(pattern.count_ones(), 0);
+
+        // And back down
+        assert!(pattern.decrement());
+        while !pattern.decrement() {
+            count -= 1;
+        }
+        assert_eq!((count, pattern.count_ones()), (1, 9));
+    }
+}
@@ -0,0 +1,279 @@
+//! ## Bit arrays
+//! A fixed-size companion to BitVec for flag sets of known size, held in an
+//! array of `N` bytes, so `N * 8` bits, with no heap allocation. It has the
+//! same bit access and logical operations as BitVec, packed MSB first in the
+//! same way, and converts to and from a BitVec of the same length.
+
+use core::ops;
+
+use crate::{popcount, BitVec, BitVecError};
+
+/// A fixed-size array of `N * 8` bits
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitArray, BitVec};
+///
+/// // 256 flags
+/// let mut flags = BitArray::<32>::new();
+/// flags.set_bit(200, true);
+/// assert!(flags.get_bit(200));
+/// assert_eq!(flags.count_ones(), 1);
+///
+/// let bv = BitVec::from(flags);
+/// assert_eq!(BitArray::<32>::try_from(&bv), Ok(flags));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitArray<const N: usize> {
+    data: [u8; N],
+}
+
+impl<const N: usize> BitArray<N> {
+    /// Constructs a new BitArray with every bit clear
+    pub const fn new() -> Self {
+        Self { data: [0; N] }
+    }
+
+    /// Constructs a BitArray from its packed bytes
+    pub const fn from_bytes(data: [u8; N]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the packed bytes
+    pub const fn as_bytes(&self) -> &[u8; N] {
+        &self.data
+    }
+
+    /// Get the length of the array in bits
+    pub const fn len(&self) -> usize {
+        N * 8
+    }
+
+    /// Checks if the array has no bits
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// beyond the end of the array.
+    pub fn get_bit(&self, index: usize) -> bool {
+        if index >= N * 8 {
+            panic!("BitVec: index out of bounds")
+        }
+        self.data[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+
+    /// Sets the bit at the desired index. Panics if the index is beyond the
+    /// end of the array.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= N * 8 {
+            panic!("BitVec: index out of bounds")
+        }
+        if value {
+            self.data[index / 8] |= 0x80 >> (index % 8);
+        } else {
+            self.data[index / 8] &= !(0x80 >> (index % 8));
+        }
+    }
+
+    /// Sets every bit to the same value
+    pub fn fill(&mut self, value: bool) {
+        self.data = [if value { 0xFF } else { 0 }; N];
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        popcount(&self.data)
+    }
+
+    /// Returns the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        N * 8 - self.count_ones()
+    }
+
+    /// Checks if any bit is set
+    pub fn any(&self) -> bool {
+        self.data.iter().any(|&byte| byte != 0)
+    }
+
+    /// Checks if every bit is set
+    pub fn all(&self) -> bool {
+        self.data.iter().all(|&byte| byte == 0xFF)
+    }
+
+    /// Combines two arrays byte by byte
+    fn combine(&self, other: &Self, op: impl Fn(u8, u8) -> u8) -> Self {
+        Self { data: core::array::from_fn(|index| op(self.data[index], other.data[index])) }
+    }
+
+    /// Deprecated as the name is misspelt
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the complement function instead.")]
+    pub fn compliment(&self) -> Self {
+        self.complement()
+    }
+
+    /// Deprecated as the name is abbreviated
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the intersection function instead.")]
+    pub fn intersec(&self, other: &Self) -> Self {
+        self.intersection(other)
+    }
+
+    /// Deprecated as the name is abbreviated
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the symmetric_difference function instead.")]
+    pub fn symm_diff(&self, other: &Self) -> Self {
+        self.symmetric_difference(other)
+    }
+
+    /// Returns the inversion of the array (aka NOT)
+    pub fn complement(&self) -> Self {
+        Self { data: self.data.map(|byte| !byte) }
+    }
+
+    /// Returns the similarities between two arrays (aka AND)
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Returns the symmetric difference between two arrays (aka XOR)
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Returns a union of two arrays (aka OR)
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a | b)
+    }
+}
+
+impl<const N: usize> Default for BitArray<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> From<BitArray<N>> for BitVec {
+    fn from(array: BitArray<N>) -> Self {
+        BitVec::from(&array.data)
+    }
+}
+
+/// Converts a BitVec of exactly `N * 8` bits
+impl<const N: usize> TryFrom<&BitVec> for BitArray<N> {
+    type Error = BitVecError;
+
+    fn try_from(bv: &BitVec) -> Result<Self, Self::Error> {
+        if bv.len != N * 8 {
+            return Err(BitVecError::LengthMismatch { expected: N * 8, found: bv.len });
+        }
+        Ok(Self { data: core::array::from_fn(|index| bv.data[index]) })
+    }
+}
+
+// ############################################################################
+// Custom ops
+// ############################################################################
+
+impl<const N: usize> ops::BitAnd for BitArray<N> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs)
+    }
+}
+
+impl<const N: usize> ops::BitAndAssign for BitArray<N> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = self.intersection(&rhs)
+    }
+}
+
+impl<const N: usize> ops::BitOr for BitArray<N> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(&rhs)
+    }
+}
+
+impl<const N: usize> ops::BitOrAssign for BitArray<N> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(&rhs)
+    }
+}
+
+impl<const N: usize> ops::BitXor for BitArray<N> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.symmetric_difference(&rhs)
+    }
+}
+
+impl<const N: usize> ops::BitXorAssign for BitArray<N> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        *self = self.symmetric_difference(&rhs)
+    }
+}
+
+impl<const N: usize> ops::Not for BitArray<N> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        self.complement()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_and_counts() {
+        let mut flags = BitArray::<8>::default();
+        assert_eq!(flags.len(), 64);
+        assert!(!flags.any());
+        flags.set_bit(0, true);
+        flags.set_bit(63, true);
+        assert_eq!(flags.as_bytes()[0], 0x80);
+        assert_eq!(flags.count_ones(), 2);
+        flags.set_bit(0, false);
+        assert!(!flags.get_bit(0) && flags.get_bit(63));
+
+        flags.fill(true);
+        assert!(flags.all());
+        assert_eq!(flags.count_zeros(), 0);
+    }
+
+    #[test]
+    fn logical_ops() {
+        let a = BitArray::from_bytes([0xF0, 0x0F]);
+        let b = BitArray::from_bytes([0x3C, 0x3C]);
+        assert_eq!(a & b, BitArray::from_bytes([0x30, 0x0C]));
+        assert_eq!(a | b, BitArray::from_bytes([0xFC, 0x3F]));
+        assert_eq!(a ^ b, BitArray::from_bytes([0xCC, 0x33]));
+        assert_eq!(!a, BitArray::from_bytes([0x0F, 0xF0]));
+
+        let mut c = a;
+        c ^= a;
+        assert!(!c.any());
+    }
+
+    #[test]
+    fn bitvec_conversions() {
+        let array = BitArray::from_bytes([0xA5, 0x5A]);
+        let bv = BitVec::from(array);
+        assert_eq!(bv.len_bits(), 16);
+        assert_eq!(bv.to_hex_string(), "a55a");
+        assert_eq!(BitArray::try_from(&bv), Ok(array));
+
+        let short = BitVec::from_bit_str("1010").unwrap();
+        assert_eq!(
+            BitArray::<2>::try_from(&short),
+            Err(BitVecError::LengthMismatch { expected: 16, found: 4 })
+        );
+    }
+}
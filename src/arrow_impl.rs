@@ -0,0 +1,89 @@
+//! ## Arrow interop
+//! Conversions to and from the `BooleanBuffer` and `NullBuffer` validity
+//! bitmaps of arrow-rs, behind the `arrow` feature. Arrow packs bits LSB
+//! first within each byte, so every byte has its bits reversed on the way
+//! through. A set bit in a `NullBuffer` means the slot is valid.
+
+use arrow_buffer::{BooleanBuffer, Buffer, NullBuffer};
+
+use crate::{BitOrder, BitVec, GrowthPolicy};
+
+impl From<&BooleanBuffer> for BitVec {
+    fn from(buffer: &BooleanBuffer) -> Self {
+        // Buffers at a byte offset are sliced without copying
+        let sliced = buffer.sliced();
+        let len = buffer.len();
+        let data = sliced[..len.div_ceil(8)].iter().map(|byte| byte.reverse_bits()).collect();
+
+        let mut bv = BitVec { data, len, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        bv.clear_padding();
+        bv
+    }
+}
+
+impl From<&BitVec> for BooleanBuffer {
+    fn from(bv: &BitVec) -> Self {
+        let mut data: Vec<u8> = bv.data[..bv.len.div_ceil(8)].to_vec();
+        let tail = bv.len % 8;
+        if tail > 0 {
+            data[bv.len / 8] &= BitVec::mask_msb(tail);
+        }
+        for byte in data.iter_mut() {
+            *byte = byte.reverse_bits();
+        }
+
+        BooleanBuffer::new(Buffer::from_vec(data), 0, bv.len)
+    }
+}
+
+impl From<&NullBuffer> for BitVec {
+    fn from(nulls: &NullBuffer) -> Self {
+        BitVec::from(nulls.inner())
+    }
+}
+
+impl From<&BitVec> for NullBuffer {
+    fn from(bv: &BitVec) -> Self {
+        NullBuffer::new(BooleanBuffer::from(bv))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boolean_buffer_round_trip() {
+        // 0b01101011 0b101xxxxx
+        let bv = BitVec { data: vec![0x6B, 0xBF], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let buffer = BooleanBuffer::from(&bv);
+        assert_eq!(buffer.len(), 11);
+        assert_eq!(buffer.values(), &[0xD6, 0x05]);
+        let expected: Vec<bool> = (0..11).map(|i| bv.get_bit(i)).collect();
+        assert_eq!(buffer.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(BitVec::from(&buffer), bv);
+    }
+
+    #[test]
+    fn unaligned_arrow_slices() {
+        let bools: Vec<bool> = (0..30).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let buffer = BooleanBuffer::from(bools.clone());
+        for (offset, len) in [(0, 30), (8, 20), (3, 11), (5, 25)] {
+            let bv = BitVec::from(&buffer.slice(offset, len));
+            assert_eq!(bv.len_bits(), len);
+            let bits: Vec<bool> = (0..len).map(|i| bv.get_bit(i)).collect();
+            assert_eq!(bits, bools[offset..offset + len]);
+        }
+    }
+
+    #[test]
+    fn null_buffers() {
+        let nulls = NullBuffer::from(vec![true, false, true, true, false]);
+        let bv = BitVec::from(&nulls);
+        assert_eq!(bv.data, vec![0b1011_0000]);
+        assert_eq!(NullBuffer::from(&bv), nulls);
+    }
+}
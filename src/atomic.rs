@@ -0,0 +1,186 @@
+//! ## Atomic bit vectors
+//! A fixed-length bit vector that many threads can set and clear at once
+//! without a lock, such as a shared occupancy bitmap. The bits are packed MSB
+//! first into `AtomicU64` words, and every change is a single atomic
+//! read-modify-write of one word.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::BitVec;
+
+/// Bits in each word
+const WORD_BITS: usize = 64;
+
+/// A fixed-length bit vector of atomic words, shared by reference
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::AtomicBitVec;
+///
+/// let flags = AtomicBitVec::new(100);
+/// std::thread::scope(|scope| {
+///     for start in 0..4 {
+///         let flags = &flags;
+///         scope.spawn(move || (start..100).step_by(4).for_each(|index| flags.set_bit(index)));
+///     }
+/// });
+/// assert_eq!(flags.to_bitvec().count_ones(), 100);
+/// ```
+pub struct AtomicBitVec {
+    words: Vec<AtomicU64>,  // packed bits, MSB first
+    len:   usize,           // length in bits
+}
+
+impl AtomicBitVec {
+    /// Constructs a new AtomicBitVec of `len` clear bits
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: (0..len.div_ceil(WORD_BITS)).map(|_| AtomicU64::new(0)).collect(),
+            len,
+        }
+    }
+
+    /// Get the number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the vector has no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the word holding a bit and the mask for it within the word
+    fn locate(&self, index: usize) -> (&AtomicU64, u64) {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+        (&self.words[index / WORD_BITS], 1 << (WORD_BITS - 1 - index % WORD_BITS))
+    }
+
+    /// Mask of the bits within the vector for a word
+    fn word_mask(&self, word_index: usize) -> u64 {
+        let tail = self.len % WORD_BITS;
+        if word_index == self.words.len() - 1 && tail > 0 {
+            !(u64::MAX >> tail)
+        } else {
+            u64::MAX
+        }
+    }
+
+    /// Returns the value of a bit. Panics if the index is beyond the end.
+    pub fn get_bit(&self, index: usize) -> bool {
+        let (word, mask) = self.locate(index);
+        word.load(Ordering::Acquire) & mask != 0
+    }
+
+    /// Sets a bit. Panics if the index is beyond the end.
+    pub fn set_bit(&self, index: usize) {
+        let (word, mask) = self.locate(index);
+        word.fetch_or(mask, Ordering::AcqRel);
+    }
+
+    /// Clears a bit. Panics if the index is beyond the end.
+    pub fn clear_bit(&self, index: usize) {
+        let (word, mask) = self.locate(index);
+        word.fetch_and(!mask, Ordering::AcqRel);
+    }
+
+    /// Sets a bit, returning whether it was already set, so only one of
+    /// several threads setting the same bit sees false. Panics if the index
+    /// is beyond the end.
+    pub fn test_and_set(&self, index: usize) -> bool {
+        let (word, mask) = self.locate(index);
+        word.fetch_or(mask, Ordering::AcqRel) & mask != 0
+    }
+
+    /// ORs a mask into a whole word of 64 bits, the first bit of the word in
+    /// the MSB, returning the previous word. Bits of the mask past the end
+    /// of the vector are ignored. Panics if the word is beyond the end.
+    pub fn fetch_or_word(&self, word_index: usize, mask: u64) -> u64 {
+        if word_index >= self.words.len() {
+            panic!("BitVec: index out of bounds")
+        }
+        self.words[word_index].fetch_or(mask & self.word_mask(word_index), Ordering::AcqRel)
+    }
+
+    /// Copies the bits into a BitVec. Each word is read atomically, but
+    /// changes made during the copy may or may not be seen.
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut bytes = Vec::with_capacity(self.words.len() * 8);
+        for word in &self.words {
+            bytes.extend_from_slice(&word.load(Ordering::Acquire).to_be_bytes());
+        }
+        let mut bv = BitVec::from(&bytes[..]);
+        bv.truncate(self.len);
+        bv
+    }
+}
+
+impl From<&BitVec> for AtomicBitVec {
+    fn from(bv: &BitVec) -> Self {
+        let atomic = AtomicBitVec::new(bv.len_bits());
+        for (word_index, chunk) in bv.as_bytes().chunks(8).enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            atomic.fetch_or_word(word_index, u64::from_be_bytes(bytes));
+        }
+        atomic
+    }
+}
+
+impl fmt::Debug for AtomicBitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicBitVec").field("len", &self.len).field("bits", &self.to_bitvec()).finish()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_thread_bits() {
+        let flags = AtomicBitVec::new(70);
+        assert_eq!(flags.len(), 70);
+        flags.set_bit(0);
+        flags.set_bit(69);
+        assert!(!flags.test_and_set(64));
+        assert!(flags.test_and_set(64));
+        flags.clear_bit(0);
+        assert!(!flags.get_bit(0) && flags.get_bit(69));
+
+        // Bits past the end are never set
+        assert_eq!(flags.fetch_or_word(1, u64::MAX), 0x8400_0000_0000_0000);
+        assert_eq!(flags.to_bitvec().count_ones(), 6);
+
+        let bv = BitVec::from(&[0xA5, 0x5A, 0xFF]).slice(..20).to_bitvec();
+        assert_eq!(AtomicBitVec::from(&bv).to_bitvec(), bv);
+    }
+
+    #[test]
+    fn concurrent_setters() {
+        let flags = AtomicBitVec::new(1000);
+        let winners: usize = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| (0..1000).filter(|&index| !flags.test_and_set(index)).count()))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+        });
+        // Each bit was first set by exactly one thread
+        assert_eq!(winners, 1000);
+        assert_eq!(flags.to_bitvec().count_ones(), 1000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_past_end() {
+        AtomicBitVec::new(10).set_bit(10);
+    }
+}
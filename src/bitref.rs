@@ -0,0 +1,161 @@
+//! ## Bit and byte references
+//! Indexing a BitVec gives a bit, so assigning through an index needs a
+//! proxy, as a `&mut bool` cannot point into packed bytes. The packed bytes
+//! themselves are reached through an explicit byte API, which keeps the
+//! padding bits of the last byte clear.
+
+use core::ops::{Deref, DerefMut};
+
+use crate::BitVec;
+
+/// A mutable reference to one bit of a BitVec
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let mut bv = BitVec::from_bit_str("0000").unwrap();
+/// bv.at_mut(1).set(true);
+/// bv.at_mut(2).flip();
+/// assert!(bv[1] && bv[2] && !bv[3]);
+/// ```
+#[derive(Debug)]
+pub struct BitRef<'a> {
+    bv:    &'a mut BitVec,
+    index: usize,
+}
+
+impl BitRef<'_> {
+    /// Get the value of the bit
+    pub fn get(&self) -> bool {
+        self.bv.get_bit(self.index)
+    }
+
+    /// Sets the value of the bit
+    pub fn set(&mut self, value: bool) {
+        self.bv.set_bit(self.index, value);
+    }
+
+    /// Inverts the bit, returning its new value
+    pub fn flip(&mut self) -> bool {
+        let value = !self.get();
+        self.set(value);
+        value
+    }
+
+    /// Sets the value of the bit, returning the old value
+    pub fn replace(&mut self, value: bool) -> bool {
+        let old = self.get();
+        self.set(value);
+        old
+    }
+}
+
+/// A mutable reference to one packed byte of a BitVec, which clears any
+/// padding bits set through it when dropped
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let mut bv = BitVec::from_bit_str("1010 1010 1").unwrap();
+/// *bv.byte_mut(1) = 0xFF;
+/// assert_eq!(bv.byte(1), 0x80);
+/// assert_eq!(bv.count_ones(), 5);
+/// ```
+#[derive(Debug)]
+pub struct ByteMut<'a> {
+    bv:    &'a mut BitVec,
+    index: usize,
+}
+
+impl Deref for ByteMut<'_> {
+    type Target = u8;
+
+    fn deref(&self) -> &u8 {
+        &self.bv.data[self.index]
+    }
+}
+
+impl DerefMut for ByteMut<'_> {
+    fn deref_mut(&mut self) -> &mut u8 {
+        &mut self.bv.data[self.index]
+    }
+}
+
+impl Drop for ByteMut<'_> {
+    fn drop(&mut self) {
+        self.bv.clear_padding();
+    }
+}
+
+impl BitVec {
+    /// Returns a mutable reference to a bit. Panics if the index is beyond
+    /// the end of the vector.
+    pub fn at_mut(&mut self, index: usize) -> BitRef<'_> {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+        BitRef { bv: self, index }
+    }
+
+    /// Returns a packed byte, MSB first, with the padding bits of the last
+    /// byte clear. Panics if the byte is beyond the end of the vector.
+    pub fn byte(&self, index: usize) -> u8 {
+        if index >= self.data.len() {
+            panic!("BitVec: byte index out of bounds")
+        }
+        self.data[index]
+    }
+
+    /// Returns a mutable reference to a packed byte, MSB first. Bits set in
+    /// the padding of the last byte are cleared again. Panics if the byte is
+    /// beyond the end of the vector.
+    pub fn byte_mut(&mut self, index: usize) -> ByteMut<'_> {
+        if index >= self.data.len() {
+            panic!("BitVec: byte index out of bounds")
+        }
+        ByteMut { bv: self, index }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_references() {
+        let mut bv = BitVec::from(&[0x00, 0xFF]).slice(..12).to_bitvec();
+        assert!(!bv[0] && bv[8] && bv[11]);
+        let mut bit = bv.at_mut(11);
+        assert!(bit.get());
+        assert!(!bit.flip());
+        assert!(!bit.replace(true));
+        assert!(bit.get());
+        bv.at_mut(0).set(true);
+        assert_eq!(bv.to_bit_string(), "100000001111");
+        assert_eq!(bv.len_bits(), 12);
+    }
+
+    #[test]
+    fn byte_access_keeps_padding_clear() {
+        let mut bv = BitVec::from(&[0x12, 0xFF]).slice(..13).to_bitvec();
+        assert_eq!((bv.byte(0), bv.byte(1)), (0x12, 0xF8));
+        *bv.byte_mut(0) = 0xAB;
+        *bv.byte_mut(1) |= 0x07;
+        assert_eq!(bv.as_bytes(), &[0xAB, 0xF8]);
+        assert_eq!(bv.len_bits(), 13);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn bit_reference_past_the_end() {
+        let mut bv = BitVec::from_bit_str("101").unwrap();
+        bv.at_mut(3);
+    }
+}
@@ -0,0 +1,141 @@
+//! ## Java and .NET bitsets
+//! The byte forms that JVM and .NET code persist their bitsets in. Both
+//! number bits from the least significant bit of the first byte, so every
+//! byte has its bits reversed on the way through.
+//!
+//! - Java `BitSet.toByteArray` gives little-endian bytes with trailing zero
+//!   bytes dropped, and `BitSet.valueOf` reads them back. A BitSet has no
+//!   length of its own beyond its highest set bit. `toLongArray` gives the
+//!   same bits as little-endian u64 words.
+//! - .NET `BitArray.CopyTo` into a `byte[]` gives the bytes in the same
+//!   order, rounded up to a whole byte, and into an `int[]` the same bits as
+//!   little-endian i32 words. The length is kept separately, as
+//!   `BitArray.Length`.
+
+use alloc::vec::Vec;
+
+use crate::{BitOrder, BitVec, BitVecError};
+
+impl BitVec {
+    /// Builds a vector of `len` bits from bytes numbered LSB first
+    fn from_lsb_bytes(bytes: &[u8], len: usize) -> BitVec {
+        let mut bv = BitVec::from_with_order(&bytes[..len.div_ceil(8)], BitOrder::Lsb0);
+        bv.truncate(len);
+        bv
+    }
+
+    /// Reads the bytes of a Java `BitSet.toByteArray`, as long as needed to
+    /// hold the highest set bit, like `BitSet.length`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // BitSet with bits 0, 3 and 9 set
+    /// let bv = BitVec::from_java_bitset_bytes(&[0x09, 0x02]);
+    /// assert_eq!(bv.to_bit_string(), "1001000001");
+    /// assert_eq!(bv.to_java_bitset_bytes(), vec![0x09, 0x02]);
+    /// ```
+    pub fn from_java_bitset_bytes(bytes: &[u8]) -> BitVec {
+        let len = match bytes.iter().rposition(|&byte| byte != 0) {
+            Some(last) => last * 8 + 8 - bytes[last].leading_zeros() as usize,
+            None => 0,
+        };
+        BitVec::from_lsb_bytes(bytes, len)
+    }
+
+    /// Reads the words of a Java `BitSet.toLongArray`, as long as needed to
+    /// hold the highest set bit
+    pub fn from_java_bitset_words(words: &[u64]) -> BitVec {
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        BitVec::from_java_bitset_bytes(&bytes)
+    }
+
+    /// Returns the bytes `BitSet.toByteArray` would give for the set bits,
+    /// with trailing zero bytes dropped
+    pub fn to_java_bitset_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.data.iter().map(|&byte| BitOrder::Lsb0.convert(byte)).collect();
+        let used = bytes.iter().rposition(|&byte| byte != 0).map_or(0, |last| last + 1);
+        bytes.truncate(used);
+        bytes
+    }
+
+    /// Reads `len` bits from the bytes of a .NET `BitArray.CopyTo` into a
+    /// `byte[]`, or from its `int[]` words as little-endian bytes. Returns an
+    /// error if there are too few bytes; any after the bits are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // new BitArray(new bool[] { true, false, true, true, false })
+    /// let bv = BitVec::from_dotnet_bitarray(&[0x0D], 5).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "10110");
+    /// assert_eq!(bv.to_dotnet_bitarray(), vec![0x0D]);
+    /// ```
+    pub fn from_dotnet_bitarray(bytes: &[u8], len: usize) -> Result<BitVec, BitVecError> {
+        if bytes.len() < len.div_ceil(8) {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: bytes.len() * 8 });
+        }
+        Ok(BitVec::from_lsb_bytes(bytes, len))
+    }
+
+    /// Returns the bytes `BitArray.CopyTo` would give into a `byte[]`,
+    /// rounded up to a whole byte
+    pub fn to_dotnet_bitarray(&self) -> Vec<u8> {
+        self.data.iter().map(|&byte| BitOrder::Lsb0.convert(byte)).collect()
+    }
+
+    /// Returns the words `BitArray.CopyTo` would give into an `int[]`,
+    /// rounded up to a whole word
+    pub fn to_dotnet_bitarray_ints(&self) -> Vec<i32> {
+        let mut bytes = self.to_dotnet_bitarray();
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+        bytes.chunks_exact(4).map(|word| i32::from_le_bytes([word[0], word[1], word[2], word[3]])).collect()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_bitsets() {
+        // BitSet.valueOf(new long[] { 1L << 63 | 1, 0, 0x10 })
+        let bv = BitVec::from_java_bitset_words(&[1 << 63 | 1, 0, 0x10]);
+        assert_eq!(bv.len_bits(), 133);
+        assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![0, 63, 132]);
+        let bytes = bv.to_java_bitset_bytes();
+        assert_eq!(bytes.len(), 17);
+        assert_eq!((bytes[0], bytes[7], bytes[16]), (0x01, 0x80, 0x10));
+        assert_eq!(BitVec::from_java_bitset_bytes(&bytes), bv);
+
+        // Trailing clear bits are not part of a BitSet
+        let mut padded = BitVec::from_bit_str("01").unwrap();
+        padded.resize(40, false);
+        assert_eq!(padded.to_java_bitset_bytes(), vec![0x02]);
+        assert_eq!(BitVec::from_java_bitset_bytes(&[0, 0]).len_bits(), 0);
+        assert!(BitVec::new().to_java_bitset_bytes().is_empty());
+    }
+
+    #[test]
+    fn dotnet_bitarrays() {
+        let bv = BitVec::from_bit_str("1100 0000 0000 1").unwrap();
+        assert_eq!(bv.to_dotnet_bitarray(), vec![0x03, 0x10]);
+        assert_eq!(bv.to_dotnet_bitarray_ints(), vec![0x1003]);
+        assert_eq!(BitVec::from_dotnet_bitarray(&[0x03, 0x10, 0, 0], 13), Ok(bv));
+
+        // Bits past the length are dropped
+        assert_eq!(BitVec::from_dotnet_bitarray(&[0xFF], 3).unwrap().to_bit_string(), "111");
+        assert_eq!(
+            BitVec::from_dotnet_bitarray(&[0xFF], 9),
+            Err(BitVecError::NotEnoughBits { requested: 9, available: 8 })
+        );
+        assert_eq!(BitVec::from(&[0x80; 5]).to_dotnet_bitarray_ints(), vec![0x0101_0101, 0x01]);
+    }
+}
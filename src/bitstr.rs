@@ -0,0 +1,386 @@
+//! ## Borrowed bits
+//! `BitStr` is an unsized run of bits, only ever seen behind a reference,
+//! as `str` is only seen as `&str`. A BitSlice is built on demand and passed
+//! by value, so nothing can hand out a reference to one, but a `&BitStr` can
+//! be handed out, which is what lets `bv[3..10]` work through `Index`.
+//!
+//! BitVec and BitSlice both dereference to a BitStr, so its read-only
+//! methods, such as `iter`, are written once and work on vectors and views
+//! alike. A method of BitVec or BitSlice with the same name is found first:
+//! `len` on a BitVec still counts bytes, while `len` on a BitStr counts bits.
+//!
+//! A `&BitStr` is a fat pointer. Its address is that of the first byte
+//! holding its bits, and its length field packs the bit length and the
+//! offset of the first bit within that byte, as `len << 3 | offset`. It is
+//! a slice of zero-sized `()`s, so it never claims the bytes for itself;
+//! they are reached again through the provenance exposed when it was made.
+
+use alloc::borrow::ToOwned;
+use core::{borrow::Borrow, cmp, fmt, hash, ops, ptr, slice};
+
+use crate::slice::load_byte;
+use crate::{BitSlice, BitVec, Bits};
+
+/// Bytes hashed at a time
+const HASH_CHUNK: usize = 32;
+
+/// An unsized view of bits, used as `&BitStr`, and made by indexing a
+/// BitVec with a range or by `BitSlice::as_bitstr`
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// // 0b01101011 0b10100000
+/// let bv = BitVec::from(&[0x6B, 0xA0]);
+/// let bits = &bv[3..11];
+/// assert_eq!(bits.len(), 8);
+/// assert_eq!(bits.count_ones(), 5);
+/// assert_eq!(bits[1..4].to_bitvec().to_bit_string(), "101");
+/// ```
+#[repr(transparent)]
+pub struct BitStr {
+    bits: [()],     // address of the first byte, and len << 3 | offset
+}
+
+impl BitStr {
+    /// Views the `len` bits starting `offset` bits into some bytes. Panics
+    /// if the bytes hold fewer bits, or there are too many to encode.
+    pub(crate) fn from_parts(data: &[u8], offset: usize, len: usize) -> &BitStr {
+        if offset >= 8 || len > usize::MAX >> 3 {
+            panic!("BitVec: cannot borrow {} bits at offset {} as a BitStr", len, offset)
+        }
+        let data = &data[..(offset + len).div_ceil(8)];
+        data.as_ptr().expose_provenance();
+
+        let raw = ptr::slice_from_raw_parts(data.as_ptr().cast::<()>(), len << 3 | offset) as *const BitStr;
+        // SAFETY: BitStr is a transparent wrapper around [()], which takes
+        // no memory at any length, and the pointer is non-null and aligned
+        unsafe { &*raw }
+    }
+
+    /// Returns the bytes holding the bits, the offset of the first bit, and
+    /// the number of bits
+    fn parts(&self) -> (&[u8], usize, usize) {
+        let (len, offset) = (self.bits.len() >> 3, self.bits.len() & 7);
+        let data = ptr::with_exposed_provenance::<u8>(self.bits.as_ptr().addr());
+        // SAFETY: the address, byte count, and exposed provenance are those
+        // of the bytes the BitStr was made from, which stay borrowed for as
+        // long as it does
+        let data = unsafe { slice::from_raw_parts(data, (offset + len).div_ceil(8)) };
+        (data, offset, len)
+    }
+
+    /// Returns the bits as a BitSlice
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        let (data, offset, len) = self.parts();
+        BitSlice::from_parts(data, offset, len)
+    }
+
+    /// Get the number of bits
+    pub fn len(&self) -> usize {
+        self.bits.len() >> 3
+    }
+
+    /// Checks if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// beyond the end.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.as_bitslice().get_bit(index)
+    }
+
+    /// Returns the bit value at the desired index, or None if the index is
+    /// beyond the end
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.as_bitslice().get(index)
+    }
+
+    /// Returns an iterator over the bits
+    pub fn iter(&self) -> Bits<'_> {
+        self.as_bitslice().iter()
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.as_bitslice().count_ones()
+    }
+
+    /// Returns the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        self.as_bitslice().count_zeros()
+    }
+
+    /// Returns the bits as an unsigned integer, the first bit being the most
+    /// significant. Panics if there are more than 64 bits.
+    pub fn to_u64(&self) -> u64 {
+        self.as_bitslice().to_u64()
+    }
+
+    /// Copies the bits into a new BitVec
+    pub fn to_bitvec(&self) -> BitVec {
+        self.as_bitslice().to_bitvec()
+    }
+}
+
+impl<'a> IntoIterator for &'a BitStr {
+    type Item = bool;
+    type IntoIter = Bits<'a>;
+
+    fn into_iter(self) -> Bits<'a> {
+        self.iter()
+    }
+}
+
+// ############################################################################
+// Standard traits
+// ############################################################################
+
+/// Equal when they hold the same bits, wherever they start
+impl PartialEq for BitStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bitslice() == other.as_bitslice()
+    }
+}
+
+impl Eq for BitStr {}
+
+/// Hashes the bits up to the length, the same way wherever they start, and
+/// the same way as a BitVec holding them
+impl hash::Hash for BitStr {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        let (data, offset, len) = self.parts();
+        len.hash(state);
+        let full_bytes = len / 8;
+        if offset == 0 {
+            data[..full_bytes].chunks(HASH_CHUNK).for_each(|chunk| state.write(chunk));
+        } else {
+            let mut buffer = [0; HASH_CHUNK];
+            for start in (0..full_bytes).step_by(HASH_CHUNK) {
+                let count = (full_bytes - start).min(HASH_CHUNK);
+                for (index, byte) in buffer[..count].iter_mut().enumerate() {
+                    *byte = load_byte(data, offset + (start + index) * 8, 8);
+                }
+                state.write(&buffer[..count]);
+            }
+        }
+        if len % 8 > 0 {
+            state.write_u8(load_byte(data, offset + full_bytes * 8, len % 8));
+        }
+    }
+}
+
+impl PartialOrd for BitStr {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Lexicographic order over the bits, with a prefix ordered before any
+/// longer run of bits that starts with it, as for BitVec
+impl Ord for BitStr {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        let ((data, offset, len), (other_data, other_offset, other_len)) = (self.parts(), other.parts());
+        let common = len.min(other_len);
+        (0..common).step_by(8)
+            .map(|index| {
+                let count = (common - index).min(8);
+                load_byte(data, offset + index, count).cmp(&load_byte(other_data, other_offset + index, count))
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(cmp::Ordering::Equal)
+            .then(len.cmp(&other_len))
+    }
+}
+
+/// The owned form of a BitStr is a BitVec, as `String` is of `str`
+impl ToOwned for BitStr {
+    type Owned = BitVec;
+
+    fn to_owned(&self) -> BitVec {
+        self.to_bitvec()
+    }
+}
+
+/// Lets a map keyed by BitVecs be searched with a `&BitStr`
+impl Borrow<BitStr> for BitVec {
+    fn borrow(&self) -> &BitStr {
+        self
+    }
+}
+
+impl AsRef<BitStr> for BitVec {
+    fn as_ref(&self) -> &BitStr {
+        self
+    }
+}
+
+impl AsRef<BitStr> for BitStr {
+    fn as_ref(&self) -> &BitStr {
+        self
+    }
+}
+
+impl PartialEq<BitStr> for BitVec {
+    fn eq(&self, other: &BitStr) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<BitVec> for BitStr {
+    fn eq(&self, other: &BitVec) -> bool {
+        *self == **other
+    }
+}
+
+impl fmt::Debug for BitStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BitStr[")?;
+        for bit in self {
+            write!(f, "{}", bit as u8)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Formats the bits as 0s and 1s
+impl fmt::Display for BitStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|bit| f.write_str(if bit { "1" } else { "0" }))
+    }
+}
+
+impl ops::Deref for BitVec {
+    type Target = BitStr;
+
+    fn deref(&self) -> &BitStr {
+        self.as_bitslice().as_bitstr()
+    }
+}
+
+impl<'a> ops::Deref for BitSlice<'a> {
+    type Target = BitStr;
+
+    fn deref(&self) -> &BitStr {
+        self.as_bitstr()
+    }
+}
+
+/// Returns the bit at index. Panics if the index is beyond the end.
+impl ops::Index<usize> for BitStr {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        if self.get_bit(index) { &true } else { &false }
+    }
+}
+
+macro_rules! impl_range_index {
+    ($($range:ty),*) => {$(
+        /// Returns the bits of a range. Panics if the range runs past the end.
+        impl ops::Index<$range> for BitVec {
+            type Output = BitStr;
+
+            fn index(&self, range: $range) -> &BitStr {
+                self.slice(range).as_bitstr()
+            }
+        }
+
+        /// Returns the bits of a range. Panics if the range runs past the end.
+        impl ops::Index<$range> for BitStr {
+            type Output = BitStr;
+
+            fn index(&self, range: $range) -> &BitStr {
+                self.as_bitslice().slice(range).as_bitstr()
+            }
+        }
+    )*};
+}
+
+impl_range_index!(
+    ops::Range<usize>, ops::RangeFrom<usize>, ops::RangeTo<usize>, ops::RangeFull,
+    ops::RangeInclusive<usize>, ops::RangeToInclusive<usize>
+);
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_indexing() {
+        // 0b01101011 0b10100000
+        let bv = BitVec::from(&[0x6B, 0xA0]);
+        assert_eq!(bv[..].to_bitvec(), bv);
+        assert_eq!(bv[3..11].to_bitvec().to_bit_string(), "01011101");
+        assert_eq!(bv[..=2].to_bitvec().to_bit_string(), "011");
+        assert_eq!(bv[..4].len(), 4);
+        assert_eq!(bv[12..].to_bitvec().to_bit_string(), "0000");
+        assert!(bv[16..].is_empty());
+
+        // Indexing a BitStr again, and the bits within it
+        let inner = &bv[3..11][2..=5];
+        assert_eq!(inner.to_string(), "0111");
+        assert!(inner[1]);
+        assert_eq!(inner.get(4), None);
+        assert_eq!(inner.to_u64(), 0b0111);
+        assert_eq!(inner.iter().filter(|&bit| bit).count(), inner.count_ones());
+    }
+
+    #[test]
+    fn equal_wherever_they_start() {
+        let bv = BitVec::from_bit_str("1011 0101 1000").unwrap();
+        assert_eq!(&bv[0..3], &bv[5..8]);
+        assert_ne!(&bv[0..3], &bv[0..4]);
+        assert_eq!(format!("{:?}", &bv[4..8]), "BitStr[0101]");
+        assert_eq!(bv.slice(2..6).as_bitstr().as_bitslice(), bv.slice(2..6));
+    }
+
+    #[test]
+    fn deref_from_vectors_and_views() {
+        let bv = BitVec::from_bit_str("1101 0").unwrap();
+        assert_eq!(bv.iter().collect::<Vec<_>>(), vec![true, true, false, true, false]);
+        // BitVec::len counts bytes, BitStr::len bits
+        assert_eq!((bv.len(), (*bv).len()), (1, 5));
+        assert!(!bv.is_empty());
+        assert_eq!(&*bv.slice(1..4), &bv[1..4]);
+        assert_eq!(bv.slice(..)[2..].to_string(), "010");
+
+        let first: fn(&BitStr) -> Option<bool> = |bits| bits.get(0);
+        assert_eq!(first(&bv), Some(true));
+        assert_eq!(first(&bv.slice(2..)), Some(false));
+    }
+
+    #[test]
+    fn borrowed_as_map_keys() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let bv = BitVec::from_bit_str("0110 1011 1010 0110 1011 1010 0110 1011 1010 0110 1011 1010 011").unwrap();
+        let mut map = HashMap::new();
+        map.insert(bv.slice(5..).to_bitvec(), 1);
+        map.insert(BitVec::from_bit_str("101").unwrap(), 2);
+        // Found through unaligned views, past a whole chunk of hashed bytes
+        assert_eq!(map.get(&bv[5..]), Some(&1));
+        assert_eq!(map.get(&bv[4..7]), Some(&2));
+        assert_eq!(map.get(&bv[5..8]), None);
+
+        let set: BTreeSet<BitVec> = [bv[..3].to_owned(), bv[1..3].to_owned(), bv[4..].to_owned()].into();
+        assert!(set.contains(&bv[1..3]));
+        assert_eq!(set.iter().next().unwrap(), &bv[..3]);
+        assert!(bv[..3] < bv[1..3] && bv[..2] < bv[..3]);
+        assert_eq!(bv.as_ref() as &BitStr, &bv[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn range_past_the_end() {
+        let bv = BitVec::from(&[0xFF]);
+        let _ = &bv[4..9];
+    }
+}
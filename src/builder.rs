@@ -0,0 +1,159 @@
+//! ## Builder
+//! A BitVec wrapped for long-running encoders that need to watch the size
+//! of their output. A callback set with `on_grow` is told the size each time
+//! the output grows into a new byte, and can stop the builder, after which
+//! every push fails with `BitVecError::Stopped` until the bits are taken.
+//! The encoder itself only pushes through `BitSink`, and sees the error.
+
+use alloc::boxed::Box;
+use core::fmt;
+
+use crate::{BitSink, BitVec, BitVecError};
+
+/// A callback told the size of the output, returning false to stop
+type GrowCallback = Box<dyn FnMut(usize) -> bool>;
+
+/// A BitVec being built, with a callback to watch its size
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitSink, BitVecBuilder, BitVecError};
+///
+/// // Stop once the output goes over 2 bytes
+/// let mut builder = BitVecBuilder::new();
+/// builder.on_grow(|bits| bits <= 16);
+/// let result = (0..10).try_for_each(|value| builder.push_bits(value, 4));
+/// assert_eq!(result, Err(BitVecError::Stopped { len: 17 }));
+/// assert_eq!(builder.estimated_bits(), 17);
+/// ```
+#[derive(Default)]
+pub struct BitVecBuilder {
+    bits:    BitVec,
+    on_grow: Option<GrowCallback>,
+    stopped: bool,
+}
+
+impl BitVecBuilder {
+    /// Constructs a new, empty, BitVecBuilder with no callback
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new, empty, BitVecBuilder with room for at least
+    /// `capacity` bits
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { bits: BitVec::with_capacity(capacity), ..Self::default() }
+    }
+
+    /// Sets the callback told the number of bits each time the output grows
+    /// into a new byte, replacing any callback already set. Returning false
+    /// stops the builder; the bit that grew it is kept.
+    pub fn on_grow<F: FnMut(usize) -> bool + 'static>(&mut self, callback: F) {
+        self.on_grow = Some(Box::new(callback));
+    }
+
+    /// Get the number of bits the output takes if finished now
+    pub fn estimated_bits(&self) -> usize {
+        self.bits.len
+    }
+
+    /// Checks if the callback has stopped the builder
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Returns the bits built so far
+    pub fn as_bitvec(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Takes the bits built so far, as when flushing, leaving the builder
+    /// empty and no longer stopped, with the same callback
+    pub fn take(&mut self) -> BitVec {
+        self.stopped = false;
+        core::mem::take(&mut self.bits)
+    }
+
+    /// Returns the bits built
+    pub fn finish(self) -> BitVec {
+        self.bits
+    }
+}
+
+impl BitSink for BitVecBuilder {
+    type Error = BitVecError;
+
+    fn push_bit(&mut self, value: bool) -> Result<(), BitVecError> {
+        if self.stopped {
+            return Err(BitVecError::Stopped { len: self.bits.len });
+        }
+
+        self.bits.push_bit(value);
+        if self.bits.len % 8 == 1
+            && let Some(callback) = &mut self.on_grow
+        {
+            self.stopped = !callback(self.bits.len);
+        }
+        Ok(())
+    }
+}
+
+impl From<BitVec> for BitVecBuilder {
+    fn from(bits: BitVec) -> Self {
+        Self { bits, ..Self::default() }
+    }
+}
+
+impl fmt::Debug for BitVecBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitVecBuilder")
+            .field("bits", &self.bits)
+            .field("stopped", &self.stopped)
+            .finish_non_exhaustive()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    #[test]
+    fn callback_on_each_new_byte() {
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&sizes);
+        let mut builder = BitVecBuilder::from(BitVec::from_bit_str("101").unwrap());
+        builder.on_grow(move |bits| {
+            seen.borrow_mut().push(bits);
+            true
+        });
+        builder.push_bits(0x3FFF, 14).unwrap();
+        builder.push_bytes(&[0xA5]).unwrap();
+        assert_eq!(*sizes.borrow(), vec![9, 17, 25]);
+        assert_eq!(builder.estimated_bits(), 25);
+        assert!(!builder.is_stopped());
+        assert_eq!(builder.as_bitvec().len_bits(), 25);
+        assert_eq!(builder.finish().slice(17..).to_u64(), 0xA5);
+    }
+
+    #[test]
+    fn stopping_and_flushing() {
+        let mut builder = BitVecBuilder::with_capacity(64);
+        builder.on_grow(|bits| bits < 9);
+        builder.push_bits(0xFF, 8).unwrap();
+        assert_eq!(builder.push_bits(0b10, 2), Err(BitVecError::Stopped { len: 9 }));
+        assert!(builder.is_stopped());
+
+        // Taking the bits lets it carry on
+        assert_eq!(builder.take().to_bit_string(), "111111111");
+        assert!(!builder.is_stopped());
+        builder.push_bit(false).unwrap();
+        assert_eq!(builder.estimated_bits(), 1);
+    }
+}
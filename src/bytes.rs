@@ -0,0 +1,199 @@
+//! ## Byte views and serialization
+//! The packed bytes of a BitVec do not say how many bits of the last byte
+//! are used, so a 13-bit vector comes back as 16 bits. Here the bytes can be
+//! borrowed or copied as they are, or written after a header holding the
+//! exact bit length as a LEB128 varint, so the length round-trips.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+impl BitVec {
+    /// Returns the packed bytes, MSB first, with the padding bits of the last
+    /// byte cleared
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1010 1").unwrap();
+    /// assert_eq!(bv.as_bytes(), &[0xA8]);
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Builds a new BitVec from bytes, MSB first, filling it as the bytes
+    /// come without collecting them first. The lower bound of the iterator's
+    /// size hint is reserved up front.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bytes_iter((0..4u8).map(|n| n * 0x11));
+    /// assert_eq!(bv.as_bytes(), &[0x00, 0x11, 0x22, 0x33]);
+    /// ```
+    pub fn from_bytes_iter<I: IntoIterator<Item = u8>>(bytes: I) -> Self {
+        let bytes = bytes.into_iter();
+        let mut data = Vec::with_capacity(bytes.size_hint().0);
+        data.extend(bytes);
+        Self::from(data)
+    }
+
+    /// Takes ownership of bytes packed MSB first as a BitVec of `len` bits,
+    /// without copying them. Spare bytes are dropped and padding bits
+    /// cleared. Returns an error if the bytes hold fewer bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_vec(vec![0xFF, 0xFF, 0x00], 12).unwrap();
+    /// assert_eq!(bv.as_bytes(), &[0xFF, 0xF0]);
+    /// assert_eq!(bv.into_vec(), (vec![0xFF, 0xF0], 12));
+    /// ```
+    pub fn from_vec(mut data: Vec<u8>, len: usize) -> Result<Self, BitVecError> {
+        if len > data.len() * 8 {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: data.len() * 8 });
+        }
+        data.truncate(len.div_ceil(8));
+
+        let mut bv = BitVec::from(data);
+        bv.len = len;
+        bv.clear_padding();
+        Ok(bv)
+    }
+
+    /// Unwraps the vector, returning its packed bytes, MSB first with the
+    /// padding bits cleared, and its length in bits, without copying
+    pub fn into_vec(self) -> (Vec<u8>, usize) {
+        (self.data, self.len)
+    }
+
+    /// Copies the packed bytes in the vector's bit order, with the padding
+    /// bits of the last byte cleared
+    pub fn to_raw_bytes(&self) -> Vec<u8> {
+        self.data.iter().map(|&byte| self.order.convert(byte)).collect()
+    }
+
+    /// Copies the packed bytes, MSB first, after a header holding the bit
+    /// length as a LEB128 varint
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1111 0000 1010 1").unwrap();
+    /// let bytes = bv.to_bytes_with_len();
+    /// assert_eq!(bytes, vec![13, 0xF0, 0xA8]);
+    /// assert_eq!(BitVec::from_bytes_with_len(&bytes), Some(bv));
+    /// ```
+    pub fn to_bytes_with_len(&self) -> Vec<u8> {
+        let mut header = BitVec::new();
+        header.push_varint(self.len as u64);
+
+        let mut bytes = header.data;
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Reads bytes written by `to_bytes_with_len`. Returns None if the header
+    /// is invalid or the number of bytes after it does not match the length.
+    pub fn from_bytes_with_len(bytes: &[u8]) -> Option<BitVec> {
+        let mut header = BitVec::from(bytes);
+        let len = usize::try_from(header.read_varint()?).ok()?;
+        let start = header.get_read_position() / 8;
+        if bytes.len() - start != len.div_ceil(8) {
+            return None;
+        }
+
+        let mut bv = BitVec::from(&bytes[start..]);
+        bv.truncate(len);
+        Some(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitOrder;
+
+    #[test]
+    fn exact_length_round_trip() {
+        for len in [0, 1, 7, 8, 13, 200] {
+            let mut bv = BitVec::new();
+            for index in 0..len {
+                bv.push_bit(index % 3 == 0);
+            }
+            let bytes = bv.to_bytes_with_len();
+            let back = BitVec::from_bytes_with_len(&bytes).unwrap();
+            assert_eq!(back.len_bits(), len);
+            assert_eq!(back, bv);
+        }
+        // A two byte header
+        assert_eq!(&BitVec::from(&[0xFF; 20]).to_bytes_with_len()[..2], &[0xA0, 0x01]);
+    }
+
+    #[test]
+    fn bytes_from_iterators() {
+        let bv = BitVec::from_bytes_iter([0xA5u8; 3].into_iter().chain([0x0F]));
+        assert_eq!(bv, BitVec::from(&[0xA5, 0xA5, 0xA5, 0x0F]));
+        // No size hint to go on
+        let bv = BitVec::from_bytes_iter((0..=255u8).filter(|byte| byte % 64 == 0));
+        assert_eq!(bv.as_bytes(), &[0x00, 0x40, 0x80, 0xC0]);
+        assert_eq!(BitVec::from_bytes_iter(core::iter::empty()).len_bits(), 0);
+    }
+
+    #[test]
+    fn owned_buffers() {
+        let data = vec![0xAB, 0xCD, 0xEF];
+        let pointer = data.as_ptr();
+        let bv = BitVec::from_vec(data, 20).unwrap();
+        assert_eq!(bv.len_bits(), 20);
+        assert_eq!(bv.to_hex_string(), "abcde");
+        let (data, len) = bv.into_vec();
+        assert_eq!((data.as_ptr(), len), (pointer, 20));
+        assert_eq!(data, vec![0xAB, 0xCD, 0xE0]);
+
+        assert_eq!(BitVec::from_vec(Vec::new(), 0).unwrap().len_bits(), 0);
+        assert_eq!(
+            BitVec::from_vec(vec![0xFF], 9),
+            Err(BitVecError::NotEnoughBits { requested: 9, available: 8 })
+        );
+    }
+
+    #[test]
+    fn borrowed_views() {
+        let bytes = [0xAB, 0xCD, 0xEF];
+        let view = crate::BitSlice::from_bytes(&bytes, 20).unwrap();
+        assert_eq!(view.to_bitvec(), BitVec::from_vec(bytes.to_vec(), 20).unwrap());
+        assert_eq!(view.slice(4..12).to_u64(), 0xBC);
+        assert_eq!(view.count_ones(), 13);
+        assert!(crate::BitSlice::from_bytes(&bytes, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_headers() {
+        assert_eq!(BitVec::from_bytes_with_len(&[]), None);
+        assert_eq!(BitVec::from_bytes_with_len(&[13, 0xF0]), None);
+        assert_eq!(BitVec::from_bytes_with_len(&[13, 0xF0, 0xA8, 0x00]), None);
+        assert_eq!(BitVec::from_bytes_with_len(&[0x80]), None);
+    }
+
+    #[test]
+    fn raw_bytes_in_bit_order() {
+        let mut bv = BitVec::with_order(BitOrder::Lsb0);
+        bv.push_bit(true);
+        bv.push_bit(true);
+        assert_eq!(bv.as_bytes(), &[0xC0]);
+        assert_eq!(bv.to_raw_bytes(), vec![0x03]);
+    }
+}
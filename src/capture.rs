@@ -0,0 +1,285 @@
+//! ## Sampled captures
+//! Raw logic analyser or oscilloscope samples converted into a BitVec, one
+//! bit per sample, keeping the sample rate alongside so that bit indices can
+//! be turned back into timestamps.
+
+use std::io::{self, BufRead};
+
+use crate::BitVec;
+
+/// A BitVec of digital samples together with the rate they were taken at
+pub struct SampleCapture {
+    pub bits:        BitVec, // one bit per sample
+    pub sample_rate: f64,    // samples per second
+}
+
+impl SampleCapture {
+    /// Builds a capture from digital samples
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::SampleCapture;
+    ///
+    /// let capture = SampleCapture::from_samples([false, true, true, false], 1e6);
+    /// assert_eq!(capture.bits.edges(), vec![1, 3]);
+    /// ```
+    pub fn from_samples<I: IntoIterator<Item = bool>>(samples: I, sample_rate: f64) -> Self {
+        let mut bits = BitVec::new();
+        for sample in samples {
+            bits.push_bit(sample);
+        }
+
+        Self { bits, sample_rate }
+    }
+
+    /// Builds a capture from analog samples, reading a sample as 1 when it
+    /// is at or above the threshold
+    pub fn from_analog<I: IntoIterator<Item = f64>>(samples: I, threshold: f64, sample_rate: f64) -> Self {
+        Self::from_samples(samples.into_iter().map(|sample| sample >= threshold), sample_rate)
+    }
+
+    /// Imports one column of a CSV capture, such as those exported by sigrok.
+    /// Lines starting with `;` or `#` are comments, and a `Samplerate:`
+    /// comment (e.g. `; Samplerate: 1 MHz`) sets the sample rate, otherwise
+    /// `default_rate` is used. A leading row of column names is skipped.
+    /// Values are compared against the threshold, so both `0`/`1` logic
+    /// columns and analog columns can be read.
+    pub fn from_csv<R: BufRead>(reader: R, column: usize, threshold: f64, default_rate: f64) -> io::Result<Self> {
+        let mut bits = BitVec::new();
+        let mut sample_rate = None;
+        let mut first_row = true;
+
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix(';').or_else(|| line.strip_prefix('#')) {
+                if sample_rate.is_none() {
+                    sample_rate = parse_sample_rate(comment);
+                }
+                continue;
+            }
+
+            let field = line.split(',').nth(column).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("CSV row has no column {}", column))
+            })?;
+            match field.trim().parse::<f64>() {
+                Ok(value) => bits.push_bit(value >= threshold),
+                Err(_) if first_row => (),
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid CSV sample {}", field),
+                    ));
+                }
+            }
+            first_row = false;
+        }
+
+        Ok(Self {
+            bits,
+            sample_rate: sample_rate.unwrap_or(default_rate),
+        })
+    }
+
+    /// Returns the time, in seconds from the first sample, of every
+    /// transition in the capture
+    pub fn edge_times(&self) -> Vec<f64> {
+        self.bits.edges()
+            .into_iter()
+            .map(|index| index as f64 / self.sample_rate)
+            .collect()
+    }
+}
+
+/// Parses a rate such as `Samplerate: 24 MHz` from a comment line
+fn parse_sample_rate(comment: &str) -> Option<f64> {
+    let (key, value) = comment.split_once(':')?;
+    if !key.trim().eq_ignore_ascii_case("samplerate") {
+        return None;
+    }
+
+    let value = value.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(value.len());
+    let number = value[..split].parse::<f64>().ok()?;
+    let scale = match value[split..].trim().to_ascii_lowercase().as_str() {
+        "" | "hz" => 1.0,
+        "khz" => 1e3,
+        "mhz" => 1e6,
+        "ghz" => 1e9,
+        _ => return None,
+    };
+
+    Some(number * scale)
+}
+
+impl BitVec {
+    /// Returns the index of every transition, i.e. every bit which differs
+    /// from the bit before it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b00111010
+    /// let bv = BitVec::from(&[0x3A]);
+    /// assert_eq!(bv.edges(), vec![2, 5, 6, 7]);
+    /// ```
+    pub fn edges(&self) -> Vec<usize> {
+        let mut edges = Vec::new();
+        if self.len == 0 {
+            return edges;
+        }
+
+        let mut previous = self.get_bit(0);
+        for index in 1..self.len {
+            let bit = self.get_bit(index);
+            if bit != previous {
+                edges.push(index);
+                previous = bit;
+            }
+        }
+
+        edges
+    }
+
+    /// Down-samples an oversampled capture, taking one bit every
+    /// `period_bits` samples starting at sample `phase`. The period may be
+    /// fractional, for sample rates that are not a whole multiple of the bit
+    /// rate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 4 samples per bit: 1, 0, 1, 1
+    /// let capture = BitVec::from(&[0xF0, 0xFF]);
+    /// let bits = capture.sample_at(4.0, 2.0);
+    /// assert_eq!(bits.len_bits(), 4);
+    /// ```
+    pub fn sample_at(&self, period_bits: f64, phase: f64) -> BitVec {
+        if period_bits < 1.0 {
+            panic!("BitVec: sampling period must be at least 1 sample")
+        }
+
+        let mut bits = BitVec::new();
+        let mut position = phase.rem_euclid(period_bits);
+        while position < self.len as f64 {
+            bits.push_bit(self.get_bit(position as usize));
+            position += period_bits;
+        }
+
+        bits
+    }
+
+    /// Estimates the sampling phase for a given bit period from the
+    /// positions of the transitions. Edges are averaged around the bit period
+    /// (as a circular mean, so edges either side of a bit boundary don't
+    /// cancel out) and the phase is placed half a period after them, in the
+    /// middle of each bit. Without any edges the middle of the first period
+    /// is used.
+    pub fn estimate_phase(&self, period_bits: f64) -> f64 {
+        let (mut sin_sum, mut cos_sum) = (0.0, 0.0);
+        for edge in self.edges() {
+            let angle = (edge as f64).rem_euclid(period_bits) / period_bits * std::f64::consts::TAU;
+            sin_sum += angle.sin();
+            cos_sum += angle.cos();
+        }
+
+        let edge_phase = if sin_sum == 0.0 && cos_sum == 0.0 {
+            0.0
+        } else {
+            sin_sum.atan2(cos_sum) / std::f64::consts::TAU * period_bits
+        };
+
+        (edge_phase + period_bits / 2.0).rem_euclid(period_bits)
+    }
+
+    /// Recovers the bit sequence from an oversampled capture, sampling at
+    /// the phase found by `estimate_phase`
+    pub fn recover_clock(&self, period_bits: f64) -> BitVec {
+        self.sample_at(period_bits, self.estimate_phase(period_bits))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    #[test]
+    fn analog_threshold() {
+        let capture = SampleCapture::from_analog([0.1, 3.2, 3.3, 1.65, 0.0], 1.65, 1000.0);
+        assert_eq!(capture.bits.len_bits(), 5);
+        // 0b01110xxx
+        assert_eq!(capture.bits.data, vec![0x70]);
+        assert_eq!(capture.edge_times(), vec![0.001, 0.004]);
+    }
+
+    #[test]
+    fn sigrok_csv_import() {
+        let csv = "\
+; CSV, generated by libsigrok4DSL 0.2.0
+; Samplerate: 2 kHz
+Time(s),D0,D1
+0.0000,0,1
+0.0005,1,1
+0.0010,1,0
+0.0015,0,0
+";
+        let capture = SampleCapture::from_csv(csv.as_bytes(), 1, 0.5, 1.0).unwrap();
+        assert_eq!(capture.sample_rate, 2000.0);
+        assert_eq!(capture.bits.len_bits(), 4);
+        assert_eq!(capture.bits.edges(), vec![1, 3]);
+        assert_eq!(capture.edge_times(), vec![0.0005, 0.0015]);
+
+        let missing = SampleCapture::from_csv(csv.as_bytes(), 3, 0.5, 1.0);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn recover_oversampled_bits() {
+        // Bits 1, 0, 1, 1, 0 at 5 samples per bit, delayed by 2 samples
+        let pattern = [true, false, true, true, false];
+        let mut capture = BitVec::new();
+        capture.push_bit(false);
+        capture.push_bit(false);
+        for &bit in &pattern {
+            for _ in 0..5 {
+                capture.push_bit(bit);
+            }
+        }
+
+        let phase = capture.estimate_phase(5.0);
+        assert!((phase - 4.5).abs() < 1e-9);
+        let bits = capture.recover_clock(5.0);
+        assert_eq!(bits.len_bits(), 5);
+        for (index, &bit) in pattern.iter().enumerate() {
+            assert_eq!(bits.get_bit(index), bit);
+        }
+    }
+
+    #[test]
+    fn fractional_period() {
+        // 2.5 samples per bit: 1 1 0 0 0 | 1 1 1 0 0
+        let capture = BitVec { data: vec![0xC7, 0x00], len: 10, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let bits = capture.sample_at(2.5, 1.0);
+        // Sample points 1, 3.5, 6, 8.5
+        assert_eq!(bits.len_bits(), 4);
+        assert_eq!(bits.data, vec![0xA0]);
+    }
+
+    #[test]
+    fn edges_of_empty_and_constant() {
+        assert!(BitVec::new().edges().is_empty());
+        assert!(BitVec::from(&[0xFF, 0xFF]).edges().is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! ## Channel error injection
+//! Flipping bits as a noisy channel would, to stress test FEC and framing
+//! code: random errors at a bit error rate (BER), behind the `rand` feature,
+//! and bursts of consecutive errors.
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Flips each bit independently with probability `ber`, returning the
+    /// number of bits flipped. Panics if `ber` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(7);
+    /// let mut bv = BitVec::repeat_bit(false, 10_000);
+    /// let flipped = bv.inject_errors(&mut rng, 0.01);
+    /// assert_eq!(bv.count_ones(), flipped);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn inject_errors<R: Rng + ?Sized>(&mut self, rng: &mut R, ber: f64) -> usize {
+        if !(0.0..=1.0).contains(&ber) {
+            panic!("BitVec: bit error rate must be between 0 and 1")
+        }
+
+        let mut flipped = 0;
+        for index in 0..self.len {
+            if rng.random_bool(ber) {
+                self.flip_range(index..index + 1);
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
+    /// Flips `len` consecutive bits from `start`, as a burst of errors.
+    /// Panics if the burst runs past the end of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bit_str("1111 0000").unwrap();
+    /// bv.inject_burst(2, 4);
+    /// assert_eq!(bv.to_bit_string(), "11001100");
+    /// ```
+    pub fn inject_burst(&mut self, start: usize, len: usize) {
+        self.flip_range(start..start + len);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts() {
+        let mut bv = BitVec::from(&[0x0F, 0xF0, 0x00]).slice(..20).to_bitvec();
+        bv.inject_burst(4, 8);
+        assert_eq!(bv.count_ones(), 0);
+        bv.inject_burst(19, 1);
+        assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![19]);
+        bv.inject_burst(20, 0);
+        assert_eq!(bv.count_ones(), 1);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_errors_follow_the_rate() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let original = BitVec::repeat(&BitVec::from_bit_str("10110").unwrap(), 20_000);
+        let mut bv = original.clone();
+        let flipped = bv.inject_errors(&mut rng, 0.05);
+        assert_eq!(bv.hamming_distance(&original), Ok(flipped));
+        assert!((4_500..5_500).contains(&flipped));
+
+        assert_eq!(bv.clone().inject_errors(&mut rng, 0.0), 0);
+        let mut inverted = bv.clone();
+        assert_eq!(inverted.inject_errors(&mut rng, 1.0), 100_000);
+        assert_eq!(inverted.count_ones(), 100_000 - bv.count_ones());
+    }
+}
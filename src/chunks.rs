@@ -0,0 +1,236 @@
+//! ## Chunks and windows
+//! Iterators over fixed-width runs of bits, mirroring `chunks`,
+//! `chunks_exact`, and `windows` on slices. Each item is a BitSlice, which
+//! can be read as a number with `to_u64`, as when decoding packed records
+//! such as 12-bit samples.
+
+use core::iter::FusedIterator;
+
+use crate::{BitSlice, BitVec};
+
+/// Iterator over runs of `size` bits, the last of which may be shorter,
+/// created by `chunks`
+pub struct Chunks<'a> {
+    slice: BitSlice<'a>,
+    size:  usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = BitSlice<'a>;
+
+    fn next(&mut self) -> Option<BitSlice<'a>> {
+        if self.slice.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.slice.split_at(self.size.min(self.slice.len()));
+        self.slice = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.slice.len().div_ceil(self.size);
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Chunks<'_> {}
+
+impl FusedIterator for Chunks<'_> {}
+
+/// Iterator over runs of exactly `size` bits, leaving out the bits at the
+/// end that do not fill a run, created by `chunks_exact`
+pub struct ChunksExact<'a> {
+    slice:     BitSlice<'a>,
+    remainder: BitSlice<'a>,
+    size:      usize,
+}
+
+impl<'a> ChunksExact<'a> {
+    /// Returns the bits at the end that do not fill a run
+    pub fn remainder(&self) -> BitSlice<'a> {
+        self.remainder
+    }
+}
+
+impl<'a> Iterator for ChunksExact<'a> {
+    type Item = BitSlice<'a>;
+
+    fn next(&mut self) -> Option<BitSlice<'a>> {
+        if self.slice.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.slice.split_at(self.size);
+        self.slice = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.slice.len() / self.size;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for ChunksExact<'_> {}
+
+impl FusedIterator for ChunksExact<'_> {}
+
+/// Iterator over every run of `size` consecutive bits, each starting one
+/// bit after the last, created by `windows`
+pub struct Windows<'a> {
+    slice: BitSlice<'a>,
+    size:  usize,
+}
+
+impl<'a> Iterator for Windows<'a> {
+    type Item = BitSlice<'a>;
+
+    fn next(&mut self) -> Option<BitSlice<'a>> {
+        if self.slice.len() < self.size {
+            return None;
+        }
+        let window = self.slice.slice(..self.size);
+        self.slice = self.slice.slice(1..);
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = (self.slice.len() + 1).saturating_sub(self.size);
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Windows<'_> {}
+
+impl FusedIterator for Windows<'_> {}
+
+/// Panics if a chunk or window size is 0
+fn check_size(size: usize) {
+    if size == 0 {
+        panic!("BitVec: chunk size must be non-zero")
+    }
+}
+
+impl<'a> BitSlice<'a> {
+    /// Returns an iterator over runs of `size` bits, the last of which may be
+    /// shorter. Panics if the size is 0.
+    pub fn chunks(&self, size: usize) -> Chunks<'a> {
+        check_size(size);
+        Chunks { slice: *self, size }
+    }
+
+    /// Returns an iterator over runs of exactly `size` bits. The bits left
+    /// over at the end are available from `remainder`. Panics if the size
+    /// is 0.
+    pub fn chunks_exact(&self, size: usize) -> ChunksExact<'a> {
+        check_size(size);
+        let (slice, remainder) = self.split_at(self.len() - self.len() % size);
+        ChunksExact { slice, remainder, size }
+    }
+
+    /// Returns an iterator over every run of `size` consecutive bits.
+    /// Panics if the size is 0.
+    pub fn windows(&self, size: usize) -> Windows<'a> {
+        check_size(size);
+        Windows { slice: *self, size }
+    }
+}
+
+impl BitVec {
+    /// Returns an iterator over runs of `size` bits, the last of which may be
+    /// shorter. Panics if the size is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // Two 12-bit samples and a 4-bit tail
+    /// let bv = BitVec::from(&[0x0A, 0xBC, 0x01, 0x23]);
+    /// let samples: Vec<u64> = bv.slice(4..).chunks(12).map(|chunk| chunk.to_u64()).collect();
+    /// assert_eq!(samples, vec![0xABC, 0x012, 0x3]);
+    /// ```
+    pub fn chunks(&self, size: usize) -> Chunks<'_> {
+        self.as_bitslice().chunks(size)
+    }
+
+    /// Returns an iterator over runs of exactly `size` bits. The bits left
+    /// over at the end are available from `remainder`. Panics if the size
+    /// is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("101 110 01").unwrap();
+    /// let mut chunks = bv.chunks_exact(3);
+    /// assert_eq!(chunks.next().map(|chunk| chunk.to_u64()), Some(0b101));
+    /// assert_eq!(chunks.next().map(|chunk| chunk.to_u64()), Some(0b110));
+    /// assert!(chunks.next().is_none());
+    /// assert_eq!(chunks.remainder().to_u64(), 0b01);
+    /// ```
+    pub fn chunks_exact(&self, size: usize) -> ChunksExact<'_> {
+        self.as_bitslice().chunks_exact(size)
+    }
+
+    /// Returns an iterator over every run of `size` consecutive bits.
+    /// Panics if the size is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_> {
+        self.as_bitslice().windows(size)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_and_remainders() {
+        // Five 13-bit records across byte boundaries, and 2 spare bits
+        let records = [0x1ABC_u64, 0x0001, 0x1FFF, 0x0800, 0x1234];
+        let mut bv = BitVec::new();
+        for record in records {
+            for shift in (0..13).rev() {
+                bv.push_bit(record >> shift & 1 == 1);
+            }
+        }
+        bv.push_bit(true);
+        bv.push_bit(false);
+
+        let chunks = bv.chunks(13);
+        assert_eq!(chunks.len(), 6);
+        let values: Vec<u64> = chunks.map(|chunk| chunk.to_u64()).collect();
+        assert_eq!(&values[..5], &records);
+        assert_eq!(values[5], 0b10);
+
+        let mut exact = bv.chunks_exact(13);
+        assert_eq!(exact.len(), 5);
+        assert_eq!(exact.by_ref().map(|chunk| chunk.to_u64()).collect::<Vec<_>>(), records);
+        assert_eq!(exact.remainder().len(), 2);
+        assert!(bv.slice(..26).chunks_exact(13).remainder().is_empty());
+        assert_eq!(BitVec::new().chunks(4).count(), 0);
+    }
+
+    #[test]
+    fn sliding_windows() {
+        let bv = BitVec::from_bit_str("0110 1").unwrap();
+        let windows: Vec<u64> = bv.windows(3).map(|window| window.to_u64()).collect();
+        assert_eq!(windows, vec![0b011, 0b110, 0b101]);
+        assert_eq!(bv.windows(5).len(), 1);
+        assert_eq!(bv.windows(6).count(), 0);
+
+        // A window as wide as a word
+        let mut wide = BitVec::from(&[0xFF; 9]);
+        wide.truncate(67);
+        assert!(wide.windows(64).all(|window| window.to_u64() == u64::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_size() {
+        BitVec::new().chunks(0);
+    }
+}
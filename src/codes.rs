@@ -0,0 +1,334 @@
+//! ## Universal codes
+//! Variable-length codes for integers, as used by inverted indexes and
+//! compressed posting lists, written at the end of the vector and read from
+//! the current reading position.
+//!
+//! - Unary: `n` 1s and a 0
+//! - Elias gamma: for `n >= 1`, one 0 for each bit of `n` after its leading
+//!   1, then `n` in binary
+//! - Elias delta: for `n >= 1`, the number of bits of `n` as a gamma code,
+//!   then `n` in binary without its leading 1
+//! - Golomb-Rice: `n >> k` in unary, then the low `k` bits of `n`, as in
+//!   the Rice coded gaps
+//!
+//! Each read has a `try_` form returning the bit offset where a code that
+//! cannot be decoded failed, and `error_context` returns the bits around
+//! it, for finding the damage in a corrupt stream.
+
+use core::ops::{Bound, RangeBounds};
+
+use crate::{BitSlice, BitVec, BitVecError};
+
+/// Most bits in a value
+const MAX_BITS: u32 = 64;
+
+impl BitVec {
+    /// Reads `count` bits from the reading position as a number, most
+    /// significant first
+    fn read_low_bits(&mut self, count: u32) -> Option<u64> {
+        (0..count).try_fold(0, |value, _| Some(value << 1 | self.read_bit_seq()? as u64))
+    }
+
+    /// Runs a read, moving the reading position back to where it started if
+    /// the read fails, and returning the offset it failed at
+    fn read_or_rewind(&mut self, read: impl FnOnce(&mut Self) -> Option<u64>) -> Result<u64, BitVecError> {
+        let start = self.get_read_position();
+        read(self).ok_or_else(|| {
+            let offset = self.get_read_position();
+            self.set_read_position(start);
+            BitVecError::Undecodable { offset }
+        })
+    }
+
+    /// Returns the bits of a range around an error offset, such as
+    /// `offset - 16..offset + 16`, cut to the bounds of the vector
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, BitVecError};
+    ///
+    /// let mut bv = BitVec::from_bit_str("0001001 000").unwrap();
+    /// assert_eq!(bv.try_read_gamma(), Ok(9));
+    /// let Err(BitVecError::Undecodable { offset }) = bv.try_read_gamma() else { panic!() };
+    /// assert_eq!(offset, 10);
+    /// assert_eq!(bv.error_context(offset - 4..offset + 4).to_bitvec().to_bit_string(), "1000");
+    /// ```
+    pub fn error_context<R: RangeBounds<usize>>(&self, range: R) -> BitSlice<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        let end = end.min(self.len);
+        self.slice(start.min(end)..end)
+    }
+
+    /// Counts a run of bits with a value at the reading position, then
+    /// reads the bit that ends it. Returns None if the run reaches the end
+    /// or is longer than `limit`.
+    fn read_run(&mut self, value: u8, limit: u64) -> Option<u64> {
+        let mut count = 0;
+        while self.read_bit_seq()? == value {
+            count += 1;
+            if count > limit {
+                return None;
+            }
+        }
+        Some(count)
+    }
+
+    /// Pushes a value in unary, as `n` 1s and a 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.write_unary(3);
+    /// assert_eq!(bv.to_bit_string(), "1110");
+    /// assert_eq!(bv.read_unary(), Some(3));
+    /// ```
+    pub fn write_unary(&mut self, n: u64) {
+        for _ in 0..n {
+            self.push_bit(true);
+        }
+        self.push_bit(false);
+    }
+
+    /// Reads a unary value from the reading position. Returns None, without
+    /// moving the reading position, if the code is truncated.
+    pub fn read_unary(&mut self) -> Option<u64> {
+        self.try_read_unary().ok()
+    }
+
+    /// Reads a unary value as `read_unary` does, returning an error with the
+    /// offset it failed at
+    pub fn try_read_unary(&mut self) -> Result<u64, BitVecError> {
+        self.read_or_rewind(|bv| bv.read_run(1, u64::MAX))
+    }
+
+    /// Pushes an Elias gamma code. Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.write_gamma(9);
+    /// assert_eq!(bv.to_bit_string(), "0001001");
+    /// assert_eq!(bv.read_gamma(), Some(9));
+    /// ```
+    pub fn write_gamma(&mut self, n: u64) {
+        if n == 0 {
+            panic!("BitVec: gamma codes start at 1")
+        }
+        let bits = MAX_BITS - n.leading_zeros();
+        self.push_low_bits(0, bits - 1);
+        self.push_low_bits(n, bits);
+    }
+
+    /// Reads an Elias gamma code from the reading position. Returns None,
+    /// without moving the reading position, if the code is truncated or too
+    /// long for a u64.
+    pub fn read_gamma(&mut self) -> Option<u64> {
+        self.try_read_gamma().ok()
+    }
+
+    /// Reads an Elias gamma code as `read_gamma` does, returning an error
+    /// with the offset it failed at
+    pub fn try_read_gamma(&mut self) -> Result<u64, BitVecError> {
+        self.read_or_rewind(Self::read_gamma_bits)
+    }
+
+    /// Reads the bits of an Elias gamma code, leaving the reading position
+    /// where a failed read stopped
+    fn read_gamma_bits(&mut self) -> Option<u64> {
+        let zeros = self.read_run(0, MAX_BITS as u64 - 1)? as u32;
+        Some(1 << zeros | self.read_low_bits(zeros)?)
+    }
+
+    /// Pushes an Elias delta code. Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.write_delta(9);
+    /// assert_eq!(bv.to_bit_string(), "00100001");
+    /// assert_eq!(bv.read_delta(), Some(9));
+    /// ```
+    pub fn write_delta(&mut self, n: u64) {
+        if n == 0 {
+            panic!("BitVec: delta codes start at 1")
+        }
+        let bits = MAX_BITS - n.leading_zeros();
+        self.write_gamma(bits as u64);
+        self.push_low_bits(n, bits - 1);
+    }
+
+    /// Reads an Elias delta code from the reading position. Returns None,
+    /// without moving the reading position, if the code is truncated or too
+    /// long for a u64.
+    pub fn read_delta(&mut self) -> Option<u64> {
+        self.try_read_delta().ok()
+    }
+
+    /// Reads an Elias delta code as `read_delta` does, returning an error
+    /// with the offset it failed at
+    pub fn try_read_delta(&mut self) -> Result<u64, BitVecError> {
+        self.read_or_rewind(|bv| {
+            let bits = bv.read_gamma_bits()?;
+            if bits > MAX_BITS as u64 {
+                return None;
+            }
+            let low = bv.read_low_bits(bits as u32 - 1)?;
+            Some(1u64.checked_shl(bits as u32 - 1)? | low)
+        })
+    }
+
+    /// Pushes a Golomb-Rice code with parameter `k`, the quotient `n >> k`
+    /// in unary followed by the low `k` bits. Panics if `k` is 64 or more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.write_rice(2, 9);
+    /// assert_eq!(bv.to_bit_string(), "11001");
+    /// assert_eq!(bv.read_rice(2), Some(9));
+    /// ```
+    pub fn write_rice(&mut self, k: u32, n: u64) {
+        if k >= MAX_BITS {
+            panic!("BitVec: Rice parameter must be below 64")
+        }
+        self.write_unary(n >> k);
+        self.push_low_bits(n, k);
+    }
+
+    /// Reads a Golomb-Rice code with parameter `k` from the reading
+    /// position. Returns None, without moving the reading position, if the
+    /// code is truncated or too long for a u64. Panics if `k` is 64 or more.
+    pub fn read_rice(&mut self, k: u32) -> Option<u64> {
+        self.try_read_rice(k).ok()
+    }
+
+    /// Reads a Golomb-Rice code as `read_rice` does, returning an error with
+    /// the offset it failed at. Panics if `k` is 64 or more.
+    pub fn try_read_rice(&mut self, k: u32) -> Result<u64, BitVecError> {
+        if k >= MAX_BITS {
+            panic!("BitVec: Rice parameter must be below 64")
+        }
+        self.read_or_rewind(|bv| {
+            let quotient = bv.read_run(1, u64::MAX >> k)?;
+            Some(quotient << k | bv.read_low_bits(k)?)
+        })
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [u64; 9] = [1, 2, 3, 4, 7, 8, 100, 1 << 40, u64::MAX];
+
+    #[test]
+    fn round_trips() {
+        let mut bv = BitVec::new();
+        for value in VALUES {
+            bv.write_gamma(value);
+            bv.write_delta(value);
+            bv.write_rice(5, value.min(1000));
+            bv.write_unary(value % 20);
+        }
+        for value in VALUES {
+            assert_eq!(bv.read_gamma(), Some(value));
+            assert_eq!(bv.read_delta(), Some(value));
+            assert_eq!(bv.read_rice(5), Some(value.min(1000)));
+            assert_eq!(bv.read_unary(), Some(value % 20));
+        }
+        assert_eq!(bv.get_read_position(), bv.len_bits());
+        assert_eq!(bv.read_unary(), None);
+    }
+
+    #[test]
+    fn code_lengths() {
+        let mut bv = BitVec::new();
+        bv.write_gamma(1);
+        assert_eq!(bv.to_bit_string(), "1");
+        bv.write_delta(1);
+        assert_eq!(bv.len_bits(), 2);
+        bv.write_rice(0, 4);
+        assert_eq!(bv.to_bit_string(), "1111110");
+
+        // Delta codes are shorter than gamma codes for large values
+        let (mut gamma, mut delta) = (BitVec::new(), BitVec::new());
+        gamma.write_gamma(1 << 40);
+        delta.write_delta(1 << 40);
+        assert_eq!((gamma.len_bits(), delta.len_bits()), (81, 51));
+    }
+
+    #[test]
+    fn truncated_codes_rewind() {
+        let mut bv = BitVec::new();
+        bv.write_gamma(9);
+        bv.truncate(5);
+        bv.set_read_position(1);
+        assert_eq!(bv.read_gamma(), None);
+        assert_eq!(bv.get_read_position(), 1);
+
+        // 64 zeros is too long for a gamma code
+        let mut zeros = BitVec::repeat_bit(false, 70);
+        zeros.push_bit(true);
+        assert_eq!(zeros.read_gamma(), None);
+        assert_eq!(zeros.read_delta(), None);
+        assert_eq!(zeros.get_read_position(), 0);
+    }
+
+    #[test]
+    fn error_offsets() {
+        let mut bv = BitVec::new();
+        bv.write_gamma(9);
+        bv.write_delta(300);
+        bv.truncate(12);
+        assert_eq!(bv.try_read_gamma(), Ok(9));
+        // Truncated inside the delta code, which starts at 7
+        assert_eq!(bv.try_read_delta(), Err(BitVecError::Undecodable { offset: 12 }));
+        assert_eq!(bv.get_read_position(), 7);
+        assert_eq!(bv.try_read_unary(), Ok(0));
+        assert_eq!(bv.try_read_rice(8), Err(BitVecError::Undecodable { offset: 12 }));
+
+        // The 64th zero is where a gamma code becomes too long
+        let mut zeros = BitVec::repeat_bit(false, 70);
+        assert_eq!(zeros.try_read_gamma(), Err(BitVecError::Undecodable { offset: 64 }));
+        assert_eq!(zeros.error_context(60..).len(), 10);
+        assert_eq!(zeros.error_context(..=200).len(), 70);
+        assert!(zeros.error_context(100..120).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "below 64")]
+    fn rice_parameter_too_large() {
+        BitVec::repeat_bit(false, 100).read_rice(64);
+    }
+
+    #[test]
+    #[should_panic(expected = "below 64")]
+    fn try_rice_parameter_too_large() {
+        let _ = BitVec::repeat_bit(true, 100).try_read_rice(70);
+    }
+}
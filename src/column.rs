@@ -0,0 +1,228 @@
+//! ## Nullable boolean columns
+//! A column of booleans that may be NULL, as in SQL and Arrow: a BitVec of
+//! values paired with a BitVec of validity, where a clear validity bit marks
+//! a NULL. The value bit under a NULL is kept clear, so columns compare
+//! equal whenever they hold the same values and NULLs.
+//!
+//! Logical operations follow three-valued (Kleene) logic, built on the
+//! operations over bare value and validity bitmaps.
+
+use crate::{BitVec, BitVecError};
+
+/// A column of booleans that may be NULL
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::NullableBitColumn;
+///
+/// let a: NullableBitColumn = [Some(true), Some(false), None, None].into_iter().collect();
+/// let b: NullableBitColumn = [None, None, Some(false), Some(true)].into_iter().collect();
+/// let and = a.and(&b).unwrap();
+/// assert_eq!(and.get(0), None);
+/// assert_eq!(and.get(1), Some(false));
+/// assert_eq!(and.get(2), Some(false));
+/// assert_eq!(and.null_count(), 2);
+/// assert_eq!(a.compact().to_bit_string(), "10");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NullableBitColumn {
+    values:   BitVec,   // clear under every NULL
+    validity: BitVec,   // set for every row that is not NULL
+}
+
+impl NullableBitColumn {
+    /// Constructs a new, empty, column
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a column from its values and validity. Values under a NULL are
+    /// cleared. Returns an error if the lengths differ.
+    pub fn from_parts(values: BitVec, validity: BitVec) -> Result<Self, BitVecError> {
+        if values.len != validity.len {
+            return Err(BitVecError::LengthMismatch { expected: values.len, found: validity.len });
+        }
+
+        let mut values = values;
+        for (byte, &valid) in values.data.iter_mut().zip(&validity.data) {
+            *byte &= valid;
+        }
+        Ok(Self { values, validity })
+    }
+
+    /// Unwraps the column, returning its values and validity
+    pub fn into_parts(self) -> (BitVec, BitVec) {
+        (self.values, self.validity)
+    }
+
+    /// Returns the values, with every NULL as a clear bit
+    pub fn values(&self) -> &BitVec {
+        &self.values
+    }
+
+    /// Returns the validity, with every NULL as a clear bit
+    pub fn validity(&self) -> &BitVec {
+        &self.validity
+    }
+
+    /// Get the number of rows
+    pub fn len(&self) -> usize {
+        self.values.len
+    }
+
+    /// Returns true if the column has no rows
+    pub fn is_empty(&self) -> bool {
+        self.values.len_bits() == 0
+    }
+
+    /// Returns the number of NULLs
+    pub fn null_count(&self) -> usize {
+        self.validity.len - self.validity.count_ones_in(..)
+    }
+
+    /// Pushes a row
+    pub fn push(&mut self, value: Option<bool>) {
+        self.values.push_bit(value == Some(true));
+        self.validity.push_bit(value.is_some());
+    }
+
+    /// Returns the row at an index, or None if it is NULL. Panics if the
+    /// index is out of bounds.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len() {
+            panic!("BitVec: index out of bounds")
+        }
+        self.validity.get_bit(index).then(|| self.values.get_bit(index))
+    }
+
+    /// Sets the row at an index. Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, value: Option<bool>) {
+        if index >= self.len() {
+            panic!("BitVec: index out of bounds")
+        }
+        self.values.set_bit(index, value == Some(true));
+        self.validity.set_bit(index, value.is_some());
+    }
+
+    /// Returns an iterator over the rows
+    pub fn iter(&self) -> impl Iterator<Item = Option<bool>> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    /// Returns the values of the rows that are not NULL, in order
+    pub fn compact(&self) -> BitVec {
+        let mut compacted = BitVec::with_capacity(self.len() - self.null_count());
+        for index in self.validity.iter_ones() {
+            compacted.push_bit(self.values.get_bit(index));
+        }
+        compacted
+    }
+
+    /// Returns the Kleene AND of two columns, where false wins over NULL.
+    /// Returns an error if the lengths differ.
+    pub fn and(&self, other: &Self) -> Result<Self, BitVecError> {
+        let (values, validity) = self.values.and_kleene(&other.values, &self.validity, &other.validity)?;
+        Ok(Self { values, validity })
+    }
+
+    /// Returns the Kleene OR of two columns, where true wins over NULL.
+    /// Returns an error if the lengths differ.
+    pub fn or(&self, other: &Self) -> Result<Self, BitVecError> {
+        let (values, validity) = self.values.or_kleene(&other.values, &self.validity, &other.validity)?;
+        Ok(Self { values, validity })
+    }
+
+    /// Deprecated as the name is misspelt
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the complement function instead.")]
+    pub fn compliment(&self) -> Self {
+        self.complement()
+    }
+
+    /// Returns the Kleene NOT of the column, where NULLs stay NULL
+    pub fn complement(&self) -> Self {
+        let (values, validity) = self.values.not_kleene(&self.validity)
+            .expect("BitVec: column values and validity differ in length");
+        Self { values, validity }
+    }
+}
+
+impl FromIterator<Option<bool>> for NullableBitColumn {
+    fn from_iter<I: IntoIterator<Item = Option<bool>>>(iter: I) -> Self {
+        let mut column = Self::new();
+        column.extend(iter);
+        column
+    }
+}
+
+impl Extend<Option<bool>> for NullableBitColumn {
+    fn extend<I: IntoIterator<Item = Option<bool>>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS: [Option<bool>; 3] = [Some(false), Some(true), None];
+
+    /// Every pair of rows, as two columns, with enough rows to span bytes
+    fn truth_table() -> (NullableBitColumn, NullableBitColumn) {
+        let pairs = ROWS.iter().flat_map(|&a| ROWS.iter().map(move |&b| (a, b))).cycle().take(21);
+        pairs.unzip()
+    }
+
+    #[test]
+    fn kleene_truth_tables() {
+        let (a, b) = truth_table();
+        let and = a.and(&b).unwrap();
+        let or = a.or(&b).unwrap();
+        let not = a.complement();
+        for index in 0..a.len() {
+            let (x, y) = (a.get(index), b.get(index));
+            let expected_and = match (x, y) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+            let expected_or = match (x, y) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            };
+            assert_eq!(and.get(index), expected_and);
+            assert_eq!(or.get(index), expected_or);
+            assert_eq!(not.get(index), x.map(|x| !x));
+        }
+
+        // Values under NULLs stay clear, so equal columns compare equal
+        assert_eq!(and.values().count_ones_in(..), and.compact().count_ones_in(..));
+        assert_eq!(not.complement(), a);
+    }
+
+    #[test]
+    fn parts_and_compaction() {
+        let values = BitVec::from_bit_str("1111 0000 11").unwrap();
+        let validity = BitVec::from_bit_str("1010 1010 01").unwrap();
+        let mut column = NullableBitColumn::from_parts(values, validity).unwrap();
+        assert_eq!(column.values().to_bit_string(), "1010000001");
+        assert_eq!(column.null_count(), 5);
+        assert_eq!(column.compact().to_bit_string(), "11001");
+
+        column.set(1, Some(false));
+        column.set(0, None);
+        column.push(Some(true));
+        assert_eq!(column.iter().take(3).collect::<Vec<_>>(), vec![None, Some(false), Some(true)]);
+        assert_eq!(column.len(), 11);
+
+        let short = NullableBitColumn::from_parts(BitVec::new(), BitVec::from_bit_str("1").unwrap());
+        assert_eq!(short, Err(BitVecError::LengthMismatch { expected: 0, found: 1 }));
+        assert!(column.and(&NullableBitColumn::new()).is_err());
+    }
+}
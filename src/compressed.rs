@@ -0,0 +1,248 @@
+//! ## Compressed bit vectors
+//! A run-length encoded bit vector for sparse bitmaps, which are mostly 0s
+//! with runs of 1s. Only the runs of set bits are stored, as sorted,
+//! non-overlapping, non-adjacent ranges, so the size depends on the number
+//! of runs rather than the length. Lookups binary search the runs, and the
+//! logical operations merge the runs without decompressing.
+
+use alloc::vec::Vec;
+use core::ops::{self, Range};
+
+use crate::BitVec;
+
+/// A run-length encoded bit vector, storing only the runs of set bits
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, CompressedBitVec};
+///
+/// let mut bv = BitVec::from(vec![0; 125_000]);
+/// bv.slice_mut(1000..2000).fill(true);
+/// let compressed = CompressedBitVec::from(&bv);
+/// assert_eq!(compressed.num_runs(), 1);
+/// assert!(compressed.get_bit(1500));
+/// assert_eq!(compressed.to_bitvec(), bv);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompressedBitVec {
+    runs: Vec<Range<usize>>,    // runs of set bits, in order
+    len:  usize,                // length in bits
+}
+
+impl CompressedBitVec {
+    /// Constructs a new CompressedBitVec of `len` clear bits
+    pub fn new(len: usize) -> Self {
+        Self { runs: Vec::new(), len }
+    }
+
+    /// Get the length of the vector in bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the vector has no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of runs of set bits
+    pub fn num_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Returns the runs of set bits, in order
+    pub fn runs(&self) -> &[Range<usize>] {
+        &self.runs
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// beyond the end of the vector.
+    pub fn get_bit(&self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        // Last run starting at or before the index
+        let next = self.runs.partition_point(|run| run.start <= index);
+        next > 0 && self.runs[next - 1].end > index
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.runs.iter().map(|run| run.len()).sum()
+    }
+
+    /// Returns the number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns an iterator over the indices of the set bits
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.runs.iter().flat_map(|run| run.clone())
+    }
+
+    /// Decompresses into a BitVec
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut bv = BitVec::new();
+        bv.resize(self.len, false);
+        for run in &self.runs {
+            bv.slice_mut(run.clone()).fill(true);
+        }
+        bv
+    }
+
+    /// Combines two vectors by sweeping over every run boundary of both,
+    /// where the bits of both are constant between boundaries
+    fn combine(&self, other: &CompressedBitVec, len: usize, op: impl Fn(bool, bool) -> bool) -> CompressedBitVec {
+        let mut bounds: Vec<usize> = self.runs.iter()
+            .chain(&other.runs)
+            .flat_map(|run| [run.start, run.end])
+            .chain([0, len])
+            .filter(|&bound| bound <= len)
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut combined = CompressedBitVec::new(len);
+        let (mut a, mut b) = (0, 0);
+        for segment in bounds.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            while a < self.runs.len() && self.runs[a].end <= start {
+                a += 1;
+            }
+            while b < other.runs.len() && other.runs[b].end <= start {
+                b += 1;
+            }
+            let in_a = a < self.runs.len() && self.runs[a].start <= start;
+            let in_b = b < other.runs.len() && other.runs[b].start <= start;
+
+            if op(in_a, in_b) {
+                match combined.runs.last_mut() {
+                    Some(last) if last.end == start => last.end = end,
+                    _ => combined.runs.push(start..end),
+                }
+            }
+        }
+
+        combined
+    }
+
+    /// Returns the AND of two vectors, as long as the shorter one
+    pub fn and(&self, other: &CompressedBitVec) -> CompressedBitVec {
+        self.combine(other, self.len.min(other.len), |a, b| a && b)
+    }
+
+    /// Returns the OR of two vectors, as long as the longer one
+    pub fn or(&self, other: &CompressedBitVec) -> CompressedBitVec {
+        self.combine(other, self.len.max(other.len), |a, b| a || b)
+    }
+
+    /// Returns the XOR of two vectors, as long as the longer one
+    pub fn xor(&self, other: &CompressedBitVec) -> CompressedBitVec {
+        self.combine(other, self.len.max(other.len), |a, b| a != b)
+    }
+
+    /// Returns the inversion of the vector (aka NOT)
+    pub fn not(&self) -> CompressedBitVec {
+        self.combine(&CompressedBitVec::new(0), self.len, |a, _| !a)
+    }
+}
+
+impl From<&BitVec> for CompressedBitVec {
+    fn from(bv: &BitVec) -> Self {
+        let mut compressed = CompressedBitVec::new(bv.len);
+        let mut index = 0;
+        while let Some(start) = bv.next_set_bit(index) {
+            let end = bv.next_clear_bit(start).unwrap_or(bv.len);
+            compressed.runs.push(start..end);
+            index = end;
+        }
+
+        compressed
+    }
+}
+
+impl From<&CompressedBitVec> for BitVec {
+    fn from(compressed: &CompressedBitVec) -> Self {
+        compressed.to_bitvec()
+    }
+}
+
+// ############################################################################
+// Custom ops
+// ############################################################################
+
+impl ops::BitAnd for &CompressedBitVec {
+    type Output = CompressedBitVec;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.and(rhs)
+    }
+}
+
+impl ops::BitOr for &CompressedBitVec {
+    type Output = CompressedBitVec;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.or(rhs)
+    }
+}
+
+impl ops::BitXor for &CompressedBitVec {
+    type Output = CompressedBitVec;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.xor(rhs)
+    }
+}
+
+impl ops::Not for &CompressedBitVec {
+    type Output = CompressedBitVec;
+
+    fn not(self) -> Self::Output {
+        CompressedBitVec::not(self)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitvec(bits: &str) -> BitVec {
+        BitVec::from_bit_str(bits).unwrap()
+    }
+
+    #[test]
+    fn compress_and_query() {
+        let bv = bitvec("0011 1000 0000 0111 1111 01");
+        let compressed = CompressedBitVec::from(&bv);
+        assert_eq!(compressed.runs(), &[2..5, 13..20, 21..22]);
+        assert_eq!(compressed.len(), 22);
+        assert_eq!(compressed.count_ones(), 11);
+        for index in 0..22 {
+            assert_eq!(compressed.get_bit(index), bv.get_bit(index));
+        }
+        assert_eq!(compressed.iter_ones().collect::<Vec<_>>(), bv.iter_ones().collect::<Vec<_>>());
+        assert_eq!(BitVec::from(&compressed), bv);
+        assert_eq!(CompressedBitVec::from(&BitVec::new()), CompressedBitVec::new(0));
+    }
+
+    #[test]
+    fn logical_ops_on_runs() {
+        let a = bitvec("1111 0000 1111 0011");
+        let b = bitvec("0011 1100 0110 1");
+        let (ca, cb) = (CompressedBitVec::from(&a), CompressedBitVec::from(&b));
+
+        assert_eq!((&ca & &cb).to_bitvec().to_bit_string(), "0011000001100");
+        assert_eq!((&ca | &cb).to_bitvec().to_bit_string(), "1111110011111011");
+        assert_eq!((&ca ^ &cb).to_bitvec().to_bit_string(), "1100110010011011");
+        assert_eq!((!&ca).to_bitvec().to_bit_string(), "0000111100001100");
+        // Touching runs are merged
+        assert_eq!((&ca | &cb).runs(), &[0..6, 8..13, 14..16]);
+    }
+}
@@ -0,0 +1,214 @@
+//! ## Convolutional codes
+//! A rate `1/n` convolutional encoder and a hard-decision Viterbi decoder.
+//! The encoder shifts each data bit into a register of `k` bits, the
+//! constraint length, and sends the parity of the register under each
+//! generator polynomial. The data is followed by `k - 1` 0s so the register
+//! ends clear, which the decoder relies on.
+//!
+//! Generator polynomials are in the usual notation, where the MSB of the
+//! `k` bits taps the newest data bit, so the NASA `k = 7` code is `0o171`
+//! and `0o133`. The decoder decides each bit `traceback` steps after it
+//! arrives, following the best path back from the newest step.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{BitGrid, BitVec};
+
+/// Path metric of a state that cannot be reached
+const UNREACHABLE: u32 = u32::MAX / 2;
+
+/// A convolutional code with a constraint length and generator polynomials
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, ConvolutionalCode};
+///
+/// let code = ConvolutionalCode::new(3, &[0b111, 0b101]);
+/// let data = BitVec::from_bit_str("1011").unwrap();
+/// let mut sent = code.encode(&data);
+/// assert_eq!(sent.to_bit_string(), "111000010111");
+///
+/// // A flipped bit is corrected
+/// sent.flip_range(4..5);
+/// assert_eq!(code.decode(&sent, 15), Some(data));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConvolutionalCode {
+    k:           usize,     // constraint length in bits
+    polynomials: Vec<u32>,  // generator polynomials of k bits
+}
+
+impl ConvolutionalCode {
+    /// Constructs a code with a constraint length and generator polynomials.
+    /// Panics if the constraint length is not 2 to 16, there are no
+    /// polynomials, or a polynomial is wider than the constraint length.
+    pub fn new(k: usize, polynomials: &[u32]) -> Self {
+        if !(2..=16).contains(&k) {
+            panic!("BitVec: constraint length must be 2 to 16")
+        }
+        if polynomials.is_empty() || polynomials.iter().any(|&poly| poly >> k != 0) {
+            panic!("BitVec: invalid generator polynomials")
+        }
+
+        Self { k, polynomials: polynomials.to_vec() }
+    }
+
+    /// Get the number of states of the register before each data bit
+    fn num_states(&self) -> usize {
+        1 << (self.k - 1)
+    }
+
+    /// Returns the output bits for a register, the first polynomial in the
+    /// highest bit
+    fn outputs(&self, register: u32) -> u32 {
+        self.polynomials
+            .iter()
+            .fold(0, |outputs, poly| outputs << 1 | (register & poly).count_ones() & 1)
+    }
+
+    /// Encodes data, followed by `k - 1` 0s to clear the register, sending
+    /// a bit for each polynomial for each bit
+    pub fn encode(&self, data: &BitVec) -> BitVec {
+        let n = self.polynomials.len();
+        let mut encoded = BitVec::new();
+        let mut state = 0;
+        for index in 0..data.len_bits() + self.k - 1 {
+            let input = (index < data.len_bits() && data.get_bit(index)) as u32;
+            let register = input << (self.k - 1) | state;
+            let outputs = self.outputs(register);
+            encoded.push_low_bits(outputs as u64, n as u32);
+            state = register >> 1;
+        }
+        encoded
+    }
+
+    /// Returns the state before a step, from the state after it and the bit
+    /// that fell out of the register
+    fn previous_state(&self, state: usize, dropped: bool) -> usize {
+        (state << 1) & (self.num_states() - 1) | dropped as usize
+    }
+
+    /// Follows the survivor path back from a state after a step, for a
+    /// number of steps, returning the state reached
+    fn trace_back(&self, decisions: &BitGrid, mut state: usize, step: usize, count: usize) -> usize {
+        for back in 0..count {
+            state = self.previous_state(state, decisions.get(state, step - back));
+        }
+        state
+    }
+
+    /// Decodes bits sent by `encode`, correcting errors with the Viterbi
+    /// algorithm. Each data bit is decided `traceback` steps after it, where
+    /// about five constraint lengths is usual. Returns None if the length
+    /// does not fit the code.
+    pub fn decode(&self, received: &BitVec, traceback: usize) -> Option<BitVec> {
+        let n = self.polynomials.len();
+        if !received.len_bits().is_multiple_of(n) || received.len_bits() / n < self.k - 1 {
+            return None;
+        }
+
+        let steps = received.len_bits() / n;
+        let top = self.k - 2;
+        let mut metrics = vec![UNREACHABLE; self.num_states()];
+        metrics[0] = 0;
+        // Whether the bit dropped on the best path into each state was set
+        let mut decisions = BitGrid::new(self.num_states(), steps);
+        let mut decoded = BitVec::new();
+
+        for step in 0..steps {
+            let symbol = (0..n).fold(0, |symbol, offset| symbol << 1 | received.get_bit(step * n + offset) as u32);
+            let mut next_metrics = vec![UNREACHABLE; self.num_states()];
+            for (state, next_metric) in next_metrics.iter_mut().enumerate() {
+                let input = (state >> top) as u32;
+                for dropped in [false, true] {
+                    let previous = self.previous_state(state, dropped);
+                    let register = input << (self.k - 1) | previous as u32;
+                    let metric = metrics[previous] + (self.outputs(register) ^ symbol).count_ones();
+                    if metric < *next_metric {
+                        *next_metric = metric;
+                        decisions.set(state, step, dropped);
+                    }
+                }
+            }
+            metrics = next_metrics;
+
+            // Decide the bit from `traceback` steps ago on the best path
+            if step >= traceback {
+                let best = (0..metrics.len()).min_by_key(|&state| metrics[state]).unwrap();
+                decoded.push_bit(self.trace_back(&decisions, best, step, traceback) >> top == 1);
+            }
+        }
+
+        // The register ends clear, so the rest follow the path from state 0
+        let decided = decoded.len_bits();
+        let mut rest = BitVec::new();
+        let mut state = 0;
+        for step in (decided..steps).rev() {
+            rest.push_bit(state >> top == 1);
+            state = self.trace_back(&decisions, state, step, 1);
+        }
+        rest.reverse();
+        decoded.extend(&rest);
+        decoded.truncate(steps - (self.k - 1));
+        Some(decoded)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 200 bits of pseudo-random data
+    fn data() -> BitVec {
+        let mut bv = BitVec::new();
+        let mut state = 0x1234_5678_u32;
+        for _ in 0..200 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            bv.push_bit(state & 1 == 1);
+        }
+        bv
+    }
+
+    #[test]
+    fn corrects_scattered_errors() {
+        let code = ConvolutionalCode::new(7, &[0o171, 0o133]);
+        let data = data();
+        let mut sent = code.encode(&data);
+        assert_eq!(sent.len_bits(), 2 * (200 + 6));
+        assert_eq!(code.decode(&sent, 35), Some(data.clone()));
+
+        for index in [3, 50, 101, 180, 260, 330, 400] {
+            sent.flip_range(index..index + 1);
+        }
+        assert_eq!(code.decode(&sent, 35), Some(data.clone()));
+        // Tracing back over the whole block
+        assert_eq!(code.decode(&sent, 1000), Some(data));
+    }
+
+    #[test]
+    fn rate_one_third_and_invalid_lengths() {
+        let code = ConvolutionalCode::new(3, &[0b111, 0b011, 0b101]);
+        let data = BitVec::from_bit_str("1100 1011 1").unwrap();
+        let mut sent = code.encode(&data);
+        assert_eq!(sent.len_bits(), 33);
+        sent.flip_range(10..11);
+        assert_eq!(code.decode(&sent, 0).map(|bits| bits.len_bits()), Some(9));
+        assert_eq!(code.decode(&sent, 10), Some(data));
+
+        assert_eq!(code.decode(&BitVec::from_bit_str("1111").unwrap(), 5), None);
+        assert_eq!(code.decode(&BitVec::from_bit_str("111").unwrap(), 5), None);
+        assert_eq!(code.decode(&code.encode(&BitVec::new()), 5), Some(BitVec::new()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn polynomial_too_wide() {
+        ConvolutionalCode::new(3, &[0b1111]);
+    }
+}
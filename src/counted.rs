@@ -0,0 +1,208 @@
+//! ## Cached range counts
+//! Counting the set bits between two positions, over and over, on a bitmap
+//! that keeps changing, as schedulers do. `count_ones_between` on a BitVec
+//! scans the range, while `CountedBitVec` keeps the count of every block in
+//! a Fenwick tree, so each query adds up a logarithmic number of blocks and
+//! scans at most one partial block at each end. Setting a bit through it
+//! updates the tree in place. A bulk change through `bits_mut` marks the
+//! counts stale, and they are rebuilt at the next query.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// Bits counted by each block
+const BLOCK_BITS: usize = 512;
+
+impl BitVec {
+    /// Returns the number of set bits from `start` up to `end`. Panics if
+    /// the range is backwards or runs past the end of the vector.
+    pub fn count_ones_between(&self, start: usize, end: usize) -> usize {
+        if start > end {
+            panic!("BitVec: range {}..{} is backwards", start, end)
+        }
+        self.count_ones_in(start..end)
+    }
+}
+
+/// A BitVec with cached block counts, for fast repeated range counts while
+/// it changes
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, CountedBitVec};
+///
+/// let mut counted = CountedBitVec::new(BitVec::from(vec![0xFF; 1000]));
+/// assert_eq!(counted.count_ones_between(100, 7000), 6900);
+/// counted.set_bit(5000, false);
+/// assert_eq!(counted.count_ones_between(100, 7000), 6899);
+///
+/// counted.bits_mut().slice_mut(..64).fill(false);
+/// assert_eq!(counted.count_ones_between(0, 128), 64);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CountedBitVec {
+    bits:  BitVec,
+    tree:  Vec<usize>,  // Fenwick tree of block counts, from 1
+    stale: bool,        // the tree no longer matches the bits
+}
+
+impl CountedBitVec {
+    /// Builds the counts over a BitVec
+    pub fn new(bits: BitVec) -> Self {
+        let mut counted = Self { bits, tree: Vec::new(), stale: true };
+        counted.rebuild();
+        counted
+    }
+
+    /// Counts every block again, in linear time
+    fn rebuild(&mut self) {
+        let blocks = self.bits.len.div_ceil(BLOCK_BITS);
+        let mut tree = vec![0; blocks + 1];
+        for block in 0..blocks {
+            let start = block * BLOCK_BITS;
+            tree[block + 1] += self.bits.count_ones_in(start..(start + BLOCK_BITS).min(self.bits.len));
+            // Each node passes its sum up to its parent
+            let parent = (block + 1) + ((block + 1) & (block + 1).wrapping_neg());
+            if parent <= blocks {
+                tree[parent] += tree[block + 1];
+            }
+        }
+        self.tree = tree;
+        self.stale = false;
+    }
+
+    /// Returns the set bits in the whole blocks before `block`
+    fn blocks_before(&self, block: usize) -> usize {
+        let mut sum = 0;
+        let mut node = block;
+        while node > 0 {
+            sum += self.tree[node];
+            node &= node - 1;
+        }
+        sum
+    }
+
+    /// Returns the set bits before an index
+    fn ones_before(&self, index: usize) -> usize {
+        let block = index / BLOCK_BITS;
+        self.blocks_before(block) + self.bits.count_ones_in(block * BLOCK_BITS..index)
+    }
+
+    /// Returns the BitVec
+    pub fn bits(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Returns the BitVec to change in bulk, marking the counts stale
+    pub fn bits_mut(&mut self) -> &mut BitVec {
+        self.stale = true;
+        &mut self.bits
+    }
+
+    /// Unwraps the counts, returning the BitVec
+    pub fn into_inner(self) -> BitVec {
+        self.bits
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// out of bounds.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.bits.get_bit(index)
+    }
+
+    /// Sets a bit, updating the count of its block. An index past the end
+    /// grows the vector, or panics, as the growth policy says.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.bits.len || self.stale {
+            self.bits.set_bit(index, value);
+            self.stale = true;
+            return;
+        }
+        if self.bits.get_bit(index) == value {
+            return;
+        }
+
+        self.bits.set_bit(index, value);
+        let mut node = index / BLOCK_BITS + 1;
+        while node < self.tree.len() {
+            if value {
+                self.tree[node] += 1;
+            } else {
+                self.tree[node] -= 1;
+            }
+            node += node & node.wrapping_neg();
+        }
+    }
+
+    /// Returns the number of set bits from `start` up to `end`, first
+    /// counting every block again if the counts are stale. Panics if the
+    /// range is backwards or runs past the end of the vector.
+    pub fn count_ones_between(&mut self, start: usize, end: usize) -> usize {
+        if start > end || end > self.bits.len {
+            panic!("BitVec: range {}..{} out of bounds for length {}", start, end, self.bits.len)
+        }
+        if self.stale {
+            self.rebuild();
+        }
+        self.ones_before(end) - self.ones_before(start)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pseudo-random pattern, so every block has a different count
+    fn pattern(len: usize) -> BitVec {
+        let mut bv = BitVec::new();
+        let mut state: u32 = 0x1234_5678;
+        for _ in 0..len {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            bv.push_bit(state & 3 == 0);
+        }
+        bv
+    }
+
+    #[test]
+    fn matches_scanning_through_changes() {
+        let mut counted = CountedBitVec::new(pattern(5000));
+        let ranges = [(0, 0), (0, 5000), (1, 511), (511, 513), (700, 4100), (4096, 5000), (2500, 2500)];
+        for step in 0..300 {
+            let index = (step * 1031) % 5000;
+            counted.set_bit(index, step % 3 != 0);
+            for &(start, end) in &ranges {
+                let expected = counted.bits().count_ones_between(start, end);
+                assert_eq!(counted.count_ones_between(start, end), expected, "{}..{} at step {}", start, end, step);
+            }
+        }
+    }
+
+    #[test]
+    fn stale_after_bulk_changes_and_growth() {
+        let mut counted = CountedBitVec::new(BitVec::from(vec![0; 200]));
+        counted.bits_mut().slice_mut(100..1500).fill(true);
+        assert_eq!(counted.count_ones_between(0, 1600), 1400);
+
+        // Growing past the end adds a block
+        counted.set_bit(1700, true);
+        assert_eq!(counted.count_ones_between(1600, 1701), 1);
+        assert_eq!(counted.bits().len_bits(), 1701);
+        assert_eq!(counted.into_inner().count_ones(), 1401);
+
+        assert_eq!(CountedBitVec::new(BitVec::new()).count_ones_between(0, 0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "backwards")]
+    fn backwards_range() {
+        BitVec::from(&[0xFF]).count_ones_between(5, 2);
+    }
+}
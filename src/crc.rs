@@ -0,0 +1,137 @@
+//! ## Cyclic redundancy checks
+//! CRCs over any range of bits, so a checksum can cover a payload that does
+//! not start or end on a byte boundary. Bits are fed in stream order, MSB
+//! first, with the first bit as the highest power of the message, as with
+//! `gf2_mod`. The register is not reflected, so for whole bytes the results
+//! match the MSB-first CRC catalogue entries, such as CRC-32/BZIP2 and
+//! CRC-16/IBM-3740.
+
+use core::ops::RangeBounds;
+
+use crate::BitVec;
+use crate::slice::{load_byte, resolve_range};
+
+/// The CRC-32 generator polynomial, without its x^32 term
+const CRC32_POLY: u32 = 0x04C1_1DB7;
+
+impl BitVec {
+    /// Runs a CRC register of `width` bits over a range, from an initial
+    /// value, a byte at a time where the range allows
+    fn crc_bits(&self, start: usize, end: usize, width: u32, poly: u64, init: u64) -> u64 {
+        let top = 1 << (width - 1);
+        let mask = u64::MAX >> (64 - width);
+        let mut crc = init & mask;
+
+        for position in (start..end).step_by(8) {
+            let count = (end - position).min(8);
+            let byte = load_byte(&self.data, position, count);
+            for shift in 0..count {
+                let bit = (byte >> (7 - shift)) & 1 == 1;
+                let feedback = (crc & top != 0) != bit;
+                crc = (crc << 1) & mask;
+                if feedback {
+                    crc ^= poly;
+                }
+            }
+        }
+        crc
+    }
+
+    /// Returns the CRC-32 of a range of bits, using the CRC-32 polynomial
+    /// with the register starting as all 1s and inverted at the end. Panics
+    /// if the range runs past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(b"123456789");
+    /// assert_eq!(bv.crc32(..), 0xFC89_1918);
+    /// // A payload starting 3 bits into the frame
+    /// assert_eq!(bv.crc32(3..70), bv.slice(3..70).to_bitvec().crc32(..));
+    /// ```
+    pub fn crc32<R: RangeBounds<usize>>(&self, range: R) -> u32 {
+        let (start, end) = resolve_range(range, self.len);
+        !(self.crc_bits(start, end, 32, CRC32_POLY as u64, u32::MAX as u64) as u32)
+    }
+
+    /// Returns a 16-bit CRC of a range of bits, with a generator polynomial,
+    /// without its x^16 term, and an initial register value. The result is
+    /// not inverted. Panics if the range runs past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE
+    /// let bv = BitVec::from(b"123456789");
+    /// assert_eq!(bv.crc16(0x1021, 0xFFFF, ..), 0x29B1);
+    /// ```
+    pub fn crc16<R: RangeBounds<usize>>(&self, poly: u16, init: u16, range: R) -> u16 {
+        let (start, end) = resolve_range(range, self.len);
+        self.crc_bits(start, end, 16, poly as u64, init as u64) as u16
+    }
+
+    /// Pushes the CRC-32 of every bit in the vector onto its end, MSB first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut frame = BitVec::from_bit_str("1011 0").unwrap();
+    /// frame.append_crc32();
+    /// assert_eq!(frame.len_bits(), 37);
+    /// assert_eq!(frame.crc32(..5) as u64, frame.slice(5..).to_u64());
+    /// ```
+    pub fn append_crc32(&mut self) {
+        let crc = self.crc32(..);
+        self.push_low_bits(crc as u64, 32);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogue_check_values() {
+        let bv = BitVec::from(b"123456789");
+        assert_eq!(bv.crc32(..), 0xFC89_1918);
+        // CRC-16/IBM-3740, CRC-16/XMODEM and CRC-16/UMTS
+        assert_eq!(bv.crc16(0x1021, 0xFFFF, ..), 0x29B1);
+        assert_eq!(bv.crc16(0x1021, 0, ..), 0x31C3);
+        assert_eq!(bv.crc16(0x8005, 0, ..), 0xFEE8);
+        assert_eq!(BitVec::new().crc32(..), 0);
+        assert_eq!(BitVec::new().crc16(0x1021, 0xFFFF, ..), 0xFFFF);
+    }
+
+    #[test]
+    fn partial_bytes() {
+        // Every unaligned range agrees with the same bits copied out aligned
+        let bv = BitVec::from(b"The quick brown fox");
+        for (start, end) in [(0, 1), (1, 9), (3, 70), (7, 152), (13, 14)] {
+            let copy = bv.slice(start..end).to_bitvec();
+            assert_eq!(bv.crc32(start..end), copy.crc32(..));
+            assert_eq!(bv.crc16(0x1021, 0x1D0F, start..end), copy.crc16(0x1021, 0x1D0F, ..));
+        }
+    }
+
+    #[test]
+    fn appended_crc_checks() {
+        let mut frame = BitVec::from(&[0xDE, 0xAD, 0xBE]);
+        frame.push_bit(true);
+        frame.append_crc32();
+        let len = frame.len_bits();
+        assert_eq!(frame.crc32(..len - 32), frame.slice(len - 32..).to_u64() as u32);
+
+        // A single flipped bit is caught
+        frame.flip_range(5..6);
+        assert_ne!(frame.crc32(..len - 32), frame.slice(len - 32..).to_u64() as u32);
+    }
+}
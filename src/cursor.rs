@@ -0,0 +1,228 @@
+//! ## Bit cursors
+//! A BitVec has one sequential reading position built in, and pushing
+//! always appends at the end. `BitCursor` keeps its own position instead,
+//! over a borrowed or owned BitVec, like `std::io::Cursor` over bytes. Any
+//! cursor can read and peek, and a cursor over a mutable BitVec can write
+//! too, overwriting bits before the end and appending past it. Values of
+//! several bits are read and written MSB first.
+
+use core::borrow::{Borrow, BorrowMut};
+
+use crate::BitVec;
+
+/// A reading and writing position over a BitVec
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitCursor, BitVec};
+///
+/// let mut writer = BitCursor::new(BitVec::new());
+/// writer.write_bits(0b101, 3);
+/// writer.pad_to_byte();
+/// writer.write_bits(0xABC, 12);
+///
+/// let bv = writer.into_inner();
+/// let mut reader = BitCursor::new(&bv);
+/// assert_eq!(reader.read_bits(3), Some(0b101));
+/// reader.align_to_byte();
+/// assert_eq!(reader.peek_bits(4), Some(0xA));
+/// assert_eq!(reader.read_bits(12), Some(0xABC));
+/// assert_eq!(reader.bits_remaining(), 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitCursor<T> {
+    inner:    T,
+    position: usize,    // index of the next bit
+}
+
+impl<T> BitCursor<T> {
+    /// Constructs a new BitCursor at the start of a BitVec
+    pub fn new(inner: T) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Get the index of the next bit to be read or written
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns a reference to the underlying BitVec
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Consumes the cursor, returning the underlying BitVec
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Borrow<BitVec>> BitCursor<T> {
+    /// Moves the cursor to an index. Returns false, without moving, if the
+    /// index is past the end of the vector.
+    pub fn set_position(&mut self, position: usize) -> bool {
+        if position > self.inner.borrow().len {
+            return false;
+        }
+        self.position = position;
+        true
+    }
+
+    /// Returns the number of bits from the cursor to the end of the vector,
+    /// or 0 if the vector has been shortened past the cursor
+    pub fn bits_remaining(&self) -> usize {
+        self.inner.borrow().len.saturating_sub(self.position)
+    }
+
+    /// Reads the next bit, or None at the end of the vector
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let bv = self.inner.borrow();
+        if self.position >= bv.len {
+            return None;
+        }
+        let bit = bv.get_bit(self.position);
+        self.position += 1;
+        Some(bit)
+    }
+
+    /// Reads the next `count` bits (up to 64) without moving the cursor.
+    /// Returns None if there are not enough bits left.
+    pub fn peek_bits(&self, count: u32) -> Option<u64> {
+        if count > u64::BITS {
+            panic!("BitVec: cannot read more than 64 bits at once")
+        }
+        if (count as usize) > self.bits_remaining() {
+            return None;
+        }
+
+        let bv = self.inner.borrow();
+        Some((self.position..self.position + count as usize)
+            .fold(0, |value, index| (value << 1) | bv.get_bit(index) as u64))
+    }
+
+    /// Reads the next `count` bits (up to 64). Returns None, without moving
+    /// the cursor, if there are not enough bits left.
+    pub fn read_bits(&mut self, count: u32) -> Option<u64> {
+        let value = self.peek_bits(count)?;
+        self.position += count as usize;
+        Some(value)
+    }
+
+    /// Moves the cursor forward to the next byte boundary, stopping at the
+    /// end of the vector
+    pub fn align_to_byte(&mut self) {
+        self.position = self.position.next_multiple_of(8).min(self.inner.borrow().len);
+    }
+}
+
+impl<T: BorrowMut<BitVec>> BitCursor<T> {
+    /// Returns a mutable reference to the underlying BitVec. The cursor
+    /// stays where it is, even if the vector is shortened past it.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Writes a bit at the cursor, overwriting the bit there or appending at
+    /// the end of the vector. If the vector has been shortened past the
+    /// cursor, the gap up to it is filled with 0s first.
+    pub fn write_bit(&mut self, value: bool) {
+        let bv = self.inner.borrow_mut();
+        if self.position < bv.len {
+            bv.set_bit(self.position, value);
+        } else {
+            bv.resize(self.position, false);
+            bv.push_bit(value);
+        }
+        self.position += 1;
+    }
+
+    /// Writes the low `count` bits (up to 64) of a value, MSB first
+    pub fn write_bits(&mut self, value: u64, count: u32) {
+        if count > u64::BITS {
+            panic!("BitVec: cannot write more than 64 bits at once")
+        }
+        for shift in (0..count).rev() {
+            self.write_bit((value >> shift) & 1 == 1);
+        }
+    }
+
+    /// Writes 0s up to the next byte boundary
+    pub fn pad_to_byte(&mut self) {
+        while !self.position.is_multiple_of(8) {
+            self.write_bit(false);
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_and_peeking() {
+        let bv = BitVec::from(&[0xA5, 0x0F]);
+        let mut cursor = BitCursor::new(&bv);
+        assert_eq!(cursor.peek_bits(4), Some(0xA));
+        assert_eq!(cursor.position(), 0);
+        assert_eq!(cursor.read_bit(), Some(true));
+        assert_eq!(cursor.read_bits(7), Some(0x25));
+        assert_eq!(cursor.read_bits(9), None);
+        assert_eq!(cursor.bits_remaining(), 8);
+        assert_eq!(cursor.read_bits(0), Some(0));
+
+        cursor.read_bit();
+        cursor.align_to_byte();
+        assert_eq!(cursor.position(), 16);
+        assert_eq!(cursor.read_bit(), None);
+        cursor.align_to_byte();
+        assert_eq!(cursor.position(), 16);
+
+        assert!(!cursor.set_position(17));
+        assert!(cursor.set_position(12));
+        assert_eq!(cursor.read_bits(4), Some(0xF));
+    }
+
+    #[test]
+    fn overwriting_and_appending() {
+        let mut bv = BitVec::from(&[0xFF]);
+        let mut cursor = BitCursor::new(&mut bv);
+        assert!(cursor.set_position(4));
+        // Half overwrites, half appends
+        cursor.write_bits(0x0A, 8);
+        assert_eq!(cursor.bits_remaining(), 0);
+        cursor.write_bit(true);
+        cursor.pad_to_byte();
+        assert_eq!(cursor.position(), 16);
+        assert_eq!(bv.as_binary(), "11110000 10101000");
+        // The BitVec's own reading position is untouched
+        assert_eq!(bv.get_read_position(), 0);
+    }
+
+    #[test]
+    fn wide_values() {
+        let mut cursor = BitCursor::new(BitVec::new());
+        cursor.write_bit(false);
+        cursor.write_bits(u64::MAX - 1, 64);
+        cursor.set_position(1);
+        assert_eq!(cursor.read_bits(64), Some(u64::MAX - 1));
+        assert_eq!(cursor.get_ref().len_bits(), 65);
+    }
+
+    #[test]
+    fn vector_shortened_past_cursor() {
+        let mut cursor = BitCursor::new(BitVec::from(&[0xFF]));
+        cursor.set_position(6);
+        cursor.get_mut().truncate(2);
+        assert_eq!(cursor.bits_remaining(), 0);
+        assert_eq!(cursor.read_bits(1), None);
+
+        // Writing fills the gap up to the cursor
+        cursor.write_bit(true);
+        assert_eq!(cursor.position(), 7);
+        assert_eq!(cursor.get_ref().to_bit_string(), "1100001");
+    }
+}
@@ -0,0 +1,163 @@
+//! ## DEFLATE bit layout
+//! The pieces of the DEFLATE format (RFC 1951) that are fiddly to get right
+//! bit by bit: the fixed Huffman codes and the 3-bit block header. DEFLATE
+//! packs bits into bytes LSB first, so the vectors are best built with
+//! `with_order(BitOrder::Lsb0)` or read with `from_with_order`. In stream
+//! order, Huffman codes go most significant bit first, as `extend` pushes
+//! them, while every other field goes least significant bit first, as
+//! `push_lsb_first` pushes it.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// How the data of a DEFLATE block is coded, from the BTYPE field
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeflateBlockType {
+    /// Uncompressed bytes, after padding to a byte boundary (00)
+    Stored,
+    /// The fixed Huffman codes (01)
+    Fixed,
+    /// Huffman codes given at the start of the block (10)
+    Dynamic,
+}
+
+impl BitVec {
+    /// Returns the fixed Huffman codes of the 288 literal/length symbols:
+    /// 8 bits for 0 to 143, 9 bits for 144 to 255, 7 bits for 256 to 279,
+    /// and 8 bits for 280 to 287
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let codes = BitVec::deflate_fixed_literal_codes();
+    /// // End of block
+    /// assert_eq!(codes[256].to_bit_string(), "0000000");
+    /// assert_eq!(codes[0].to_bit_string(), "00110000");
+    /// ```
+    pub fn deflate_fixed_literal_codes() -> Vec<BitVec> {
+        let mut lengths = alloc::vec![8; 144];
+        lengths.resize(256, 9);
+        lengths.resize(280, 7);
+        lengths.resize(288, 8);
+        BitVec::canonical_codes(&lengths)
+    }
+
+    /// Returns the fixed Huffman codes of the 32 distance symbols, each the
+    /// symbol in 5 bits. Symbols 30 and 31 never appear in valid data.
+    pub fn deflate_fixed_distance_codes() -> Vec<BitVec> {
+        BitVec::canonical_codes(&[5; 32])
+    }
+
+    /// Pushes the low `count` bits of a value, least significant first, as
+    /// DEFLATE packs every field but the Huffman codes. Panics if the count
+    /// is more than 64.
+    pub fn push_lsb_first(&mut self, value: u64, count: u32) {
+        if count > 64 {
+            panic!("BitVec: cannot push more than 64 bits of a value")
+        }
+        for shift in 0..count {
+            self.push_bit(value >> shift & 1 == 1);
+        }
+    }
+
+    /// Reads `count` bits from the reading position as a number, least
+    /// significant first, or None if fewer remain. Panics if the count is
+    /// more than 64.
+    pub fn read_lsb_first(&mut self, count: u32) -> Option<u64> {
+        if count > 64 {
+            panic!("BitVec: cannot read more than 64 bits into a value")
+        }
+        if self.len - self.get_read_position() < count as usize {
+            return None;
+        }
+        Some((0..count).fold(0, |value, shift| value | (self.read_bit_seq().unwrap() as u64) << shift))
+    }
+
+    /// Pushes a block header: BFINAL, set on the last block of the stream,
+    /// then the 2 bits of BTYPE
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitOrder, BitVec, DeflateBlockType};
+    ///
+    /// let mut bv = BitVec::with_order(BitOrder::Lsb0);
+    /// bv.push_deflate_header(true, DeflateBlockType::Fixed);
+    /// // End of block straight away
+    /// bv.extend(&BitVec::deflate_fixed_literal_codes()[256]);
+    /// assert_eq!(bv.read_byte(), Some(0x03));
+    /// ```
+    pub fn push_deflate_header(&mut self, last: bool, block_type: DeflateBlockType) {
+        self.push_bit(last);
+        self.push_lsb_first(block_type as u64, 2);
+    }
+
+    /// Reads a block header from the reading position, returning whether it
+    /// is the last block and its type, or None if fewer than 3 bits remain
+    /// or the type is the reserved 11. Either way the 3 bits are read.
+    pub fn read_deflate_header(&mut self) -> Option<(bool, DeflateBlockType)> {
+        let header = self.read_lsb_first(3)?;
+        let block_type = match header >> 1 {
+            0 => DeflateBlockType::Stored,
+            1 => DeflateBlockType::Fixed,
+            2 => DeflateBlockType::Dynamic,
+            _ => return None,
+        };
+        Some((header & 1 == 1, block_type))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitOrder;
+
+    #[test]
+    fn fixed_codes() {
+        let codes = BitVec::deflate_fixed_literal_codes();
+        assert_eq!(codes.len(), 288);
+        // The ranges of RFC 1951 section 3.2.6
+        let ranges = [
+            (0, 143, "00110000", "10111111"),
+            (144, 255, "110010000", "111111111"),
+            (256, 279, "0000000", "0010111"),
+            (280, 287, "11000000", "11000111"),
+        ];
+        for (symbol, end, first, last) in ranges {
+            assert_eq!(codes[symbol].to_bit_string(), first);
+            assert_eq!(codes[end].to_bit_string(), last);
+        }
+
+        let distances = BitVec::deflate_fixed_distance_codes();
+        assert_eq!(distances.len(), 32);
+        assert_eq!(distances[19].to_bit_string(), "10011");
+    }
+
+    #[test]
+    fn headers_and_fields() {
+        let mut bv = BitVec::with_order(BitOrder::Lsb0);
+        bv.push_deflate_header(false, DeflateBlockType::Stored);
+        bv.push_deflate_header(false, DeflateBlockType::Dynamic);
+        bv.push_deflate_header(true, DeflateBlockType::Fixed);
+        bv.push_lsb_first(0b1101, 4);
+        bv.push_lsb_first(0b111, 3);
+        assert_eq!(bv.read_byte(), Some(0xE0));
+        assert_eq!(bv.read_byte(), Some(0xFA));
+
+        let mut bv = BitVec::from_with_order(&[0xE0, 0xFA], BitOrder::Lsb0);
+        assert_eq!(bv.read_deflate_header(), Some((false, DeflateBlockType::Stored)));
+        assert_eq!(bv.read_deflate_header(), Some((false, DeflateBlockType::Dynamic)));
+        assert_eq!(bv.read_deflate_header(), Some((true, DeflateBlockType::Fixed)));
+        assert_eq!(bv.read_lsb_first(4), Some(0b1101));
+        // The reserved type
+        assert_eq!(bv.read_deflate_header(), None);
+        assert_eq!(bv.read_lsb_first(1), None);
+        assert_eq!(bv.read_lsb_first(0), Some(0));
+    }
+}
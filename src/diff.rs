@@ -0,0 +1,211 @@
+//! ## Diffs
+//! The difference between two versions of a vector as the runs of bits that
+//! changed, so a large bitmap can be kept in step by sending only its
+//! changes. A diff also carries the new length: bits past the end of a
+//! vector that grows count as clear before the change, and bits past the end
+//! of one that shrinks are simply dropped.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::BitVec;
+
+/// The runs of bits that differ between two vectors, and the length of the
+/// newer one, created by `BitVec::diff`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitDiff {
+    len:  usize,                // length of the vector after the diff
+    runs: Vec<Range<usize>>,    // maximal runs of changed bits, in order
+}
+
+impl BitDiff {
+    /// Builds a diff from the length of the newer vector and the runs of
+    /// bits that changed. Returns None if the runs are empty, out of order,
+    /// touching or past the length.
+    pub fn from_runs(len: usize, runs: Vec<Range<usize>>) -> Option<Self> {
+        let mut end = 0;
+        for (index, run) in runs.iter().enumerate() {
+            if run.start >= run.end || run.end > len || index > 0 && run.start <= end {
+                return None;
+            }
+            end = run.end;
+        }
+        Some(Self { len, runs })
+    }
+
+    /// Get the length of the vector after the diff is applied
+    pub fn new_len(&self) -> usize {
+        self.len
+    }
+
+    /// Get the runs of bits that changed
+    pub fn runs(&self) -> &[Range<usize>] {
+        &self.runs
+    }
+
+    /// Returns the number of bits that changed
+    pub fn changed_bits(&self) -> usize {
+        self.runs.iter().map(|run| run.len()).sum()
+    }
+
+    /// Returns true if no bits changed, though the length may have
+    pub fn is_unchanged(&self) -> bool {
+        self.runs.is_empty()
+    }
+}
+
+impl BitVec {
+    /// Returns the runs of bits that change to make this vector into
+    /// another, comparing a byte at a time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let old = BitVec::from_bit_str("1100 1010 01").unwrap();
+    /// let new = BitVec::from_bit_str("1111 1010 0100 1").unwrap();
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.runs(), &[2..4, 12..13]);
+    ///
+    /// let mut synced = old.clone();
+    /// synced.apply_diff(&diff);
+    /// assert_eq!(synced, new);
+    /// ```
+    pub fn diff(&self, other: &BitVec) -> BitDiff {
+        let mut changes = BitVec::with_capacity(other.len);
+        changes.resize(other.len, false);
+        for (index, byte) in changes.data.iter_mut().enumerate() {
+            *byte = self.data.get(index).copied().unwrap_or(0) ^ other.data[index];
+        }
+        // Bits this vector has past the end of the other are dropped
+        changes.clear_padding();
+
+        let mut runs = Vec::new();
+        let mut start = 0;
+        for (changed, run_len) in changes.iter_runs() {
+            if changed {
+                runs.push(start..start + run_len);
+            }
+            start += run_len;
+        }
+        BitDiff { len: other.len, runs }
+    }
+
+    /// Applies a diff taken from this vector, setting its length and
+    /// flipping the bits that changed. Panics if a run is past the new
+    /// length.
+    pub fn apply_diff(&mut self, diff: &BitDiff) {
+        self.resize(diff.len, false);
+        for run in &diff.runs {
+            self.flip_range(run.clone());
+        }
+    }
+
+    /// Compares two vectors block by block, returning a bit per block of
+    /// `block_bits`, set where the blocks match, so a sync can send only the
+    /// blocks whose bit is clear. The blocks cover the longer vector, and a
+    /// block running past the end of only one of them does not match.
+    /// Panics if `block_bits` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let old = BitVec::from(&[0x00, 0xFF, 0x0F]);
+    /// let new = BitVec::from(&[0x00, 0xFE, 0x0F, 0x01]);
+    /// assert_eq!(old.block_equality(&new, 8).to_bit_string(), "1010");
+    /// ```
+    pub fn block_equality(&self, other: &BitVec, block_bits: usize) -> BitVec {
+        if block_bits == 0 {
+            panic!("BitVec: blocks must hold at least one bit")
+        }
+
+        let len = self.len.max(other.len);
+        let mut equal = BitVec::with_capacity(len.div_ceil(block_bits));
+        for start in (0..len).step_by(block_bits) {
+            let end = start + block_bits;
+            let ours = self.slice(start.min(self.len)..end.min(self.len));
+            let theirs = other.slice(start.min(other.len)..end.min(other.len));
+            equal.push_bit(ours == theirs);
+        }
+        equal
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_and_apply() {
+        let old = BitVec::from(&[0x00, 0xFF, 0x0F, 0xAA]);
+        let mut new = old.clone();
+        new.set_range(4..12, false);
+        new.flip_range(20..21);
+        let diff = old.diff(&new);
+        assert_eq!(diff.runs(), &[8..12, 20..21]);
+        assert_eq!(diff.changed_bits(), 5);
+        assert_eq!(diff.new_len(), 32);
+
+        let mut patched = old.clone();
+        patched.apply_diff(&diff);
+        assert_eq!(patched, new);
+        assert!(old.diff(&old).is_unchanged());
+    }
+
+    #[test]
+    fn length_changes() {
+        let long = BitVec::from_bit_str("1011 0011 1").unwrap();
+        let short = BitVec::from_bit_str("1010").unwrap();
+
+        let shrink = long.diff(&short);
+        assert_eq!(shrink.runs().len(), 1);
+        assert_eq!(shrink.runs()[0], 3..4);
+        let mut patched = long.clone();
+        patched.apply_diff(&shrink);
+        assert_eq!(patched, short);
+
+        let grow = short.diff(&long);
+        let mut patched = short.clone();
+        patched.apply_diff(&grow);
+        assert_eq!(patched, long);
+
+        // Only the length changes
+        let mut patched = BitVec::from_bit_str("10").unwrap();
+        let diff = patched.diff(&BitVec::from_bit_str("1000").unwrap());
+        assert!(diff.is_unchanged());
+        patched.apply_diff(&diff);
+        assert_eq!(patched.to_bit_string(), "1000");
+    }
+
+    #[test]
+    fn runs_are_checked() {
+        assert!(BitDiff::from_runs(10, vec![1..3, 5..10]).is_some());
+        assert!(BitDiff::from_runs(10, vec![1..3, 3..4]).is_none());
+        assert!(BitDiff::from_runs(10, vec![5..6, 1..3]).is_none());
+        assert!(BitDiff::from_runs(10, vec![0..1, 2..2]).is_none());
+        assert!(BitDiff::from_runs(10, vec![0..1, 8..11]).is_none());
+    }
+
+    #[test]
+    fn block_equality() {
+        let old = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0x55]);
+        let mut new = old.clone();
+        new.flip_range(13..14);
+        assert_eq!(old.block_equality(&new, 8).to_bit_string(), "10111");
+        assert_eq!(old.block_equality(&new, 12).to_bit_string(), "1011");
+        assert_eq!(old.block_equality(&old, 7).count_zeros(), 0);
+
+        // A ragged tail matches only if both vectors end in the same place
+        let mut longer = old.clone();
+        longer.push_bit(false);
+        assert_eq!(old.block_equality(&longer, 16).to_bit_string(), "110");
+        assert_eq!(old.block_equality(&longer, 20).to_bit_string(), "110");
+        assert_eq!(BitVec::new().block_equality(&BitVec::new(), 8).len_bits(), 0);
+    }
+}
@@ -0,0 +1,130 @@
+//! ## Length-preserving text encodings
+//! Base64 and hex strings of a BitVec for JSON APIs and other text formats.
+//! Both encode the bytes of `to_bytes_with_len`, so the LEB128 header keeps
+//! the exact bit length through the round trip. For the bare digits, without
+//! a header, see `to_hex_string`.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{BitVec, BitVecError};
+
+/// The standard Base64 alphabet
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl BitVec {
+    /// Encodes the vector and its length as standard Base64, with padding
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1111 0000 1010 1").unwrap();
+    /// assert_eq!(bv.to_base64(), "DfCo");
+    /// assert_eq!(BitVec::from_base64("DfCo"), Ok(bv));
+    /// ```
+    pub fn to_base64(&self) -> String {
+        let bytes = self.to_bytes_with_len();
+        let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for group in bytes.chunks(3) {
+            let value = group.iter().enumerate().fold(0u32, |value, (index, &byte)| value | (byte as u32) << (16 - 8 * index));
+            for index in 0..4 {
+                if index <= group.len() {
+                    encoded.push(BASE64[(value >> (18 - 6 * index) & 0x3F) as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Decodes a vector written by `to_base64`. Padding is optional, and
+    /// whitespace is skipped. Returns an error for a character outside the
+    /// standard alphabet, or if the length header does not match the data.
+    pub fn from_base64(s: &str) -> Result<Self, BitVecError> {
+        let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+        let (mut value, mut bits) = (0u32, 0);
+        for (position, digit) in s.trim_end_matches(|c: char| c == '=' || c.is_whitespace()).char_indices() {
+            if digit.is_whitespace() {
+                continue;
+            }
+            let Some(index) = BASE64.iter().position(|&c| c as char == digit) else {
+                return Err(BitVecError::InvalidDigit { position, digit });
+            };
+            value = value << 6 | index as u32;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((value >> bits) as u8);
+            }
+        }
+
+        BitVec::from_bytes_with_len(&bytes).ok_or(BitVecError::InvalidEncoding)
+    }
+
+    /// Encodes the vector and its length as lower case hex digits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1111 0000 1010 1").unwrap();
+    /// assert_eq!(bv.to_hex(), "0df0a8");
+    /// assert_eq!(BitVec::from_hex("0df0a8"), Ok(bv));
+    /// ```
+    pub fn to_hex(&self) -> String {
+        BitVec::from(self.to_bytes_with_len()).to_hex_string()
+    }
+
+    /// Decodes a vector written by `to_hex`, with the same prefix and
+    /// separators as `from_hex_str`. Returns an error for a character that
+    /// is not a hex digit, or if the length header does not match the data.
+    pub fn from_hex(s: &str) -> Result<Self, BitVecError> {
+        let digits = BitVec::from_hex_str(s)?;
+        if !digits.len.is_multiple_of(8) {
+            return Err(BitVecError::InvalidEncoding);
+        }
+
+        BitVec::from_bytes_with_len(&digits.data).ok_or(BitVecError::InvalidEncoding)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_keep_length() {
+        for len in [0, 1, 7, 8, 9, 15, 16, 17, 200] {
+            let bv = BitVec::repeat(&BitVec::from_bit_str("1101 0").unwrap(), 40).slice(..len).to_bitvec();
+            assert_eq!(BitVec::from_base64(&bv.to_base64()), Ok(bv.clone()), "length {}", len);
+            assert_eq!(BitVec::from_hex(&bv.to_hex()), Ok(bv.clone()), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn base64_padding() {
+        // Headers of one byte, so 2, 3 and 4 encoded bytes
+        let bv = BitVec::from(&[0xFF]);
+        assert_eq!(bv.to_base64(), "CP8=");
+        assert_eq!(BitVec::from(&[0xFF, 0x00]).to_base64(), "EP8A");
+        assert_eq!(BitVec::new().to_base64(), "AA==");
+        assert_eq!(BitVec::from_base64("CP8"), Ok(bv.clone()));
+        assert_eq!(BitVec::from_base64(" CP 8=\n"), Ok(bv));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(BitVec::from_base64("CP*="), Err(BitVecError::InvalidDigit { position: 2, digit: '*' }));
+        // Header of 9 bits with only one byte of data
+        assert_eq!(BitVec::from_base64("Cf8="), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_hex("09ff"), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_hex("08f"), Err(BitVecError::InvalidEncoding));
+        assert!(matches!(BitVec::from_hex("08fg"), Err(BitVecError::InvalidDigit { digit: 'g', .. })));
+    }
+}
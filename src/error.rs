@@ -0,0 +1,72 @@
+//! ## Errors
+//! The error type returned by the fallible (`try_`) variants of the BitVec
+//! API.
+
+use core::{error, fmt, str};
+
+/// Errors from fallible BitVec operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitVecError {
+    /// A bit index at or beyond the length of the vector
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Removing from a vector with no bits
+    Empty,
+    /// Reading more bits than remain after the reading position
+    NotEnoughBits { requested: usize, available: usize },
+    /// The data is not valid UTF-8
+    InvalidUtf8(str::Utf8Error),
+    /// A character in a bit or hex string that is not a valid digit
+    InvalidDigit { position: usize, digit: char },
+    /// A vector whose length does not match the length required
+    LengthMismatch { expected: usize, found: usize },
+    /// A value with more bits than the field it is packed into
+    ValueTooWide { value: u64, width: u32 },
+    /// Encoded data with a length header that does not match the data
+    InvalidEncoding,
+    /// A push after the size callback of a builder asked it to stop
+    Stopped { len: usize },
+    /// Encoded data that cannot be decoded, failing at this bit offset
+    Undecodable { offset: usize },
+}
+
+impl fmt::Display for BitVecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds for length {}", index, len)
+            }
+            Self::Empty => write!(f, "vector is empty"),
+            Self::NotEnoughBits { requested, available } => {
+                write!(f, "requested {} bits but only {} remain", requested, available)
+            }
+            Self::InvalidUtf8(e) => write!(f, "invalid UTF-8: {}", e),
+            Self::InvalidDigit { position, digit } => {
+                write!(f, "invalid digit {:?} at position {}", digit, position)
+            }
+            Self::LengthMismatch { expected, found } => {
+                write!(f, "expected a length of {} bits but found {}", expected, found)
+            }
+            Self::ValueTooWide { value, width } => {
+                write!(f, "value {} does not fit in {} bits", value, width)
+            }
+            Self::InvalidEncoding => write!(f, "length header does not match the encoded data"),
+            Self::Stopped { len } => write!(f, "builder stopped at {} bits", len),
+            Self::Undecodable { offset } => write!(f, "cannot decode the data at bit {}", offset),
+        }
+    }
+}
+
+impl error::Error for BitVecError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<str::Utf8Error> for BitVecError {
+    fn from(e: str::Utf8Error) -> Self {
+        Self::InvalidUtf8(e)
+    }
+}
@@ -0,0 +1,195 @@
+//! ## Fault campaigns
+//! Simulated single event upsets: each bit of a packed blob, such as a
+//! firmware configuration, is flipped in turn, a check supplied by the
+//! caller says what the fault did, and the outcomes are gathered into a
+//! report of which bits are sensitive. Every bit can be tried, or a chosen
+//! or randomly sampled subset of them, behind the `rand` feature, when
+//! there are too many to try them all.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::BitVec;
+
+/// What a single flipped bit did
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FaultOutcome {
+    /// The fault had no visible effect
+    Masked,
+    /// The fault was caught, by a checksum or a sanity check
+    Detected,
+    /// The fault changed the result without being caught
+    Silent,
+}
+
+/// The outcomes of a fault campaign, by bit
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, FaultOutcome};
+///
+/// // Only the top 4 bits are used, and bit 0 is checked
+/// let config = BitVec::from(&[0xA5]);
+/// let report = config.fault_campaign(|index, _| match index {
+///     0 => FaultOutcome::Detected,
+///     1..4 => FaultOutcome::Silent,
+///     _ => FaultOutcome::Masked,
+/// });
+/// assert_eq!(report.count(FaultOutcome::Silent), 3);
+/// assert_eq!(report.sensitivity(), 0.5);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FaultReport {
+    outcomes: Vec<(usize, FaultOutcome)>,   // in the order tried
+}
+
+impl FaultReport {
+    /// Get the number of faults tried
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Checks if no faults were tried
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    /// Returns each bit flipped with its outcome, in the order tried
+    pub fn outcomes(&self) -> &[(usize, FaultOutcome)] {
+        &self.outcomes
+    }
+
+    /// Get the number of faults with an outcome
+    pub fn count(&self, outcome: FaultOutcome) -> usize {
+        self.outcomes.iter().filter(|&&(_, seen)| seen == outcome).count()
+    }
+
+    /// Returns the bits whose fault had an outcome, in the order tried
+    pub fn bits_with(&self, outcome: FaultOutcome) -> Vec<usize> {
+        self.outcomes.iter().filter(|&&(_, seen)| seen == outcome).map(|&(index, _)| index).collect()
+    }
+
+    /// Returns the fraction of the faults that were not masked, or 0 if
+    /// none were tried
+    pub fn sensitivity(&self) -> f64 {
+        match self.outcomes.len() {
+            0 => 0.0,
+            len => (len - self.count(FaultOutcome::Masked)) as f64 / len as f64,
+        }
+    }
+}
+
+impl BitVec {
+    /// Flips each of the given bits in turn, calling `check` with the index
+    /// and the faulty vector, and restoring the bit after. Panics if an
+    /// index is out of bounds.
+    pub fn fault_campaign_at<I, F>(&self, indices: I, mut check: F) -> FaultReport
+    where
+        I: IntoIterator<Item = usize>,
+        F: FnMut(usize, &BitVec) -> FaultOutcome,
+    {
+        let mut faulty = self.clone();
+        let mut outcomes = Vec::new();
+        for index in indices {
+            if index >= self.len {
+                panic!("BitVec: index {} out of bounds for length {}", index, self.len)
+            }
+            faulty.flip_range(index..index + 1);
+            outcomes.push((index, check(index, &faulty)));
+            faulty.flip_range(index..index + 1);
+        }
+        FaultReport { outcomes }
+    }
+
+    /// Flips every bit in turn, calling `check` with the index and the
+    /// faulty vector
+    pub fn fault_campaign<F>(&self, check: F) -> FaultReport
+    where
+        F: FnMut(usize, &BitVec) -> FaultOutcome,
+    {
+        self.fault_campaign_at(0..self.len, check)
+    }
+
+    /// Flips `samples` bits chosen uniformly at random, with repeats, in
+    /// turn, calling `check` with the index and the faulty vector. Panics
+    /// if there are samples to take from an empty vector.
+    #[cfg(feature = "rand")]
+    pub fn sampled_fault_campaign<R, F>(&self, rng: &mut R, samples: usize, check: F) -> FaultReport
+    where
+        R: Rng + ?Sized,
+        F: FnMut(usize, &BitVec) -> FaultOutcome,
+    {
+        if samples > 0 && self.len == 0 {
+            panic!("BitVec: cannot sample faults from an empty vector")
+        }
+        let indices: Vec<usize> = (0..samples).map(|_| rng.random_range(0..self.len)).collect();
+        self.fault_campaign_at(indices, check)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A blob of one data byte and its parity bit
+    fn parity_check(_: usize, blob: &BitVec) -> FaultOutcome {
+        if blob.count_ones() % 2 == 1 {
+            FaultOutcome::Detected
+        } else if blob.slice(0..8).to_u64() == 0x3C {
+            FaultOutcome::Masked
+        } else {
+            FaultOutcome::Silent
+        }
+    }
+
+    #[test]
+    fn every_bit() {
+        let blob = BitVec::from_bit_str("0011 1100 0").unwrap();
+        let report = blob.fault_campaign(parity_check);
+        assert_eq!(report.len(), 9);
+        assert_eq!(report.count(FaultOutcome::Detected), 9);
+        assert_eq!(report.sensitivity(), 1.0);
+
+        // Each fault sees only its own bit flipped
+        let mut seen = Vec::new();
+        blob.fault_campaign(|index, faulty| {
+            seen.push(faulty.hamming_distance(&blob).unwrap());
+            if index == 8 { FaultOutcome::Masked } else { FaultOutcome::Silent }
+        });
+        assert_eq!(seen, vec![1; 9]);
+    }
+
+    #[test]
+    fn chosen_bits() {
+        let blob = BitVec::from(&[0x3C]);
+        let report = blob.fault_campaign_at([7, 2, 7], |index, _| {
+            if index == 7 { FaultOutcome::Masked } else { FaultOutcome::Silent }
+        });
+        assert_eq!(report.outcomes(), &[(7, FaultOutcome::Masked), (2, FaultOutcome::Silent), (7, FaultOutcome::Masked)]);
+        assert_eq!(report.bits_with(FaultOutcome::Masked), vec![7, 7]);
+        assert!((report.sensitivity() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(BitVec::new().fault_campaign(parity_check), FaultReport::default());
+        assert_eq!(FaultReport::default().sensitivity(), 0.0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sampled_bits() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let blob = BitVec::from(vec![0; 100]);
+        let mut rng = SmallRng::seed_from_u64(5);
+        let report = blob.sampled_fault_campaign(&mut rng, 50, |_, faulty| {
+            assert_eq!(faulty.count_ones(), 1);
+            FaultOutcome::Silent
+        });
+        assert_eq!(report.len(), 50);
+        assert!(report.outcomes().iter().all(|&(index, _)| index < 800));
+    }
+}
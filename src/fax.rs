@@ -0,0 +1,391 @@
+//! ## CCITT fax decoding
+//! Decoding of 1-bit scanlines compressed with the ITU-T T.4 and T.6 fax
+//! codecs, as found in fax transmissions and TIFF files: Modified Huffman
+//! (Group 3, one-dimensional), Modified READ (Group 3, two-dimensional),
+//! and Modified Modified READ (Group 4).
+//!
+//! Each row is a series of alternating white and black runs, starting with
+//! white, coded with the run length tables. The two-dimensional modes code
+//! each row against the one above it instead, by where its colour changes
+//! relative to those of the reference row. Black pixels are decoded as set
+//! bits, and the rows are returned in one BitVec, row after row, as with
+//! `from_packed_rows`.
+
+use crate::{BitVec, VarLenCodeTable};
+
+/// White run length codes, terminating codes then make-up codes
+const WHITE_CODES: [(u16, &str); 91] = [
+    (0, "00110101"), (1, "000111"), (2, "0111"), (3, "1000"),
+    (4, "1011"), (5, "1100"), (6, "1110"), (7, "1111"),
+    (8, "10011"), (9, "10100"), (10, "00111"), (11, "01000"),
+    (12, "001000"), (13, "000011"), (14, "110100"), (15, "110101"),
+    (16, "101010"), (17, "101011"), (18, "0100111"), (19, "0001100"),
+    (20, "0001000"), (21, "0010111"), (22, "0000011"), (23, "0000100"),
+    (24, "0101000"), (25, "0101011"), (26, "0010011"), (27, "0100100"),
+    (28, "0011000"), (29, "00000010"), (30, "00000011"), (31, "00011010"),
+    (32, "00011011"), (33, "00010010"), (34, "00010011"), (35, "00010100"),
+    (36, "00010101"), (37, "00010110"), (38, "00010111"), (39, "00101000"),
+    (40, "00101001"), (41, "00101010"), (42, "00101011"), (43, "00101100"),
+    (44, "00101101"), (45, "00000100"), (46, "00000101"), (47, "00001010"),
+    (48, "00001011"), (49, "01010010"), (50, "01010011"), (51, "01010100"),
+    (52, "01010101"), (53, "00100100"), (54, "00100101"), (55, "01011000"),
+    (56, "01011001"), (57, "01011010"), (58, "01011011"), (59, "01001010"),
+    (60, "01001011"), (61, "00110010"), (62, "00110011"), (63, "00110100"),
+    (64, "11011"), (128, "10010"), (192, "010111"), (256, "0110111"),
+    (320, "00110110"), (384, "00110111"), (448, "01100100"), (512, "01100101"),
+    (576, "01101000"), (640, "01100111"), (704, "011001100"), (768, "011001101"),
+    (832, "011010010"), (896, "011010011"), (960, "011010100"), (1024, "011010101"),
+    (1088, "011010110"), (1152, "011010111"), (1216, "011011000"), (1280, "011011001"),
+    (1344, "011011010"), (1408, "011011011"), (1472, "010011000"), (1536, "010011001"),
+    (1600, "010011010"), (1664, "011000"), (1728, "010011011"),
+];
+
+/// Black run length codes, terminating codes then make-up codes
+const BLACK_CODES: [(u16, &str); 91] = [
+    (0, "0000110111"), (1, "010"), (2, "11"), (3, "10"),
+    (4, "011"), (5, "0011"), (6, "0010"), (7, "00011"),
+    (8, "000101"), (9, "000100"), (10, "0000100"), (11, "0000101"),
+    (12, "0000111"), (13, "00000100"), (14, "00000111"), (15, "000011000"),
+    (16, "0000010111"), (17, "0000011000"), (18, "0000001000"), (19, "00001100111"),
+    (20, "00001101000"), (21, "00001101100"), (22, "00000110111"), (23, "00000101000"),
+    (24, "00000010111"), (25, "00000011000"), (26, "000011001010"), (27, "000011001011"),
+    (28, "000011001100"), (29, "000011001101"), (30, "000001101000"), (31, "000001101001"),
+    (32, "000001101010"), (33, "000001101011"), (34, "000011010010"), (35, "000011010011"),
+    (36, "000011010100"), (37, "000011010101"), (38, "000011010110"), (39, "000011010111"),
+    (40, "000001101100"), (41, "000001101101"), (42, "000011011010"), (43, "000011011011"),
+    (44, "000001010100"), (45, "000001010101"), (46, "000001010110"), (47, "000001010111"),
+    (48, "000001100100"), (49, "000001100101"), (50, "000001010010"), (51, "000001010011"),
+    (52, "000000100100"), (53, "000000110111"), (54, "000000111000"), (55, "000000100111"),
+    (56, "000000101000"), (57, "000001011000"), (58, "000001011001"), (59, "000000101011"),
+    (60, "000000101100"), (61, "000001011010"), (62, "000001100110"), (63, "000001100111"),
+    (64, "0000001111"), (128, "000011001000"), (192, "000011001001"), (256, "000001011011"),
+    (320, "000000110011"), (384, "000000110100"), (448, "000000110101"), (512, "0000001101100"),
+    (576, "0000001101101"), (640, "0000001001010"), (704, "0000001001011"), (768, "0000001001100"),
+    (832, "0000001001101"), (896, "0000001110010"), (960, "0000001110011"), (1024, "0000001110100"),
+    (1088, "0000001110101"), (1152, "0000001110110"), (1216, "0000001110111"), (1280, "0000001010010"),
+    (1344, "0000001010011"), (1408, "0000001010100"), (1472, "0000001010101"), (1536, "0000001011010"),
+    (1600, "0000001011011"), (1664, "0000001100100"), (1728, "0000001100101"),
+];
+
+/// Extended make-up codes, shared by both colours
+const EXTENDED_CODES: [(u16, &str); 13] = [
+    (1792, "00000001000"), (1856, "00000001100"), (1920, "00000001101"), (1984, "000000010010"),
+    (2048, "000000010011"), (2112, "000000010100"), (2176, "000000010101"), (2240, "000000010110"),
+    (2304, "000000010111"), (2368, "000000011100"), (2432, "000000011101"), (2496, "000000011110"),
+    (2560, "000000011111"),
+];
+
+/// Two-dimensional coding modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Mode {
+    /// Skip past the next two changes of the reference row
+    Pass,
+    /// Two runs coded as in one-dimensional rows
+    Horizontal,
+    /// A change offset from the next change of the reference row
+    Vertical(isize),
+}
+
+/// Two-dimensional mode codes
+const MODE_CODES: [(Mode, &str); 9] = [
+    (Mode::Pass, "0001"), (Mode::Horizontal, "001"),
+    (Mode::Vertical(0), "1"), (Mode::Vertical(1), "011"),
+    (Mode::Vertical(2), "000011"), (Mode::Vertical(3), "0000011"),
+    (Mode::Vertical(-1), "010"), (Mode::Vertical(-2), "000010"),
+    (Mode::Vertical(-3), "0000010"),
+];
+
+/// The code tables, along with the row geometry
+struct FaxDecoder {
+    white: VarLenCodeTable<u16>,
+    black: VarLenCodeTable<u16>,
+    modes: VarLenCodeTable<Mode>,
+    width: usize,
+}
+
+impl FaxDecoder {
+    fn new(width: usize) -> Self {
+        let table = |codes: &[(u16, &str)]| {
+            VarLenCodeTable::new(
+                codes.iter()
+                    .chain(&EXTENDED_CODES)
+                    .map(|&(run, code)| (run, BitVec::from_bit_str(code).unwrap()))
+            ).unwrap()
+        };
+
+        Self {
+            white: table(&WHITE_CODES),
+            black: table(&BLACK_CODES),
+            modes: VarLenCodeTable::new(
+                MODE_CODES.iter().map(|&(mode, code)| (mode, BitVec::from_bit_str(code).unwrap()))
+            ).unwrap(),
+            width,
+        }
+    }
+
+    /// Reads a run length, made of any make-up codes and a terminating code
+    fn read_run(&self, bits: &mut BitVec, black: bool) -> Option<usize> {
+        let table = if black { &self.black } else { &self.white };
+        let mut run = 0;
+        loop {
+            let length = *table.decode_symbol(bits)? as usize;
+            run += length;
+            if length < 64 {
+                return Some(run);
+            }
+        }
+    }
+
+    /// Skips an EOL code, 11 or more 0s then a 1, including any fill bits.
+    /// Returns false, without moving the reading position, if there is none.
+    fn skip_eol(&self, bits: &mut BitVec) -> bool {
+        let start = bits.get_read_position();
+        let mut zeros = 0;
+        while let Some(bit) = bits.read_bit_seq() {
+            if bit == 1 && zeros >= 11 {
+                return true;
+            }
+            if bit == 1 {
+                break;
+            }
+            zeros += 1;
+        }
+
+        bits.set_read_position(start);
+        false
+    }
+
+    /// Decodes a one-dimensional row into the positions where the colour
+    /// changes, starting from white
+    fn decode_1d(&self, bits: &mut BitVec) -> Option<Vec<usize>> {
+        let mut changes = Vec::new();
+        let mut a0 = 0;
+        let mut black = false;
+        while a0 < self.width {
+            a0 += self.read_run(bits, black)?;
+            if a0 > self.width {
+                return None;
+            }
+            changes.push(a0);
+            black = !black;
+        }
+
+        Some(changes)
+    }
+
+    /// Decodes a two-dimensional row against the changes of the reference
+    /// row
+    fn decode_2d(&self, bits: &mut BitVec, reference: &[usize]) -> Option<Vec<usize>> {
+        let mut changes = Vec::new();
+        // None is the imaginary white pixel before the start of the row
+        let mut a0: Option<usize> = None;
+        let mut black = false;
+
+        while a0.is_none_or(|a0| a0 < self.width) {
+            // The next change of the reference row to the right of a0 that
+            // is to the opposite colour, and the change after it
+            let mut next = reference.partition_point(|&change| a0.is_some_and(|a0| change <= a0));
+            if next % 2 != black as usize {
+                next += 1;
+            }
+            let b1 = reference.get(next).copied().unwrap_or(self.width).min(self.width);
+            let b2 = reference.get(next + 1).copied().unwrap_or(self.width).min(self.width);
+            let base = a0.unwrap_or(0);
+
+            match *self.modes.decode_symbol(bits)? {
+                Mode::Pass => a0 = Some(b2),
+                Mode::Horizontal => {
+                    let a1 = base + self.read_run(bits, black)?;
+                    let a2 = a1 + self.read_run(bits, !black)?;
+                    if a2 > self.width {
+                        return None;
+                    }
+                    changes.extend([a1, a2]);
+                    a0 = Some(a2);
+                }
+                Mode::Vertical(offset) => {
+                    let a1 = b1.checked_add_signed(offset)?;
+                    if a1 < base || a1 > self.width {
+                        return None;
+                    }
+                    changes.push(a1);
+                    a0 = Some(a1);
+                    black = !black;
+                }
+            }
+        }
+
+        Some(changes)
+    }
+
+    /// Pushes the pixels of a row from its changes
+    fn push_row(&self, bv: &mut BitVec, changes: &[usize]) {
+        let mut position = 0;
+        let mut black = false;
+        for &change in changes.iter().chain(&[self.width]) {
+            let change = change.clamp(position, self.width);
+            bv.resize(bv.len + change - position, black);
+            position = change;
+            black = !black;
+        }
+    }
+}
+
+impl BitVec {
+    /// Decodes `rows` rows of `width` pixels from Group 3 one-dimensional
+    /// (Modified Huffman) data, with black pixels as set bits. EOL codes,
+    /// with any fill bits before them, may come before each row. Returns
+    /// None if the data is invalid or runs out, or the width is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 3 white, 3 black, and 2 white pixels
+    /// let bv = BitVec::from_fax_g3(&[0b1000_1001, 0b1100_0000], 8, 1).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "00011100");
+    /// ```
+    pub fn from_fax_g3(data: &[u8], width: usize, rows: usize) -> Option<BitVec> {
+        if width == 0 {
+            return None;
+        }
+        let decoder = FaxDecoder::new(width);
+        let mut bits = BitVec::from(data);
+        let mut bv = BitVec::new();
+
+        for _ in 0..rows {
+            decoder.skip_eol(&mut bits);
+            let changes = decoder.decode_1d(&mut bits)?;
+            decoder.push_row(&mut bv, &changes);
+        }
+
+        Some(bv)
+    }
+
+    /// Decodes `rows` rows of `width` pixels from Group 3 two-dimensional
+    /// (Modified READ) data. Every row starts with an EOL code, then a tag
+    /// bit which is 1 for a one-dimensional row or 0 for a row coded against
+    /// the one above. Returns None if the data is invalid or runs out, or
+    /// the width is 0.
+    pub fn from_fax_g3_2d(data: &[u8], width: usize, rows: usize) -> Option<BitVec> {
+        if width == 0 {
+            return None;
+        }
+        let decoder = FaxDecoder::new(width);
+        let mut bits = BitVec::from(data);
+        let mut bv = BitVec::new();
+        let mut reference = Vec::new();
+
+        for _ in 0..rows {
+            if !decoder.skip_eol(&mut bits) {
+                return None;
+            }
+            reference = match bits.read_bit_seq()? {
+                1 => decoder.decode_1d(&mut bits)?,
+                _ => decoder.decode_2d(&mut bits, &reference)?,
+            };
+            decoder.push_row(&mut bv, &reference);
+        }
+
+        Some(bv)
+    }
+
+    /// Decodes `rows` rows of `width` pixels from Group 4 (Modified Modified
+    /// READ) data, where every row is coded against the one above, starting
+    /// from an all white row. Returns None if the data is invalid or runs
+    /// out, or the width is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // A row coded in horizontal mode, then the same row again
+    /// let bv = BitVec::from_fax_g4(&[0b0011_0001, 0b0111_1000], 8, 2).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "0001110000011100");
+    /// ```
+    pub fn from_fax_g4(data: &[u8], width: usize, rows: usize) -> Option<BitVec> {
+        if width == 0 {
+            return None;
+        }
+        let decoder = FaxDecoder::new(width);
+        let mut bits = BitVec::from(data);
+        let mut bv = BitVec::new();
+        let mut reference = Vec::new();
+
+        for _ in 0..rows {
+            reference = decoder.decode_2d(&mut bits, &reference)?;
+            decoder.push_row(&mut bv, &reference);
+        }
+
+        Some(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs a string of 0s and 1s into bytes, padded with 0s
+    fn bytes(bits: &str) -> Vec<u8> {
+        BitVec::from_bit_str(bits).unwrap().data
+    }
+
+    #[test]
+    fn modified_huffman_rows() {
+        // 3 white, 3 black, 2 white, then an EOL with fill bits and a row of
+        // 8 black, which starts with a white run of 0
+        let data = bytes("1000 10 0111 0000 000000000001 00110101 000101");
+        let bv = BitVec::from_fax_g3(&data, 8, 2).unwrap();
+        assert_eq!(bv.to_bit_string(), "0001110011111111");
+    }
+
+    #[test]
+    fn make_up_codes() {
+        // 1800 white using an extended make-up code, then 70 black and 30
+        // white using the make-up codes for each colour
+        let data = bytes("00000001000 10011 0000001111 0010 00000011");
+        let bv = BitVec::from_fax_g3(&data, 1900, 1).unwrap();
+        assert_eq!(bv.len_bits(), 1900);
+        assert_eq!(bv.count_ones(), 70);
+        assert_eq!(bv.next_set_bit(0), Some(1800));
+        assert_eq!(bv.next_clear_bit(1800), Some(1870));
+    }
+
+    #[test]
+    fn modified_read_rows() {
+        // A one-dimensional row, then the same row coded against it with
+        // three V0 codes
+        let data = bytes("000000000001 1 1000 10 0111 000000000001 0 111");
+        let bv = BitVec::from_fax_g3_2d(&data, 8, 2).unwrap();
+        assert_eq!(bv.to_bit_string(), "0001110000011100");
+        // The EOLs are required
+        assert!(BitVec::from_fax_g3_2d(&bytes("1 1000 10 0111"), 8, 1).is_none());
+    }
+
+    #[test]
+    fn group_4_modes() {
+        // Horizontal, then vertical, then vertical left, then pass mode,
+        // followed by EOFB
+        let data = bytes("001 1000 10 1  111  010 010 1  0001 1  000000000001 000000000001");
+        let bv = BitVec::from_fax_g4(&data, 8, 4).unwrap();
+        assert_eq!(bv.to_bit_string(), "00011100000111000011100000000000");
+    }
+
+    #[test]
+    fn invalid_data() {
+        // Runs past the end of the row
+        assert!(BitVec::from_fax_g3(&bytes("10011 0000 0000"), 4, 1).is_none());
+        // Out of data
+        assert!(BitVec::from_fax_g3(&bytes("1000 10"), 8, 1).is_none());
+        assert!(BitVec::from_fax_g4(&bytes("001 1000 10 1"), 8, 2).is_none());
+        // Vertical mode left of the start
+        assert!(BitVec::from_fax_g4(&bytes("1 0000010"), 2, 2).is_none());
+        assert!(BitVec::from_fax_g4(&[], 0, 1).is_none());
+        assert_eq!(BitVec::from_fax_g4(&[], 8, 0).unwrap().len_bits(), 0);
+    }
+}
@@ -0,0 +1,223 @@
+//! ## Simple FEC codes
+//! Lightweight forward error correction for small systems: repetition codes
+//! decoded by majority vote, and first order Reed–Muller codes RM(1, m),
+//! which send `m + 1` bits as `2^m` and correct up to `2^(m-2) - 1` errors
+//! in each block. RM(1, m) is decoded by maximum likelihood with a fast
+//! Hadamard transform.
+
+use alloc::vec;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Repeats each bit `n` times in a row. Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("10").unwrap();
+    /// let mut sent = bv.encode_repetition(3);
+    /// assert_eq!(sent.to_bit_string(), "111000");
+    ///
+    /// sent.flip_range(1..2);
+    /// assert_eq!(sent.decode_repetition_majority(3), Some(bv));
+    /// ```
+    pub fn encode_repetition(&self, n: usize) -> BitVec {
+        if n == 0 {
+            panic!("BitVec: repetition count must be non-zero")
+        }
+
+        let mut bv = BitVec::new();
+        bv.resize(self.len * n, false);
+        for index in self.iter_ones() {
+            bv.set_range(index * n..(index + 1) * n, true);
+        }
+        bv
+    }
+
+    /// Decodes runs of `n` repeated bits by majority vote, where a tie with
+    /// an even `n` gives 0. Returns None if `n` is 0 or does not divide the
+    /// length.
+    pub fn decode_repetition_majority(&self, n: usize) -> Option<BitVec> {
+        if n == 0 || !self.len.is_multiple_of(n) {
+            return None;
+        }
+
+        let mut bv = BitVec::new();
+        for start in (0..self.len).step_by(n) {
+            bv.push_bit(self.count_ones_in(start..start + n) * 2 > n);
+        }
+        Some(bv)
+    }
+}
+
+/// A first order Reed–Muller code RM(1, m), sending blocks of `m + 1` bits
+/// as code words of `2^m` bits
+///
+/// The first bit of a block flips the whole code word, and the other `m`
+/// bits, as a number `y`, give the bit at each position `x` as the parity of
+/// `x & y`.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, ReedMuller};
+///
+/// // RM(1, 3) corrects 1 error in each 8-bit code word
+/// let code = ReedMuller::new(3);
+/// let message = BitVec::from_bit_str("0101").unwrap();
+/// let mut sent = code.encode(&message);
+/// assert_eq!(sent.to_bit_string(), "01011010");
+///
+/// sent.flip_range(6..7);
+/// assert_eq!(code.decode(&sent), Some(message));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReedMuller {
+    m: usize,
+}
+
+impl ReedMuller {
+    /// Constructs the code RM(1, m). Panics if `m` is not 1 to 20.
+    pub fn new(m: usize) -> Self {
+        if !(1..=20).contains(&m) {
+            panic!("BitVec: Reed-Muller order must be 1 to 20")
+        }
+        Self { m }
+    }
+
+    /// Get the number of message bits in each block
+    pub fn block_len(&self) -> usize {
+        self.m + 1
+    }
+
+    /// Get the number of bits in each code word
+    pub fn code_len(&self) -> usize {
+        1 << self.m
+    }
+
+    /// Encodes blocks of `m + 1` bits as code words. Panics if the length
+    /// is not a whole number of blocks.
+    pub fn encode(&self, message: &BitVec) -> BitVec {
+        if !message.len_bits().is_multiple_of(self.block_len()) {
+            panic!("BitVec: message length is not a whole number of blocks")
+        }
+
+        let mut encoded = BitVec::new();
+        for block in message.chunks(self.block_len()) {
+            let flip = block.get_bit(0);
+            let y = block.slice(1..).to_u64();
+            for x in 0..self.code_len() as u64 {
+                encoded.push_bit(flip ^ ((x & y).count_ones() & 1 == 1));
+            }
+        }
+        encoded
+    }
+
+    /// Decodes code words to the nearest blocks of `m + 1` bits, choosing
+    /// the lowest block on a tie. Returns None if the length is not a whole
+    /// number of code words.
+    pub fn decode(&self, received: &BitVec) -> Option<BitVec> {
+        if !received.len_bits().is_multiple_of(self.code_len()) {
+            return None;
+        }
+
+        let mut decoded = BitVec::new();
+        let mut spectrum = vec![0i32; self.code_len()];
+        for word in received.chunks(self.code_len()) {
+            for (x, value) in spectrum.iter_mut().enumerate() {
+                *value = if word.get_bit(x) { -1 } else { 1 };
+            }
+            hadamard_transform(&mut spectrum);
+
+            // The strongest correlation gives y, and its sign the flip bit
+            let y = (0..spectrum.len()).max_by_key(|&y| (spectrum[y].abs(), core::cmp::Reverse(y))).unwrap();
+            decoded.push_bit(spectrum[y] < 0);
+            for shift in (0..self.m).rev() {
+                decoded.push_bit(y >> shift & 1 == 1);
+            }
+        }
+        Some(decoded)
+    }
+}
+
+/// Applies the fast Walsh–Hadamard transform in place to a power of two
+/// number of values
+fn hadamard_transform(values: &mut [i32]) {
+    let mut half = 1;
+    while half < values.len() {
+        for start in (0..values.len()).step_by(half * 2) {
+            for index in start..start + half {
+                let (a, b) = (values[index], values[index + half]);
+                values[index] = a + b;
+                values[index + half] = a - b;
+            }
+        }
+        half *= 2;
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repetition_majority() {
+        let bv = BitVec::from(&[0xA5, 0x3C]).slice(..13).to_bitvec();
+        let mut sent = bv.encode_repetition(5);
+        assert_eq!(sent.len_bits(), 65);
+        // Two errors in every run of 5
+        for run in 0..13 {
+            sent.flip_range(run * 5..run * 5 + 2);
+        }
+        assert_eq!(sent.decode_repetition_majority(5), Some(bv));
+
+        // A tie decodes as 0
+        let tie = BitVec::from_bit_str("1100").unwrap();
+        assert_eq!(tie.decode_repetition_majority(4).unwrap().to_bit_string(), "0");
+        assert_eq!(tie.decode_repetition_majority(3), None);
+        assert_eq!(tie.decode_repetition_majority(0), None);
+    }
+
+    #[test]
+    fn reed_muller_corrects_errors() {
+        // RM(1, 5) corrects up to 7 errors in each 32-bit word
+        let code = ReedMuller::new(5);
+        assert_eq!((code.block_len(), code.code_len()), (6, 32));
+
+        let message = BitVec::from(&[0xDE, 0xAD, 0xBE]);
+        let mut sent = code.encode(&message);
+        assert_eq!(sent.len_bits(), 4 * 32);
+        assert_eq!(code.decode(&sent), Some(message.clone()));
+
+        for word in 0..4 {
+            for error in 0..7 {
+                let index = word * 32 + error * 4 + word;
+                sent.flip_range(index..index + 1);
+            }
+        }
+        assert_eq!(code.decode(&sent), Some(message));
+        assert_eq!(code.decode(&BitVec::from_bit_str("101").unwrap()), None);
+    }
+
+    #[test]
+    fn code_words_are_far_apart() {
+        // Every pair of RM(1, 4) code words differs in at least 8 bits
+        let code = ReedMuller::new(4);
+        let words: Vec<BitVec> = (0u8..32)
+            .map(|block| code.encode(&BitVec::from(&[block << 3]).slice(..5).to_bitvec()))
+            .collect();
+        for (i, a) in words.iter().enumerate() {
+            for b in &words[i + 1..] {
+                let mut difference = a.clone();
+                difference ^= b.clone();
+                assert!(difference.count_ones() >= 8);
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! ## Fixed-width fields
+//! An unsigned value whose width is part of its type, so the width of each
+//! field of a format is checked when the code is compiled, and a value too
+//! wide for its field is rejected when the field is made rather than cut
+//! short when it is pushed.
+
+use crate::{BitVec, BitVecError};
+
+/// An unsigned value of exactly `N` bits, where `N` is 1 to 64
+///
+/// This plays the part of a `Bits<N>` type for fields; the name `Bits` is
+/// already taken by the iterator over a BitSlice.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitField, BitVec};
+///
+/// let mut bv = BitVec::new();
+/// bv.push_exact(BitField::<3>::new(0b101).unwrap());
+/// bv.push_exact(BitField::<5>::new(31).unwrap());
+/// assert!(BitField::<5>::new(32).is_err());
+/// assert_eq!(bv.read_exact::<3>().map(BitField::get), Some(0b101));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitField<const N: usize>(u64);
+
+impl<const N: usize> BitField<N> {
+    /// Number of bits in the field
+    pub const WIDTH: u32 = {
+        assert!(N >= 1 && N <= 64, "BitVec: a field must be 1 to 64 bits");
+        N as u32
+    };
+
+    /// Largest value the field holds
+    pub const MAX: u64 = u64::MAX >> (64 - Self::WIDTH);
+
+    /// Makes a field holding a value. Returns an error if the value needs
+    /// more than `N` bits.
+    pub fn new(value: u64) -> Result<Self, BitVecError> {
+        if value > Self::MAX {
+            return Err(BitVecError::ValueTooWide { value, width: Self::WIDTH });
+        }
+        Ok(Self(value))
+    }
+
+    /// Get the value of the field
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const N: usize> TryFrom<u64> for BitField<N> {
+    type Error = BitVecError;
+
+    fn try_from(value: u64) -> Result<Self, BitVecError> {
+        Self::new(value)
+    }
+}
+
+impl<const N: usize> From<BitField<N>> for u64 {
+    fn from(field: BitField<N>) -> u64 {
+        field.0
+    }
+}
+
+impl BitVec {
+    /// Pushes a field of exactly `N` bits, MSB first
+    pub fn push_exact<const N: usize>(&mut self, field: BitField<N>) {
+        self.push_low_bits(field.0, BitField::<N>::WIDTH);
+    }
+
+    /// Reads a field of exactly `N` bits in sequence, MSB first. Returns
+    /// None, without moving the reading position, if fewer bits than that
+    /// remain.
+    pub fn read_exact<const N: usize>(&mut self) -> Option<BitField<N>> {
+        self.read_int::<u64>(BitField::<N>::WIDTH).map(BitField)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_limits() {
+        assert_eq!(BitField::<1>::MAX, 1);
+        assert_eq!(BitField::<12>::MAX, 0xFFF);
+        assert_eq!(BitField::<64>::MAX, u64::MAX);
+        assert_eq!(BitField::<12>::new(0x1000), Err(BitVecError::ValueTooWide { value: 0x1000, width: 12 }));
+        assert_eq!(BitField::<64>::try_from(u64::MAX).map(u64::from), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn push_and_read_exact() {
+        let mut bv = BitVec::new();
+        bv.push_exact(BitField::<1>::new(1).unwrap());
+        bv.push_exact(BitField::<12>::new(0xABC).unwrap());
+        bv.push_exact(BitField::<64>::new(0x0123_4567_89AB_CDEF).unwrap());
+        assert_eq!(bv.len_bits(), 77);
+        assert_eq!(bv.slice(..13).to_u64(), 0x1ABC);
+
+        assert_eq!(bv.read_exact::<1>().map(u64::from), Some(1));
+        assert_eq!(bv.read_exact::<12>().map(u64::from), Some(0xABC));
+        assert_eq!(bv.read_exact::<64>().map(u64::from), Some(0x0123_4567_89AB_CDEF));
+        assert_eq!(bv.read_exact::<1>(), None);
+    }
+}
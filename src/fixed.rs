@@ -0,0 +1,171 @@
+//! ## Fixed-point numbers
+//! Packing real numbers as Qm.n fixed-point fields, as telemetry and DSP
+//! formats do: `m` integer bits and `n` fractional bits, MSB first. When the
+//! field is signed it holds two's complement, and the sign bit is counted
+//! among the integer bits, so a signed Q1.15 field is 16 bits wide and holds
+//! values from -1 up to just under 1.
+
+use crate::BitVec;
+
+/// How a value between two fixed-point steps is rounded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// To the nearest step, with ties going to the even step
+    #[default]
+    Nearest,
+    /// To the step nearer zero, cutting off the extra fractional bits
+    TowardZero,
+    /// To the step below
+    Down,
+    /// To the step above
+    Up,
+}
+
+impl Rounding {
+    /// Rounds a value of less than 2^64 either way to a whole number, with
+    /// casts rather than float methods, which are missing without `std`
+    fn round(self, value: f64) -> i128 {
+        let whole = value as i128;
+        let rest = value - whole as f64;
+        let tie = whole & 1 == 1;
+        let step = match self {
+            Rounding::Nearest if rest > 0.5 || rest == 0.5 && tie => 1,
+            Rounding::Nearest if rest < -0.5 || rest == -0.5 && tie => -1,
+            Rounding::Down if rest < 0.0 => -1,
+            Rounding::Up if rest > 0.0 => 1,
+            _ => 0,
+        };
+        whole + step
+    }
+}
+
+/// Returns 2 to the power of `frac_bits`, exactly
+fn scale(frac_bits: u32) -> f64 {
+    f64::from_bits((1023 + frac_bits as u64) << 52)
+}
+
+/// Returns the width of a field, panicking if it is not 1 to 64 bits
+fn field_width(int_bits: u32, frac_bits: u32) -> u32 {
+    let width = int_bits + frac_bits;
+    if !(1..=64).contains(&width) {
+        panic!("BitVec: a fixed-point field must be 1 to 64 bits")
+    }
+    width
+}
+
+impl BitVec {
+    /// Pushes a value as a fixed-point field of `int_bits` integer and
+    /// `frac_bits` fractional bits, rounded to a step of the field as
+    /// chosen. Values outside the range of the field saturate to its ends.
+    /// Panics if the field is not 1 to 64 bits wide or the value is NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, Rounding};
+    ///
+    /// let mut bv = BitVec::new();
+    /// // Signed Q4.4
+    /// bv.push_fixed(-2.3, 4, 4, true, Rounding::Nearest);
+    /// assert_eq!(bv.to_bit_string(), "11011011");
+    /// assert_eq!(bv.read_fixed(4, 4, true), Some(-2.3125));
+    /// ```
+    pub fn push_fixed(&mut self, value: f64, int_bits: u32, frac_bits: u32, signed: bool, rounding: Rounding) {
+        let width = field_width(int_bits, frac_bits);
+        if value.is_nan() {
+            panic!("BitVec: cannot pack NaN as fixed point")
+        }
+
+        let (min, max) = if signed {
+            (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+        } else {
+            (0, (1i128 << width) - 1)
+        };
+        // Rounding never carries a value back inside the range, so values
+        // outside it can saturate first
+        let scaled = value * scale(frac_bits);
+        let raw = if scaled <= min as f64 {
+            min
+        } else if scaled >= max as f64 {
+            max
+        } else {
+            rounding.round(scaled)
+        };
+
+        // The low bits of a negative value are its two's complement
+        self.push_low_bits(raw as u64, width);
+    }
+
+    /// Reads a fixed-point field of `int_bits` integer and `frac_bits`
+    /// fractional bits in sequence. Returns None, without moving the reading
+    /// position, if fewer bits than that remain. Panics if the field is not
+    /// 1 to 64 bits wide.
+    pub fn read_fixed(&mut self, int_bits: u32, frac_bits: u32, signed: bool) -> Option<f64> {
+        let width = field_width(int_bits, frac_bits);
+        let raw = if signed {
+            self.read_int::<i64>(width)? as f64
+        } else {
+            self.read_int::<u64>(width)? as f64
+        };
+        Some(raw / scale(frac_bits))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs and reads back a value in one field
+    fn round_trip(value: f64, int_bits: u32, frac_bits: u32, signed: bool, rounding: Rounding) -> f64 {
+        let mut bv = BitVec::new();
+        bv.push_fixed(value, int_bits, frac_bits, signed, rounding);
+        assert_eq!(bv.len_bits(), (int_bits + frac_bits) as usize);
+        bv.read_fixed(int_bits, frac_bits, signed).unwrap()
+    }
+
+    #[test]
+    fn rounding_modes() {
+        // Steps of 0.25
+        assert_eq!(round_trip(1.1, 4, 2, false, Rounding::Nearest), 1.0);
+        assert_eq!(round_trip(1.125, 4, 2, false, Rounding::Nearest), 1.0);
+        assert_eq!(round_trip(1.375, 4, 2, false, Rounding::Nearest), 1.5);
+        assert_eq!(round_trip(1.2, 4, 2, false, Rounding::Up), 1.25);
+        assert_eq!(round_trip(-1.1, 4, 2, true, Rounding::TowardZero), -1.0);
+        assert_eq!(round_trip(-1.1, 4, 2, true, Rounding::Down), -1.25);
+        assert_eq!(round_trip(-1.1, 4, 2, true, Rounding::Up), -1.0);
+    }
+
+    #[test]
+    fn ranges_saturate() {
+        // Signed Q1.15 holds -1 up to 1 - 2^-15
+        assert_eq!(round_trip(-1.0, 1, 15, true, Rounding::Nearest), -1.0);
+        assert_eq!(round_trip(1.0, 1, 15, true, Rounding::Nearest), 1.0 - 2f64.powi(-15));
+        assert_eq!(round_trip(-5.0, 1, 15, true, Rounding::Nearest), -1.0);
+        assert_eq!(round_trip(-0.5, 8, 0, false, Rounding::Nearest), 0.0);
+        assert_eq!(round_trip(300.0, 8, 0, false, Rounding::Nearest), 255.0);
+        assert_eq!(round_trip(f64::INFINITY, 32, 32, true, Rounding::Nearest), (i64::MAX as f64) / 2f64.powi(32));
+        assert_eq!(round_trip(f64::NEG_INFINITY, 64, 0, false, Rounding::Nearest), 0.0);
+        // Only fractional bits
+        assert_eq!(round_trip(0.75, 0, 2, false, Rounding::Nearest), 0.75);
+    }
+
+    #[test]
+    fn fields_in_sequence() {
+        let mut bv = BitVec::new();
+        bv.push_fixed(3.5, 3, 1, false, Rounding::Nearest);
+        bv.push_fixed(-0.25, 2, 3, true, Rounding::Nearest);
+        assert_eq!(bv.to_bit_string(), "011111110");
+        assert_eq!(bv.read_fixed(3, 1, false), Some(3.5));
+        assert_eq!(bv.read_fixed(2, 4, true), None);
+        assert_eq!(bv.read_fixed(2, 3, true), Some(-0.25));
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN")]
+    fn nan_is_rejected() {
+        BitVec::new().push_fixed(f64::NAN, 8, 8, true, Rounding::Nearest);
+    }
+}
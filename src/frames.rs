@@ -0,0 +1,233 @@
+//! ## Replication frames
+//! Self-describing frames for keeping a copy of a bitmap in sync over any
+//! byte transport. Each frame carries a run of the packed bytes of the
+//! bitmap, says where they go and how long the bitmap is, and ends with a
+//! checksum, so frames can be applied in any order, and a damaged one is
+//! rejected rather than applied.
+//!
+//! A frame is, in network order:
+//!
+//! | Bytes | Field                                          |
+//! |-------|------------------------------------------------|
+//! | 8     | length of the bitmap in bits                   |
+//! | 4     | page size in bytes                             |
+//! | 4     | page id                                        |
+//! | 4     | byte offset of the payload within the page     |
+//! | 4     | payload length in bytes                        |
+//! | n     | payload, the packed bytes MSB first            |
+//! | 4     | CRC-32 of everything before it                 |
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// Bytes in a frame around the payload
+const FRAME_OVERHEAD: usize = 28;
+
+/// Returns a frame field as a u32, panicking with its name if it does not fit
+fn field_u32(value: usize, name: &str) -> u32 {
+    u32::try_from(value).unwrap_or_else(|_| panic!("BitVec: {} does not fit in a frame", name))
+}
+
+/// Reads a big-endian u32 from 4 bytes
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().unwrap())
+}
+
+impl BitVec {
+    /// Encodes pages of `page_bytes` bytes as frames of at most
+    /// `max_frame_bytes` bytes, splitting pages that do not fit in one
+    fn encode_frames(&self, page_bytes: usize, pages: &[usize], max_frame_bytes: usize) -> Vec<Vec<u8>> {
+        if max_frame_bytes <= FRAME_OVERHEAD {
+            panic!("BitVec: frames must be longer than {} bytes", FRAME_OVERHEAD)
+        }
+        let (Ok(page_size), true) = (u32::try_from(page_bytes), page_bytes > 0) else {
+            panic!("BitVec: page size must be from 1 to u32::MAX bytes")
+        };
+
+        let mut frames = Vec::new();
+        for &page in pages {
+            let page_id = field_u32(page, "page id");
+            let start = page.saturating_mul(page_bytes).min(self.data.len());
+            let bytes = &self.data[start..(start + page_bytes).min(self.data.len())];
+            // An empty page still says how long the bitmap is
+            let mut chunks: Vec<&[u8]> = bytes.chunks(max_frame_bytes - FRAME_OVERHEAD).collect();
+            if chunks.is_empty() {
+                chunks.push(&[]);
+            }
+
+            let mut offset = 0;
+            for chunk in chunks {
+                let mut frame = Vec::with_capacity(chunk.len() + FRAME_OVERHEAD);
+                frame.extend_from_slice(&(self.len as u64).to_be_bytes());
+                frame.extend_from_slice(&page_size.to_be_bytes());
+                frame.extend_from_slice(&page_id.to_be_bytes());
+                frame.extend_from_slice(&field_u32(offset, "payload offset").to_be_bytes());
+                frame.extend_from_slice(&field_u32(chunk.len(), "payload length").to_be_bytes());
+                frame.extend_from_slice(chunk);
+                let crc = BitVec::from(&frame[..]).crc32(..);
+                frame.extend_from_slice(&crc.to_be_bytes());
+                frames.push(frame);
+                offset += chunk.len();
+            }
+        }
+        frames
+    }
+
+    /// Encodes the whole vector as frames of at most `max_frame_bytes`
+    /// bytes. Panics if a frame cannot hold at least one byte of payload, 29
+    /// bytes in all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let source = BitVec::from(vec![0x5A; 100]);
+    /// let mut copy = BitVec::new();
+    /// for frame in source.encode_update_frames(64) {
+    ///     copy.apply_update_frame(&frame).unwrap();
+    /// }
+    /// assert_eq!(copy, source);
+    /// ```
+    pub fn encode_update_frames(&self, max_frame_bytes: usize) -> Vec<Vec<u8>> {
+        let page_bytes = (max_frame_bytes.saturating_sub(FRAME_OVERHEAD)).clamp(1, u32::MAX as usize);
+        let pages: Vec<usize> = (0..self.data.len().div_ceil(page_bytes).max(1)).collect();
+        self.encode_frames(page_bytes, &pages, max_frame_bytes)
+    }
+
+    /// Encodes only some pages of `page_bits` bits, such as those from
+    /// `changed_pages_since`, as frames of at most `max_frame_bytes` bytes.
+    /// Panics if the page size is not a whole number of bytes, if a page id
+    /// does not fit in a u32, or if a frame cannot hold at least one byte of
+    /// payload.
+    pub fn encode_page_frames(&self, page_bits: usize, pages: &[usize], max_frame_bytes: usize) -> Vec<Vec<u8>> {
+        if !page_bits.is_multiple_of(8) {
+            panic!("BitVec: page size must be a whole number of bytes")
+        }
+        self.encode_frames(page_bits / 8, pages, max_frame_bytes)
+    }
+
+    /// Applies a frame, resizing the vector to the length it gives and
+    /// writing its payload in place. Returns an error, changing nothing, if
+    /// the frame is damaged or its payload lies past the end of the bitmap,
+    /// with the bit offset within the frame of what was found wrong:
+    ///
+    /// - the end of a frame too short to hold every field
+    /// - the payload length, when the frame is not as long as it says
+    /// - the checksum, when it does not match, as the damage could be
+    ///   anywhere before it
+    /// - the bitmap length, when it does not fit in a usize
+    /// - the page size, when the page size, page id and payload offset
+    ///   place the payload past `usize::MAX`
+    /// - the payload, when it lies past the end of the bitmap
+    pub fn apply_update_frame(&mut self, frame: &[u8]) -> Result<(), BitVecError> {
+        let at = |byte: usize| BitVecError::Undecodable { offset: byte * 8 };
+        if frame.len() < FRAME_OVERHEAD {
+            return Err(at(frame.len()));
+        }
+        let (body, crc) = frame.split_at(frame.len() - 4);
+        let payload_len = be_u32(&body[20..24]) as usize;
+        if body.len() != FRAME_OVERHEAD - 4 + payload_len {
+            return Err(at(20));
+        }
+        if BitVec::from(body).crc32(..) != be_u32(crc) {
+            return Err(at(body.len()));
+        }
+
+        let len = usize::try_from(u64::from_be_bytes(body[..8].try_into().unwrap())).map_err(|_| at(0))?;
+        let start = (be_u32(&body[8..12]) as usize)
+            .checked_mul(be_u32(&body[12..16]) as usize)
+            .and_then(|start| start.checked_add(be_u32(&body[16..20]) as usize))
+            .ok_or(at(8))?;
+        if start + payload_len > len.div_ceil(8) {
+            return Err(at(24));
+        }
+
+        self.resize(len, false);
+        self.data[start..start + payload_len].copy_from_slice(&body[24..]);
+        self.clear_padding();
+        Ok(())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_vector_frames() {
+        let source = BitVec::from_bit_str("1100 1010 0111 0").unwrap();
+        let frames = source.encode_update_frames(FRAME_OVERHEAD + 1);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            &frames[1][..25],
+            &[0, 0, 0, 0, 0, 0, 0, 13, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0x70]
+        );
+
+        // In any order, over a longer vector
+        let mut copy = BitVec::from(vec![0xFF; 4]);
+        for frame in frames.iter().rev() {
+            copy.apply_update_frame(frame).unwrap();
+        }
+        assert_eq!(copy, source);
+
+        // An empty vector still sends its length
+        let frames = BitVec::new().encode_update_frames(100);
+        assert_eq!(frames.len(), 1);
+        copy.apply_update_frame(&frames[0]).unwrap();
+        assert_eq!(copy.len_bits(), 0);
+    }
+
+    #[test]
+    fn changed_pages_only() {
+        let mut source = BitVec::from(vec![0; 64]);
+        let mut replica = source.clone();
+        let tree = source.merkle_tree(128);
+        source.set_bit(200, true);
+        source.slice_mut(400..420).fill(true);
+
+        let changed = source.changed_pages_since(&tree);
+        assert_eq!(changed, vec![1, 3]);
+        // Each page of 16 bytes splits in two
+        let frames = source.encode_page_frames(128, &changed, FRAME_OVERHEAD + 10);
+        assert_eq!(frames.len(), 4);
+        for frame in &frames {
+            replica.apply_update_frame(frame).unwrap();
+        }
+        assert_eq!(replica, source);
+    }
+
+    #[test]
+    fn damaged_frames() {
+        let source = BitVec::from(&[0xAB, 0xCD]);
+        let frame = &source.encode_update_frames(40)[0];
+        let mut copy = BitVec::from(&[0x12]);
+
+        let mut flipped = frame.clone();
+        flipped[25] ^= 0x10;
+        // The checksum, the payload length, and the end of a short frame
+        assert_eq!(copy.apply_update_frame(&flipped), Err(BitVecError::Undecodable { offset: 208 }));
+        assert_eq!(copy.apply_update_frame(&frame[..frame.len() - 1]), Err(BitVecError::Undecodable { offset: 160 }));
+        assert_eq!(copy.apply_update_frame(&[0; 10]), Err(BitVecError::Undecodable { offset: 80 }));
+        assert_eq!(copy, BitVec::from(&[0x12]));
+
+        // A payload past the end of the bitmap, with a valid checksum
+        let mut past = BitVec::from(&[0xFF; 3]).encode_page_frames(8, &[2], 40).remove(0);
+        past[7] = 16;
+        past.truncate(past.len() - 4);
+        let crc = BitVec::from(&past[..]).crc32(..);
+        past.extend_from_slice(&crc.to_be_bytes());
+        assert_eq!(copy.apply_update_frame(&past), Err(BitVecError::Undecodable { offset: 192 }));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    #[should_panic(expected = "page id does not fit")]
+    fn page_id_too_large() {
+        BitVec::from(&[0xFF]).encode_page_frames(8, &[1 << 32], 40);
+    }
+}
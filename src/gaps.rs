@@ -0,0 +1,251 @@
+//! ## Gap statistics
+//! Sparse bitmaps, such as posting lists, are usually stored as the gaps
+//! between consecutive set bits. The first gap is measured from just before
+//! the start of the vector, so every gap is at least 1. Gaps can be written
+//! compactly with a Rice code, whose parameter is chosen from the histogram.
+
+use alloc::{vec, vec::Vec};
+use crate::{BitOrder, BitVec, GrowthPolicy};
+
+impl BitVec {
+    /// Returns the gaps between consecutive set bits, with the first gap
+    /// being the index of the first set bit plus 1
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01001001
+    /// let bv = BitVec::from(&[0x49]);
+    /// assert_eq!(bv.gaps(), vec![2, 3, 3]);
+    /// ```
+    pub fn gaps(&self) -> Vec<usize> {
+        let mut gaps = Vec::new();
+        let mut previous = None;
+
+        for index in self.iter_ones() {
+            gaps.push(match previous {
+                Some(previous) => index - previous,
+                None => index + 1,
+            });
+            previous = Some(index);
+        }
+
+        gaps
+    }
+
+    /// Returns a histogram of the gaps between consecutive set bits, bucketed
+    /// by power of two. Bucket `b` counts the gaps in `2^b..2^(b+1)`, and the
+    /// histogram ends at the highest non-empty bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // Gaps of 2, 3, and 3
+    /// let bv = BitVec::from(&[0x49]);
+    /// assert_eq!(bv.gap_histogram(), vec![0, 3]);
+    /// ```
+    pub fn gap_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for gap in self.gaps() {
+            let bucket = gap.ilog2() as usize;
+            if histogram.len() <= bucket {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+
+        histogram
+    }
+
+    /// Picks the Rice parameter giving the smallest encoding of the gaps,
+    /// estimated from the gap histogram by taking each gap as the middle of
+    /// its bucket
+    pub fn estimate_rice_parameter(&self) -> u8 {
+        let histogram = self.gap_histogram();
+        let cost = |k: usize| -> usize {
+            histogram.iter()
+                .enumerate()
+                .map(|(bucket, &count)| {
+                    // Middle of 2^b..2^(b+1), less 1 as gaps are coded from 0
+                    let value = (3usize << bucket) / 2 - 1;
+                    count * (1 + k + (value >> k))
+                })
+                .sum()
+        };
+
+        (0..=histogram.len()).min_by_key(|&k| cost(k)).unwrap_or(0) as u8
+    }
+
+    /// Encodes the set bits as Rice-coded gaps, which for sparse bitmaps is
+    /// typically far smaller than the packed bytes. The encoding starts with
+    /// the Rice parameter (6 bits) and the bit length (64 bits), followed by
+    /// each gap less 1, as a unary quotient and a `k` bit remainder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut sparse = BitVec::from(&[0; 1000]);
+    /// sparse.set_bit(10, true);
+    /// sparse.set_bit(5000, true);
+    /// let encoded = sparse.encode_gaps_rice();
+    /// assert!(encoded.len_bits() < 100);
+    /// ```
+    pub fn encode_gaps_rice(&self) -> BitVec {
+        let k = self.estimate_rice_parameter();
+        let mut encoded = BitVec::new();
+
+        for shift in (0..6).rev() {
+            encoded.push_bit((k >> shift) & 1 == 1);
+        }
+        for byte in (self.len as u64).to_be_bytes() {
+            encoded.push_byte(byte);
+        }
+
+        for gap in self.gaps() {
+            let value = gap - 1;
+            for _ in 0..value >> k {
+                encoded.push_bit(true);
+            }
+            encoded.push_bit(false);
+            encoded.push_low_bits(value as u64, k as u32);
+        }
+
+        encoded
+    }
+
+    /// Decodes a bitmap written by `encode_gaps_rice`. Returns None if the
+    /// encoding is truncated or a gap runs past the end of the bitmap.
+    pub fn decode_gaps_rice(encoded: &BitVec) -> Option<BitVec> {
+        fn read_bits(encoded: &BitVec, position: &mut usize, count: usize) -> Option<u64> {
+            if *position + count > encoded.len_bits() {
+                return None;
+            }
+            let value = (*position..*position + count)
+                .fold(0, |value, index| (value << 1) | encoded.get_bit(index) as u64);
+            *position += count;
+            Some(value)
+        }
+
+        let mut position = 0;
+        let k = read_bits(encoded, &mut position, 6)? as usize;
+        let len = usize::try_from(read_bits(encoded, &mut position, 64)?).ok()?;
+        let mut decoded = BitVec {
+            data: vec![0; len.div_ceil(8)],
+            len,
+            byte_idx: 0,
+            bit_idx: 0,
+            order: BitOrder::Msb0,
+            growth: GrowthPolicy::Grow,
+        };
+
+        let mut index: Option<usize> = None;
+        while position < encoded.len_bits() {
+            let mut quotient: usize = 0;
+            while read_bits(encoded, &mut position, 1)? == 1 {
+                quotient += 1;
+            }
+            // A hostile quotient or gap could overflow, which no real bitmap has
+            let high = 1usize.checked_shl(k as u32).and_then(|scale| quotient.checked_mul(scale))?;
+            let value = high | read_bits(encoded, &mut position, k)? as usize;
+            let next = match index {
+                Some(index) => index.checked_add(value)?.checked_add(1)?,
+                None => value,
+            };
+            if next >= len {
+                return None;
+            }
+            decoded.set_bit(next, true);
+            index = Some(next);
+        }
+
+        Some(decoded)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_across_bytes() {
+        let mut bv = BitVec::new();
+        for index in [0, 1, 9, 40, 41, 200] {
+            bv.set_bit(index, true);
+        }
+        bv.len = 201;
+        assert_eq!(bv.gaps(), vec![1, 1, 8, 31, 1, 159]);
+        // 1, 1, 1 | 8 | 31 | 159
+        assert_eq!(bv.gap_histogram(), vec![3, 0, 0, 1, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn rice_round_trip() {
+        let mut bv = BitVec::from(&[0; 512]);
+        for index in (3..4096).step_by(37) {
+            bv.set_bit(index, true);
+        }
+        bv.len = 4093;
+
+        // Gaps of 37 take 7 bits with k = 4 or 5, and ties go to the smaller
+        assert_eq!(bv.estimate_rice_parameter(), 4);
+        let encoded = bv.encode_gaps_rice();
+        assert!(encoded.len_bits() < bv.len_bits() / 4);
+
+        let decoded = BitVec::decode_gaps_rice(&encoded).unwrap();
+        assert_eq!(decoded.len_bits(), 4093);
+        assert_eq!(decoded.data, bv.data);
+    }
+
+    #[test]
+    fn rice_rejects_bad_input() {
+        let mut bv = BitVec::from(&[0; 4]);
+        bv.set_bit(30, true);
+        let mut encoded = bv.encode_gaps_rice();
+        encoded.pop_bit();
+        assert!(BitVec::decode_gaps_rice(&encoded).is_none());
+        assert!(BitVec::decode_gaps_rice(&BitVec::from(&[0x04])).is_none());
+
+        let empty = BitVec::decode_gaps_rice(&BitVec::new().encode_gaps_rice()).unwrap();
+        assert_eq!(empty.len_bits(), 0);
+    }
+
+    #[test]
+    fn rice_rejects_overflowing_gaps() {
+        // k = 63 and a length of 8, then a first gap of 0
+        let mut header = BitVec::new();
+        header.push_int(63u8, 6).unwrap();
+        header.push_int(8u64, 64).unwrap();
+        header.push_bit(false);
+        header.extend(&BitVec::repeat_bit(false, 63));
+
+        // A quotient of 1 and all ones, a gap of usize::MAX
+        let mut wrapping = header.clone();
+        wrapping.extend(&BitVec::from_bit_str("10").unwrap());
+        wrapping.extend(&BitVec::repeat_bit(true, 63));
+        assert!(BitVec::decode_gaps_rice(&wrapping).is_none());
+
+        // A quotient of 2 shifted up by 63
+        let mut shifted = header.clone();
+        shifted.extend(&BitVec::from_bit_str("110").unwrap());
+        shifted.extend(&BitVec::repeat_bit(false, 63));
+        assert!(BitVec::decode_gaps_rice(&shifted).is_none());
+
+        assert_eq!(BitVec::decode_gaps_rice(&header).unwrap().iter_ones().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn no_set_bits() {
+        let bv = BitVec::from(&[0, 0]);
+        assert!(bv.gaps().is_empty());
+        assert!(bv.gap_histogram().is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+//! ## Genetic operators
+//! Crossover and mutation for bit string genomes, as genetic algorithms use
+//! them. Each operator leaves its parents alone and returns new vectors.
+//! The random operators are behind the `rand` feature.
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::{BitVec, BitVecError};
+
+impl BitVec {
+    /// Returns the two children of swapping the tails of two parents at
+    /// `point`: this parent's bits before it with the other's after, and
+    /// the reverse. Returns an error if the parents differ in length, and
+    /// panics if the point is past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let a = BitVec::from_bit_str("111111").unwrap();
+    /// let b = BitVec::from_bit_str("000000").unwrap();
+    /// let (c, d) = a.one_point_crossover(&b, 2).unwrap();
+    /// assert_eq!(c.to_bit_string(), "110000");
+    /// assert_eq!(d.to_bit_string(), "001111");
+    /// ```
+    pub fn one_point_crossover(&self, other: &BitVec, point: usize) -> Result<(BitVec, BitVec), BitVecError> {
+        if self.len != other.len {
+            return Err(BitVecError::LengthMismatch { expected: self.len, found: other.len });
+        }
+        if point > self.len {
+            panic!("BitVec: crossover point {} out of bounds for length {}", point, self.len)
+        }
+
+        let mut first = self.slice(..point).to_bitvec();
+        first.extend(&other.slice(point..).to_bitvec());
+        let mut second = other.slice(..point).to_bitvec();
+        second.extend(&self.slice(point..).to_bitvec());
+        Ok((first, second))
+    }
+
+    /// Returns the two children of swapping each bit between two parents
+    /// independently with probability `p`. Returns an error if the parents
+    /// differ in length, and panics if `p` is not between 0 and 1.
+    #[cfg(feature = "rand")]
+    pub fn uniform_crossover<R: Rng + ?Sized>(&self, other: &BitVec, rng: &mut R, p: f64) -> Result<(BitVec, BitVec), BitVecError> {
+        if self.len != other.len {
+            return Err(BitVecError::LengthMismatch { expected: self.len, found: other.len });
+        }
+        if !(0.0..=1.0).contains(&p) {
+            panic!("BitVec: crossover probability must be between 0 and 1")
+        }
+
+        let (mut first, mut second) = (self.clone(), other.clone());
+        for index in 0..self.len {
+            if rng.random_bool(p) {
+                first.set_bit(index, other.get_bit(index));
+                second.set_bit(index, self.get_bit(index));
+            }
+        }
+        Ok((first, second))
+    }
+
+    /// Returns a copy with each bit flipped independently with probability
+    /// `rate`. Panics if the rate is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let genome = BitVec::repeat_bit(false, 64);
+    /// let child = genome.mutate(&mut SmallRng::seed_from_u64(9), 1.0);
+    /// assert_eq!(child.count_ones(), 64);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R, rate: f64) -> BitVec {
+        if !(0.0..=1.0).contains(&rate) {
+            panic!("BitVec: mutation rate must be between 0 and 1")
+        }
+        let mut child = self.clone();
+        child.inject_errors(rng, rate);
+        child
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_point() {
+        let a = BitVec::from_bit_str("1010 1010 1").unwrap();
+        let b = BitVec::from_bit_str("0000 1111 0").unwrap();
+        // Crossing at either end swaps or keeps the parents
+        assert_eq!(a.one_point_crossover(&b, 0), Ok((b.clone(), a.clone())));
+        assert_eq!(a.one_point_crossover(&b, 9), Ok((a.clone(), b.clone())));
+        let (c, d) = a.one_point_crossover(&b, 5).unwrap();
+        assert_eq!(c.to_bit_string(), "101011110");
+        assert_eq!(d.to_bit_string(), "000010101");
+
+        let short = BitVec::from_bit_str("1").unwrap();
+        assert_eq!(a.one_point_crossover(&short, 0), Err(BitVecError::LengthMismatch { expected: 9, found: 1 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn point_past_the_end() {
+        let a = BitVec::from(&[0xFF]);
+        let _ = a.one_point_crossover(&a, 9);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_operators() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64(17);
+        let a = BitVec::repeat_bit(true, 1000);
+        let b = BitVec::repeat_bit(false, 1000);
+        let (c, d) = a.uniform_crossover(&b, &mut rng, 0.5).unwrap();
+        // Bits are swapped, not lost
+        assert_eq!(c.count_ones() + d.count_ones(), 1000);
+        assert!((400..600).contains(&c.count_ones()));
+        assert_eq!(a.uniform_crossover(&b, &mut rng, 0.0).unwrap(), (a.clone(), b.clone()));
+
+        assert_eq!(a.mutate(&mut rng, 0.0), a);
+        let child = b.mutate(&mut rng, 0.1);
+        assert_eq!(b.count_ones(), 0);
+        assert!((50..150).contains(&child.count_ones()));
+    }
+}
@@ -0,0 +1,302 @@
+//! ## Gorilla compression
+//! The bit layout of Facebook's Gorilla time-series compression, which
+//! packs timestamps as the change between successive deltas and values as
+//! the XOR of each with the one before.
+//!
+//! A block usually starts with its first timestamp and value written whole,
+//! with `push_bits`, and then one delta-of-delta and one XOR per point. A
+//! delta-of-delta goes in one of five buckets:
+//!
+//! | Range               | Bits                |
+//! |---------------------|---------------------|
+//! | 0                   | `0`                 |
+//! | -63 to 64           | `10` then 7 bits    |
+//! | -255 to 256         | `110` then 9 bits   |
+//! | -2047 to 2048       | `1110` then 12 bits |
+//! | -(2^31 - 1) to 2^31 | `1111` then 32 bits |
+//!
+//! An XOR of 0 is a single `0`. Otherwise `1` is followed by `0` and the
+//! meaningful bits, if they fall within the window of the last XOR written
+//! with a header, or by `1`, 5 bits of leading zeros, 6 bits of length and
+//! the meaningful bits, which then become the window.
+
+use crate::{BitVec, BitVecError};
+
+/// Delta-of-delta buckets, as the control bits, their count and the width
+/// of the value after them
+const BUCKETS: [(u64, u32, u32); 4] = [(0b10, 2, 7), (0b110, 3, 9), (0b1110, 4, 12), (0b1111, 4, 32)];
+
+/// The meaningful bits of the last XOR written with a header, which later
+/// XORs reuse when their own set bits fall within them. Writer and reader
+/// each keep one, starting from the default of no window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct XorWindow {
+    leading: u32,       // zeros before the meaningful bits
+    meaningful: u32,    // 0 until an XOR has been written with a header
+}
+
+impl XorWindow {
+    /// Get the number of leading zeros of the window
+    pub fn leading_zeros(&self) -> u32 {
+        self.leading
+    }
+
+    /// Get the number of meaningful bits of the window, or 0 if there is no
+    /// window yet
+    pub fn meaningful_bits(&self) -> u32 {
+        self.meaningful
+    }
+
+    /// Returns the number of zeros after the meaningful bits
+    fn trailing(&self) -> u32 {
+        64 - self.leading - self.meaningful
+    }
+}
+
+impl BitVec {
+    /// Pushes a delta-of-delta in the smallest bucket that holds it. Returns
+    /// an error, pushing nothing, if it is outside the largest bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_timestamp_dod(0).unwrap();
+    /// bv.push_timestamp_dod(-3).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "0101111101");
+    /// assert_eq!(bv.read_timestamp_dod(), Some(0));
+    /// assert_eq!(bv.read_timestamp_dod(), Some(-3));
+    /// ```
+    pub fn push_timestamp_dod(&mut self, dod: i64) -> Result<(), BitVecError> {
+        if dod == 0 {
+            self.push_bit(false);
+            return Ok(());
+        }
+
+        let &(control, control_bits, width) = BUCKETS.iter()
+            .find(|&&(_, _, width)| -(1 << (width - 1)) < dod && dod <= 1 << (width - 1))
+            .ok_or(BitVecError::ValueTooWide { value: dod as u64, width: 32 })?;
+        self.push_low_bits(control, control_bits);
+        self.push_low_bits(dod as u64, width);
+        Ok(())
+    }
+
+    /// Reads a delta-of-delta. Returns None, without moving the reading
+    /// position, if it is cut short.
+    pub fn read_timestamp_dod(&mut self) -> Option<i64> {
+        let start = self.get_read_position();
+        let dod = self.read_dod();
+        if dod.is_none() {
+            self.place_read_position(start);
+        }
+        dod
+    }
+
+    /// Reads a delta-of-delta, leaving the reading position wherever it
+    /// stops
+    fn read_dod(&mut self) -> Option<i64> {
+        let mut ones = 0;
+        while ones < 4 && self.read_int::<u8>(1)? == 1 {
+            ones += 1;
+        }
+        if ones == 0 {
+            return Some(0);
+        }
+
+        let width = BUCKETS[ones - 1].2;
+        let raw = self.read_int::<u64>(width)? as i64;
+        // The top of each range is positive, the rest of the top half negative
+        Some(if raw > 1 << (width - 1) { raw - (1 << width) } else { raw })
+    }
+
+    /// Pushes the XOR of a value's bits with the previous value's, reusing
+    /// the window of meaningful bits where it can and updating it where it
+    /// cannot
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, XorWindow};
+    ///
+    /// let values = [12.0f64, 12.0, 24.0, 15.0];
+    /// let mut bv = BitVec::new();
+    /// let mut window = XorWindow::default();
+    /// for pair in values.windows(2) {
+    ///     bv.push_value_xor(pair[0].to_bits() ^ pair[1].to_bits(), &mut window);
+    /// }
+    ///
+    /// let mut window = XorWindow::default();
+    /// let mut value = values[0].to_bits();
+    /// for &expected in &values[1..] {
+    ///     value ^= bv.read_value_xor(&mut window).unwrap();
+    ///     assert_eq!(f64::from_bits(value), expected);
+    /// }
+    /// ```
+    pub fn push_value_xor(&mut self, xor: u64, window: &mut XorWindow) {
+        if xor == 0 {
+            self.push_bit(false);
+            return;
+        }
+        self.push_bit(true);
+
+        // The leading zero count has only 5 bits
+        let leading = xor.leading_zeros().min(31);
+        let trailing = xor.trailing_zeros();
+        if window.meaningful > 0 && leading >= window.leading && trailing >= window.trailing() {
+            self.push_bit(false);
+            self.push_low_bits(xor >> window.trailing(), window.meaningful);
+            return;
+        }
+
+        let meaningful = 64 - leading - trailing;
+        self.push_bit(true);
+        self.push_low_bits(leading as u64, 5);
+        // A length of 64 wraps to 0
+        self.push_low_bits(meaningful as u64, 6);
+        self.push_low_bits(xor >> trailing, meaningful);
+        *window = XorWindow { leading, meaningful };
+    }
+
+    /// Reads the XOR of a value's bits with the previous value's, updating
+    /// the window when the XOR brings a new one. Returns None, without
+    /// moving the reading position or changing the window, if the XOR is cut
+    /// short or reuses a window before there is one.
+    pub fn read_value_xor(&mut self, window: &mut XorWindow) -> Option<u64> {
+        let start = self.get_read_position();
+        let xor = self.read_xor(window);
+        if xor.is_none() {
+            self.place_read_position(start);
+        }
+        xor
+    }
+
+    /// Reads an XOR, leaving the reading position wherever it stops
+    fn read_xor(&mut self, window: &mut XorWindow) -> Option<u64> {
+        if self.read_int::<u8>(1)? == 0 {
+            return Some(0);
+        }
+
+        if self.read_int::<u8>(1)? == 0 {
+            if window.meaningful == 0 {
+                return None;
+            }
+            let bits = self.read_int::<u64>(window.meaningful)?;
+            return Some(bits << window.trailing());
+        }
+
+        let leading = self.read_int::<u32>(5)?;
+        let meaningful = match self.read_int::<u32>(6)? {
+            0 => 64,
+            length => length,
+        };
+        if leading + meaningful > 64 {
+            return None;
+        }
+        let bits = self.read_int::<u64>(meaningful)?;
+        let new_window = XorWindow { leading, meaningful };
+        *window = new_window;
+        Some(bits << new_window.trailing())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_buckets() {
+        let dods = [0, 1, -1, 64, -63, 65, -64, 256, -255, 2048, -2047, 2049, 1 << 31, -(1 << 31) + 1];
+        let mut bv = BitVec::new();
+        for &dod in &dods {
+            bv.push_timestamp_dod(dod).unwrap();
+        }
+        let sizes = [1, 9, 9, 9, 9, 12, 12, 12, 12, 16, 16, 36, 36, 36];
+        assert_eq!(bv.len_bits(), sizes.iter().sum::<usize>());
+
+        for &dod in &dods {
+            assert_eq!(bv.read_timestamp_dod(), Some(dod));
+        }
+        assert_eq!(bv.read_timestamp_dod(), None);
+
+        assert_eq!(
+            bv.push_timestamp_dod(-(1 << 31)),
+            Err(BitVecError::ValueTooWide { value: (-(1i64 << 31)) as u64, width: 32 })
+        );
+        assert!(bv.push_timestamp_dod((1 << 31) + 1).is_err());
+    }
+
+    #[test]
+    fn series_round_trip() {
+        // A block of regular timestamps with jitter, and slowly moving values
+        let timestamps: Vec<i64> = (0..50).map(|i| 1_700_000_000 + 60 * i + (i % 7 == 3) as i64 * 5).collect();
+        let values: Vec<f64> = (0..50).map(|i| 20.0 + (i / 5) as f64 * 0.5).collect();
+
+        let mut bv = BitVec::new();
+        bv.push_low_bits(timestamps[0] as u64, 64);
+        bv.push_low_bits(values[0].to_bits(), 64);
+        let mut window = XorWindow::default();
+        let mut delta = 0;
+        for i in 1..timestamps.len() {
+            let next_delta = timestamps[i] - timestamps[i - 1];
+            bv.push_timestamp_dod(next_delta - delta).unwrap();
+            delta = next_delta;
+            bv.push_value_xor(values[i - 1].to_bits() ^ values[i].to_bits(), &mut window);
+        }
+        assert!(bv.len_bits() < 50 * 16);
+
+        let mut timestamp = bv.read_int::<i64>(64).unwrap();
+        let mut value = bv.read_int::<u64>(64).unwrap();
+        let mut window = XorWindow::default();
+        let mut delta = 0;
+        for i in 1..timestamps.len() {
+            delta += bv.read_timestamp_dod().unwrap();
+            timestamp += delta;
+            value ^= bv.read_value_xor(&mut window).unwrap();
+            assert_eq!(timestamp, timestamps[i]);
+            assert_eq!(f64::from_bits(value), values[i]);
+        }
+        assert_eq!(bv.get_read_position(), bv.len_bits());
+    }
+
+    #[test]
+    fn xor_windows() {
+        let mut bv = BitVec::new();
+        let mut window = XorWindow::default();
+        // A narrow window, an XOR inside it, one spilling past its end with
+        // more than 31 leading zeros, and one of the full width
+        bv.push_value_xor(0x10, &mut window);
+        assert_eq!((window.leading_zeros(), window.meaningful_bits()), (31, 29));
+        bv.push_value_xor(0x0F00, &mut window);
+        assert_eq!(window.meaningful_bits(), 29);
+        bv.push_value_xor(1, &mut window);
+        assert_eq!((window.leading_zeros(), window.meaningful_bits()), (31, 33));
+        bv.push_value_xor(u64::MAX, &mut window);
+        assert_eq!((window.leading_zeros(), window.meaningful_bits()), (0, 64));
+        assert_eq!(bv.len_bits(), (2 + 11 + 29) + (2 + 29) + (2 + 11 + 33) + (2 + 11 + 64));
+
+        let mut window = XorWindow::default();
+        for xor in [0x10, 0x0F00, 1, u64::MAX] {
+            assert_eq!(bv.read_value_xor(&mut window), Some(xor));
+        }
+    }
+
+    #[test]
+    fn malformed_xor() {
+        // Reusing a window before there is one
+        let mut bv = BitVec::from_bit_str("10 1").unwrap();
+        let mut window = XorWindow::default();
+        assert_eq!(bv.read_value_xor(&mut window), None);
+        assert_eq!(bv.get_read_position(), 0);
+
+        // Cut short after the header
+        let mut bv = BitVec::from_bit_str("11 00010 000100 101").unwrap();
+        assert_eq!(bv.read_value_xor(&mut window), None);
+        assert_eq!(bv.get_read_position(), 0);
+        assert_eq!(window, XorWindow::default());
+    }
+}
@@ -0,0 +1,765 @@
+//! ## Bit grids
+//! A 2D grid of bits, such as a binary image or a cellular automaton. Each
+//! row is packed MSB first into its own run of 64-bit words, so neighbouring
+//! cells can be worked on a whole word at a time, by shifting rows sideways
+//! and combining them with the rows above and below. Cells outside the grid
+//! count as clear.
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::BitVec;
+
+/// Bits in each word of a row
+const WORD_BITS: usize = 64;
+
+/// Adds three words bit by bit, returning the sum and carry words
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    (a ^ b ^ c, (a & b) | (c & (a ^ b)))
+}
+
+/// Which neighbouring cells count as connected
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// Only the cells above, below, and to either side
+    Four,
+    /// The diagonal cells as well
+    Eight,
+}
+
+/// Finds the root of a set in a union-find forest, halving the path
+fn find_root(parents: &mut [usize], node: usize) -> usize {
+    let mut node = node;
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+/// A 2D grid of bits, stored row by row
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitGrid;
+///
+/// // A blinker flips between a row and a column
+/// let mut grid = BitGrid::new(5, 5);
+/// for x in 1..4 {
+///     grid.set(x, 2, true);
+/// }
+/// grid.life_step();
+/// assert!(grid.get(2, 1) && grid.get(2, 2) && grid.get(2, 3));
+/// assert_eq!(grid.count_ones(), 3);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    words:  Vec<u64>,   // rows of packed cells, MSB first
+    width:  usize,      // cells in each row
+    height: usize,      // number of rows
+    stride: usize,      // words in each row
+}
+
+impl BitGrid {
+    /// Constructs a new BitGrid of clear cells
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(WORD_BITS);
+        Self {
+            words: vec![0; stride * height],
+            width,
+            height,
+            stride,
+        }
+    }
+
+    /// Builds a grid from the rows of a BitVec, one after another with no
+    /// padding. Panics if the width does not divide the length.
+    pub fn from_bitvec(bv: &BitVec, width: usize) -> Self {
+        if width == 0 && bv.len > 0 || width > 0 && !bv.len.is_multiple_of(width) {
+            panic!("BitVec: invalid row width for the length")
+        }
+
+        let mut grid = BitGrid::new(width, bv.len.checked_div(width).unwrap_or(0));
+        for index in bv.iter_ones() {
+            grid.set(index % width, index / width, true);
+        }
+        grid
+    }
+
+    /// Returns the cells as a BitVec, row after row
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut bv = BitVec::new();
+        bv.resize(self.width * self.height, false);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    bv.set_bit(y * self.width + x, true);
+                }
+            }
+        }
+        bv
+    }
+
+    /// Builds a grid with `rows` rows of `cols` cells from the (row, column)
+    /// pairs of its set cells, as used by sparse matrix formats such as COO.
+    /// Panics if a cell is outside the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// let grid = BitGrid::from_cells(2, 3, [(0, 2), (1, 0)]);
+    /// assert!(grid.get(2, 0));
+    /// assert_eq!(grid.set_cells(), vec![(0, 2), (1, 0)]);
+    /// ```
+    pub fn from_cells<I: IntoIterator<Item = (usize, usize)>>(rows: usize, cols: usize, cells: I) -> Self {
+        let mut grid = BitGrid::new(cols, rows);
+        for (row, col) in cells {
+            grid.set(col, row, true);
+        }
+        grid
+    }
+
+    /// Returns the (row, column) pairs of the set cells, in row order
+    pub fn set_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::with_capacity(self.count_ones());
+        for y in 0..self.height {
+            for run in self.row_runs(y) {
+                cells.extend(run.map(|x| (y, x)));
+            }
+        }
+        cells
+    }
+
+    /// Get the number of cells in each row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the word holding a cell and the mask for it within the word
+    fn locate(&self, x: usize, y: usize) -> (usize, u64) {
+        if x >= self.width || y >= self.height {
+            panic!("BitVec: index out of bounds")
+        }
+        (y * self.stride + x / WORD_BITS, 1 << (WORD_BITS - 1 - x % WORD_BITS))
+    }
+
+    /// Returns the value of the cell in column `x` of row `y`. Panics if the
+    /// cell is outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (word, mask) = self.locate(x, y);
+        self.words[word] & mask != 0
+    }
+
+    /// Sets the cell in column `x` of row `y`. Panics if the cell is outside
+    /// the grid.
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        let (word, mask) = self.locate(x, y);
+        if value {
+            self.words[word] |= mask;
+        } else {
+            self.words[word] &= !mask;
+        }
+    }
+
+    /// Returns the number of set cells
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns the words of a row
+    pub(crate) fn row_words(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    /// Returns the words of a row to change
+    pub(crate) fn row_words_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    /// Returns the number of set cells in each row
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// let grid = BitGrid::from_cells(2, 3, [(0, 0), (0, 2), (1, 2)]);
+    /// assert_eq!(grid.row_popcounts(), vec![2, 1]);
+    /// assert_eq!(grid.col_popcounts(), vec![1, 0, 2]);
+    /// ```
+    pub fn row_popcounts(&self) -> Vec<usize> {
+        (0..self.height)
+            .map(|y| self.row_words(y).iter().map(|word| word.count_ones() as usize).sum())
+            .collect()
+    }
+
+    /// Returns the number of set cells in each column, adding up the set
+    /// cells of each row word by word
+    pub fn col_popcounts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.width];
+        for y in 0..self.height {
+            for (index, &word) in self.row_words(y).iter().enumerate() {
+                let mut remaining = word;
+                while remaining != 0 {
+                    let offset = remaining.leading_zeros() as usize;
+                    counts[index * WORD_BITS + offset] += 1;
+                    remaining &= !(1 << (WORD_BITS - 1 - offset));
+                }
+            }
+        }
+        counts
+    }
+
+    /// Returns a BitVec with a bit for each row, set if any cell of the row
+    /// is set
+    pub fn rows_any(&self) -> BitVec {
+        let mut bv = BitVec::new();
+        for y in 0..self.height {
+            bv.push_bit(self.row_words(y).iter().any(|&word| word != 0));
+        }
+        bv
+    }
+
+    /// Returns a BitVec with a bit for each column, set if every cell of the
+    /// column is set, by ANDing the rows together
+    pub fn cols_all(&self) -> BitVec {
+        let mut columns = vec![u64::MAX; self.stride];
+        for y in 0..self.height {
+            for (column, &word) in columns.iter_mut().zip(self.row_words(y)) {
+                *column &= word;
+            }
+        }
+
+        let mut bv = BitVec::new();
+        for x in 0..self.width {
+            bv.push_bit(columns[x / WORD_BITS] & (1 << (WORD_BITS - 1 - x % WORD_BITS)) != 0);
+        }
+        bv
+    }
+
+    /// Finds the first cell in row `y` with a value, starting from column
+    /// `x`, a word at a time
+    fn next_in_row(&self, y: usize, x: usize, value: bool) -> Option<usize> {
+        let row = &self.words[y * self.stride..(y + 1) * self.stride];
+        let mut index = x / WORD_BITS;
+        let mut mask = u64::MAX.checked_shr((x % WORD_BITS) as u32).unwrap_or(0);
+        while index < self.stride {
+            let word = if value { row[index] } else { !row[index] } & mask;
+            if word != 0 {
+                let found = index * WORD_BITS + word.leading_zeros() as usize;
+                return (found < self.width).then_some(found);
+            }
+            index += 1;
+            mask = u64::MAX;
+        }
+        None
+    }
+
+    /// Finds the last cell in row `y` with a value before column `x`, a word
+    /// at a time
+    fn prev_in_row(&self, y: usize, x: usize, value: bool) -> Option<usize> {
+        let last = x.checked_sub(1)?;
+        let row = &self.words[y * self.stride..(y + 1) * self.stride];
+        let mut index = last / WORD_BITS;
+        let mut mask = !u64::MAX.checked_shr((last % WORD_BITS + 1) as u32).unwrap_or(0);
+        loop {
+            let word = if value { row[index] } else { !row[index] } & mask;
+            if word != 0 {
+                return Some(index * WORD_BITS + WORD_BITS - 1 - word.trailing_zeros() as usize);
+            }
+            index = index.checked_sub(1)?;
+            mask = u64::MAX;
+        }
+    }
+
+    /// Mask of the cells of a run within a word of a row
+    fn run_mask(run: &Range<usize>, index: usize) -> u64 {
+        let from = run.start.saturating_sub(index * WORD_BITS);
+        let to = (run.end - index * WORD_BITS).min(WORD_BITS);
+        (u64::MAX >> from) & !u64::MAX.checked_shr(to as u32).unwrap_or(0)
+    }
+
+    /// Sets a run of cells in row `y`, a word at a time
+    pub(crate) fn set_run(&mut self, y: usize, run: Range<usize>, value: bool) {
+        for index in run.start / WORD_BITS..run.end.div_ceil(WORD_BITS) {
+            let mask = Self::run_mask(&run, index);
+            let word = &mut self.words[y * self.stride + index];
+            if value {
+                *word |= mask;
+            } else {
+                *word &= !mask;
+            }
+        }
+    }
+
+    /// Counts the set cells in a block of columns and rows, a word at a time
+    pub(crate) fn count_ones_in_block(&self, columns: Range<usize>, rows: Range<usize>) -> usize {
+        if columns.is_empty() {
+            return 0;
+        }
+        rows.map(|y| {
+            (columns.start / WORD_BITS..columns.end.div_ceil(WORD_BITS))
+                .map(|index| (self.words[y * self.stride + index] & Self::run_mask(&columns, index)).count_ones() as usize)
+                .sum::<usize>()
+        }).sum()
+    }
+
+    /// Returns the runs of set cells in row `y`, as column ranges
+    pub fn row_runs(&self, y: usize) -> Vec<Range<usize>> {
+        if y >= self.height {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let mut runs = Vec::new();
+        let mut x = 0;
+        while let Some(start) = self.next_in_row(y, x, true) {
+            let end = self.next_in_row(y, start, false).unwrap_or(self.width);
+            runs.push(start..end);
+            x = end;
+        }
+        runs
+    }
+
+    /// Finds the groups of connected set cells. Each component is returned
+    /// as its runs of set cells, as the row and the range of columns, in
+    /// row order. Components are ordered by their first cell.
+    ///
+    /// Runs are found a word at a time, then joined with a union-find over
+    /// the runs that touch in neighbouring rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitGrid, Connectivity};
+    ///
+    /// let mut grid = BitGrid::new(4, 2);
+    /// grid.set(0, 0, true);
+    /// grid.set(1, 1, true);
+    /// assert_eq!(grid.connected_components(Connectivity::Four).len(), 2);
+    /// assert_eq!(grid.connected_components(Connectivity::Eight), vec![vec![(0, 0..1), (1, 1..2)]]);
+    /// ```
+    pub fn connected_components(&self, connectivity: Connectivity) -> Vec<Vec<(usize, Range<usize>)>> {
+        let reach = match connectivity {
+            Connectivity::Four => 0,
+            Connectivity::Eight => 1,
+        };
+
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        let mut parents: Vec<usize> = Vec::new();
+        let mut previous = 0..0;
+        for y in 0..self.height {
+            let first = runs.len();
+            for run in self.row_runs(y) {
+                let node = runs.len();
+                parents.push(node);
+
+                // Join with every run in the row above that touches this one
+                for other in previous.clone() {
+                    let above: &Range<usize> = &runs[other].1;
+                    if above.start < run.end + reach && run.start < above.end + reach {
+                        let (a, b) = (find_root(&mut parents, other), find_root(&mut parents, node));
+                        parents[a.max(b)] = a.min(b);
+                    }
+                }
+                runs.push((y, run));
+            }
+            previous = first..runs.len();
+        }
+
+        // Roots are always the earliest run of their component
+        let mut components: Vec<Vec<(usize, Range<usize>)>> = Vec::new();
+        let mut slots = vec![usize::MAX; runs.len()];
+        for (node, run) in runs.into_iter().enumerate() {
+            let root = find_root(&mut parents, node);
+            if slots[root] == usize::MAX {
+                slots[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[slots[root]].push(run);
+        }
+        components
+    }
+
+    /// Sets the 4-connected region of cells sharing the value of the cell in
+    /// column `x` of row `y` to a new value, returning the number of cells
+    /// changed. Panics if the cell is outside the grid.
+    ///
+    /// Each row of the region is found and filled as a whole run, a word at
+    /// a time, before looking for more of the region in the rows either side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// // Fill the inside of a box
+    /// let mut grid = BitGrid::new(5, 5);
+    /// for i in 0..5 {
+    ///     grid.set(i, 0, true);
+    ///     grid.set(i, 4, true);
+    ///     grid.set(0, i, true);
+    ///     grid.set(4, i, true);
+    /// }
+    /// assert_eq!(grid.flood_fill(2, 2, true), 9);
+    /// assert_eq!(grid.count_ones(), 25);
+    /// ```
+    pub fn flood_fill(&mut self, x: usize, y: usize, value: bool) -> usize {
+        let target = self.get(x, y);
+        if target == value {
+            return 0;
+        }
+
+        let mut filled = 0;
+        let mut seeds = vec![(x, y)];
+        while let Some((x, y)) = seeds.pop() {
+            if self.get(x, y) != target {
+                continue;
+            }
+            let start = self.prev_in_row(y, x, !target).map_or(0, |prev| prev + 1);
+            let end = self.next_in_row(y, x, !target).unwrap_or(self.width);
+            self.set_run(y, start..end, value);
+            filled += end - start;
+
+            // One seed for each run of the region touching this one
+            for other in [y.checked_sub(1), Some(y + 1)].into_iter().flatten().filter(|&other| other < self.height) {
+                let mut column = start;
+                while let Some(found) = self.next_in_row(other, column, target).filter(|&found| found < end) {
+                    seeds.push((found, other));
+                    column = self.next_in_row(other, found, !target).unwrap_or(self.width);
+                }
+            }
+        }
+
+        filled
+    }
+
+    /// Returns a row of words, or clear words for rows outside the grid
+    fn row(&self, y: isize) -> &[u64] {
+        match usize::try_from(y) {
+            Ok(y) if y < self.height => &self.words[y * self.stride..(y + 1) * self.stride],
+            _ => &[],
+        }
+    }
+
+    /// Returns the word of a row holding, for each cell of the word at
+    /// `index`, the cell `offset` columns to the east of it
+    fn shifted(row: &[u64], index: usize, offset: isize) -> u64 {
+        let word = |index: isize| usize::try_from(index).ok()
+            .and_then(|index| row.get(index))
+            .copied()
+            .unwrap_or(0);
+        let index = index as isize + offset.div_euclid(WORD_BITS as isize);
+        let shift = offset.rem_euclid(WORD_BITS as isize) as usize;
+        if shift == 0 {
+            word(index)
+        } else {
+            (word(index) << shift) | (word(index + 1) >> (WORD_BITS - shift))
+        }
+    }
+
+    /// Returns the word of a row holding each cell's west neighbour, the
+    /// word itself, and the word holding each cell's east neighbour
+    fn neighbours(row: &[u64], index: usize) -> (u64, u64, u64) {
+        (Self::shifted(row, index, -1), Self::shifted(row, index, 0), Self::shifted(row, index, 1))
+    }
+
+    /// Mask of the cells within the grid for a word of a row
+    pub(crate) fn word_mask(&self, index: usize) -> u64 {
+        let tail = self.width % WORD_BITS;
+        if index == self.stride - 1 && tail > 0 {
+            !(u64::MAX >> tail)
+        } else {
+            u64::MAX
+        }
+    }
+
+    /// Advances the grid by one generation of Conway's Game of Life. A set
+    /// cell survives with 2 or 3 set neighbours and a clear cell becomes set
+    /// with exactly 3, where cells beyond the edges are always clear.
+    ///
+    /// Neighbours are counted for 64 cells at once, with the count held as
+    /// one word per binary digit and summed by bitwise adders.
+    pub fn life_step(&mut self) {
+        let mut next = vec![0; self.words.len()];
+
+        for y in 0..self.height {
+            let (above, row, below) = (self.row(y as isize - 1), self.row(y as isize), self.row(y as isize + 1));
+            for index in 0..self.stride {
+                let (nw, n, ne) = Self::neighbours(above, index);
+                let (w, alive, e) = Self::neighbours(row, index);
+                let (sw, s, se) = Self::neighbours(below, index);
+
+                // Ones digit, with the carries into the twos
+                let (sum_a, carry_a) = full_add(nw, n, ne);
+                let (sum_b, carry_b) = full_add(w, e, sw);
+                let (sum_c, carry_c) = (s ^ se, s & se);
+                let (ones, carry_d) = full_add(sum_a, sum_b, sum_c);
+
+                // Twos digit, with the carries into the fours
+                let (sum_e, carry_e) = full_add(carry_a, carry_b, carry_c);
+                let (twos, carry_f) = (sum_e ^ carry_d, sum_e & carry_d);
+                let fours = carry_e | carry_f;
+
+                // A count of 2 or 3 has the twos digit alone set, ignoring
+                // the ones, and a count of 8 has no twos
+                next[y * self.stride + index] = twos & !fours & (ones | alive) & self.word_mask(index);
+            }
+        }
+
+        self.words = next;
+    }
+
+    /// Combines copies of the grid offset by each set cell of a kernel,
+    /// whose centre cell is at no offset
+    fn morph(&self, kernel: &BitGrid, sign: isize, init: u64, op: impl Fn(u64, u64) -> u64) -> BitGrid {
+        let mut result = BitGrid::new(self.width, self.height);
+        result.words.fill(init);
+        let (centre_x, centre_y) = (kernel.width as isize / 2, kernel.height as isize / 2);
+
+        for ky in 0..kernel.height {
+            for kx in 0..kernel.width {
+                if !kernel.get(kx, ky) {
+                    continue;
+                }
+                let (dx, dy) = (sign * (kx as isize - centre_x), sign * (ky as isize - centre_y));
+                for y in 0..self.height {
+                    let row = self.row(y as isize + dy);
+                    for index in 0..self.stride {
+                        let word = &mut result.words[y * self.stride + index];
+                        *word = op(*word, Self::shifted(row, index, dx));
+                    }
+                }
+            }
+        }
+
+        for y in 0..self.height {
+            for index in 0..self.stride {
+                result.words[y * self.stride + index] &= self.word_mask(index);
+            }
+        }
+        result
+    }
+
+    /// Returns the dilation of the grid by a kernel, centred on its middle
+    /// cell, so a cell is set if the kernel placed there, reflected, covers
+    /// any set cell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// let mut grid = BitGrid::new(5, 5);
+    /// grid.set(2, 2, true);
+    /// let mut cross = BitGrid::new(3, 3);
+    /// for (x, y) in [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)] {
+    ///     cross.set(x, y, true);
+    /// }
+    /// let grown = grid.dilate(&cross);
+    /// assert_eq!(grown.count_ones(), 5);
+    /// assert_eq!(grown.erode(&cross), grid);
+    /// ```
+    pub fn dilate(&self, kernel: &BitGrid) -> BitGrid {
+        self.morph(kernel, -1, 0, |word, shifted| word | shifted)
+    }
+
+    /// Returns the erosion of the grid by a kernel, centred on its middle
+    /// cell, so a cell stays set only if the kernel placed there covers only
+    /// set cells. Cells beyond the edges count as clear.
+    pub fn erode(&self, kernel: &BitGrid) -> BitGrid {
+        self.morph(kernel, 1, u64::MAX, |word, shifted| word & shifted)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> BitGrid {
+        let bv = BitVec::from_bit_str(&rows.concat()).unwrap();
+        BitGrid::from_bitvec(&bv, rows[0].len())
+    }
+
+    #[test]
+    fn cells_and_conversion() {
+        let mut g = BitGrid::new(70, 3);
+        g.set(0, 0, true);
+        g.set(63, 1, true);
+        g.set(64, 1, true);
+        g.set(69, 2, true);
+        assert!(g.get(64, 1));
+        assert!(!g.get(65, 1));
+        assert_eq!(g.count_ones(), 4);
+
+        let bv = g.to_bitvec();
+        assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![0, 133, 134, 209]);
+        assert_eq!(BitGrid::from_bitvec(&bv, 70), g);
+    }
+
+    #[test]
+    fn still_life_and_oscillator() {
+        let block = grid(&["0000", "0110", "0110", "0000"]);
+        let mut next = block.clone();
+        next.life_step();
+        assert_eq!(next, block);
+
+        // A toad in the corner, where cells beyond the edge stay clear
+        let mut toad = grid(&["0111", "1110", "0000"]);
+        toad.life_step();
+        assert_eq!(toad, grid(&["1001", "1001", "0100"]));
+    }
+
+    #[test]
+    fn glider_across_words() {
+        // A glider crossing the boundary between the first and second words
+        let mut g = BitGrid::new(130, 8);
+        for (x, y) in [(62, 0), (63, 1), (61, 2), (62, 2), (63, 2)] {
+            g.set(x, y, true);
+        }
+        for _ in 0..4 {
+            g.life_step();
+            assert_eq!(g.count_ones(), 5);
+        }
+        // The glider has moved one cell down and to the right
+        for (x, y) in [(63, 1), (64, 2), (62, 3), (63, 3), (64, 3)] {
+            assert!(g.get(x, y));
+        }
+    }
+
+    #[test]
+    fn dilate_and_erode() {
+        let g = grid(&["000000", "011000", "011010", "000000"]);
+        let square = grid(&["111", "111", "111"]);
+        assert_eq!(g.dilate(&square), grid(&["111100", "111111", "111111", "111111"]));
+        // Erosion removes the speck and shrinks the block onto the centre
+        // of the kernel, and the edges count as clear
+        let eroded = g.erode(&grid(&["11", "11"]));
+        assert_eq!(eroded, grid(&["000000", "000000", "001000", "000000"]));
+        assert_eq!(g.dilate(&square).erode(&square), grid(&["000000", "011000", "011110", "000000"]));
+    }
+
+    #[test]
+    fn morphology_across_words() {
+        let mut g = BitGrid::new(140, 1);
+        g.set(63, 0, true);
+        let line = grid(&["11111111111"]);
+        let grown = g.dilate(&line);
+        assert_eq!(grown.count_ones(), 11);
+        assert!(grown.get(58, 0) && grown.get(68, 0) && !grown.get(69, 0));
+        assert_eq!(grown.erode(&line), g);
+        // A lopsided kernel grows the grid on its own side
+        let grown = g.dilate(&grid(&["011"]));
+        assert!(grown.get(63, 0) && grown.get(64, 0) && !grown.get(62, 0));
+    }
+
+    #[test]
+    fn components() {
+        let g = grid(&[
+            "1100011",
+            "0100010",
+            "0011110",
+            "0000000",
+            "1010001",
+        ]);
+        let four = g.connected_components(Connectivity::Four);
+        assert_eq!(four.len(), 5);
+        assert_eq!(four[0], vec![(0, 0..2), (1, 1..2)]);
+        assert_eq!(four[1], vec![(0, 5..7), (1, 5..6), (2, 2..6)]);
+        assert_eq!(four[2], vec![(4, 0..1)]);
+
+        // The first blob touches the second diagonally
+        let eight = g.connected_components(Connectivity::Eight);
+        assert_eq!(eight.len(), 4);
+        assert_eq!(eight[0].len(), 5);
+        assert_eq!(eight[3], vec![(4, 6..7)]);
+        assert!(BitGrid::new(3, 3).connected_components(Connectivity::Eight).is_empty());
+    }
+
+    #[test]
+    fn runs_across_words() {
+        let mut g = BitGrid::new(200, 2);
+        for x in 60..130 {
+            g.set(x, 0, true);
+        }
+        g.set(199, 0, true);
+        g.set(130, 1, true);
+        assert_eq!(g.row_runs(0), vec![60..130, 199..200]);
+        assert_eq!(g.connected_components(Connectivity::Four).len(), 3);
+        assert_eq!(g.connected_components(Connectivity::Eight).len(), 2);
+    }
+
+    #[test]
+    fn flood_fill_regions() {
+        let mut g = grid(&[
+            "0010000",
+            "0010110",
+            "1110100",
+            "0000111",
+        ]);
+        // The top left pocket is closed off diagonally
+        assert_eq!(g.flood_fill(0, 0, true), 4);
+        assert_eq!(g.flood_fill(0, 0, true), 0);
+        // Clearing the right hand shape, then filling everything clear
+        assert_eq!(g.flood_fill(4, 1, false), 6);
+        assert_eq!(g.flood_fill(6, 0, true), 19);
+        assert_eq!(g.count_ones(), g.width() * g.height());
+    }
+
+    #[test]
+    fn flood_fill_across_words() {
+        let mut g = BitGrid::new(150, 3);
+        for y in 0..3 {
+            g.set(100, y, true);
+        }
+        g.set(100, 1, false);
+        assert_eq!(g.flood_fill(0, 0, true), 100 * 3 + 1 + 49 * 3);
+        assert_eq!(g.count_ones(), 450);
+    }
+
+    #[test]
+    fn sparse_cells() {
+        let cells = vec![(0, 69), (2, 0), (2, 1), (2, 64), (4, 3)];
+        let g = BitGrid::from_cells(5, 70, cells.iter().copied());
+        assert_eq!((g.width(), g.height()), (70, 5));
+        assert!(g.get(64, 2));
+        assert_eq!(g.set_cells(), cells);
+        assert!(BitGrid::new(3, 3).set_cells().is_empty());
+    }
+
+    #[test]
+    fn row_and_column_reductions() {
+        let mut g = BitGrid::new(70, 3);
+        for y in 0..3 {
+            g.set(1, y, true);
+            g.set(66, y, true);
+        }
+        g.set(0, 0, true);
+        g.set(69, 2, true);
+        assert_eq!(g.row_popcounts(), vec![3, 2, 3]);
+
+        let counts = g.col_popcounts();
+        assert_eq!(counts.len(), 70);
+        assert_eq!((counts[0], counts[1], counts[66], counts[69]), (1, 3, 3, 1));
+        assert_eq!(counts.iter().sum::<usize>(), g.count_ones());
+
+        g.set(69, 2, false);
+        g.set(0, 0, false);
+        g.set(1, 1, false);
+        assert_eq!(g.rows_any().to_bit_string(), "111");
+        assert_eq!(BitGrid::from_cells(3, 2, [(1, 1)]).rows_any().to_bit_string(), "010");
+        assert_eq!(g.cols_all().iter_ones().collect::<Vec<_>>(), vec![66]);
+        // Every column of a grid with no rows is vacuously full
+        assert_eq!(BitGrid::new(5, 0).cols_all().count_ones(), 5);
+    }
+}
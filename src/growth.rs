@@ -0,0 +1,93 @@
+//! ## Growth policy
+//! Bitmap users expect setting a bit past the end to grow the vector, while
+//! users of fixed-size frames would rather catch the stray index. The growth
+//! policy of a BitVec decides which `set_bit` does. Whatever the policy,
+//! `set_bit_strict` never grows the vector.
+
+use crate::{BitVec, BitVecError};
+
+/// What `set_bit` does with an index beyond the end of the vector
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GrowthPolicy {
+    /// The vector grows to hold the bit, filling any gap with 0s
+    #[default]
+    Grow,
+    /// The index is out of bounds, and `set_bit` panics
+    Strict,
+}
+
+impl BitVec {
+    /// Constructs a new, empty, BitVec with the given growth policy
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, GrowthPolicy};
+    ///
+    /// let mut bv = BitVec::with_growth_policy(GrowthPolicy::Strict);
+    /// bv.push_bit(false);
+    /// bv.set_bit(0, true);
+    /// assert!(bv.set_bit_strict(1, true).is_err());
+    /// ```
+    pub fn with_growth_policy(policy: GrowthPolicy) -> Self {
+        let mut bv = BitVec::new();
+        bv.growth = policy;
+        bv
+    }
+
+    /// Get the growth policy
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth
+    }
+
+    /// Changes the growth policy
+    pub fn set_growth_policy(&mut self, policy: GrowthPolicy) {
+        self.growth = policy;
+    }
+
+    /// Sets the bit at the desired index, returning an error rather than
+    /// growing the vector if the index is beyond the end, whatever the growth
+    /// policy
+    pub fn set_bit_strict(&mut self, index: usize, value: bool) -> Result<(), BitVecError> {
+        self.try_set_bit(index, value)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growing() {
+        let mut bv = BitVec::from_bit_str("101").unwrap();
+        assert_eq!(bv.growth_policy(), GrowthPolicy::Grow);
+        bv.set_bit(10, true);
+        assert_eq!(bv.len_bits(), 11);
+        assert_eq!(bv.to_bit_string(), "10100000001");
+        bv.set_bit(12, false);
+        assert_eq!(bv.to_bit_string(), "1010000000100");
+        assert_eq!(bv.as_bytes(), &[0xA0, 0x20]);
+
+        // Growing keeps the read position
+        bv.set_read_position(4);
+        bv.set_bit(20, true);
+        assert_eq!(bv.get_read_position(), 4);
+        assert_eq!(
+            bv.set_bit_strict(21, true),
+            Err(BitVecError::IndexOutOfBounds { index: 21, len: 21 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn strict_set_bit_past_the_end() {
+        let mut bv = BitVec::from(&[0x00]);
+        bv.set_growth_policy(GrowthPolicy::Strict);
+        bv.set_bit(7, true);
+        assert_eq!(bv.clone().growth_policy(), GrowthPolicy::Strict);
+        bv.set_bit(8, true);
+    }
+}
@@ -0,0 +1,156 @@
+//! ## Hilbert curve order
+//! Reading the cells of a BitGrid along a Hilbert curve rather than row by
+//! row. Cells that are close together in the grid stay close together in
+//! the BitVec, which helps range queries and the compression of spatial
+//! masks, since blobs become long runs.
+//!
+//! The curve covers the square with a power of two side that covers the
+//! grid, and the cells outside the grid are skipped, so the BitVec has one
+//! bit for each cell. Each stretch of the curve fills an aligned square, so
+//! whole squares outside the grid are stepped over at once, and a thin grid
+//! costs about its own number of cells rather than the covering square.
+
+use crate::{BitGrid, BitVec};
+
+/// Maps a distance along the Hilbert curve filling a square of `side` cells
+/// (a power of two) to its column and row
+fn hilbert_to_cell(side: usize, distance: usize) -> (usize, usize) {
+    let (mut x, mut y) = (0, 0);
+    let mut remaining = distance;
+    let mut scale = 1;
+    while scale < side {
+        let rx = 1 & (remaining / 2);
+        let ry = 1 & (remaining ^ rx);
+
+        // Rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = scale - 1 - x;
+                y = scale - 1 - y;
+            }
+            (x, y) = (y, x);
+        }
+        x += scale * rx;
+        y += scale * ry;
+        remaining /= 4;
+        scale *= 2;
+    }
+    (x, y)
+}
+
+impl BitGrid {
+    /// Returns the cells of the grid within the covering square, in Hilbert
+    /// curve order
+    fn hilbert_cells(width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> {
+        let side = width.max(height).max(1).next_power_of_two();
+        let mut distance = 0;
+        core::iter::from_fn(move || {
+            while distance < side * side {
+                let (x, y) = hilbert_to_cell(side, distance);
+                if x < width && y < height {
+                    distance += 1;
+                    return Some((x, y));
+                }
+
+                // The 4^k cells from a distance aligned to 4^k fill an aligned
+                // square, so skip the largest one lying wholly outside the grid
+                let mut block = 1;
+                while block < side && distance % (4 * block * block) == 0 {
+                    let mask = !(2 * block - 1);
+                    if x & mask < width && y & mask < height {
+                        break;
+                    }
+                    block *= 2;
+                }
+                distance += block * block;
+            }
+            None
+        })
+    }
+
+    /// Returns the cells in Hilbert curve order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// // The curve runs down, across, and back up a 2x2 grid
+    /// let mut grid = BitGrid::new(2, 2);
+    /// grid.set(1, 0, true);
+    /// assert_eq!(grid.to_hilbert_order().to_bit_string(), "0001");
+    /// ```
+    pub fn to_hilbert_order(&self) -> BitVec {
+        let mut bv = BitVec::new();
+        for (x, y) in Self::hilbert_cells(self.width(), self.height()) {
+            bv.push_bit(self.get(x, y));
+        }
+        bv
+    }
+
+    /// Builds a grid from cells in Hilbert curve order, as made by
+    /// `to_hilbert_order`. Panics if the length is not the number of cells.
+    pub fn from_hilbert_order(bv: &BitVec, width: usize, height: usize) -> BitGrid {
+        if bv.len != width * height {
+            panic!("BitVec: invalid grid size for the length")
+        }
+
+        let mut grid = BitGrid::new(width, height);
+        for (index, (x, y)) in Self::hilbert_cells(width, height).enumerate() {
+            if bv.get_bit(index) {
+                grid.set(x, y, true);
+            }
+        }
+        grid
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_order() {
+        // The order 2 curve visits each 2x2 quadrant in turn
+        let cells: Vec<(usize, usize)> = (0..16).map(|distance| hilbert_to_cell(4, distance)).collect();
+        assert_eq!(&cells[..4], &[(0, 0), (1, 0), (1, 1), (0, 1)]);
+        assert_eq!(&cells[4..8], &[(0, 2), (0, 3), (1, 3), (1, 2)]);
+        assert_eq!(cells[15], (3, 0));
+        // Each step moves to a neighbouring cell
+        for pair in cells.windows(2) {
+            assert_eq!(pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1), 1);
+        }
+    }
+
+    #[test]
+    fn round_trip_odd_sizes() {
+        let mut grid = BitGrid::new(13, 6);
+        for (x, y) in [(0, 0), (12, 5), (4, 2), (5, 2), (4, 3), (7, 1)] {
+            grid.set(x, y, true);
+        }
+        let ordered = grid.to_hilbert_order();
+        assert_eq!(ordered.len_bits(), 78);
+        assert_eq!(ordered.count_ones(), 6);
+        assert_eq!(BitGrid::from_hilbert_order(&ordered, 13, 6), grid);
+    }
+
+    #[test]
+    fn skipping_matches_filtering() {
+        for (width, height) in [(1usize, 1usize), (5, 1), (1, 9), (13, 6), (3, 17), (16, 16), (0, 4)] {
+            let side = width.max(height).max(1).next_power_of_two();
+            let expected: Vec<(usize, usize)> = (0..side * side)
+                .map(|distance| hilbert_to_cell(side, distance))
+                .filter(|&(x, y)| x < width && y < height)
+                .collect();
+            let cells: Vec<(usize, usize)> = BitGrid::hilbert_cells(width, height).collect();
+            assert_eq!(cells, expected, "{}x{}", width, height);
+        }
+
+        // A thin strip does not walk its covering square
+        let strip = BitGrid::new(1 << 16, 1);
+        assert_eq!(strip.to_hilbert_order().len_bits(), 1 << 16);
+    }
+}
@@ -0,0 +1,146 @@
+//! ## Canonical Huffman codes
+//! A canonical Huffman code is fixed by the length of each symbol's code
+//! alone, so formats such as DEFLATE, JPEG and Brotli store only the
+//! lengths. The codes are handed out in order of length, then of symbol,
+//! each one the previous code plus one, shifted left to the new length, as
+//! in RFC 1951 section 3.2.2. A length of 0 means the symbol has no code.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// Longest code that can be assigned
+const MAX_LENGTH: u8 = 64;
+
+impl BitVec {
+    /// Returns the canonical code for each symbol from the length of its
+    /// code, with an empty code for a length of 0. Panics if a length is
+    /// more than 64, or if the lengths oversubscribe the code space, so that
+    /// no prefix-free code can have them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // The example from RFC 1951
+    /// let codes = BitVec::canonical_codes(&[3, 3, 3, 3, 3, 2, 4, 4]);
+    /// assert_eq!(codes[0].to_bit_string(), "010");
+    /// assert_eq!(codes[5].to_bit_string(), "00");
+    /// assert_eq!(codes[7].to_bit_string(), "1111");
+    /// assert_eq!(BitVec::code_lengths(&codes), Some(vec![3, 3, 3, 3, 3, 2, 4, 4]));
+    /// ```
+    pub fn canonical_codes(lengths: &[u8]) -> Vec<BitVec> {
+        let mut counts = [0u128; MAX_LENGTH as usize + 1];
+        for &length in lengths {
+            if length > MAX_LENGTH {
+                panic!("BitVec: code length is more than 64 bits")
+            }
+            counts[length as usize] += 1;
+        }
+
+        // The first code of each length, checking the codes of each length
+        // fit in the space the shorter ones leave
+        let mut next = [0u128; MAX_LENGTH as usize + 1];
+        let mut code = 0;
+        for length in 1..=MAX_LENGTH as usize {
+            code = (code + counts[length - 1] * (length > 1) as u128) << 1;
+            next[length] = code;
+            if code + counts[length] > 1 << length {
+                panic!("BitVec: code lengths oversubscribe the code space")
+            }
+        }
+
+        lengths
+            .iter()
+            .map(|&length| {
+                let mut bv = BitVec::with_capacity(length as usize);
+                if length > 0 {
+                    let code = next[length as usize] as u64;
+                    next[length as usize] += 1;
+                    bv.push_low_bits(code, length as u32);
+                }
+                bv
+            })
+            .collect()
+    }
+
+    /// Returns the length of each code, the inverse of `canonical_codes`, or
+    /// None if the codes are not the canonical codes for their lengths
+    pub fn code_lengths(codes: &[BitVec]) -> Option<Vec<u8>> {
+        let mut lengths = Vec::with_capacity(codes.len());
+        for code in codes {
+            lengths.push(u8::try_from(code.len).ok().filter(|&length| length <= MAX_LENGTH)?);
+        }
+
+        let mut counts = [0u128; MAX_LENGTH as usize + 1];
+        for &length in &lengths {
+            counts[length as usize] += 1;
+        }
+        let mut space = 0;
+        for (length, &count) in counts.iter().enumerate().skip(1) {
+            space += count << (MAX_LENGTH as usize - length);
+        }
+        if space > 1 << MAX_LENGTH {
+            return None;
+        }
+
+        (Self::canonical_codes(&lengths).as_slice() == codes).then_some(lengths)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The codes as strings of 0s and 1s
+    fn strings(codes: &[BitVec]) -> Vec<alloc::string::String> {
+        codes.iter().map(BitVec::to_bit_string).collect()
+    }
+
+    #[test]
+    fn rfc_1951_example() {
+        let lengths = [3, 3, 3, 3, 3, 2, 4, 4];
+        let codes = BitVec::canonical_codes(&lengths);
+        assert_eq!(strings(&codes), ["010", "011", "100", "101", "110", "00", "1110", "1111"]);
+        assert_eq!(BitVec::code_lengths(&codes), Some(lengths.to_vec()));
+    }
+
+    #[test]
+    fn unused_symbols_and_incomplete_codes() {
+        // Symbols without codes are skipped, and the code need not be full
+        let codes = BitVec::canonical_codes(&[0, 1, 0, 3]);
+        assert_eq!(strings(&codes), ["", "0", "", "100"]);
+        assert_eq!(BitVec::code_lengths(&codes), Some(vec![0, 1, 0, 3]));
+        assert!(BitVec::canonical_codes(&[]).is_empty());
+
+        // Longest codes fill the space exactly
+        let mut lengths = vec![64, 64];
+        lengths.extend(1..64);
+        let codes = BitVec::canonical_codes(&lengths);
+        assert_eq!(codes[0].as_bitslice().to_u64(), u64::MAX - 1);
+        assert_eq!(codes[1].as_bitslice().to_u64(), u64::MAX);
+        assert_eq!(BitVec::code_lengths(&codes), Some(lengths));
+    }
+
+    #[test]
+    fn non_canonical_codes() {
+        let mut codes = BitVec::canonical_codes(&[2, 1, 2]);
+        assert_eq!(strings(&codes), ["10", "0", "11"]);
+        codes.swap(0, 2);
+        assert_eq!(BitVec::code_lengths(&codes), None);
+        // Too many codes for their lengths
+        let codes = [BitVec::from_bit_str("0").unwrap(), BitVec::from_bit_str("1").unwrap(), BitVec::from_bit_str("1").unwrap()];
+        assert_eq!(BitVec::code_lengths(&codes), None);
+        assert_eq!(BitVec::code_lengths(&[BitVec::from(&[0; 9])]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "oversubscribe")]
+    fn oversubscribed_lengths() {
+        BitVec::canonical_codes(&[1, 1, 2]);
+    }
+}
@@ -0,0 +1,308 @@
+//! ## Multi-byte integers
+//! Reading and popping 16, 32, and 64-bit unsigned integers with an explicit
+//! byte order, for parsing binary headers. The bytes are read and popped as
+//! `read_byte` and `pop_byte` do, so they may sit at any bit offset. Either
+//! the whole integer is available, or None is returned and nothing changes.
+//!
+//! Fields of any width are pushed and read MSB first as any primitive integer
+//! type up to 64 bits through `BitInt`, with signed types stored in two's
+//! complement and sign-extended when read.
+
+use crate::{BitVec, BitVecError};
+
+/// A primitive integer type of up to 64 bits which fields can be pushed from
+/// and read into
+pub trait BitInt: Copy {
+    /// Number of bits in the type
+    const BITS: u32;
+    /// Whether the type is signed, and so stored in two's complement
+    const SIGNED: bool;
+
+    /// Returns the value as 64 bits, sign-extended if the type is signed
+    fn to_u64(self) -> u64;
+
+    /// Returns the low bits of a 64-bit value as this type
+    fn from_u64(bits: u64) -> Self;
+}
+
+macro_rules! impl_bit_int {
+    ($($int:ty => $signed:expr),*) => {$(
+        impl BitInt for $int {
+            const BITS: u32 = <$int>::BITS;
+            const SIGNED: bool = $signed;
+
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            fn from_u64(bits: u64) -> Self {
+                bits as $int
+            }
+        }
+    )*};
+}
+
+impl_bit_int!(
+    u8 => false, u16 => false, u32 => false, u64 => false, usize => false,
+    i8 => true, i16 => true, i32 => true, i64 => true, isize => true
+);
+
+impl BitVec {
+    /// Reads `bytes` bytes from the reading position as an unsigned integer
+    fn read_uint(&mut self, bytes: usize, big_endian: bool) -> Option<u64> {
+        if self.get_read_position() + bytes * 8 > self.len {
+            return None;
+        }
+
+        Some((0..bytes).fold(0, |value, index| {
+            let byte = self.read_byte().unwrap() as u64;
+            if big_endian { value << 8 | byte } else { value | byte << (8 * index) }
+        }))
+    }
+
+    /// Pops the last `bytes` bytes as an unsigned integer
+    fn pop_uint(&mut self, bytes: usize, big_endian: bool) -> Option<u64> {
+        if self.len < bytes * 8 {
+            return None;
+        }
+
+        Some((0..bytes).fold(0, |value, index| {
+            let byte = self.pop_byte().unwrap() as u64;
+            if big_endian { value | byte << (8 * index) } else { value << 8 | byte }
+        }))
+    }
+
+    /// Reads 16 bits in sequence as a big-endian integer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x12, 0x34, 0x56, 0x78]);
+    /// assert_eq!(bv.read_u16_be(), Some(0x1234));
+    /// assert_eq!(bv.read_u16_le(), Some(0x7856));
+    /// assert_eq!(bv.read_u16_be(), None);
+    /// ```
+    pub fn read_u16_be(&mut self) -> Option<u16> {
+        self.read_uint(2, true).map(|value| value as u16)
+    }
+
+    /// Reads 16 bits in sequence as a little-endian integer
+    pub fn read_u16_le(&mut self) -> Option<u16> {
+        self.read_uint(2, false).map(|value| value as u16)
+    }
+
+    /// Reads 32 bits in sequence as a big-endian integer
+    pub fn read_u32_be(&mut self) -> Option<u32> {
+        self.read_uint(4, true).map(|value| value as u32)
+    }
+
+    /// Reads 32 bits in sequence as a little-endian integer
+    pub fn read_u32_le(&mut self) -> Option<u32> {
+        self.read_uint(4, false).map(|value| value as u32)
+    }
+
+    /// Reads 64 bits in sequence as a big-endian integer
+    pub fn read_u64_be(&mut self) -> Option<u64> {
+        self.read_uint(8, true)
+    }
+
+    /// Reads 64 bits in sequence as a little-endian integer
+    pub fn read_u64_le(&mut self) -> Option<u64> {
+        self.read_uint(8, false)
+    }
+
+    /// Removes and returns the last 16 bits as a big-endian integer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x12, 0x34, 0x56, 0x78]);
+    /// assert_eq!(bv.pop_u16_be(), Some(0x5678));
+    /// assert_eq!(bv.pop_u16_le(), Some(0x3412));
+    /// assert_eq!(bv.len_bits(), 0);
+    /// ```
+    pub fn pop_u16_be(&mut self) -> Option<u16> {
+        self.pop_uint(2, true).map(|value| value as u16)
+    }
+
+    /// Removes and returns the last 16 bits as a little-endian integer
+    pub fn pop_u16_le(&mut self) -> Option<u16> {
+        self.pop_uint(2, false).map(|value| value as u16)
+    }
+
+    /// Removes and returns the last 32 bits as a big-endian integer
+    pub fn pop_u32_be(&mut self) -> Option<u32> {
+        self.pop_uint(4, true).map(|value| value as u32)
+    }
+
+    /// Removes and returns the last 32 bits as a little-endian integer
+    pub fn pop_u32_le(&mut self) -> Option<u32> {
+        self.pop_uint(4, false).map(|value| value as u32)
+    }
+
+    /// Removes and returns the last 64 bits as a big-endian integer
+    pub fn pop_u64_be(&mut self) -> Option<u64> {
+        self.pop_uint(8, true)
+    }
+
+    /// Removes and returns the last 64 bits as a little-endian integer
+    pub fn pop_u64_le(&mut self) -> Option<u64> {
+        self.pop_uint(8, false)
+    }
+
+    /// Pushes an integer as a field of `bits` bits, MSB first, in two's
+    /// complement if the type is signed. Returns an error, pushing nothing,
+    /// if the value does not fit. Panics if the field is wider than the type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_int(5u8, 3).unwrap();
+    /// bv.push_int(-2i32, 4).unwrap();
+    /// assert!(bv.push_int(8u16, 3).is_err());
+    /// assert_eq!(bv.to_bit_string(), "1011110");
+    /// assert_eq!(bv.read_int::<u8>(3), Some(5));
+    /// assert_eq!(bv.read_int::<i64>(4), Some(-2));
+    /// ```
+    pub fn push_int<T: BitInt>(&mut self, value: T, bits: u32) -> Result<(), BitVecError> {
+        if bits > T::BITS {
+            panic!("BitVec: a {}-bit field is wider than the integer type", bits)
+        }
+
+        let raw = value.to_u64();
+        let fits = if bits == 64 {
+            true
+        } else if T::SIGNED {
+            let high = (raw as i64) >> bits.saturating_sub(1);
+            high == 0 || (high == -1 && bits > 0)
+        } else {
+            raw >> bits == 0
+        };
+        if !fits {
+            return Err(BitVecError::ValueTooWide { value: raw, width: bits });
+        }
+
+        self.push_low_bits(raw, bits);
+        Ok(())
+    }
+
+    /// Pushes the low `count` bits of a value, MSB first, ignoring any bits
+    /// above them. Panics if the count is more than 64.
+    pub(crate) fn push_low_bits(&mut self, value: u64, count: u32) {
+        if count > 64 {
+            panic!("BitVec: cannot push more than 64 bits of a value")
+        }
+        for shift in (0..count).rev() {
+            self.push_bit(value >> shift & 1 == 1);
+        }
+    }
+
+    /// Reads a field of `bits` bits in sequence, MSB first, as an integer,
+    /// sign-extending it if the type is signed. Returns None, without moving
+    /// the reading position, if fewer bits than that remain. Panics if the
+    /// field is wider than the type.
+    pub fn read_int<T: BitInt>(&mut self, bits: u32) -> Option<T> {
+        if bits > T::BITS {
+            panic!("BitVec: a {}-bit field is wider than the integer type", bits)
+        }
+
+        let start = self.get_read_position();
+        let end = start + bits as usize;
+        if end > self.len {
+            return None;
+        }
+
+        let mut raw = self.slice(start..end).to_u64();
+        if T::SIGNED && (1..64).contains(&bits) {
+            raw = (((raw << (64 - bits)) as i64) >> (64 - bits)) as u64;
+        }
+        self.place_read_position(end);
+        Some(T::from_u64(raw))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_at_bit_offsets() {
+        // A 3-bit field, then integers straddling byte boundaries
+        let mut bv = BitVec::from_bit_str("101").unwrap();
+        bv.extend(&BitVec::from(&0xDEADBEEFu32.to_be_bytes()));
+        bv.extend(&BitVec::from(&0x0123456789ABCDEFu64.to_le_bytes()));
+        bv.extend(&BitVec::from(&[0xFF]));
+        bv.set_read_position(3);
+
+        assert_eq!(bv.read_u32_be(), Some(0xDEADBEEF));
+        assert_eq!(bv.read_u64_le(), Some(0x0123456789ABCDEF));
+        // Only 8 bits left
+        assert_eq!(bv.read_u16_le(), None);
+        assert_eq!(bv.get_read_position(), 99);
+        assert_eq!(bv.read_byte(), Some(0xFF));
+    }
+
+    #[test]
+    fn pops_keep_order() {
+        let mut bv = BitVec::from_bit_str("1").unwrap();
+        bv.extend(&BitVec::from(&0xCAFEu16.to_le_bytes()));
+        bv.extend(&BitVec::from(&0x0123456789ABCDEFu64.to_be_bytes()));
+        bv.extend(&BitVec::from(&0x12345678u32.to_le_bytes()));
+
+        assert_eq!(bv.pop_u32_le(), Some(0x12345678));
+        assert_eq!(bv.pop_u64_be(), Some(0x0123456789ABCDEF));
+        assert_eq!(bv.pop_u32_be(), None);
+        assert_eq!(bv.pop_u16_le(), Some(0xCAFE));
+        assert_eq!(bv.to_bit_string(), "1");
+        assert_eq!(bv.pop_u16_be(), None);
+    }
+
+    #[test]
+    fn generic_fields() {
+        let mut bv = BitVec::new();
+        bv.push_int(0x1FFu16, 9).unwrap();
+        bv.push_int(-1i8, 1).unwrap();
+        bv.push_int(-64i64, 7).unwrap();
+        bv.push_int(63isize, 7).unwrap();
+        bv.push_int(u64::MAX, 64).unwrap();
+        bv.push_int(i64::MIN, 64).unwrap();
+        bv.push_int(0usize, 0).unwrap();
+        assert_eq!(bv.len_bits(), 152);
+
+        assert_eq!(bv.read_int::<u16>(9), Some(0x1FF));
+        assert_eq!(bv.read_int::<i8>(1), Some(-1));
+        assert_eq!(bv.read_int::<i8>(7), Some(-64));
+        assert_eq!(bv.read_int::<i32>(7), Some(63));
+        assert_eq!(bv.read_int::<u64>(64), Some(u64::MAX));
+        assert_eq!(bv.read_int::<i64>(64), Some(i64::MIN));
+        assert_eq!(bv.read_int::<u8>(0), Some(0));
+        assert_eq!(bv.read_int::<u8>(1), None);
+    }
+
+    #[test]
+    fn values_too_wide() {
+        let mut bv = BitVec::new();
+        assert_eq!(bv.push_int(64i8, 7), Err(BitVecError::ValueTooWide { value: 64, width: 7 }));
+        assert!(bv.push_int(-65i16, 7).is_err());
+        assert!(bv.push_int(1u8, 0).is_err());
+        assert!(bv.push_int(-1i8, 0).is_err());
+        assert!(bv.push_int(256usize, 8).is_err());
+        assert_eq!(bv.len_bits(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "wider than the integer type")]
+    fn field_wider_than_type() {
+        BitVec::new().push_int(1u8, 9).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+//! ## Interleaving and Gray codes
+//! Bit interleaving, which merges coordinates into a Morton (Z-order) code
+//! for spatial indexing, and reflected binary Gray codes, where neighbouring
+//! values differ in one bit. Both work on vectors of any length, taken as
+//! unsigned numbers with the most significant bit first.
+//!
+//! There are also the block and convolutional interleavers of FEC chains,
+//! which spread the bits of a burst of errors across many code words, so
+//! that the decoder sees scattered errors it can correct, and the stable
+//! merge of two vectors under a control mask used to build succinct data
+//! structures and shuffle networks.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError, LengthPolicy};
+
+impl BitVec {
+    /// Interleaves the bits of several vectors, taking the first bit of each
+    /// in turn, then the second, and so on. Shorter vectors are padded with
+    /// leading 0s to the length of the longest, as numbers would be.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // The Morton code of x = 0b101, y = 0b011
+    /// let x = BitVec::from_bit_str("101").unwrap();
+    /// let y = BitVec::from_bit_str("011").unwrap();
+    /// let code = BitVec::interleave(&[&x, &y]);
+    /// assert_eq!(code.to_bit_string(), "100111");
+    /// assert_eq!(code.deinterleave(2), vec![x, y]);
+    /// ```
+    pub fn interleave(streams: &[&BitVec]) -> BitVec {
+        let len = streams.iter().map(|stream| stream.len).max().unwrap_or(0);
+        let mut bv = BitVec::new();
+        bv.resize(len * streams.len(), false);
+        for (offset, stream) in streams.iter().enumerate() {
+            let padding = len - stream.len;
+            for index in stream.iter_ones() {
+                bv.set_bit((padding + index) * streams.len() + offset, true);
+            }
+        }
+        bv
+    }
+
+    /// Splits the bits into a number of vectors, dealing them out in turn,
+    /// which undoes `interleave`. Panics if the number of streams is 0.
+    pub fn deinterleave(&self, n_streams: usize) -> Vec<BitVec> {
+        if n_streams == 0 {
+            panic!("BitVec: number of streams must be non-zero")
+        }
+
+        let mut streams: Vec<BitVec> = (0..n_streams)
+            .map(|offset| {
+                let mut stream = BitVec::new();
+                stream.resize((self.len + n_streams - 1 - offset) / n_streams, false);
+                stream
+            })
+            .collect();
+        for index in self.iter_ones() {
+            streams[index % n_streams].set_bit(index / n_streams, true);
+        }
+        streams
+    }
+
+    /// Writes the bits into a matrix row by row and reads them out column by
+    /// column. Panics if the length is not `rows * cols`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("111 000").unwrap();
+    /// let interleaved = bv.block_interleave(2, 3);
+    /// assert_eq!(interleaved.to_bit_string(), "101010");
+    /// assert_eq!(interleaved.block_deinterleave(2, 3), bv);
+    /// ```
+    pub fn block_interleave(&self, rows: usize, cols: usize) -> BitVec {
+        if self.len != rows * cols {
+            panic!("BitVec: length does not match the interleaver size")
+        }
+
+        let mut bv = BitVec::new();
+        bv.resize(self.len, false);
+        for index in self.iter_ones() {
+            bv.set_bit(index % cols * rows + index / cols, true);
+        }
+        bv
+    }
+
+    /// Undoes `block_interleave` with the same matrix size, writing the bits
+    /// column by column and reading them row by row. Panics if the length is
+    /// not `rows * cols`.
+    pub fn block_deinterleave(&self, rows: usize, cols: usize) -> BitVec {
+        self.block_interleave(cols, rows)
+    }
+
+    /// Passes the bits through a convolutional interleaver with `branches`
+    /// delay lines, where line `i` delays its bits by `i * depth` turns of
+    /// the commutator. The lines start clear, so 0s come out first.
+    pub fn convolutional_interleave(&self, branches: usize, depth: usize) -> BitVec {
+        self.delay_lines(branches, |branch| branch * depth)
+    }
+
+    /// Passes the bits through the convolutional deinterleaver matching
+    /// `convolutional_interleave`, where line `i` delays its bits by
+    /// `(branches - 1 - i) * depth` turns. The two together delay every bit
+    /// by `branches * (branches - 1) * depth` places.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1101 0011 1").unwrap();
+    /// let sent = bv.convolutional_interleave(2, 1);
+    /// assert_eq!(sent.to_bit_string(), "100101101");
+    /// assert_eq!(sent.convolutional_deinterleave(2, 1).to_bit_string(), "001101001");
+    /// ```
+    pub fn convolutional_deinterleave(&self, branches: usize, depth: usize) -> BitVec {
+        self.delay_lines(branches, |branch| (branches - 1 - branch) * depth)
+    }
+
+    /// Deals the bits out to delay lines in turn, delaying the bits of each
+    /// line by a number of turns. Panics if there are no lines.
+    fn delay_lines(&self, branches: usize, delay: impl Fn(usize) -> usize) -> BitVec {
+        if branches == 0 {
+            panic!("BitVec: number of branches must be non-zero")
+        }
+
+        let mut bv = BitVec::new();
+        bv.resize(self.len, false);
+        for index in self.iter_ones() {
+            let target = index + delay(index % branches) * branches;
+            if target < self.len {
+                bv.set_bit(target, true);
+            }
+        }
+        bv
+    }
+
+    /// Returns the reflected binary Gray code of the bits, each bit XORed
+    /// with the one before it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("0111").unwrap();
+    /// assert_eq!(bv.to_gray().to_bit_string(), "0100");
+    /// assert_eq!(bv.to_gray().from_gray(), bv);
+    /// ```
+    pub fn to_gray(&self) -> BitVec {
+        let mut shifted = self.clone();
+        shifted.shift_right(1);
+        let mut gray = self.clone();
+        let _ = gray.xor_with(&shifted, LengthPolicy::Reject);
+        gray
+    }
+
+    /// Returns the bits from their reflected binary Gray code, each bit the
+    /// XOR of every bit up to it, worked out with whole-vector shifts
+    pub fn from_gray(&self) -> BitVec {
+        let mut bv = self.clone();
+        let mut shift = 1;
+        while shift < self.len {
+            let mut shifted = bv.clone();
+            shifted.shift_right(shift);
+            let _ = bv.xor_with(&shifted, LengthPolicy::Reject);
+            shift *= 2;
+        }
+        bv
+    }
+
+    /// Merges two vectors in order under a control mask, taking the next bit
+    /// of `a` where the mask is 0 and the next bit of `b` where it is 1, a
+    /// run of the mask at a time. Returns an error unless the mask has a 0
+    /// for every bit of `a` and a 1 for every bit of `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let a = BitVec::from_bit_str("111").unwrap();
+    /// let b = BitVec::from_bit_str("00").unwrap();
+    /// let mask = BitVec::from_bit_str("01001").unwrap();
+    /// let merged = BitVec::merge_by_mask(&a, &b, &mask).unwrap();
+    /// assert_eq!(merged.to_bit_string(), "10110");
+    /// assert_eq!(merged.split_by_mask(&mask), Ok((a, b)));
+    /// ```
+    pub fn merge_by_mask(a: &BitVec, b: &BitVec, mask: &BitVec) -> Result<BitVec, BitVecError> {
+        if mask.len != a.len + b.len {
+            return Err(BitVecError::LengthMismatch { expected: a.len + b.len, found: mask.len });
+        }
+        let ones = mask.count_ones_in(..);
+        if ones != b.len {
+            return Err(BitVecError::LengthMismatch { expected: b.len, found: ones });
+        }
+
+        let mut merged = BitVec::with_capacity(mask.len);
+        merged.resize(mask.len, false);
+        let (mut position, mut taken_a, mut taken_b) = (0, 0, 0);
+        for (from_b, len) in mask.iter_runs() {
+            if from_b {
+                merged.copy_bits_from(b, taken_b, position, len);
+                taken_b += len;
+            } else {
+                merged.copy_bits_from(a, taken_a, position, len);
+                taken_a += len;
+            }
+            position += len;
+        }
+        Ok(merged)
+    }
+
+    /// Splits the bits under a control mask into those where it is 0 and
+    /// those where it is 1, in order, which undoes `merge_by_mask`. Returns
+    /// an error if the mask is a different length.
+    pub fn split_by_mask(&self, mask: &BitVec) -> Result<(BitVec, BitVec), BitVecError> {
+        if mask.len != self.len {
+            return Err(BitVecError::LengthMismatch { expected: self.len, found: mask.len });
+        }
+
+        let ones = mask.count_ones_in(..);
+        let mut a = BitVec::with_capacity(self.len - ones);
+        let mut b = BitVec::with_capacity(ones);
+        let mut position = 0;
+        for (to_b, len) in mask.iter_runs() {
+            let run = self.slice(position..position + len).to_bitvec();
+            if to_b {
+                b.append_bits(&run);
+            } else {
+                a.append_bits(&run);
+            }
+            position += len;
+        }
+        Ok((a, b))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn morton_codes() {
+        // Three 70-bit coordinates, one of them shorter
+        let x = BitVec::from(&[0xF0, 0x0F, 0xA5, 0x5A, 0x33, 0xCC, 0x81, 0x7E, 0xFF]).slice(..70).to_bitvec();
+        let y = BitVec::from(&[0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x0F]).slice(2..72).to_bitvec();
+        let z = BitVec::from_bit_str("1011").unwrap();
+
+        let code = BitVec::interleave(&[&x, &y, &z]);
+        assert_eq!(code.len_bits(), 210);
+        for index in 0..70 {
+            assert_eq!(code.get_bit(3 * index), x.get_bit(index));
+            assert_eq!(code.get_bit(3 * index + 1), y.get_bit(index));
+        }
+        let streams = code.deinterleave(3);
+        assert_eq!(&streams[..2], &[x, y]);
+        assert_eq!(streams[2].count_ones(), 3);
+        assert_eq!(streams[2].slice(66..).to_bitvec(), z);
+
+        // Uneven lengths deal the extra bits to the first streams
+        let lengths: Vec<usize> = BitVec::from_bit_str("11111").unwrap().deinterleave(3).iter().map(|stream| stream.len_bits()).collect();
+        assert_eq!(lengths, vec![2, 2, 1]);
+        assert_eq!(BitVec::interleave(&[]).len_bits(), 0);
+    }
+
+    #[test]
+    fn block_interleaver_spreads_bursts() {
+        let mut bv = BitVec::new();
+        bv.resize(12 * 8, false);
+        let mut sent = bv.block_interleave(12, 8);
+        // A burst of 8 errors lands in 8 different rows
+        sent.flip_range(40..48);
+        let received = sent.block_deinterleave(12, 8);
+        let rows: Vec<usize> = received.iter_ones().map(|index| index / 8).collect();
+        assert_eq!(rows.len(), 8);
+        assert!(rows.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let data = BitVec::from(&[0xDE, 0xAD, 0xBE]).slice(..21).to_bitvec();
+        assert_eq!(data.block_interleave(3, 7).block_deinterleave(3, 7), data);
+    }
+
+    #[test]
+    fn convolutional_interleaver_round_trip() {
+        let data = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x23, 0x45, 0x67]);
+        let (branches, depth) = (4, 2);
+        let delay = branches * (branches - 1) * depth;
+        let received = data.convolutional_interleave(branches, depth).convolutional_deinterleave(branches, depth);
+
+        assert_eq!(received.slice(..delay).count_ones(), 0);
+        assert_eq!(received.slice(delay..).to_bitvec(), data.slice(..64 - delay).to_bitvec());
+        // A single line passes the bits straight through
+        assert_eq!(data.convolutional_interleave(1, 5), data);
+    }
+
+    #[test]
+    fn gray_codes() {
+        // Consecutive 10-bit values differ in one bit of Gray code
+        let mut previous = BitVec::from(&[0x00, 0x00]).slice(..10).to_bitvec().to_gray();
+        for value in 1u16..1024 {
+            let bv = BitVec::from(&(value << 6).to_be_bytes()).slice(..10).to_bitvec();
+            let gray = bv.to_gray();
+            let mut changed = gray.clone();
+            changed.xor_with(&previous, LengthPolicy::Reject).unwrap();
+            assert_eq!(changed.count_ones(), 1, "value {}", value);
+            assert_eq!(gray.from_gray(), bv);
+            previous = gray;
+        }
+
+        // Long codes decode across bytes
+        let bv = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89]);
+        assert_eq!(bv.to_gray().from_gray(), bv);
+        assert_eq!(BitVec::new().to_gray().len_bits(), 0);
+    }
+
+    #[test]
+    fn merge_and_split_by_mask() {
+        // Runs that cross byte boundaries in every vector
+        let a = BitVec::from(&[0xA5, 0x3C]);
+        let b = BitVec::from_bit_str("1111 0000 1100 1").unwrap();
+        let mut mask = BitVec::new();
+        for index in 0..29 {
+            mask.push_bit(index % 7 < 3 && index < 26 || index == 28);
+        }
+        assert_eq!(mask.count_ones_in(..), 13);
+
+        let merged = BitVec::merge_by_mask(&a, &b, &mask).unwrap();
+        let (mut next_a, mut next_b) = (0, 0);
+        for index in 0..29 {
+            let expected = if mask.get_bit(index) {
+                next_b += 1;
+                b.get_bit(next_b - 1)
+            } else {
+                next_a += 1;
+                a.get_bit(next_a - 1)
+            };
+            assert_eq!(merged.get_bit(index), expected);
+        }
+        assert_eq!(merged.split_by_mask(&mask), Ok((a.clone(), b.clone())));
+
+        let short = mask.slice(..28).to_bitvec();
+        assert_eq!(
+            BitVec::merge_by_mask(&a, &b, &short),
+            Err(BitVecError::LengthMismatch { expected: 29, found: 28 })
+        );
+        let mut shifted = mask.clone();
+        shifted.set_bit(27, true);
+        assert_eq!(
+            BitVec::merge_by_mask(&a, &b, &shifted),
+            Err(BitVecError::LengthMismatch { expected: 13, found: 14 })
+        );
+        assert!(merged.split_by_mask(&short).is_err());
+    }
+}
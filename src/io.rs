@@ -0,0 +1,791 @@
+//! ## I/O adapters
+//! Streaming a BitVec to and from `std::io` sources and sinks, along with
+//! buffered bit-level readers and writers for streams that never need to be
+//! held in memory all at once.
+
+use std::collections::VecDeque;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use crate::{BitOrder, BitSink, BitSource, BitVec, WidthPolicy};
+
+/// Most bytes reserved up front when reading into a BitVec
+const MAX_RESERVE: usize = 1 << 20;
+
+impl BitVec {
+    /// Builds a new BitVec from everything remaining in a reader
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::read_from(&[0xA5, 0x0F][..]).unwrap();
+    /// assert_eq!(bv.len_bits(), 16);
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        Ok(Self::from(data))
+    }
+
+    /// Builds a new BitVec from a reader, reading straight into the vector,
+    /// and stopping after `max_bits` bits if given. Space for up to
+    /// `max_bits` is reserved up front, to a limit of 1 MiB, after which the
+    /// vector grows as the bytes arrive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_reader(&[0xA5, 0x0F, 0xFF][..], Some(12)).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "101001010000");
+    /// ```
+    pub fn from_reader<R: Read>(mut reader: R, max_bits: Option<usize>) -> io::Result<Self> {
+        let mut data = Vec::new();
+        match max_bits {
+            Some(bits) => {
+                let bytes = bits.div_ceil(8);
+                data.reserve(bytes.min(MAX_RESERVE));
+                reader.take(bytes as u64).read_to_end(&mut data)?;
+            }
+            None => {
+                reader.read_to_end(&mut data)?;
+            }
+        }
+
+        let mut bv = Self::from(data);
+        if let Some(bits) = max_bits {
+            bv.truncate(bits);
+        }
+        Ok(bv)
+    }
+
+    /// Writes the packed bytes of the BitVec to a writer, in its bit order.
+    /// A final partial byte is padded with 0s, so the bit length itself is
+    /// not recorded.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        match self.order {
+            BitOrder::Msb0 => writer.write_all(&self.data),
+            order => {
+                let bytes: Vec<u8> = self.data.iter().map(|&byte| order.convert(byte)).collect();
+                writer.write_all(&bytes)
+            }
+        }
+    }
+}
+
+/// Sequential bit reader over any `io::Read` source. Reads are buffered, and
+/// bits are returned MSB first, as with `BitVec::read_bit_seq`.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitReader;
+///
+/// let mut reader = BitReader::new(&[0b1010_0000][..]);
+/// assert_eq!(reader.read_bit_seq().unwrap(), Some(1));
+/// assert_eq!(reader.read_bit_seq().unwrap(), Some(0));
+/// ```
+pub struct BitReader<R: Read> {
+    inner:     BufReader<R>,
+    current:   u8,      // byte currently being read
+    bits_left: u8,      // unread bits in the current byte
+    position:  usize,   // number of bits read so far
+    lookahead: VecDeque<u8>,    // bytes fetched by peeking, not yet read
+}
+
+impl<R: Read> BitReader<R> {
+    /// Constructs a new BitReader over a source
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: BufReader::new(inner),
+            current: 0,
+            bits_left: 0,
+            position: 0,
+            lookahead: VecDeque::new(),
+        }
+    }
+
+    /// Fetches the next whole byte from the source, or None at the end
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        if let Some(byte) = self.lookahead.pop_front() {
+            return Ok(Some(byte));
+        }
+        self.fetch_byte()
+    }
+
+    /// Reads the next byte from the source itself, bypassing the lookahead
+    fn fetch_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Deprecated as the name does not say what is read
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the read_bit_seq function instead.")]
+    pub fn seq_read(&mut self) -> io::Result<Option<u8>> {
+        self.read_bit_seq()
+    }
+
+    /// Reads the next bit, returning None at the end of the stream
+    pub fn read_bit_seq(&mut self) -> io::Result<Option<u8>> {
+        if self.bits_left == 0 {
+            match self.next_byte()? {
+                Some(byte) => {
+                    self.current = byte;
+                    self.bits_left = 8;
+                }
+                None => return Ok(None),
+            }
+        }
+
+        self.bits_left -= 1;
+        self.position += 1;
+        Ok(Some((self.current >> self.bits_left) & 1))
+    }
+
+    /// Reads 8 bits in sequence and returns a byte (can be offset). Returns
+    /// None if fewer than 8 bits remain in the stream.
+    pub fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let Some(next) = self.next_byte()? else {
+            return Ok(None);
+        };
+
+        let byte = if self.bits_left == 0 {
+            next
+        } else {
+            let used = 8 - self.bits_left;
+            let byte = (self.current << used) | (next >> self.bits_left);
+            self.current = next;
+            byte
+        };
+
+        self.position += 8;
+        Ok(Some(byte))
+    }
+
+    /// Get current reading position in bits
+    pub fn get_read_position(&self) -> usize {
+        self.position
+    }
+
+    /// Unwraps the reader, returning the underlying source. Any buffered
+    /// data which has not been read is lost.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+/// Peeked bytes are held in the reader until read, so a stream can be
+/// decoded like a BitVec. How many bits remain is never known.
+impl<R: Read> BitSource for BitReader<R> {
+    type Error = io::Error;
+
+    fn peek_bits(&mut self, count: u32) -> io::Result<Option<u64>> {
+        if count > u64::BITS {
+            panic!("BitVec: cannot read more than 64 bits at once")
+        }
+        while (self.bits_left as usize + 8 * self.lookahead.len()) < count as usize {
+            match self.fetch_byte()? {
+                Some(byte) => self.lookahead.push_back(byte),
+                None => return Ok(None),
+            }
+        }
+
+        let current = (0..self.bits_left).rev().map(|shift| (self.current >> shift) & 1);
+        let ahead = self.lookahead.iter().flat_map(|&byte| (0..8).rev().map(move |shift| (byte >> shift) & 1));
+        Ok(Some(current.chain(ahead)
+            .take(count as usize)
+            .fold(0, |value, bit| (value << 1) | bit as u64)))
+    }
+
+    fn consume(&mut self, count: u32) -> io::Result<()> {
+        for _ in 0..count {
+            if self.read_bit_seq()?.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn align(&mut self) -> io::Result<()> {
+        self.position += self.bits_left as usize;
+        self.bits_left = 0;
+        Ok(())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Default size of the refill buffer of a StreamBitReader
+const STREAM_BUFFER: usize = 8 * 1024;
+
+/// Bit source over any `io::Read` stream, such as a socket or file, for
+/// decoders built on `BitSource`. Bytes are read into a buffer in blocks and
+/// the buffer is refilled as it runs low, so reads and peeks of up to 64 bits
+/// never touch the stream bit by bit. Bits are read MSB first.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitSource, StreamBitReader};
+///
+/// let mut reader = StreamBitReader::new(&[0b1010_0000, 0xAB][..]);
+/// assert_eq!(reader.read_bits(3).unwrap(), Some(0b101));
+/// reader.align().unwrap();
+/// assert_eq!(reader.read_bits(8).unwrap(), Some(0xAB));
+/// assert_eq!(reader.read_bit().unwrap(), None);
+/// ```
+pub struct StreamBitReader<R: Read> {
+    inner:    R,
+    buffer:   Vec<u8>,  // bytes read from the stream, not all consumed
+    capacity: usize,    // most bytes held in the buffer
+    offset:   usize,    // bit offset of the next bit in the buffer
+    position: usize,    // number of bits read so far
+}
+
+impl<R: Read> StreamBitReader<R> {
+    /// Constructs a new StreamBitReader over a stream
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(STREAM_BUFFER, inner)
+    }
+
+    /// Constructs a new StreamBitReader with a refill buffer of at least
+    /// `capacity` bytes. The buffer always holds at least 16 bytes, to fit a
+    /// 64-bit read at any offset.
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        let capacity = capacity.max(16);
+        Self {
+            inner,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+            offset: 0,
+            position: 0,
+        }
+    }
+
+    /// Get the number of buffered bits not yet read
+    fn buffered(&self) -> usize {
+        self.buffer.len() * 8 - self.offset
+    }
+
+    /// Drops the consumed bytes from the buffer and reads from the stream
+    /// until `count` bits are buffered or the stream ends
+    fn refill(&mut self, count: usize) -> io::Result<()> {
+        self.buffer.drain(..self.offset / 8);
+        self.offset %= 8;
+
+        while self.buffered() < count {
+            let filled = self.buffer.len();
+            self.buffer.resize(self.capacity, 0);
+            match self.inner.read(&mut self.buffer[filled..]) {
+                Ok(0) => {
+                    self.buffer.truncate(filled);
+                    break;
+                }
+                Ok(read) => self.buffer.truncate(filled + read),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.buffer.truncate(filled),
+                Err(e) => {
+                    self.buffer.truncate(filled);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get current reading position in bits
+    pub fn get_read_position(&self) -> usize {
+        self.position
+    }
+
+    /// Unwraps the reader, returning the underlying stream. Any buffered
+    /// data which has not been read is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> BitSource for StreamBitReader<R> {
+    type Error = io::Error;
+
+    fn peek_bits(&mut self, count: u32) -> io::Result<Option<u64>> {
+        if count > u64::BITS {
+            panic!("BitVec: cannot read more than 64 bits at once")
+        }
+        if self.buffered() < count as usize {
+            self.refill(count as usize)?;
+            if self.buffered() < count as usize {
+                return Ok(None);
+            }
+        }
+
+        let mut value = 0;
+        let mut bit = self.offset;
+        let end = bit + count as usize;
+        while bit < end {
+            let take = (8 - bit % 8).min(end - bit);
+            let bits = (self.buffer[bit / 8] << (bit % 8)) >> (8 - take);
+            value = (value << take) | bits as u64;
+            bit += take;
+        }
+        Ok(Some(value))
+    }
+
+    fn consume(&mut self, count: u32) -> io::Result<()> {
+        if self.buffered() < count as usize {
+            self.refill(count as usize)?;
+        }
+        let count = (count as usize).min(self.buffered());
+        self.offset += count;
+        self.position += count;
+        Ok(())
+    }
+
+    fn align(&mut self) -> io::Result<()> {
+        let skip = (8 - self.offset % 8) % 8;
+        self.offset += skip;
+        self.position += skip;
+        Ok(())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Sequential bit writer over any `io::Write` sink. Completed bytes are
+/// buffered before being written through, and bits are packed MSB first, as
+/// with `BitVec::push_bit`. Call `finish` to write out the final partial
+/// byte; dropping the writer discards it.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitWriter;
+///
+/// let mut writer = BitWriter::new(Vec::new());
+/// writer.push_bit(true).unwrap();
+/// writer.push_byte(0xFF).unwrap();
+/// assert_eq!(writer.finish().unwrap(), vec![0xFF, 0x80]);
+/// ```
+pub struct BitWriter<W: Write> {
+    inner:     BufWriter<W>,
+    current:   u8,      // partially filled byte
+    bits_used: u8,      // number of bits filled in the current byte
+    len:       usize,   // number of bits pushed so far
+    width:     WidthPolicy, // what push_bits does with over-wide values
+}
+
+impl<W: Write> BitWriter<W> {
+    /// Constructs a new BitWriter over a sink
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: BufWriter::new(inner),
+            current: 0,
+            bits_used: 0,
+            len: 0,
+            width: WidthPolicy::Truncate,
+        }
+    }
+
+    /// Changes what `push_bits` does with a value too wide for its field.
+    /// Under `WidthPolicy::Error` the value is rejected with an error of
+    /// kind `InvalidInput`.
+    pub fn set_width_policy(&mut self, policy: WidthPolicy) {
+        self.width = policy;
+    }
+
+    /// Pushes a bit to the stream
+    pub fn push_bit(&mut self, value: bool) -> io::Result<()> {
+        if value {
+            self.current |= 1 << (7 - self.bits_used);
+        }
+        self.bits_used += 1;
+        self.len += 1;
+
+        if self.bits_used == 8 {
+            self.inner.write_all(&[self.current])?;
+            self.current = 0;
+            self.bits_used = 0;
+        }
+        Ok(())
+    }
+
+    /// Pushes a byte to the stream
+    pub fn push_byte(&mut self, byte: u8) -> io::Result<()> {
+        if self.bits_used == 0 {
+            self.inner.write_all(&[byte])?;
+        } else {
+            self.inner.write_all(&[self.current | (byte >> self.bits_used)])?;
+            self.current = byte << (8 - self.bits_used);
+        }
+
+        self.len += 8;
+        Ok(())
+    }
+
+    /// Pushes every bit of a BitVec to the stream
+    pub fn push_bitvec(&mut self, bv: &BitVec) -> io::Result<()> {
+        let whole_bytes = bv.len_bits() / 8;
+        for &byte in &bv.data[..whole_bytes] {
+            self.push_byte(byte)?;
+        }
+        for index in whole_bytes * 8..bv.len_bits() {
+            self.push_bit(bv.get_bit(index))?;
+        }
+        Ok(())
+    }
+
+    /// Get the number of bits pushed to the stream
+    pub fn len_bits(&self) -> usize {
+        self.len
+    }
+
+    /// Writes all completed bytes through to the sink. A partial byte is
+    /// kept back until it is completed or the writer is finished.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Pads the final partial byte with 0s, writes out everything, and
+    /// returns the underlying sink
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.bits_used > 0 {
+            self.inner.write_all(&[self.current])?;
+        }
+
+        self.inner.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+/// Applies a width policy, turning a rejected value into an io::Error
+fn check_width(policy: WidthPolicy, value: u64, count: u32) -> io::Result<u64> {
+    policy.apply(value, count).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+impl<W: Write> BitSink for BitWriter<W> {
+    type Error = io::Error;
+
+    fn push_bit(&mut self, value: bool) -> io::Result<()> {
+        BitWriter::push_bit(self, value)
+    }
+
+    fn push_bits(&mut self, value: u64, count: u32) -> io::Result<()> {
+        let value = check_width(self.width, value, count)?;
+        for shift in (0..count).rev() {
+            BitWriter::push_bit(self, value >> shift & 1 == 1)?;
+        }
+        Ok(())
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        bytes.iter().try_for_each(|&byte| self.push_byte(byte))
+    }
+}
+
+/// Bit sink over any `io::Write` stream, the writing side of
+/// `StreamBitReader`. Completed bytes are gathered in a buffer and written
+/// through in blocks, while values of up to 64 bits are packed a byte at a
+/// time. Bits are packed MSB first. Call `finish` to write out the final
+/// partial byte; dropping the writer discards it and any buffered bytes.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitSink, StreamBitWriter};
+///
+/// let mut writer = StreamBitWriter::new(Vec::new());
+/// writer.push_bits(0b101, 3).unwrap();
+/// writer.push_bits(0xAB, 8).unwrap();
+/// assert_eq!(writer.len_bits(), 11);
+/// assert_eq!(writer.finish().unwrap(), vec![0xB5, 0x60]);
+/// ```
+pub struct StreamBitWriter<W: Write> {
+    inner:     W,
+    buffer:    Vec<u8>, // completed bytes not yet written through
+    capacity:  usize,   // bytes gathered before writing through
+    current:   u8,      // byte currently being filled
+    bits_used: u8,      // bits filled in the current byte
+    len:       usize,   // number of bits pushed
+    width:     WidthPolicy, // what push_bits does with over-wide values
+}
+
+impl<W: Write> StreamBitWriter<W> {
+    /// Constructs a new StreamBitWriter over a stream
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(STREAM_BUFFER, inner)
+    }
+
+    /// Constructs a new StreamBitWriter which writes through every time
+    /// `capacity` bytes have been completed
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+            current: 0,
+            bits_used: 0,
+            len: 0,
+            width: WidthPolicy::Truncate,
+        }
+    }
+
+    /// Changes what `push_bits` does with a value too wide for its field.
+    /// Under `WidthPolicy::Error` the value is rejected with an error of
+    /// kind `InvalidInput`.
+    pub fn set_width_policy(&mut self, policy: WidthPolicy) {
+        self.width = policy;
+    }
+
+    /// Writes the completed bytes through if the buffer is full
+    fn write_if_full(&mut self) -> io::Result<()> {
+        if self.buffer.len() >= self.capacity {
+            self.inner.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    /// Get the number of bits pushed so far
+    pub fn len_bits(&self) -> usize {
+        self.len
+    }
+
+    /// Writes all completed bytes through to the stream and flushes it. A
+    /// partial byte is kept back until it is completed or the writer is
+    /// finished.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+        self.inner.flush()
+    }
+
+    /// Pads the final partial byte with 0s, writes out everything, and
+    /// returns the underlying stream
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.bits_used > 0 {
+            self.buffer.push(self.current);
+            self.bits_used = 0;
+        }
+        self.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> BitSink for StreamBitWriter<W> {
+    type Error = io::Error;
+
+    fn push_bit(&mut self, value: bool) -> io::Result<()> {
+        self.push_bits(value as u64, 1)
+    }
+
+    fn push_bits(&mut self, value: u64, count: u32) -> io::Result<()> {
+        let value = check_width(self.width, value, count)?;
+        let mut left = count;
+        while left > 0 {
+            let take = left.min(8 - self.bits_used as u32);
+            let bits = (value >> (left - take)) as u8 & BitVec::mask_lsb(take as usize);
+            self.current |= bits << (8 - self.bits_used as u32 - take);
+            self.bits_used += take as u8;
+            left -= take;
+
+            if self.bits_used == 8 {
+                self.buffer.push(self.current);
+                self.current = 0;
+                self.bits_used = 0;
+                self.write_if_full()?;
+            }
+        }
+        self.len += count as usize;
+        Ok(())
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.bits_used > 0 {
+            return bytes.iter().try_for_each(|&byte| self.push_bits(byte as u64, 8));
+        }
+
+        for chunk in bytes.chunks(self.capacity) {
+            self.buffer.extend_from_slice(chunk);
+            self.len += chunk.len() * 8;
+            self.write_if_full()?;
+        }
+        Ok(())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitvec_stream_round_trip() {
+        let bv = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        let mut out = Vec::new();
+        bv.write_to(&mut out).unwrap();
+        assert_eq!(out, vec![0xDE, 0xAD, 0xBE, 0xEF]);
+
+        let back = BitVec::read_from(out.as_slice()).unwrap();
+        assert_eq!(back.len_bits(), 32);
+        assert_eq!(back.data, bv.data);
+
+        // LSB first, with the last byte padded
+        let mut lsb = BitVec::with_order(BitOrder::Lsb0);
+        lsb.push_byte(0x85);
+        lsb.push_bit(true);
+        let mut out = Vec::new();
+        lsb.write_to(&mut out).unwrap();
+        assert_eq!(out, vec![0x85, 0x01]);
+    }
+
+    #[test]
+    fn reader_with_bit_limit() {
+        let bytes = [0xDE, 0xAD, 0xBE, 0xEF];
+        assert_eq!(BitVec::from_reader(&bytes[..], None).unwrap(), BitVec::from(&bytes));
+        let bv = BitVec::from_reader(&bytes[..], Some(13)).unwrap();
+        assert_eq!(bv, BitVec::from(&bytes).slice(..13).to_bitvec());
+        assert_eq!(bv.as_bytes(), &[0xDE, 0xA8]);
+
+        // A limit past the end reads everything, without reserving it all
+        let bv = BitVec::from_reader(&bytes[..], Some(usize::MAX)).unwrap();
+        assert_eq!(bv.len_bits(), 32);
+        assert_eq!(BitVec::from_reader(&[][..], Some(8)).unwrap().len_bits(), 0);
+    }
+
+    #[test]
+    fn reader_offset_bytes() {
+        // 0b11010111 0b01011001
+        let mut reader = BitReader::new(&[0xD7, 0x59][..]);
+        for bit in [1, 1, 0] {
+            assert_eq!(reader.read_bit_seq().unwrap(), Some(bit));
+        }
+        // read byte = 0b10111010
+        assert_eq!(reader.read_byte().unwrap(), Some(0xBA));
+        assert_eq!(reader.get_read_position(), 11);
+        assert_eq!(reader.read_byte().unwrap(), None);
+        for bit in [1, 1, 0, 0, 1] {
+            assert_eq!(reader.read_bit_seq().unwrap(), Some(bit));
+        }
+        assert_eq!(reader.read_bit_seq().unwrap(), None);
+    }
+
+    #[test]
+    fn writer_matches_bitvec() {
+        let mut bv = BitVec::new();
+        let mut writer = BitWriter::new(Vec::new());
+        for (i, value) in [true, false, true, true, false].into_iter().enumerate() {
+            bv.push_bit(value);
+            writer.push_bit(value).unwrap();
+            bv.push_byte(i as u8 * 37);
+            writer.push_byte(i as u8 * 37).unwrap();
+        }
+        writer.push_bitvec(&bv).unwrap();
+        let doubled = bv.concat(&bv);
+
+        assert_eq!(writer.len_bits(), doubled.len_bits());
+        assert_eq!(writer.finish().unwrap(), doubled.data);
+    }
+
+    #[test]
+    fn stream_reader_refills() {
+        let bytes: Vec<u8> = (0..100).collect();
+        let bv = BitVec::from(&bytes);
+        // A small buffer refilled many times, from a stream that trickles
+        let mut reader = StreamBitReader::with_capacity(1, io::Read::chain(&bytes[..3], &bytes[3..]));
+        let mut position = 0;
+        for count in (1..=64).cycle() {
+            let Some(value) = reader.read_bits(count).unwrap() else {
+                break;
+            };
+            assert_eq!(value, bv.slice(position..position + count as usize).to_u64());
+            position += count as usize;
+        }
+        assert_eq!(reader.get_read_position(), position);
+        assert!(800 - position < 64);
+
+        // Whatever is left can still be read in smaller pieces
+        assert_eq!(reader.peek_bits(64).unwrap(), None);
+        while reader.read_bit().unwrap().is_some() {
+            position += 1;
+        }
+        assert_eq!(position, 800);
+    }
+
+    #[test]
+    fn stream_writer_matches_bitvec() {
+        let mut bv = BitVec::new();
+        let mut writer = StreamBitWriter::with_capacity(3, Vec::new());
+        for count in 0..=64 {
+            let value = 0x9E37_79B9_7F4A_7C15u64.rotate_left(count);
+            bv.push_bits(value, count).unwrap();
+            writer.push_bits(value, count).unwrap();
+            if count % 10 == 0 {
+                bv.push_bytes(&[0xA5, 0x5A]).unwrap();
+                writer.push_bytes(&[0xA5, 0x5A]).unwrap();
+            }
+        }
+        writer.push_bit(true).unwrap();
+        bv.push_bit(true);
+
+        assert_eq!(writer.len_bits(), bv.len_bits());
+        assert_eq!(writer.finish().unwrap(), bv.as_bytes());
+    }
+
+    #[test]
+    fn writer_width_policies() {
+        let mut writer = BitWriter::new(Vec::new());
+        writer.push_bits(0x1F, 4).unwrap();
+        writer.set_width_policy(WidthPolicy::Error);
+        let e = writer.push_bits(0x1F, 4).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(writer.len_bits(), 4);
+        assert_eq!(writer.finish().unwrap(), vec![0xF0]);
+
+        let mut writer = StreamBitWriter::new(Vec::new());
+        writer.set_width_policy(WidthPolicy::Error);
+        assert!(writer.push_bits(0x100, 8).is_err());
+        writer.push_bits(0xFF, 8).unwrap();
+        assert_eq!(writer.finish().unwrap(), vec![0xFF]);
+    }
+
+    #[test]
+    fn stream_writer_round_trip() {
+        let mut writer = StreamBitWriter::new(Vec::new());
+        writer.push_bytes(&[0xDE, 0xAD]).unwrap();
+        writer.push_bits(0b11, 2).unwrap();
+        writer.flush().unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, vec![0xDE, 0xAD, 0xC0]);
+
+        let mut reader = StreamBitReader::new(&bytes[..]);
+        assert_eq!(reader.read_bits(16).unwrap(), Some(0xDEAD));
+        assert_eq!(reader.read_bits(2).unwrap(), Some(0b11));
+    }
+
+    #[test]
+    fn stream_reader_alignment() {
+        let mut reader = StreamBitReader::new(&[0xFF, 0x0F][..]);
+        reader.align().unwrap();
+        assert_eq!(reader.read_bits(1).unwrap(), Some(1));
+        reader.align().unwrap();
+        assert_eq!(reader.get_read_position(), 8);
+        assert_eq!(reader.read_bits(8).unwrap(), Some(0x0F));
+        reader.align().unwrap();
+        assert_eq!(reader.get_read_position(), 16);
+    }
+}
@@ -0,0 +1,225 @@
+//! ## Bit index iterators
+//! Iterators over the indices of the set or clear bits of a BitVec, or of a
+//! range of it, which skip whole bytes that have nothing to report. Counting
+//! what is left of an iterator uses popcounts rather than stepping through
+//! it.
+
+use core::iter::FusedIterator;
+use core::ops::RangeBounds;
+
+use crate::BitVec;
+use crate::range::count_ones_in_bytes;
+use crate::slice::resolve_range;
+
+/// Shared state for the index iterators. Bits are reported when they are set
+/// after XORing with the flip mask.
+struct Indices<'a> {
+    data:     &'a [u8], // bytes packed MSB first
+    byte_idx: usize,    // byte currently being scanned
+    byte:     u8,       // candidate bits not yet reported from that byte
+    flip:     u8,       // 0x00 to report set bits, 0xFF for clear bits
+    end:      usize,    // index after the last bit to report
+}
+
+impl<'a> Indices<'a> {
+    fn new(data: &'a [u8], flip: u8, start: usize, end: usize) -> Self {
+        let byte_idx = start / 8;
+        let byte = if start < end { (data[byte_idx] ^ flip) & (0xFF >> (start % 8)) } else { 0 };
+
+        Self { data, byte_idx, byte, flip, end }
+    }
+
+    fn next(&mut self) -> Option<usize> {
+        let num_bytes = self.end.div_ceil(8);
+
+        while self.byte == 0 {
+            if self.byte_idx + 1 >= num_bytes {
+                return None;
+            }
+            self.byte_idx += 1;
+            self.byte = self.data[self.byte_idx] ^ self.flip;
+        }
+
+        let offset = self.byte.leading_zeros() as usize;
+        self.byte &= !(0x80 >> offset);
+        let index = self.byte_idx * 8 + offset;
+
+        // Only bits past the end are left
+        if index >= self.end {
+            self.byte = 0;
+            self.byte_idx = num_bytes;
+            return None;
+        }
+
+        Some(index)
+    }
+
+    /// Counts the bits left to report without stepping through them
+    fn count(self) -> usize {
+        let base = self.byte_idx * 8;
+        if base >= self.end {
+            return 0;
+        }
+
+        let valid = self.end - base;
+        let mask = if valid >= 8 { 0xFF } else { BitVec::mask_msb(valid) };
+        let rest = (base + 8).min(self.end);
+        let ones = count_ones_in_bytes(self.data, rest, self.end);
+        let rest_count = if self.flip == 0 { ones } else { self.end - rest - ones };
+
+        (self.byte & mask).count_ones() as usize + rest_count
+    }
+}
+
+/// Iterator over the indices of the set bits of a BitVec, created by
+/// `BitVec::iter_ones`
+pub struct IterOnes<'a> {
+    inner: Indices<'a>,
+}
+
+impl Iterator for IterOnes<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.inner.next()
+    }
+
+    fn count(self) -> usize {
+        self.inner.count()
+    }
+}
+
+impl FusedIterator for IterOnes<'_> {}
+
+/// Iterator over the indices of the clear bits of a BitVec, created by
+/// `BitVec::iter_zeros`
+pub struct IterZeros<'a> {
+    inner: Indices<'a>,
+}
+
+impl Iterator for IterZeros<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.inner.next()
+    }
+
+    fn count(self) -> usize {
+        self.inner.count()
+    }
+}
+
+impl FusedIterator for IterZeros<'_> {}
+
+/// Returns an iterator over the indices of the set bits from `start` to
+/// `end` of bytes packed MSB first
+#[cfg(feature = "mmap")]
+pub(crate) fn ones_in_bytes(data: &[u8], start: usize, end: usize) -> IterOnes<'_> {
+    IterOnes { inner: Indices::new(data, 0, start, end) }
+}
+
+impl BitVec {
+    /// Returns an iterator over the indices of the set bits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01001001
+    /// let bv = BitVec::from(&[0x49]);
+    /// assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![1, 4, 7]);
+    /// ```
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes { inner: Indices::new(&self.data, 0, 0, self.len) }
+    }
+
+    /// Returns an iterator over the indices of the clear bits
+    pub fn iter_zeros(&self) -> IterZeros<'_> {
+        IterZeros { inner: Indices::new(&self.data, 0xFF, 0, self.len) }
+    }
+
+    /// Returns an iterator over the indices of the set bits in a range.
+    /// Panics if the range runs past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01001001 0b11000000
+    /// let bv = BitVec::from(&[0x49, 0xC0]);
+    /// assert_eq!(bv.ones_in(4..9).collect::<Vec<_>>(), vec![4, 7, 8]);
+    /// assert_eq!(bv.ones_in(2..).count(), 4);
+    /// ```
+    pub fn ones_in<R: RangeBounds<usize>>(&self, range: R) -> IterOnes<'_> {
+        let (start, end) = resolve_range(range, self.len);
+        IterOnes { inner: Indices::new(&self.data, 0, start, end) }
+    }
+
+    /// Returns an iterator over the indices of the clear bits in a range.
+    /// Panics if the range runs past the end.
+    pub fn zeros_in<R: RangeBounds<usize>>(&self, range: R) -> IterZeros<'_> {
+        let (start, end) = resolve_range(range, self.len);
+        IterZeros { inner: Indices::new(&self.data, 0xFF, start, end) }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    #[test]
+    fn iterate_ones_and_zeros() {
+        // bv = 0b10000000 0b00000000 0b00000000 0b01011xxx
+        let bv = BitVec { data: vec![0x80, 0, 0, 0x58], len: 29, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.iter_ones().collect::<Vec<_>>(), vec![0, 25, 27, 28]);
+
+        let zeros: Vec<_> = bv.iter_zeros().collect();
+        assert_eq!(zeros.len(), 25);
+        assert_eq!(zeros[0], 1);
+        assert_eq!(zeros[24], 26);
+    }
+
+    #[test]
+    fn padding_is_not_iterated() {
+        let bv = BitVec { data: vec![0xFF, 0xFF], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.iter_ones().count(), 11);
+        assert_eq!(bv.iter_zeros().count(), 0);
+        assert_eq!(BitVec::new().iter_ones().next(), None);
+        assert_eq!(BitVec::new().iter_zeros().next(), None);
+    }
+
+    #[test]
+    fn ranges() {
+        let bv = BitVec::from(&[0x49, 0xC0, 0xFF, 0x01]);
+        for (start, end) in [(0, 0), (3, 3), (0, 32), (1, 9), (4, 6), (7, 25), (16, 24), (30, 32)] {
+            let ones: Vec<_> = bv.iter_ones().filter(|i| (start..end).contains(i)).collect();
+            let zeros: Vec<_> = bv.iter_zeros().filter(|i| (start..end).contains(i)).collect();
+            assert_eq!(bv.ones_in(start..end).collect::<Vec<_>>(), ones);
+            assert_eq!(bv.zeros_in(start..end).collect::<Vec<_>>(), zeros);
+            assert_eq!(bv.ones_in(start..end).count(), ones.len());
+            assert_eq!(bv.zeros_in(start..end).count(), zeros.len());
+        }
+    }
+
+    #[test]
+    fn count_part_way() {
+        let bv = BitVec::from_bit_str("0110 1001 1101 0").unwrap();
+        let mut ones = bv.iter_ones();
+        ones.next();
+        ones.next();
+        assert_eq!(ones.count(), 5);
+
+        let mut zeros = bv.zeros_in(..12);
+        assert_eq!(zeros.nth(3), Some(6));
+        assert_eq!(zeros.count(), 1);
+        let mut drained = bv.ones_in(8..);
+        while drained.next().is_some() {}
+        assert_eq!(drained.count(), 0);
+    }
+}
@@ -0,0 +1,243 @@
+//! ## Write-ahead journal
+//! An optional journal for a `PersistentBitVec`, so a crash part way through
+//! writing back pages cannot corrupt the bitmap. Each change is recorded as
+//! a range of bits set or cleared, and `commit` appends the records to the
+//! journal and makes them durable. Flushing commits before writing any
+//! page, and empties the journal, a checkpoint, only once every page is
+//! durable. Opening replays whatever the journal still holds, so pages left
+//! half written are brought back up to date.
+//!
+//! Each record is 21 bytes: 1 if the bits are set or 0 if cleared, the
+//! start and end of the range as little-endian u64s, then the CRC-32 of
+//! those 17 bytes, big-endian. Replay stops at the first record that is
+//! cut short or fails its check, which a crash while appending leaves.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::{BitVec, PageStorage, PersistentBitVec};
+
+/// Bytes in a record
+const RECORD_BYTES: usize = 21;
+
+/// Storage for the records of a journal
+pub trait JournalStorage {
+    /// Appends bytes to the end of the journal
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Reads the whole journal
+    fn read_all(&mut self) -> io::Result<Vec<u8>>;
+
+    /// Empties the journal
+    fn clear(&mut self) -> io::Result<()>;
+
+    /// Makes the bytes appended so far durable
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl JournalStorage for Vec<u8> {
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn read_all(&mut self) -> io::Result<Vec<u8>> {
+        Ok(self.clone())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        Vec::clear(self);
+        Ok(())
+    }
+}
+
+impl JournalStorage for File {
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.seek(SeekFrom::End(0))?;
+        self.write_all(bytes)
+    }
+
+    fn read_all(&mut self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.seek(SeekFrom::Start(0))?;
+        self.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.set_len(0)?;
+        self.sync_data()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+/// Encodes a record of the bits from `start` to `end` set or cleared
+fn encode_record(start: usize, end: usize, value: bool) -> [u8; RECORD_BYTES] {
+    let mut record = [0; RECORD_BYTES];
+    record[0] = value as u8;
+    record[1..9].copy_from_slice(&(start as u64).to_le_bytes());
+    record[9..17].copy_from_slice(&(end as u64).to_le_bytes());
+    let crc = BitVec::from(&record[..17]).crc32(..);
+    record[17..].copy_from_slice(&crc.to_be_bytes());
+    record
+}
+
+/// Decodes a record, or None if it fails its check or is not a range
+/// within `len` bits
+fn decode_record(record: &[u8], len: usize) -> Option<(usize, usize, bool)> {
+    let crc = u32::from_be_bytes(record[17..].try_into().unwrap());
+    if record[0] > 1 || BitVec::from(&record[..17]).crc32(..) != crc {
+        return None;
+    }
+    let start = usize::try_from(u64::from_le_bytes(record[1..9].try_into().unwrap())).ok()?;
+    let end = usize::try_from(u64::from_le_bytes(record[9..17].try_into().unwrap())).ok()?;
+    (start <= end && end <= len).then_some((start, end, record[0] == 1))
+}
+
+impl<S: PageStorage> PersistentBitVec<S> {
+    /// Loads a bitmap of `len` bits from storage in pages of `page_bytes`
+    /// bytes, as `open` does, then replays the changes left in the journal
+    /// and keeps it for the changes to come. Panics if the page size is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::PersistentBitVec;
+    ///
+    /// let mut bitmap = PersistentBitVec::open_with_journal(Vec::new(), Vec::new(), 64, 4).unwrap();
+    /// bitmap.fill_range(10..20, true);
+    /// bitmap.commit().unwrap();
+    /// assert_eq!(bitmap.dirty_pages(), 1);
+    /// ```
+    pub fn open_with_journal<J: JournalStorage + 'static>(storage: S, mut journal: J, len: usize, page_bytes: usize) -> io::Result<Self> {
+        let mut bitmap = Self::open(storage, len, page_bytes)?;
+        for record in journal.read_all()?.chunks_exact(RECORD_BYTES) {
+            let Some((start, end, value)) = decode_record(record, len) else {
+                break;
+            };
+            bitmap.bits.slice_mut(start..end).fill(value);
+            bitmap.mark_dirty(start, end);
+        }
+        bitmap.journal = Some(Box::new(journal));
+        Ok(bitmap)
+    }
+
+    /// Records a change to be committed, if there is a journal
+    pub(crate) fn record(&mut self, start: usize, end: usize, value: bool) {
+        if self.journal.is_some() && start < end {
+            self.pending.extend_from_slice(&encode_record(start, end, value));
+        }
+    }
+
+    /// Appends the changes made since the last commit to the journal and
+    /// makes them durable, without writing back any pages. Does nothing
+    /// without a journal.
+    pub fn commit(&mut self) -> io::Result<()> {
+        if let Some(journal) = &mut self.journal
+            && !self.pending.is_empty()
+        {
+            journal.append(&self.pending)?;
+            journal.sync()?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    /// Empties the journal, once every change in it is in the pages
+    pub(crate) fn clear_journal(&mut self) -> io::Result<()> {
+        match &mut self.journal {
+            Some(journal) => journal.clear(),
+            None => Ok(()),
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Storage that fails every write after the first `limit`
+    struct Failing {
+        bytes: Vec<u8>,
+        limit: usize,
+    }
+
+    impl PageStorage for Failing {
+        fn read_page(&mut self, index: usize, page: &mut [u8]) -> io::Result<usize> {
+            self.bytes.read_page(index, page)
+        }
+
+        fn write_page(&mut self, index: usize, page: &[u8]) -> io::Result<()> {
+            if self.limit == 0 {
+                return Err(io::Error::other("crash"));
+            }
+            self.limit -= 1;
+            self.bytes.write_page(index, page)
+        }
+    }
+
+    #[test]
+    fn replay_after_a_crash() {
+        let storage = Failing { bytes: vec![0; 8], limit: 1 };
+        let mut bitmap = PersistentBitVec::open_with_journal(storage, Vec::new(), 64, 2).unwrap();
+        bitmap.set_bit(0, true);
+        bitmap.fill_range(20..60, true);
+        bitmap.set_bit(40, false);
+        // Only the first page is written before the crash
+        assert!(bitmap.flush().is_err());
+        let records = bitmap.journal.take().unwrap().read_all().unwrap();
+        let storage = bitmap.into_storage();
+        assert_eq!(storage.bytes, vec![0x80, 0, 0, 0, 0, 0, 0, 0]);
+
+        // The journal was committed first, so replay finishes the job
+        assert_eq!(records.len(), 3 * RECORD_BYTES);
+        let storage = Failing { bytes: storage.bytes, limit: usize::MAX };
+        let mut bitmap = PersistentBitVec::open_with_journal(storage, records, 64, 2).unwrap();
+        assert_eq!(bitmap.as_bitslice().count_ones(), 40);
+        assert!(!bitmap.get_bit(40));
+        assert_eq!(bitmap.dirty_pages(), 4);
+        bitmap.flush().unwrap();
+        assert_eq!(bitmap.storage().bytes, vec![0x80, 0, 0x0F, 0xFF, 0xFF, 0x7F, 0xFF, 0xF0]);
+    }
+
+    #[test]
+    fn torn_and_checkpointed_journals() {
+        let mut journal = Vec::new();
+        journal.extend_from_slice(&encode_record(3, 5, true));
+        journal.extend_from_slice(&encode_record(0, 8, true));
+        // The second record is cut short, and a corrupt third is ignored
+        journal.truncate(RECORD_BYTES + 10);
+        let mut corrupt = encode_record(6, 7, true);
+        corrupt[4] ^= 1;
+        let mut torn = journal[..RECORD_BYTES].to_vec();
+        torn.extend_from_slice(&corrupt);
+
+        for journal in [journal, torn] {
+            let bitmap = PersistentBitVec::open_with_journal(Vec::new(), journal, 8, 1).unwrap();
+            assert_eq!(bitmap.as_bitslice().to_bitvec().to_bit_string(), "00011000");
+        }
+
+        // A record past the end stops replay too
+        let journal = encode_record(0, 9, true).to_vec();
+        let bitmap = PersistentBitVec::open_with_journal(Vec::new(), journal, 8, 1).unwrap();
+        assert_eq!(bitmap.dirty_pages(), 0);
+
+        // Flushing is a checkpoint, leaving the journal empty
+        let mut bitmap = PersistentBitVec::open_with_journal(Vec::new(), Vec::new(), 8, 1).unwrap();
+        bitmap.set_bit(1, true);
+        bitmap.commit().unwrap();
+        assert!(bitmap.pending.is_empty());
+        bitmap.flush().unwrap();
+        let mut journal = bitmap.journal.take().unwrap();
+        assert!(journal.read_all().unwrap().is_empty());
+        assert_eq!(bitmap.storage(), &vec![0x40]);
+    }
+}
@@ -0,0 +1,150 @@
+//! ## Kleene logic
+//! Three-valued AND, OR and NOT over bitmaps of values paired with bitmaps
+//! of validity, as query engines use for NULL handling. A clear validity bit
+//! marks an unknown value: `false AND NULL` is false and `true OR NULL` is
+//! true, while any other combination with a NULL is NULL.
+//!
+//! Value bits under a NULL are ignored, so the bitmaps can come straight
+//! from formats such as Arrow that leave them undefined, and each result
+//! comes back as values and validity with the values under NULLs clear.
+
+use crate::{BitVec, BitVecError};
+
+impl BitVec {
+    /// Combines the values and validity of two operands a byte at a time,
+    /// with the values under NULLs cleared first
+    fn kleene_with(
+        &self,
+        other: &BitVec,
+        self_valid: &BitVec,
+        other_valid: &BitVec,
+        op: fn(u8, u8, u8, u8) -> (u8, u8),
+    ) -> Result<(BitVec, BitVec), BitVecError> {
+        for found in [other.len, self_valid.len, other_valid.len] {
+            if found != self.len {
+                return Err(BitVecError::LengthMismatch { expected: self.len, found });
+            }
+        }
+
+        let mut values = BitVec::with_capacity(self.len);
+        values.resize(self.len, false);
+        let mut validity = values.clone();
+        for index in 0..values.data.len() {
+            let (a_valid, b_valid) = (self_valid.data[index], other_valid.data[index]);
+            let (value, valid) = op(self.data[index] & a_valid, a_valid, other.data[index] & b_valid, b_valid);
+            values.data[index] = value & valid;
+            validity.data[index] = valid;
+        }
+        Ok((values, validity))
+    }
+
+    /// Returns the Kleene AND of two vectors of values with their validity,
+    /// as the values and validity of the result, where false wins over NULL.
+    /// Returns an error if any of the lengths differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // true, false, NULL against NULL, NULL, false
+    /// let a = BitVec::from_bit_str("101").unwrap();
+    /// let a_valid = BitVec::from_bit_str("110").unwrap();
+    /// let b = BitVec::from_bit_str("000").unwrap();
+    /// let b_valid = BitVec::from_bit_str("001").unwrap();
+    /// let (values, validity) = a.and_kleene(&b, &a_valid, &b_valid).unwrap();
+    /// assert_eq!(values.to_bit_string(), "000");
+    /// // NULL, false, false
+    /// assert_eq!(validity.to_bit_string(), "011");
+    /// ```
+    pub fn and_kleene(&self, other: &BitVec, self_valid: &BitVec, other_valid: &BitVec) -> Result<(BitVec, BitVec), BitVecError> {
+        self.kleene_with(other, self_valid, other_valid, |a, a_valid, b, b_valid| {
+            // Known when both are known, or either is a known false
+            (a & b, a_valid & b_valid | a_valid & !a | b_valid & !b)
+        })
+    }
+
+    /// Returns the Kleene OR of two vectors of values with their validity,
+    /// as the values and validity of the result, where true wins over NULL.
+    /// Returns an error if any of the lengths differ.
+    pub fn or_kleene(&self, other: &BitVec, self_valid: &BitVec, other_valid: &BitVec) -> Result<(BitVec, BitVec), BitVecError> {
+        self.kleene_with(other, self_valid, other_valid, |a, a_valid, b, b_valid| {
+            // Any set value left is a known true
+            (a | b, a_valid & b_valid | a | b)
+        })
+    }
+
+    /// Returns the Kleene NOT of a vector of values with its validity, as the
+    /// values and validity of the result, where NULLs stay NULL. Returns an
+    /// error if the lengths differ.
+    pub fn not_kleene(&self, self_valid: &BitVec) -> Result<(BitVec, BitVec), BitVecError> {
+        self.kleene_with(self, self_valid, self_valid, |a, a_valid, _, _| (!a, a_valid))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads a three-valued result at an index
+    fn get(values: &BitVec, validity: &BitVec, index: usize) -> Option<bool> {
+        validity.get_bit(index).then(|| values.get_bit(index))
+    }
+
+    #[test]
+    fn truth_tables() {
+        // Every pair of false, true and NULL, with NULLs over both value
+        // bits, repeated to span bytes
+        let mut a = BitVec::new();
+        let mut a_valid = BitVec::new();
+        let mut b = BitVec::new();
+        let mut b_valid = BitVec::new();
+        for index in 0..36 {
+            let (x, y) = (index / 3 % 3, index % 3);
+            a.push_bit(x == 1 || x == 2 && index % 2 == 0);
+            a_valid.push_bit(x < 2);
+            b.push_bit(y == 1 || y == 2 && index % 4 < 2);
+            b_valid.push_bit(y < 2);
+        }
+
+        let (and, and_valid) = a.and_kleene(&b, &a_valid, &b_valid).unwrap();
+        let (or, or_valid) = a.or_kleene(&b, &a_valid, &b_valid).unwrap();
+        let (not, not_valid) = a.not_kleene(&a_valid).unwrap();
+        for index in 0..36 {
+            let x = get(&a, &a_valid, index);
+            let y = get(&b, &b_valid, index);
+            let expected_and = match (x, y) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+            let expected_or = match (x, y) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            };
+            assert_eq!(get(&and, &and_valid, index), expected_and);
+            assert_eq!(get(&or, &or_valid, index), expected_or);
+            assert_eq!(get(&not, &not_valid, index), x.map(|x| !x));
+        }
+
+        // Values under NULLs come back clear
+        for (values, validity) in [(&and, &and_valid), (&or, &or_valid), (&not, &not_valid)] {
+            assert_eq!(values.count_ones_in(..), values.ones_in(..).filter(|&i| validity.get_bit(i)).count());
+        }
+    }
+
+    #[test]
+    fn lengths_must_match() {
+        let bv = BitVec::from_bit_str("101").unwrap();
+        let short = BitVec::from_bit_str("10").unwrap();
+        assert_eq!(
+            bv.and_kleene(&bv, &bv, &short),
+            Err(BitVecError::LengthMismatch { expected: 3, found: 2 })
+        );
+        assert!(bv.not_kleene(&short).is_err());
+    }
+}
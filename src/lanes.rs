@@ -0,0 +1,233 @@
+//! ## Bit lanes
+//! A BitVec holding `n` boolean channels interleaved sample by sample, seen
+//! as `n` lanes: lane `k` is bits `k`, `k + n`, `k + 2n`, and so on. Lanes
+//! are views, so no channel is copied out to be read or written. Many lanes
+//! can be read at once, while one at a time is written. Counting a lane
+//! gathers it a word at a time, with a mask of its bits in each word.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// Panics unless lane `lane` of `lanes` exists, returning the bits in it
+fn lane_len(len: usize, lanes: usize, lane: usize) -> usize {
+    if lane >= lanes {
+        panic!("BitVec: lane {} out of bounds for {} lanes", lane, lanes)
+    }
+    (len + lanes - 1 - lane) / lanes
+}
+
+/// A read-only view of every `n`th bit of a BitVec
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// // 3 channels, 4 samples
+/// let bv = BitVec::from_bit_str("100 110 101 100").unwrap();
+/// let lanes = bv.lanes(3);
+/// assert_eq!(lanes[0].count_ones(), 4);
+/// assert_eq!(lanes[1].to_bitvec().to_bit_string(), "0100");
+/// assert!(lanes[2].get_bit(2));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct BitLaneView<'a> {
+    bv:     &'a BitVec,
+    stride: usize,
+    offset: usize,
+    len:    usize,
+}
+
+/// A writable view of every `n`th bit of a BitVec
+#[derive(Debug)]
+pub struct BitLaneMut<'a> {
+    bv:     &'a mut BitVec,
+    stride: usize,
+    offset: usize,
+    len:    usize,
+}
+
+impl BitLaneView<'_> {
+    /// Get the number of bits in the lane
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the lane holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit value at an index of the lane. Panics if the index is
+    /// out of bounds.
+    pub fn get_bit(&self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+        self.bv.get_bit(self.offset + index * self.stride)
+    }
+
+    /// Returns an iterator over the bits of the lane
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.bv.get_bit(self.offset + index * self.stride))
+    }
+
+    /// Counts the set bits in the lane, a word at a time
+    pub fn count_ones(&self) -> usize {
+        if self.stride > 64 {
+            return self.iter().filter(|&bit| bit).count();
+        }
+
+        let data = &self.bv.data;
+        let mut count = 0;
+        // The first bit of the lane in each word, counted from its start
+        let mut first = self.offset;
+        for start in (0..data.len()).step_by(8) {
+            let mut bytes = [0; 8];
+            let end = (start + 8).min(data.len());
+            bytes[..end - start].copy_from_slice(&data[start..end]);
+            let word = u64::from_be_bytes(bytes);
+
+            let mut mask = 0;
+            let mut bit = first;
+            while bit < 64 {
+                mask |= 1 << (63 - bit);
+                bit += self.stride;
+            }
+            // Padding bits are clear, so a mask past the end counts nothing
+            count += (word & mask).count_ones() as usize;
+            first = bit - 64;
+        }
+        count
+    }
+
+    /// Copies the lane out as a BitVec
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut bv = BitVec::with_capacity(self.len);
+        for bit in self.iter() {
+            bv.push_bit(bit);
+        }
+        bv
+    }
+}
+
+impl BitLaneMut<'_> {
+    /// Get the number of bits in the lane
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the lane holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a read-only view of the lane
+    pub fn as_view(&self) -> BitLaneView<'_> {
+        BitLaneView { bv: self.bv, stride: self.stride, offset: self.offset, len: self.len }
+    }
+
+    /// Returns the bit value at an index of the lane. Panics if the index is
+    /// out of bounds.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.as_view().get_bit(index)
+    }
+
+    /// Sets the bit at an index of the lane. Panics if the index is out of
+    /// bounds, as a lane cannot grow.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+        self.bv.set_bit(self.offset + index * self.stride, value);
+    }
+
+    /// Sets every bit of the lane
+    pub fn fill(&mut self, value: bool) {
+        for index in 0..self.len {
+            self.set_bit(index, value);
+        }
+    }
+
+    /// Copies bits into the start of the lane. Panics if there are more
+    /// bits than the lane holds.
+    pub fn copy_from(&mut self, bits: &BitVec) {
+        if bits.len > self.len {
+            panic!("BitVec: {} bits do not fit in a lane of {}", bits.len, self.len)
+        }
+        for index in 0..bits.len {
+            self.set_bit(index, bits.get_bit(index));
+        }
+    }
+}
+
+impl BitVec {
+    /// Returns read-only views of the `n` lanes. Panics if `n` is 0.
+    pub fn lanes(&self, n: usize) -> Vec<BitLaneView<'_>> {
+        if n == 0 {
+            panic!("BitVec: cannot split into 0 lanes")
+        }
+        (0..n).map(|lane| BitLaneView { bv: self, stride: n, offset: lane, len: lane_len(self.len, n, lane) }).collect()
+    }
+
+    /// Returns a writable view of lane `lane` of `n`. Panics if the lane
+    /// does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::repeat_bit(false, 8);
+    /// bv.lane_mut(4, 1).fill(true);
+    /// assert_eq!(bv.to_bit_string(), "01000100");
+    /// ```
+    pub fn lane_mut(&mut self, n: usize, lane: usize) -> BitLaneMut<'_> {
+        let len = lane_len(self.len, n, lane);
+        BitLaneMut { bv: self, stride: n, offset: lane, len }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views_match_deinterleave() {
+        let bv = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x55]);
+        for n in [1, 2, 3, 5, 7, 8, 13, 64, 70] {
+            let lanes = bv.lanes(n);
+            assert_eq!(lanes.iter().map(|lane| lane.len()).sum::<usize>(), bv.len_bits());
+            for (k, lane) in lanes.iter().enumerate() {
+                let expected: Vec<bool> = (k..bv.len_bits()).step_by(n).map(|index| bv.get_bit(index)).collect();
+                assert_eq!(lane.iter().collect::<Vec<_>>(), expected, "lane {} of {}", k, n);
+                assert_eq!(lane.count_ones(), expected.iter().filter(|&&bit| bit).count(), "lane {} of {}", k, n);
+            }
+        }
+        // More lanes than bits leaves some empty
+        assert!(BitVec::from_bit_str("1").unwrap().lanes(3)[2].is_empty());
+    }
+
+    #[test]
+    fn writing_a_lane() {
+        let mut bv = BitVec::repeat_bit(false, 10);
+        let mut lane = bv.lane_mut(3, 2);
+        assert_eq!(lane.len(), 3);
+        lane.copy_from(&BitVec::from_bit_str("101").unwrap());
+        lane.set_bit(1, true);
+        assert_eq!(lane.as_view().count_ones(), 3);
+        assert_eq!(bv.to_bit_string(), "0010010010");
+        assert_eq!(bv.len_bits(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn lane_index_past_the_end() {
+        let mut bv = BitVec::repeat_bit(false, 10);
+        bv.lane_mut(3, 0).set_bit(4, true);
+    }
+}
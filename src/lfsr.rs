@@ -0,0 +1,234 @@
+//! ## LFSR sequences
+//! The Berlekamp–Massey algorithm, which finds the shortest linear feedback
+//! shift register that generates a bit sequence, as when studying a captured
+//! keystream. The length of that register is the linear complexity of the
+//! sequence.
+//!
+//! There are also the pseudo-noise sequences of spreading and
+//! synchronization: m-sequences from a register with a primitive polynomial,
+//! which repeat every `2^L - 1` bits, and Gold sequences, the XOR of two
+//! m-sequences, which have low correlation with each other.
+//!
+//! The register is given by its connection polynomial
+//! `C(x) = 1 + c1 x + ... + cL x^L`, where each bit after the first `L` is
+//! `s[n] = c1 s[n-1] ^ ... ^ cL s[n-L]`. As for the other GF(2)
+//! polynomials, the highest power comes first.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{BitVec, LengthPolicy};
+
+impl BitVec {
+    /// Runs Berlekamp–Massey over the bits, returning the coefficients of
+    /// the connection polynomial, lowest power first, and the register length
+    fn berlekamp_massey(&self) -> (Vec<bool>, usize) {
+        let mut connection = vec![true];
+        let mut previous = vec![true];
+        let mut length = 0;
+        let mut shift = 1;
+
+        for n in 0..self.len {
+            let discrepancy = (1..=length).fold(self.get_bit(n), |bit, i| bit ^ (connection[i] && self.get_bit(n - i)));
+            if !discrepancy {
+                shift += 1;
+                continue;
+            }
+
+            // Add the previous polynomial, shifted up, to cancel the discrepancy
+            let last = connection.clone();
+            connection.resize(connection.len().max(previous.len() + shift), false);
+            for (i, &coefficient) in previous.iter().enumerate() {
+                connection[i + shift] ^= coefficient;
+            }
+
+            if 2 * length <= n {
+                length = n + 1 - length;
+                previous = last;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+        }
+
+        connection.resize(length + 1, false);
+        (connection, length)
+    }
+
+    /// Returns the linear complexity of the bits, the length of the shortest
+    /// LFSR that generates them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // s[n] = s[n-1] ^ s[n-3]
+    /// let bv = BitVec::from_bit_str("1001 1101 0011 1010").unwrap();
+    /// assert_eq!(bv.linear_complexity(), 3);
+    /// ```
+    pub fn linear_complexity(&self) -> usize {
+        self.berlekamp_massey().1
+    }
+
+    /// Returns the connection polynomial of the shortest LFSR that generates
+    /// the bits, with one bit for each power up to the linear complexity,
+    /// highest first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // s[n] = s[n-1] ^ s[n-3], so C(x) = x^3 + x + 1
+    /// let bv = BitVec::from_bit_str("1001 1101 0011 1010").unwrap();
+    /// assert_eq!(bv.recover_lfsr().to_bit_string(), "1011");
+    /// ```
+    pub fn recover_lfsr(&self) -> BitVec {
+        let (connection, _) = self.berlekamp_massey();
+        let mut poly = BitVec::new();
+        for &coefficient in connection.iter().rev() {
+            poly.push_bit(coefficient);
+        }
+        poly
+    }
+
+    /// Generates `length` bits from an LFSR whose connection polynomial has
+    /// 1s at the powers in `taps`, so each bit is the XOR of the bits `taps`
+    /// places before it, starting from a register of 1s. With a primitive
+    /// polynomial this is an m-sequence, which repeats every `2^L - 1` bits
+    /// for the highest tap `L`. Panics if there are no taps or a tap is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // x^3 + x + 1, with a period of 7
+    /// let bv = BitVec::m_sequence(&[3, 1], 14);
+    /// assert_eq!(bv.to_bit_string(), "11101001110100");
+    /// assert_eq!(bv.recover_lfsr().to_bit_string(), "1011");
+    /// ```
+    pub fn m_sequence(taps: &[usize], length: usize) -> BitVec {
+        let degree = taps.iter().copied().max().unwrap_or(0);
+        if degree == 0 || taps.contains(&0) {
+            panic!("BitVec: taps must be non-empty powers of at least 1")
+        }
+
+        let mut bv = BitVec::new();
+        for n in 0..length {
+            let bit = n < degree || taps.iter().fold(false, |bit, &tap| bit ^ bv.get_bit(n - tap));
+            bv.push_bit(bit);
+        }
+        bv
+    }
+
+    /// Generates `length` bits of a Gold sequence, the XOR of the
+    /// m-sequences from two registers of the same length, the second
+    /// started `shift` bits along. Each shift of a preferred pair of
+    /// polynomials gives a different member of the family. Panics if the
+    /// highest taps differ, or as `m_sequence`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // The preferred pair x^5 + x^2 + 1 and x^5 + x^4 + x^3 + x^2 + 1
+    /// let code = BitVec::gold_sequence(&[5, 2], &[5, 4, 3, 2], 3, 31);
+    /// let mut capture = BitVec::from_bit_str("0110").unwrap();
+    /// capture.extend(&code);
+    /// assert_eq!(capture.find(&code, 0), Some(4));
+    /// ```
+    pub fn gold_sequence(taps_a: &[usize], taps_b: &[usize], shift: usize, length: usize) -> BitVec {
+        if taps_a.iter().max() != taps_b.iter().max() {
+            panic!("BitVec: Gold sequences need registers of the same length")
+        }
+
+        let mut bv = BitVec::m_sequence(taps_a, length);
+        let other = BitVec::m_sequence(taps_b, length + shift).slice(shift..).to_bitvec();
+        let _ = bv.xor_with(&other, LengthPolicy::Reject);
+        bv
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs an LFSR from its connection polynomial, highest power first,
+    /// and a seed of the first bits
+    fn generate(poly: &BitVec, seed: &BitVec, len: usize) -> BitVec {
+        let length = poly.len_bits() - 1;
+        let mut bv = seed.clone();
+        for n in length..len {
+            let bit = (1..=length).fold(false, |bit, i| bit ^ (poly.get_bit(length - i) && bv.get_bit(n - i)));
+            bv.push_bit(bit);
+        }
+        bv
+    }
+
+    #[test]
+    fn recovers_a_maximal_register() {
+        // x^16 + x^14 + x^13 + x^11 + 1, with a period of 65535
+        let mut poly = BitVec::new();
+        poly.resize(17, false);
+        for power in [16, 14, 13, 11, 0] {
+            poly.set_bit(16 - power, true);
+        }
+        let seed = BitVec::from(&[0xAC, 0xE1]);
+        let stream = generate(&poly, &seed, 200);
+
+        assert_eq!(stream.linear_complexity(), 16);
+        let recovered = stream.recover_lfsr();
+        assert_eq!(recovered, poly);
+        assert_eq!(generate(&recovered, &stream.slice(..16).to_bitvec(), 200), stream);
+    }
+
+    #[test]
+    fn simple_sequences() {
+        assert_eq!(BitVec::new().linear_complexity(), 0);
+        assert_eq!(BitVec::new().recover_lfsr().to_bit_string(), "1");
+        assert_eq!(BitVec::from_bit_str("0000").unwrap().linear_complexity(), 0);
+        // A single 1 at the end needs a register as long as the sequence
+        assert_eq!(BitVec::from_bit_str("0001").unwrap().linear_complexity(), 4);
+        // An alternating sequence is s[n] = s[n-2]
+        let alternating = BitVec::from_bit_str("1010 1010 10").unwrap();
+        assert_eq!(alternating.recover_lfsr().to_bit_string(), "101");
+    }
+
+    /// Correlates two sequences of ±1s over a period, with one shifted
+    fn correlation(a: &BitVec, b: &BitVec, shift: usize) -> i32 {
+        let period = a.len_bits();
+        (0..period).map(|n| if a.get_bit(n) == b.get_bit((n + shift) % period) { 1 } else { -1 }).sum()
+    }
+
+    #[test]
+    fn m_sequence_autocorrelation() {
+        // x^7 + x^6 + 1 is primitive, with a period of 127
+        let bv = BitVec::m_sequence(&[7, 6], 254);
+        assert_eq!(bv.slice(..127).to_bitvec(), bv.slice(127..).to_bitvec());
+        assert_eq!(bv.linear_complexity(), 7);
+
+        let period = bv.slice(..127).to_bitvec();
+        assert_eq!(period.count_ones(), 64);
+        assert_eq!(correlation(&period, &period, 0), 127);
+        for shift in 1..127 {
+            assert_eq!(correlation(&period, &period, shift), -1);
+        }
+    }
+
+    #[test]
+    fn gold_cross_correlation() {
+        // A preferred pair of degree 5 has three cross-correlation values
+        let (a, b) = ([5, 2], [5, 4, 3, 2]);
+        let first = BitVec::gold_sequence(&a, &b, 0, 31);
+        let second = BitVec::gold_sequence(&a, &b, 7, 31);
+        assert_ne!(first, second);
+        for shift in 0..31 {
+            assert!([-9, -1, 7].contains(&correlation(&first, &second, shift)));
+        }
+    }
+}
@@ -2,14 +2,209 @@
 //! A simple implementation of a vector with bitwise operation.
 //! Second revision for improved memory management, faster performance, and
 //! expanded functionality.
-
-use std::{cmp, fmt, ops};
-
+//!
+//! The crate is `no_std` with `alloc`. The `std` feature, on by default,
+//! adds the `std::io` adapters, sample captures, VCD files, and
+//! variable-length code tables.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::{format, string::{String, ToString}, vec, vec::Vec};
+use core::{cmp, fmt::{self, Write as _}, hash, ops};
+
+mod anonymize;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "arrow")]
+mod arrow_impl;
+mod arith;
+mod array;
+#[cfg(target_has_atomic = "64")]
+mod atomic;
+mod bitref;
+mod bitstr;
+mod bitset;
+mod builder;
+mod bytes;
+#[cfg(feature = "std")]
+mod capture;
+mod channel;
+mod chunks;
+mod codes;
+mod column;
+mod compressed;
+mod convolutional;
+mod counted;
+mod crc;
+mod cursor;
+mod deflate_compat;
+mod diff;
+mod encoding;
+mod error;
+#[cfg(feature = "fax")]
+mod fax;
+mod faults;
+mod fec;
+mod field;
+mod fixed;
+mod frames;
+mod gaps;
+mod genetic;
+mod gorilla;
+mod grid;
+mod growth;
+mod hilbert;
+mod huffman;
+mod integers;
+mod interleave;
+#[cfg(feature = "std")]
+mod io;
+mod iter;
+#[cfg(feature = "std")]
+mod journal;
+mod kleene;
+mod lanes;
+mod lfsr;
+mod linalg;
+mod logic;
+mod louds;
+mod mask;
+mod matrix;
+mod merkle;
+mod metrics;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod mtf;
+mod neighbors;
+mod numpy;
+mod order;
+mod outer;
+mod packer;
+#[cfg(feature = "rayon")]
+mod par;
+mod parens;
+mod parquet;
+#[cfg(feature = "std")]
+mod persist;
+mod policy;
+mod poly;
+mod postgres;
+mod prepend;
+mod privacy;
+mod quadtree;
+#[cfg(feature = "rand")]
+mod random;
+mod range;
+mod redis;
+mod rank_select;
+mod raster;
+mod repeat;
+mod reverse;
+#[cfg(feature = "roaring")]
+mod roaring;
+mod runs;
+mod schema;
+mod search;
+mod seek;
+mod selection;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sets;
+mod shift;
+mod sink;
+mod slice;
+mod sliding;
+mod soft;
+mod source;
+mod spare;
+mod splice;
+mod strings;
+mod subsets;
+mod symbols;
+mod text;
+mod trace;
+mod transitions;
+mod varint;
+#[cfg(feature = "std")]
+mod varlen;
+#[cfg(feature = "std")]
+mod vcd;
+mod wavelet;
+mod zero_runs;
+
+#[cfg(feature = "std")]
+pub use capture::SampleCapture;
+pub use array::BitArray;
+#[cfg(target_has_atomic = "64")]
+pub use atomic::AtomicBitVec;
+pub use bitref::{BitRef, ByteMut};
+pub use bitstr::BitStr;
+pub use builder::BitVecBuilder;
+pub use chunks::{Chunks, ChunksExact, Windows};
+pub use column::NullableBitColumn;
+pub use compressed::CompressedBitVec;
+pub use convolutional::ConvolutionalCode;
+pub use counted::CountedBitVec;
+pub use cursor::BitCursor;
+pub use deflate_compat::DeflateBlockType;
+pub use diff::BitDiff;
+pub use error::BitVecError;
+pub use faults::{FaultOutcome, FaultReport};
+pub use fec::ReedMuller;
+pub use field::BitField;
+pub use fixed::Rounding;
+pub use gorilla::XorWindow;
+pub use grid::{BitGrid, Connectivity};
+pub use growth::GrowthPolicy;
+pub use integers::BitInt;
+#[cfg(feature = "std")]
+pub use io::{BitReader, BitWriter, StreamBitReader, StreamBitWriter};
+pub use iter::{IterOnes, IterZeros};
+#[cfg(feature = "std")]
+pub use journal::JournalStorage;
+pub use lanes::{BitLaneMut, BitLaneView};
+pub use logic::LengthPolicy;
+pub use louds::Louds;
+pub use matrix::BitMatrix;
+pub use merkle::MerkleTree;
+#[cfg(feature = "mmap")]
+pub use mmap::FileBitVec;
+pub use neighbors::HammingNeighbors;
+pub use order::BitOrder;
+pub use packer::{BitPacker, BitUnpacker};
+pub use parens::BalancedParens;
+#[cfg(feature = "std")]
+pub use persist::{PageStorage, PersistentBitVec};
+pub use policy::{Checked, MisusePolicy, Panic, WithPolicy};
+pub use prepend::BitPrepender;
+pub use rank_select::RankSelect;
+pub use redis::RedisBitOp;
+pub use runs::Runs;
+pub use seek::SeekFrom;
+pub use selection::{Representation, SELECTION_DENSITY};
+pub use sink::{BitSink, CountingSink, WidthPolicy};
+pub use slice::{BitSlice, BitSliceMut, BitVecRef, Bits};
+pub use sliding::SlidingWindowBits;
+pub use soft::SoftBits;
+pub use source::BitSource;
+pub use symbols::TailPolicy;
+pub use text::DisplayBits;
+pub use trace::{RecordingReader, TraceMismatch, TracedRead};
+pub use transitions::TransitionMatrix;
+#[cfg(feature = "std")]
+pub use varlen::{VarLenCodeError, VarLenCodeTable};
+pub use wavelet::WaveletMatrix;
+
+#[derive(Clone, Debug)]
 pub struct BitVec {
     data:     Vec<u8>,  // data vector
     len:      usize,    // length in bits
     byte_idx: usize,    // current byte, used for sequential reading
     bit_idx:  u8,       // current bit, used for sequential reading
+    order:    BitOrder, // packing order of bits within bytes
+    growth:   GrowthPolicy, // what set_bit does past the end
 }
 
 impl BitVec {
@@ -32,6 +227,8 @@ impl BitVec {
             len: 0,
             byte_idx: 0,
             bit_idx: 0,
+            order: BitOrder::Msb0,
+            growth: GrowthPolicy::Grow,
         }
     }
 
@@ -43,26 +240,59 @@ impl BitVec {
     /// use bitvecs::BitVec;
     /// 
     /// let mut bv = BitVec::with_capacity(24);
+    /// assert_eq!(bv.len_bits(), 0);
+    /// assert!(bv.capacity_bits() >= 24);
     /// ```
     pub fn with_capacity(bits: usize) -> Self {
         let bytes = bits.div_ceil(8);
 
         Self {
             data: Vec::with_capacity(bytes),
-            len: bits,
+            len: 0,
             byte_idx: 0,
             bit_idx: 0,
+            order: BitOrder::Msb0,
+            growth: GrowthPolicy::Grow,
         }
     }
 
+    /// Constructs a BitVec holding whole bytes, MSB first, as `From` does
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bytes(&[0xA0, 0x01]);
+    /// assert_eq!(bv, BitVec::from(&[0xA0, 0x01]));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from(bytes)
+    }
+
     /// Exports the BitVec data to binary format
     pub fn export(&self) -> String {
-        match String::from_utf8(self.data.clone()) {
-            Ok(s) => return s,
+        let bytes = self.data.iter().map(|&byte| self.order.convert(byte)).collect();
+        match String::from_utf8(bytes) {
+            Ok(s) => s,
             Err(e) => panic!("{}", e),
         }
     }
 
+    /// Copies the bits into a vector of bools
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0xA0]);
+    /// assert_eq!(bv.to_bool_vec()[..3], [true, false, true]);
+    /// ```
+    pub fn to_bool_vec(&self) -> Vec<bool> {
+        (0..self.len).map(|index| self.get_bit(index)).collect()
+    }
+
     /// Completely fill the BitVec with either true or false according to
     /// the length of the vector
     pub fn fill(&mut self, value: bool) {
@@ -78,27 +308,6 @@ impl BitVec {
         }
     }
 
-    /// Generate a new BitVec from an array or other bit stream
-    /// 
-    /// # Examples
-    ///
-    /// ```
-    /// use bitvecs::BitVec;
-    /// 
-    /// let array_of_bytes = [24, 51, 67];
-    /// let mut bundle = BitVec::from(&array_of_bytes);
-    /// ```
-    pub fn from(data: &[u8]) -> Self {
-        let len = data.len() * 8;
-
-        Self {
-            data: data.to_vec(),
-            len,
-            byte_idx: 0,
-            bit_idx: 0,
-        }
-    }
-
     /// Import a BitVec from a string
     /// 
     /// # Examples
@@ -113,14 +322,17 @@ impl BitVec {
         let data = input.as_bytes().to_vec();
 
         Self {
-            len: data.len(),
+            len: data.len() * 8,
             data,
             byte_idx: 0,
-            bit_idx: 0
+            bit_idx: 0,
+            order: BitOrder::Msb0,
+            growth: GrowthPolicy::Grow,
         }
     }
 
     /// Returns the bit value at the desired index. Bit is read from MSB
+    #[allow(clippy::precedence)]
     pub fn get_bit(&self, index: usize) -> bool {
         let byte_index = index / 8;
         let bit_index = index % 8;
@@ -149,6 +361,7 @@ impl BitVec {
     }
 
     /// Get the current capacity in bytes
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -158,16 +371,60 @@ impl BitVec {
         self.len
     }
 
+    /// Get the number of bits the vector can hold without reallocating
+    pub fn capacity_bits(&self) -> usize {
+        self.data.capacity() * 8
+    }
+
+    /// Get the number of extra bytes needed to hold `additional` more bits.
+    /// Panics if the number of bits overflows.
+    fn bytes_needed(&self, additional: usize) -> usize {
+        let Some(bits) = self.len.checked_add(additional) else {
+            panic!("BitVec: capacity overflow")
+        };
+        bits.div_ceil(8) - self.data.len()
+    }
+
+    /// Reserves capacity for at least `additional` more bits, possibly more
+    /// to avoid frequent reallocation. Does nothing if the capacity is
+    /// already enough. Panics if the capacity overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0xFF]);
+    /// bv.reserve(100);
+    /// assert!(bv.capacity_bits() >= 108);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(self.bytes_needed(additional));
+    }
+
+    /// Reserves capacity for `additional` more bits, rounded up to a whole
+    /// byte, without deliberately reserving more. Does nothing if the
+    /// capacity is already enough. Panics if the capacity overflows.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.data.reserve_exact(self.bytes_needed(additional));
+    }
+
+    /// Shrinks the capacity as close to the length as possible
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
     /// Build a bit mask of a given number of bits starting from the MSB
     pub fn mask_msb(size: usize) -> u8 {
         if size > 0 {
-            0xFF << 8 - size
+            0xFF << (8 - size)
         } else {
             0xFF
         }
     }
 
     /// Build a bit mask of a given number of bits starting from the LSB
+    #[allow(clippy::precedence)]
     pub fn mask_lsb(size: usize) -> u8 {
         if size > 0 {
             0xFF >> 8 - size
@@ -183,19 +440,7 @@ impl BitVec {
         }
 
         let bit = self.get_bit(self.len - 1);
-        self.set_bit(self.len - 1, false);
-
-        // Update indices
-        if self.bit_idx == 0 {
-            if self.byte_idx > 0 {
-                self.byte_idx -= 1;
-                self.bit_idx = 7;
-            }
-        } else {
-            self.bit_idx -= 1;
-        }
-        
-        self.len -= 1;
+        self.truncate_bits(self.len - 1);
         Some(bit)
     }
 
@@ -203,14 +448,22 @@ impl BitVec {
     /// of position. If there are less than 8 bits, then it returns what is
     /// available, with the remainder set as 0s.
     pub fn pop_byte(&mut self) -> Option<u8> {
+        self.pop_msb_byte().map(|byte| self.order.convert(byte))
+    }
+
+    /// Removes and returns the last contiguous byte, packed MSB first
+    #[allow(clippy::needless_return)]
+    fn pop_msb_byte(&mut self) -> Option<u8> {
         let last_byte = self.data.pop();
 
         if self.len < 8 {
             self.len = 0;
-            return last_byte;
+            self.clamp_read_position();
+            last_byte
         } else {
             let len_tail = self.len % 8;
             self.len -= 8;
+            self.clamp_read_position();
             // When there is perfect byte alignment
             if len_tail == 0 {
                 return last_byte;
@@ -221,6 +474,7 @@ impl BitVec {
             let last_index = self.data.len() - 1;
             let head = self.data[last_index] << len_tail;
             self.data[last_index] &= 0xFF << len_head;
+            self.clamp_read_position();
 
             return Some(head | tail);
         }
@@ -234,15 +488,13 @@ impl BitVec {
 
     /// Removes and returns the last byte from the vector.
     pub fn pop_vec_byte(&mut self) -> Option<u8> {
-        let truncation = self.len % 8;
-        self.len -= truncation;
-        // handle bit read position? If bytes are being popped, the vector
-        // probably isn't being used for sequential read
-
-        self.data.pop()
+        let byte = self.data.pop()?;
+        self.truncate_bits(self.data.len() * 8);
+        Some(self.order.convert(byte))
     }
     
     /// Pushes a bit to the vector
+    #[allow(clippy::precedence)]
     pub fn push_bit(&mut self, value: bool) {
         let byte_offset = self.len / 8;
         let bit_offset = (self.len % 8) as u8;
@@ -261,6 +513,12 @@ impl BitVec {
 
     /// Pushes a byte to the vector
     pub fn push_byte(&mut self, byte: u8) {
+        self.push_msb_byte(self.order.convert(byte));
+    }
+
+    /// Pushes a byte packed MSB first to the vector
+    #[allow(clippy::precedence)]
+    pub(crate) fn push_msb_byte(&mut self, byte: u8) {
         let byte_offset = self.len / 8;
         let bit_offset = (self.len % 8) as u8;
 
@@ -276,15 +534,15 @@ impl BitVec {
     }
 
     /// Deprecated as the name is too similar to a new function
-    #[deprecated(since = "0.1.0", note = "This function is deprecated, please use the seq_read function instead.")]
+    #[deprecated(since = "0.1.0", note = "This function is deprecated, please use the read_bit_seq function instead.")]
     pub fn read_bit(&mut self) -> Option<u8> {
-        self.seq_read()
+        self.read_bit_seq()
     }
 
     /// This is a sequential read which increments the bit index, not a return of the bit value at a specific index.
     /// For the latter functionality use get_bit().
-    pub fn seq_read(&mut self) -> Option<u8> {
-        if self.byte_idx >= (self.len + 7) / 8 {
+    pub fn read_bit_seq(&mut self) -> Option<u8> {
+        if self.get_read_position() >= self.len {
             return None;
         }
 
@@ -298,8 +556,20 @@ impl BitVec {
         Some(bit)
     }
 
+    /// Deprecated as the name does not say what is read
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the read_bit_seq function instead.")]
+    pub fn seq_read(&mut self) -> Option<u8> {
+        self.read_bit_seq()
+    }
+
     /// Reads 8 bits in sequence and returns a byte (can be offset)
     pub fn read_byte(&mut self) -> Option<u8> {
+        self.read_msb_byte().map(|byte| self.order.convert(byte))
+    }
+
+    /// Reads 8 bits in sequence, packed MSB first
+    #[allow(clippy::needless_late_init)]
+    fn read_msb_byte(&mut self) -> Option<u8> {
         if (self.byte_idx * 8 + self.bit_idx as usize + 8) > self.len {
             return None;
         }
@@ -326,56 +596,143 @@ impl BitVec {
     /// Finds the next set bit in a BitVec from a start index (inclusive) and
     /// returns the index of that bit if one is found. Most useful for one-hot
     /// encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b00100000 0b00000100
+    /// let bv = BitVec::from(&[0x20, 0x04]);
+    /// assert_eq!(bv.next_set_bit(0), Some(2));
+    /// assert_eq!(bv.next_set_bit(3), Some(13));
+    /// assert_eq!(bv.next_set_bit(14), None);
+    /// ```
     pub fn next_set_bit(&self, start_idx: usize) -> Option<usize> {
-        let mut start_byte = start_idx / 8;
-        let bit_offset = start_idx % 8;
+        self.next_bit_matching(start_idx, 0)
+    }
 
-        if start_byte >= self.data.len() {
+    /// Finds the next clear bit in a BitVec from a start index (inclusive)
+    /// and returns the index of that bit if one is found
+    pub fn next_clear_bit(&self, start_idx: usize) -> Option<usize> {
+        self.next_bit_matching(start_idx, 0xFF)
+    }
+
+    /// Scans for the next bit which is set once XORed with the flip mask,
+    /// skipping whole words and bytes with no candidates
+    fn next_bit_matching(&self, start_idx: usize, flip: u8) -> Option<usize> {
+        if start_idx >= self.len {
             return None;
         }
 
-        fn find_set_bit(byte: u8, start_bit: usize) -> Option<usize> {
-            for bit in start_bit..8 {
-                if byte & (0x80 >> bit) != 0 {
-                    return Some(bit);
-                }
+        let num_bytes = self.len.div_ceil(8);
+        let flip_word = u64::from_ne_bytes([flip; 8]);
+        let mut byte_idx = start_idx / 8;
+        let mut byte = (self.data[byte_idx] ^ flip) & (0xFF >> (start_idx % 8));
+
+        while byte == 0 {
+            byte_idx += 1;
+            while byte_idx + 8 <= num_bytes
+                && u64::from_ne_bytes(self.data[byte_idx..byte_idx + 8].try_into().unwrap()) == flip_word
+            {
+                byte_idx += 8;
             }
-            None
-        }
-
-        if bit_offset > 0 {
-            let mask = 0xFF >> bit_offset;
-            if self.data[start_byte] & mask != 0 {
-                if let Some(bit_pos) = find_set_bit(self.data[start_byte], bit_offset) {
-                    return Some(start_byte * 8 + bit_pos);
-                } else {
-                    start_byte += 1;
-                }
+            if byte_idx >= num_bytes {
+                return None;
             }
+            byte = self.data[byte_idx] ^ flip;
         }
 
-        for byte_idx in start_byte..self.data.len() {
-            if self.data[byte_idx] != 0 {
-                if let Some(bit_pos) = find_set_bit(self.data[byte_idx], 0) {
-                    return Some(byte_idx * 8 + bit_pos);
-                }
-            }
+        // Padding bits may match, but they are not part of the vector
+        let index = byte_idx * 8 + byte.leading_zeros() as usize;
+        (index < self.len).then_some(index)
+    }
+
+    /// Scans backwards for the last bit before an end index (exclusive)
+    /// which is set once XORed with the flip mask, skipping whole bytes with
+    /// no candidates
+    fn prev_bit_matching(&self, end_idx: usize, flip: u8) -> Option<usize> {
+        let last = end_idx.min(self.len).checked_sub(1)?;
+        let mut byte_idx = last / 8;
+        // Only the bits up to the last index, leaving out the padding
+        let mut byte = (self.data[byte_idx] ^ flip) & BitVec::mask_msb(last % 8 + 1);
+
+        while byte == 0 {
+            byte_idx = byte_idx.checked_sub(1)?;
+            byte = self.data[byte_idx] ^ flip;
         }
 
-        None
+        Some(byte_idx * 8 + 7 - byte.trailing_zeros() as usize)
+    }
+
+    /// Returns the index of the first set bit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("0011 0100 0").unwrap();
+    /// assert_eq!(bv.first_one(), Some(2));
+    /// assert_eq!(bv.last_one(), Some(5));
+    /// assert_eq!(bv.leading_zeros(), 2);
+    /// assert_eq!(bv.trailing_zeros(), 3);
+    /// ```
+    pub fn first_one(&self) -> Option<usize> {
+        self.next_bit_matching(0, 0)
+    }
+
+    /// Returns the index of the last set bit
+    pub fn last_one(&self) -> Option<usize> {
+        self.prev_bit_matching(self.len, 0)
+    }
+
+    /// Returns the index of the first clear bit
+    pub fn first_zero(&self) -> Option<usize> {
+        self.next_bit_matching(0, 0xFF)
+    }
+
+    /// Returns the index of the last clear bit
+    pub fn last_zero(&self) -> Option<usize> {
+        self.prev_bit_matching(self.len, 0xFF)
+    }
+
+    /// Returns the number of clear bits before the first set bit
+    pub fn leading_zeros(&self) -> usize {
+        self.first_one().unwrap_or(self.len)
+    }
+
+    /// Returns the number of clear bits after the last set bit
+    pub fn trailing_zeros(&self) -> usize {
+        self.last_one().map_or(self.len, |index| self.len - 1 - index)
+    }
+
+    /// Returns the number of set bits before the first clear bit
+    pub fn leading_ones(&self) -> usize {
+        self.first_zero().unwrap_or(self.len)
+    }
+
+    /// Returns the number of set bits after the last clear bit
+    pub fn trailing_ones(&self) -> usize {
+        self.last_zero().map_or(self.len, |index| self.len - 1 - index)
     }
 
     /// Sets the bit at the desired index. If the bit to be set is beyond the
-    /// current capacity, then the vector will grow to accomodate the new bit
-    /// and fill the gap with 0s rather than panic
+    /// end, then under the default growth policy the vector will grow to
+    /// accomodate the new bit and fill the gap with 0s rather than panic.
+    /// Under the strict policy it panics.
+    #[allow(clippy::precedence)]
     pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.len {
+            match self.growth {
+                GrowthPolicy::Grow => self.resize(index + 1, false),
+                GrowthPolicy::Strict => panic!("BitVec: index out of bounds"),
+            }
+        }
+
         let byte_index = index / 8;
         let bit_index = index % 8;
 
-        while self.data.len() <= byte_index {
-            self.data.push(0);
-        }
-
         if value {
             self.data[byte_index] |= 1 << 7 - bit_index;
         } else {
@@ -404,6 +761,143 @@ impl BitVec {
         true
     }
 
+    /// Returns the number of set bits in the vector
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0x6B, 0xA0]);
+    /// assert_eq!(bv.count_ones(), 7);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        let full_bytes = self.len / 8;
+        let mut count = popcount(&self.data[..full_bytes]);
+
+        // Only the valid bits of the final byte are counted
+        let tail = self.len % 8;
+        if tail != 0 {
+            count += (self.data[full_bytes] & BitVec::mask_msb(tail)).count_ones() as usize;
+        }
+
+        count
+    }
+
+    /// Returns the number of clear bits in the vector
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Checks if any bit is set. An empty vector has no set bits.
+    pub fn any(&self) -> bool {
+        let full_bytes = self.len / 8;
+        let tail = self.len % 8;
+
+        self.data[..full_bytes].iter().any(|&byte| byte != 0)
+            || (tail != 0 && self.data[full_bytes] & BitVec::mask_msb(tail) != 0)
+    }
+
+    /// Checks if every bit is set. An empty vector has no clear bits, so
+    /// this is true.
+    pub fn all(&self) -> bool {
+        let full_bytes = self.len / 8;
+        let tail = self.len % 8;
+
+        self.data[..full_bytes].iter().all(|&byte| byte == 0xFF)
+            && (tail == 0 || self.data[full_bytes] & BitVec::mask_msb(tail) == BitVec::mask_msb(tail))
+    }
+
+    // ########################################################################
+    // Fallible functions
+    // Non-panicking variants of the main functions, returning a BitVecError
+    // where the main function would panic or silently do something else
+    // ########################################################################
+
+    /// Returns the bit value at the desired index, or an error if the index
+    /// is out of bounds
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, BitVecError};
+    ///
+    /// let bv = BitVec::from(&[0x80]);
+    /// assert_eq!(bv.try_get_bit(0), Ok(true));
+    /// assert_eq!(bv.try_get_bit(8), Err(BitVecError::IndexOutOfBounds { index: 8, len: 8 }));
+    /// ```
+    pub fn try_get_bit(&self, index: usize) -> Result<bool, BitVecError> {
+        if index >= self.len {
+            return Err(BitVecError::IndexOutOfBounds { index, len: self.len });
+        }
+
+        Ok(self.get_bit(index))
+    }
+
+    /// Sets the bit at the desired index. Unlike set_bit, the vector is not
+    /// grown, and an index beyond the end is an error.
+    pub fn try_set_bit(&mut self, index: usize, value: bool) -> Result<(), BitVecError> {
+        if index >= self.len {
+            return Err(BitVecError::IndexOutOfBounds { index, len: self.len });
+        }
+
+        self.set_bit(index, value);
+        Ok(())
+    }
+
+    /// Removes and returns the last bit in the vector, or an error if the
+    /// vector is empty
+    pub fn try_pop_bit(&mut self) -> Result<bool, BitVecError> {
+        self.pop_bit().ok_or(BitVecError::Empty)
+    }
+
+    /// Removes and returns the last contiguous byte in the vector, as with
+    /// pop_byte, or an error if the vector is empty
+    pub fn try_pop_byte(&mut self) -> Result<u8, BitVecError> {
+        if self.len == 0 {
+            return Err(BitVecError::Empty);
+        }
+
+        self.pop_byte().ok_or(BitVecError::Empty)
+    }
+
+    /// Sequentially reads the next bit, or returns an error at the end of the
+    /// vector
+    pub fn try_read_bit_seq(&mut self) -> Result<u8, BitVecError> {
+        self.read_bit_seq().ok_or(BitVecError::NotEnoughBits { requested: 1, available: 0 })
+    }
+
+    /// Deprecated as the name does not say what is read
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the try_read_bit_seq function instead.")]
+    pub fn try_seq_read(&mut self) -> Result<u8, BitVecError> {
+        self.try_read_bit_seq()
+    }
+
+    /// Sequentially reads the next 8 bits as a byte, or returns an error if
+    /// fewer than 8 bits remain
+    pub fn try_read_byte(&mut self) -> Result<u8, BitVecError> {
+        let available = self.len.saturating_sub(self.get_read_position());
+
+        self.read_byte().ok_or(BitVecError::NotEnoughBits { requested: 8, available })
+    }
+
+    /// Sets the reading position in bits, or returns an error if it is beyond
+    /// the end of the vector
+    pub fn try_set_read_position(&mut self, bit_idx: usize) -> Result<(), BitVecError> {
+        if self.set_read_position(bit_idx) {
+            Ok(())
+        } else {
+            Err(BitVecError::IndexOutOfBounds { index: bit_idx, len: self.len })
+        }
+    }
+
+    /// Exports the BitVec data as a UTF-8 string, or returns an error if the
+    /// data is not valid UTF-8
+    pub fn try_export_utf8(&self) -> Result<String, BitVecError> {
+        let bytes: Vec<u8> = self.data.iter().map(|&byte| self.order.convert(byte)).collect();
+        Ok(core::str::from_utf8(&bytes)?.to_string())
+    }
+
     // ########################################################################
     // Display functions (for debugging)
     // ########################################################################
@@ -427,57 +921,27 @@ impl BitVec {
 
     /// Concatenates two (and only two) BitVecs, creating a new BitVec
     pub fn concat(&self, other: &BitVec) -> Self {
-        let bit_offset = self.len % 8;
+        let mut new_bitvec = BitVec { data: self.data.clone(), len: self.len, byte_idx: 0, bit_idx: 0, order: self.order, growth: self.growth };
+        new_bitvec.extend(other);
 
-        // If we happen to have perfect alignment
-        if bit_offset == 0 {
-            return BitVec {
-                data: [self.data.clone(), other.data.clone()].concat(),
-                len: self.len + other.len,
-                byte_idx: 0,
-                bit_idx: 0
-            };
-        } else {
-            let mut new_bitvec = BitVec { data: self.data.clone(), len: self.len.clone(), byte_idx: 0, bit_idx: 0 };
-            new_bitvec.extend(other);
-
-            return new_bitvec;
-        }
+        new_bitvec
     }
 
-    /// Extends one BitVec with another
+    /// Extends one BitVec with another, in stream order whatever the bit
+    /// order of either
     pub fn extend(&mut self, other: &BitVec) {
-        let bit_offset = self.len % 8;
+        self.truncate_bits(self.len);
+        self.append_bits(other);
+    }
 
-        // If we happen to have perfect alignment
-        if bit_offset == 0 {
-            self.data.extend(&other.data);
-            self.len += other.len;
-        } else {
-            let mut bits_remaining = other.len;
-            let mut byte_index = 0;
-            while bits_remaining >= 8 {
-                self.push_byte(other.data[byte_index]);
-                bits_remaining -= 8;
-                byte_index += 1;
-            }
-            // last or only byte
-            let byte = other.data.last().copied().unwrap();
-            let diff = 8 - bit_offset;
-            if let Some(last_byte) = self.data.last_mut() {
-                *last_byte |= byte >> bit_offset;
-                bits_remaining -= diff;
-                self.len += diff;
-            }
-            if bits_remaining > 0 {
-                self.data.push(byte << diff);
-                self.len += bits_remaining;
-            }
-        }
+    /// Deprecated as the name is misspelt
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the complement function instead.")]
+    pub fn compliment(&self) -> Self {
+        self.complement()
     }
 
     /// Returns a new BitVector containing an inversion of the original (aka NOT)
-    pub fn compliment(&self) -> Self {
+    pub fn complement(&self) -> Self {
         let mut inverted = Vec::new();
 
         for byte in self.data.iter() {
@@ -494,76 +958,203 @@ impl BitVec {
             len: self.len,
             byte_idx: 0,
             bit_idx: 0,
+            order: self.order,
+            growth: self.growth,
         }
     }
 
-    /// Returns a new BitVector containing the similarities between two BitVecs (aka AND)
+    /// Deprecated as the name is abbreviated
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the intersection function instead.")]
     pub fn intersec(&self, other: &BitVec) -> Self {
-        let mut data = Vec::new();
-        let len = cmp::min(self.len, other.len);
-
-        for (byte1, byte2) in self.data.iter().zip(other.data.iter()) {
-            data.push(byte1 & byte2);
-        }
+        self.intersection(other)
+    }
 
-        Self {
-            data,
-            len,
-            byte_idx: 0,
-            bit_idx: 0,
-        }
+    /// Deprecated as the name is abbreviated
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the nand function instead.")]
+    pub fn comp_int(&self, other: &BitVec) -> Self {
+        self.nand(other)
     }
 
-    /// Returns a new BitVector containing the symmetric difference between two BitVecs (aka XOR)
+    /// Deprecated as the name is abbreviated
+    #[deprecated(since = "0.1.1", note = "This function is deprecated, please use the symmetric_difference function instead.")]
     pub fn symm_diff(&self, other: &BitVec) -> Self {
-        let mut data = Vec::new();
-        let len = cmp::max(self.len, other.len);
+        self.symmetric_difference(other)
+    }
 
-        let data_len1 = self.data.len();
-        let data_len2 = other.data.len();
+    /// Returns a copy to combine with another vector, reading from the start
+    fn logic_copy(&self) -> Self {
+        let mut copy = self.clone();
+        copy.byte_idx = 0;
+        copy.bit_idx = 0;
+        copy
+    }
 
-        for (byte1, byte2) in self.data.iter().zip(other.data.iter()) {
-            data.push(byte1 ^ byte2);
-        }
+    /// Returns a new BitVector containing the similarities between two BitVecs (aka AND),
+    /// truncated to the shorter length
+    pub fn intersection(&self, other: &BitVec) -> Self {
+        let mut result = self.logic_copy();
+        let _ = result.and_with(other, LengthPolicy::Truncate);
+        result
+    }
 
-        if data_len1 <= data_len2 {
-            data.extend_from_slice(&other.data[(data_len2 - data_len1)..]);
-        } else {
-            data.extend_from_slice(&self.data[(data_len1 - data_len2)..]);
-        }
+    /// Returns a new BitVector containing the complement of the intersection of two BitVecs
+    /// (aka NAND), truncated to the shorter length
+    pub fn nand(&self, other: &BitVec) -> Self {
+        self.intersection(other).complement()
+    }
 
-        Self {
-            data,
-            len,
-            byte_idx: 0,
-            bit_idx: 0,
-        }
+    /// Returns a new BitVector containing the symmetric difference between two BitVecs (aka XOR),
+    /// extended to the longer length
+    pub fn symmetric_difference(&self, other: &BitVec) -> Self {
+        let mut result = self.logic_copy();
+        let _ = result.xor_with(other, LengthPolicy::Extend);
+        result
     }
 
-    /// Returns a new BitVector containing a union of two BitVecs (aka OR)
+    /// Returns a new BitVector containing a union of two BitVecs (aka OR), extended to the
+    /// longer length
     pub fn union(&self, other: &BitVec) -> Self {
-        let mut data = Vec::new();
-        let len = cmp::max(self.len, other.len);
+        let mut result = self.logic_copy();
+        let _ = result.or_with(other, LengthPolicy::Extend);
+        result
+    }
+}
 
-        let data_len1 = self.data.len();
-        let data_len2 = other.data.len();
+/// Counts the set bits in a run of bytes, a word at a time
+pub(crate) fn popcount(bytes: &[u8]) -> usize {
+    let mut words = bytes.chunks_exact(8);
+    let mut count = words.by_ref()
+        .map(|word| u64::from_ne_bytes(word.try_into().unwrap()).count_ones() as usize)
+        .sum();
+    count += words.remainder().iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum::<usize>();
+
+    count
+}
 
-        for (byte1, byte2) in self.data.iter().zip(other.data.iter()) {
-            data.push(byte1 | byte2);
-        }
+// ############################################################################
+// Standard traits
+// ############################################################################
 
-        if data_len1 <= data_len2 {
-            data.extend_from_slice(&other.data[(data_len2 - data_len1)..]);
-        } else {
-            data.extend_from_slice(&self.data[(data_len1 - data_len2)..]);
-        }
+impl Default for BitVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+/// Generate a new BitVec from an array or other bit stream
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let array_of_bytes = [24, 51, 67];
+/// let mut bundle = BitVec::from(&array_of_bytes);
+/// ```
+impl From<&[u8]> for BitVec {
+    fn from(data: &[u8]) -> Self {
+        BitVec::from(data.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for BitVec {
+    fn from(data: &[u8; N]) -> Self {
+        BitVec::from(data.to_vec())
+    }
+}
+
+impl From<&Vec<u8>> for BitVec {
+    fn from(data: &Vec<u8>) -> Self {
+        BitVec::from(data.clone())
+    }
+}
+
+impl From<Vec<u8>> for BitVec {
+    fn from(data: Vec<u8>) -> Self {
         Self {
+            len: data.len() * 8,
             data,
-            len,
             byte_idx: 0,
             bit_idx: 0,
+            order: BitOrder::Msb0,
+            growth: GrowthPolicy::Grow,
+        }
+    }
+}
+
+/// One bit per bool, in order
+impl From<&[bool]> for BitVec {
+    fn from(bits: &[bool]) -> Self {
+        let mut bv = BitVec::new();
+        for &bit in bits {
+            bv.push_bit(bit);
         }
+        bv
+    }
+}
+
+impl<const N: usize> From<&[bool; N]> for BitVec {
+    fn from(bits: &[bool; N]) -> Self {
+        BitVec::from(&bits[..])
+    }
+}
+
+impl From<Vec<bool>> for BitVec {
+    fn from(bits: Vec<bool>) -> Self {
+        BitVec::from(&bits[..])
+    }
+}
+
+/// The packed bytes, MSB first, with the padding bits of the last byte
+/// cleared, as `as_bytes`
+impl AsRef<[u8]> for BitVec {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+// Equality, hashing, and ordering only look at the bits up to the length, so
+// padding bits and the read position make no difference
+impl PartialEq for BitVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for BitVec {}
+
+/// Hashed as a BitStr of the same bits, so a `&BitStr` can look up a BitVec
+impl hash::Hash for BitVec {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl PartialOrd for BitVec {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Lexicographic order over the bits, with a prefix ordered before any
+/// longer vector that starts with it
+impl Ord for BitVec {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        let common = cmp::min(self.len, other.len);
+        let full_bytes = common / 8;
+        let tail = common % 8;
+
+        self.data[..full_bytes].cmp(&other.data[..full_bytes])
+            .then_with(|| {
+                if tail == 0 {
+                    return cmp::Ordering::Equal;
+                }
+                let mask = BitVec::mask_msb(tail);
+                (self.data[full_bytes] & mask).cmp(&(other.data[full_bytes] & mask))
+            })
+            .then(self.len.cmp(&other.len))
     }
 }
 
@@ -571,13 +1162,42 @@ impl BitVec {
 // Custom fmt
 // ############################################################################
 
+/// Number of bits shown at each end of the alternate summary format
+const SUMMARY_BITS: usize = 32;
+
+impl BitVec {
+    /// Writes the bits of a range as 0s and 1s
+    fn write_bits(&self, f: &mut fmt::Formatter<'_>, range: ops::Range<usize>) -> fmt::Result {
+        range.into_iter().try_for_each(|index| f.write_char(if self.get_bit(index) { '1' } else { '0' }))
+    }
+}
+
+/// Formats the bits as 0s and 1s. A precision limits the number of bits
+/// shown, as `{:.64}`, with `...` after a vector cut short. The alternate
+/// format, `{:#}`, gives a summary of the length, the number of set bits,
+/// and the first and last 32 bits.
 impl fmt::Display for BitVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "BitVec {{ data: {:?}, len: {}, byte_idx: {}, bit_idx: {} }}",
-            self.data, self.len, self.byte_idx, self.bit_idx
-        )
+        if f.alternate() {
+            write!(f, "BitVec {{ len: {}, ones: {}, ", self.len, self.count_ones())?;
+            if self.len <= 2 * SUMMARY_BITS {
+                f.write_str("bits: ")?;
+                self.write_bits(f, 0..self.len)?;
+            } else {
+                f.write_str("first: ")?;
+                self.write_bits(f, 0..SUMMARY_BITS)?;
+                f.write_str(", last: ")?;
+                self.write_bits(f, self.len - SUMMARY_BITS..self.len)?;
+            }
+            return f.write_str(" }");
+        }
+
+        let shown = f.precision().map_or(self.len, |max| max.min(self.len));
+        self.write_bits(f, 0..shown)?;
+        if shown < self.len {
+            f.write_str("...")?;
+        }
+        Ok(())
     }
 }
 
@@ -605,13 +1225,13 @@ impl ops:: BitAnd for BitVec {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        self.intersec(&rhs)
+        self.intersection(&rhs)
     }
 }
 
 impl ops::BitAndAssign for BitVec {
     fn bitand_assign(&mut self, rhs: Self) {
-        *self = self.intersec(&rhs)
+        let _ = self.and_with(&rhs, LengthPolicy::Truncate);
     }
 }
 impl ops::BitOr for BitVec {
@@ -624,7 +1244,7 @@ impl ops::BitOr for BitVec {
 
 impl ops::BitOrAssign for BitVec {
     fn bitor_assign(&mut self, rhs: Self) {
-        *self = self.union(&rhs)
+        let _ = self.or_with(&rhs, LengthPolicy::Extend);
     }
 }
 
@@ -632,31 +1252,23 @@ impl ops::BitXor for BitVec {
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        self.symm_diff(&rhs)
+        self.symmetric_difference(&rhs)
     }
 }
 
 impl ops::BitXorAssign for BitVec {
     fn bitxor_assign(&mut self, rhs: Self) {
-        *self = self.symm_diff(&rhs)
+        let _ = self.xor_with(&rhs, LengthPolicy::Extend);
     }
 }
 
-/// Returns the byte at index
+/// Returns the bit at index. Panics if the index is beyond the end of the
+/// vector. Bits are set through `at_mut`, and bytes read through `byte`.
 impl ops::Index<usize> for BitVec {
-    type Output = u8;
+    type Output = bool;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index]
-    }
-}
-
-/// Modifies the byte at index
-/// This can cause a mismatch with the bit length if extra 1s are added where
-/// they should not be
-impl ops:: IndexMut<usize> for BitVec {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.data[index]
+        if self.get_bit(index) { &true } else { &false }
     }
 }
 
@@ -665,7 +1277,7 @@ impl ops::Not for BitVec {
     type Output = Self;
 
     fn not(self) -> Self::Output {
-        self.compliment()
+        self.complement()
     }
 }
 
@@ -677,6 +1289,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn return_correct_bit() {
         let test_byte = BitVec::from(&[128, 1]);
         assert_eq!(test_byte.get_bit(0), true);
@@ -703,7 +1316,7 @@ mod tests {
         // test vector: 0b01101010 0b101xxxxx (106, 160)
         // len = 11
         // expected result = 0b01010101 (85)
-        let mut bv = BitVec { data: vec![106, 160], len: 11, byte_idx: 0, bit_idx: 0};
+        let mut bv = BitVec { data: vec![106, 160], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         assert_eq!(bv.pop_byte().unwrap(), 85u8);
         assert_eq!(bv.len, 3);
         // remainder = 0b011xxxxx (96)
@@ -711,9 +1324,10 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn push_bits() {
         // Start with a byte of 0b00000010 (2)
-        let mut bv = BitVec { data: vec![2], len: 7, byte_idx: 0, bit_idx: 0};
+        let mut bv = BitVec { data: vec![2], len: 7, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         bv.push_bit(true);
         assert_eq!(bv.get_bit(7), true);
         assert_eq!(bv.len, 8);
@@ -723,10 +1337,10 @@ mod tests {
 
     #[test]
     fn fill_vectors() {
-        let mut bv1 = BitVec { data: vec![0, 0], len: 12, byte_idx: 0, bit_idx: 0};
+        let mut bv1 = BitVec { data: vec![0, 0], len: 12, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         bv1.fill(true);
         assert_eq!(bv1.data[1], 240);
-        let mut bv2 = BitVec { data: vec![0, 0], len: 16, byte_idx: 0, bit_idx: 0};
+        let mut bv2 = BitVec { data: vec![0, 0], len: 16, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         bv2.fill(true);
         assert_eq!(bv2.data[1], 255);
     }
@@ -734,9 +1348,9 @@ mod tests {
     #[test]
     fn extend_bitvec() {
         // bv1 = 0b00001xxx
-        let mut bv1 = BitVec { data: vec![0x08], len: 5, byte_idx: 0, bit_idx: 0};
+        let mut bv1 = BitVec { data: vec![0x08], len: 5, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b1011xxxx
-        let bv2 = BitVec { data: vec![0xB0], len: 4, byte_idx: 0, bit_idx: 0};
+        let bv2 = BitVec { data: vec![0xB0], len: 4, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv1 = 0b00001101 1xxxxxxx
         bv1 += bv2;
         assert_eq!(bv1.data[0], 13);
@@ -747,9 +1361,9 @@ mod tests {
     #[test]
     fn new_from_add() {
         // bv1 = 0b00001xxx
-        let bv1 = BitVec { data: vec![0x08], len: 5, byte_idx: 0, bit_idx: 0};
+        let bv1 = BitVec { data: vec![0x08], len: 5, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b1011xxxx
-        let bv2 = BitVec { data: vec![0xB0], len: 4, byte_idx: 0, bit_idx: 0};
+        let bv2 = BitVec { data: vec![0xB0], len: 4, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv1 = 0b00001101 1xxxxxxx
         let bv3 = bv1 + bv2;
         assert_eq!(bv3.data[0], 13);
@@ -757,10 +1371,121 @@ mod tests {
         assert_eq!(bv3.len, 9);
     }
 
+    #[test]
+    fn count_bits() {
+        // bv = 0b01101011 * 9, 0b101xxxxx with dirty padding
+        let mut data = vec![0x6B; 9];
+        data.push(0xBF);
+        let bv = BitVec { data, len: 75, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.count_ones(), 9 * 5 + 2);
+        assert_eq!(bv.count_zeros(), 75 - 47);
+        assert!(bv.any());
+        assert!(!bv.all());
+
+        let full = BitVec { data: vec![0xFF, 0xE0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert!(full.all());
+        let empty = BitVec::new();
+        assert!(!empty.any());
+        assert!(empty.all());
+        assert_eq!(empty.count_ones(), 0);
+    }
+
+    #[test]
+    fn next_set_and_clear_bits() {
+        // bv = 0b11111111 0b00000001 0b1110xxxx
+        let bv = BitVec { data: vec![0xFF, 0x01, 0xE0], len: 20, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.next_set_bit(0), Some(0));
+        assert_eq!(bv.next_set_bit(8), Some(15));
+        assert_eq!(bv.next_set_bit(9), Some(15));
+        assert_eq!(bv.next_set_bit(16), Some(16));
+        assert_eq!(bv.next_set_bit(19), None);
+        assert_eq!(bv.next_set_bit(20), None);
+        assert_eq!(bv.next_clear_bit(0), Some(8));
+        assert_eq!(bv.next_clear_bit(15), Some(19));
+        // Padding bits are never reported as clear
+        let full = BitVec { data: vec![0xF0], len: 4, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(full.next_clear_bit(0), None);
+    }
+
+    #[test]
+    fn display_formats() {
+        let bv = BitVec::from_bit_str("1011 0010 1").unwrap();
+        assert_eq!(format!("{}", bv), "101100101");
+        assert_eq!(format!("{:.4}", bv), "1011...");
+        assert_eq!(format!("{:.20}", bv), "101100101");
+        assert_eq!(format!("{:#}", bv), "BitVec { len: 9, ones: 5, bits: 101100101 }");
+        assert_eq!(format!("{}", BitVec::new()), "");
+
+        let mut long = BitVec::repeat_bit(false, 1000);
+        long.set_range(..4, true);
+        long.set_range(999.., true);
+        assert_eq!(
+            format!("{:#}", long),
+            format!("BitVec {{ len: 1000, ones: 5, first: 1111{}, last: {}1 }}", "0".repeat(28), "0".repeat(31))
+        );
+    }
+
+    #[test]
+    fn bytes_as_ref() {
+        fn checksum(bytes: impl AsRef<[u8]>) -> u32 {
+            bytes.as_ref().iter().map(|&byte| byte as u32).sum()
+        }
+
+        let bv = BitVec::from_bit_str("1111 1111 1111").unwrap();
+        assert_eq!(AsRef::<[u8]>::as_ref(&bv), &[0xFF, 0xF0]);
+        assert_eq!(checksum(&bv), 0x1EF);
+    }
+
+    #[test]
+    fn capacity_management() {
+        let mut bv = BitVec::with_capacity(20);
+        assert_eq!(bv.len_bits(), 0);
+        assert!(bv.capacity_bits() >= 24);
+        for _ in 0..20 {
+            bv.push_bit(true);
+        }
+        assert_eq!(bv.len_bits(), 20);
+
+        // Reserving counts from the partial last byte
+        bv.reserve_exact(4);
+        assert!(bv.capacity_bits() >= 24);
+        bv.reserve_exact(5);
+        assert!(bv.capacity_bits() >= 32);
+        bv.reserve(1000);
+        assert!(bv.capacity_bits() >= 1020);
+        bv.shrink_to_fit();
+        assert_eq!(bv.capacity_bits(), 24);
+        assert_eq!(bv.count_ones(), 20);
+    }
+
+    #[test]
+    fn fallible_variants() {
+        // bv = 0b10110xxx
+        let mut bv = BitVec { data: vec![0xB0], len: 5, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.try_get_bit(2), Ok(true));
+        assert_eq!(bv.try_get_bit(5), Err(BitVecError::IndexOutOfBounds { index: 5, len: 5 }));
+        assert_eq!(bv.try_set_bit(7, true), Err(BitVecError::IndexOutOfBounds { index: 7, len: 5 }));
+        assert_eq!(bv.data, vec![0xB0]);
+        assert_eq!(bv.try_read_byte(), Err(BitVecError::NotEnoughBits { requested: 8, available: 5 }));
+        for bit in [1, 0, 1, 1, 0] {
+            assert_eq!(bv.try_read_bit_seq(), Ok(bit));
+        }
+        // Padding bits are not read
+        assert!(bv.try_read_bit_seq().is_err());
+        assert!(bv.try_set_read_position(5).is_err());
+        assert_eq!(bv.try_pop_bit(), Ok(false));
+
+        let mut empty = BitVec::new();
+        assert_eq!(empty.try_pop_byte(), Err(BitVecError::Empty));
+        assert_eq!(empty.try_pop_bit(), Err(BitVecError::Empty));
+        assert!(matches!(BitVec::from(&[0xFF]).try_export_utf8(), Err(BitVecError::InvalidUtf8(_))));
+        assert_eq!(BitVec::from(b"ok").try_export_utf8(), Ok("ok".to_string()));
+    }
+
     #[test]
     fn read_offset_byte() {
         // bv = 0b11010111 0b01011001
-        let mut bv = BitVec { data: vec![0xD7,0x59], len: 16, byte_idx: 0, bit_idx: 3};
+        let mut bv = BitVec { data: vec![0xD7,0x59], len: 16, byte_idx: 0, bit_idx: 3, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // read byte = 0b10111010
         assert_eq!(bv.read_byte().unwrap(), 0xBA);
         assert_eq!(bv.read_byte(), None);
@@ -769,21 +1494,21 @@ mod tests {
     #[test]
     fn inverted_bytes() {
         // bv1 = 0b01101101
-        let bv1 = BitVec { data: vec![0x6D], len: 8, byte_idx: 0, bit_idx: 0};
+        let bv1 = BitVec { data: vec![0x6D], len: 8, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b10011xxx
-        let bv2 = BitVec { data: vec![0x98], len: 5, byte_idx: 0, bit_idx: 0};
-        assert_eq!(bv1.compliment().data[0], 0x92);
-        assert_eq!(bv2.compliment().data[0], 0x60);
+        let bv2 = BitVec { data: vec![0x98], len: 5, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv1.complement().data[0], 0x92);
+        assert_eq!(bv2.complement().data[0], 0x60);
     }
 
     #[test]
     fn intersec_two_vecs() {
         // bv1 = 0b01101011 0b101xxxxx len = 11
-        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0};
+        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b1110011x len = 7
-        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0};
+        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv1 & bv2 = 0b01100010
-        // let bv3 = bv1.intersec(&bv2);
+        // let bv3 = bv1.intersection(&bv2);
         let bv3 = bv1 & bv2;
         assert_eq!(bv3.data[0], 0x62);
         assert_eq!(bv3.len, 7);
@@ -792,21 +1517,21 @@ mod tests {
     #[test]
     fn symm_diff_two_vecs() {
         // bv1 = 0b01101011 0b101xxxxx len = 11
-        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0};
+        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b1110011x len = 7
-        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0};
+        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv3 = 0b10010011 len = 8
-        let bv3 = BitVec { data: vec![0x93], len: 8, byte_idx: 0, bit_idx: 0};
+        let bv3 = BitVec { data: vec![0x93], len: 8, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv1 ^ bv2 = 0b10001101 0b10100000
-        let bv4 = bv1.symm_diff(&bv2);
+        let bv4 = bv1.symmetric_difference(&bv2);
         assert_eq!(bv4.data[0], 0x8D);
         assert_eq!(bv4.data[1], 0xA0);
         assert_eq!(bv4.len, 11);
-        let bv5 = bv2.symm_diff(&bv1);
+        let bv5 = bv2.symmetric_difference(&bv1);
         assert_eq!(bv5.data[0], 0x8D);
         assert_eq!(bv5.data[1], 0xA0);
         // bv2 ^ bv3 = 0b01110101
-        // let bv6 = bv2.symm_diff(&bv3);
+        // let bv6 = bv2.symmetric_difference(&bv3);
         let bv6 = bv2 ^ bv3;
         assert_eq!(bv6.data[0], 0x75);
     }
@@ -814,11 +1539,11 @@ mod tests {
     #[test]
     fn union_two_vecs() {
         // bv1 = 0b01101011 0b101xxxxx len = 11
-        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0};
+        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv2 = 0b1110011x len = 7
-        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0};
+        let bv2 = BitVec { data: vec![0xE6], len: 7, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv3 = 0b10010011 len = 8
-        let bv3 = BitVec { data: vec![0x93], len: 8, byte_idx: 0, bit_idx: 0};
+        let bv3 = BitVec { data: vec![0x93], len: 8, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
         // bv1 ^ bv2 = 0b11101111 0b10100000
         let bv4 = bv1.union(&bv2);
         assert_eq!(bv4.data[0], 0xEF);
@@ -829,4 +1554,84 @@ mod tests {
         let bv5 = bv2 | bv3;
         assert_eq!(bv5.data[0], 0xF7);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn standard_traits() {
+        use std::collections::HashSet;
+
+        // Same bits, different padding and read position
+        let bv1 = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let mut bv2 = BitVec { data: vec![0x6B, 0xBF], len: 11, byte_idx: 1, bit_idx: 3, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv1, bv2);
+        assert_eq!(bv1.clone(), bv1);
+        let set: HashSet<BitVec> = [bv1.clone(), bv2.clone()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        bv2.set_bit(10, false);
+        assert_ne!(bv1, bv2);
+        assert!(bv2 < bv1);
+        // A prefix comes first
+        let prefix = BitVec { data: vec![0x6B], len: 8, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert!(prefix < bv1);
+        assert!(BitVec::default() < prefix);
+        assert_eq!(BitVec::default(), BitVec::new());
+    }
+
+    #[test]
+    fn bool_conversions() {
+        let bools = vec![true, false, true, true, false, false, false, false, true];
+        let bv = BitVec::from(bools.clone());
+        assert_eq!(bv.len_bits(), 9);
+        assert_eq!(bv.data, vec![0xB0, 0x80]);
+        assert_eq!(bv.to_bool_vec(), bools);
+        assert_eq!(BitVec::from(&bools[..]), bv);
+        assert_eq!(BitVec::from(&[true, false]).to_bool_vec(), vec![true, false]);
+
+        let bytes: &[u8] = &[0xB0, 0x80];
+        assert_eq!(BitVec::from(bytes).len_bits(), 16);
+        assert_eq!(BitVec::from(vec![0xB0, 0x80]), BitVec::from(&[0xB0, 0x80]));
+    }
+
+    #[test]
+    fn leading_and_trailing_runs() {
+        // The padding bits of the last byte are not counted
+        let mut bv = BitVec::from(&[0x00, 0x00, 0x10, 0xFF]);
+        bv.truncate(28);
+        assert_eq!(bv.first_one(), Some(19));
+        assert_eq!(bv.last_one(), Some(27));
+        assert_eq!(bv.leading_zeros(), 19);
+        assert_eq!(bv.trailing_zeros(), 0);
+        assert_eq!(bv.trailing_ones(), 4);
+        assert_eq!(bv.leading_ones(), 0);
+        assert_eq!(bv.last_zero(), Some(23));
+
+        let ones = BitVec::from(&[0xFF, 0xE0]);
+        assert_eq!(ones.first_zero(), Some(11));
+        assert_eq!(ones.leading_ones(), 11);
+        assert_eq!(ones.last_zero(), Some(15));
+
+        let empty = BitVec::new();
+        assert_eq!(empty.last_one(), None);
+        assert_eq!(empty.trailing_zeros(), 0);
+        let zeros = BitVec::from_bit_str("000 000").unwrap();
+        assert_eq!(zeros.last_one(), None);
+        assert_eq!(zeros.trailing_zeros(), 6);
+        assert_eq!(zeros.trailing_ones(), 0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_aliases() {
+        let a = BitVec::from_bytes(&[0xF0, 0x0F]);
+        let b = BitVec::from_bit_str("1010 1").unwrap();
+        assert_eq!(a.compliment(), a.complement());
+        assert_eq!(a.intersec(&b), a.intersection(&b));
+        assert_eq!(a.comp_int(&b), a.nand(&b));
+        assert_eq!(a.nand(&b).to_bit_string(), "01011");
+        assert_eq!(a.symm_diff(&b), a.symmetric_difference(&b));
+
+        let mut c = b.clone();
+        assert_eq!((c.seq_read(), c.try_seq_read()), (Some(1), Ok(0)));
+        assert_eq!(c.read_bit_seq(), Some(1));
+    }
+}
@@ -0,0 +1,211 @@
+//! ## Linear algebra over GF(2)
+//! Treating a BitGrid as a matrix over GF(2), with a row of the matrix for
+//! each row of the grid. Gauss-Jordan elimination works on the packed rows,
+//! so adding one row to another is an XOR of whole words, which keeps rank,
+//! solving, and inversion fast for the large matrices of coding theory and
+//! cryptanalysis.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{BitGrid, BitVec};
+
+/// Bits in each word of a row
+const WORD_BITS: usize = 64;
+
+/// XORs one row into another
+fn xor_row(rows: &mut [Vec<u64>], target: usize, source: usize) {
+    let (target_row, source_row) = if target < source {
+        let (low, high) = rows.split_at_mut(source);
+        (&mut low[target], &high[0])
+    } else {
+        let (low, high) = rows.split_at_mut(target);
+        (&mut high[0], &low[source])
+    };
+    for (word, source_word) in target_row.iter_mut().zip(source_row) {
+        *word ^= source_word;
+    }
+}
+
+/// Reduces the rows to reduced row echelon form, making the same row swaps
+/// and additions to the extra rows. Returns the pivot column of each of the
+/// leading rows, so the rank is the number of pivots.
+fn eliminate(rows: &mut [Vec<u64>], width: usize, extra: &mut [Vec<u64>]) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for column in 0..width {
+        let rank = pivots.len();
+        let (index, mask) = (column / WORD_BITS, 1 << (WORD_BITS - 1 - column % WORD_BITS));
+        let Some(found) = (rank..rows.len()).find(|&row| rows[row][index] & mask != 0) else {
+            continue;
+        };
+
+        rows.swap(rank, found);
+        extra.swap(rank, found);
+        for row in 0..rows.len() {
+            if row != rank && rows[row][index] & mask != 0 {
+                xor_row(rows, row, rank);
+                xor_row(extra, row, rank);
+            }
+        }
+        pivots.push(column);
+    }
+    pivots
+}
+
+impl BitGrid {
+    /// Copies the packed rows of the grid
+    fn matrix_rows(&self) -> Vec<Vec<u64>> {
+        (0..self.height()).map(|y| self.row_words(y).to_vec()).collect()
+    }
+
+    /// Returns the rank of the grid as a matrix over GF(2)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// // The third row is the XOR of the first two
+    /// let grid = BitGrid::from_cells(3, 3, [(0, 0), (0, 1), (1, 1), (1, 2), (2, 0), (2, 2)]);
+    /// assert_eq!(grid.rank_gf2(), 2);
+    /// ```
+    pub fn rank_gf2(&self) -> usize {
+        eliminate(&mut self.matrix_rows(), self.width(), &mut vec![Vec::new(); self.height()]).len()
+    }
+
+    /// Solves `A x = b` over GF(2), where `A` is the grid and `b` has a bit
+    /// for each row. Returns a solution with a bit for each column, taking 0
+    /// for any free variables, or None if there is no solution. Panics if
+    /// `b` does not have a bit for each row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitGrid, BitVec};
+    ///
+    /// // x0 ^ x1 = 1, x1 = 1
+    /// let grid = BitGrid::from_cells(2, 2, [(0, 0), (0, 1), (1, 1)]);
+    /// let b = BitVec::from_bit_str("11").unwrap();
+    /// assert_eq!(grid.solve_gf2(&b).unwrap().to_bit_string(), "01");
+    /// ```
+    pub fn solve_gf2(&self, rhs: &BitVec) -> Option<BitVec> {
+        if rhs.len_bits() != self.height() {
+            panic!("BitVec: right hand side length does not match the rows")
+        }
+
+        let mut rows = self.matrix_rows();
+        let mut values: Vec<Vec<u64>> = (0..self.height()).map(|y| vec![rhs.get_bit(y) as u64]).collect();
+        let pivots = eliminate(&mut rows, self.width(), &mut values);
+        if values[pivots.len()..].iter().any(|value| value[0] != 0) {
+            return None;
+        }
+
+        let mut solution = BitVec::new();
+        solution.resize(self.width(), false);
+        for (row, &column) in pivots.iter().enumerate() {
+            solution.set_bit(column, values[row][0] != 0);
+        }
+        Some(solution)
+    }
+
+    /// Returns the inverse of the grid as a matrix over GF(2), or None if
+    /// the grid is not square or is singular
+    pub fn invert_gf2(&self) -> Option<BitGrid> {
+        let size = self.width();
+        if self.height() != size {
+            return None;
+        }
+
+        let mut inverse = BitGrid::new(size, size);
+        for y in 0..size {
+            inverse.set(y, y, true);
+        }
+        let mut rows = self.matrix_rows();
+        let mut extra = inverse.matrix_rows();
+        if eliminate(&mut rows, size, &mut extra).len() < size {
+            return None;
+        }
+
+        for (y, row) in extra.iter().enumerate() {
+            inverse.row_words_mut(y).copy_from_slice(row);
+        }
+        Some(inverse)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Multiplies two grids as matrices over GF(2), cell by cell
+    fn multiply(a: &BitGrid, b: &BitGrid) -> BitGrid {
+        let mut product = BitGrid::new(b.width(), a.height());
+        for y in 0..a.height() {
+            for x in 0..b.width() {
+                let bit = (0..a.width()).fold(false, |bit, k| bit ^ (a.get(k, y) && b.get(x, k)));
+                product.set(x, y, bit);
+            }
+        }
+        product
+    }
+
+    /// A 70x70 matrix with a pseudo-random pattern and a set diagonal
+    fn wide_matrix() -> BitGrid {
+        let mut grid = BitGrid::new(70, 70);
+        let mut state = 0x2545_F491_u32;
+        for y in 0..70 {
+            for x in 0..70 {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                grid.set(x, y, x == y || x > y && state & 1 == 1);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn rank_of_singular_and_full_matrices() {
+        assert_eq!(wide_matrix().rank_gf2(), 70);
+        assert_eq!(BitGrid::new(5, 3).rank_gf2(), 0);
+
+        let mut grid = wide_matrix();
+        for x in 0..70 {
+            let bit = grid.get(x, 3) ^ grid.get(x, 60);
+            grid.set(x, 65, bit);
+        }
+        assert_eq!(grid.rank_gf2(), 69);
+    }
+
+    #[test]
+    fn solve_across_words() {
+        let grid = wide_matrix();
+        let mut x = BitVec::new();
+        for index in 0..70 {
+            x.push_bit(index % 7 == 2 || index == 69);
+        }
+        let column = BitGrid::from_bitvec(&x, 1);
+        let b = multiply(&grid, &column).to_bitvec();
+        assert_eq!(grid.solve_gf2(&b), Some(x));
+
+        // x0 = 1 and x0 = 0 together have no solution
+        let grid = BitGrid::from_cells(2, 1, [(0, 0), (1, 0)]);
+        assert_eq!(grid.solve_gf2(&BitVec::from_bit_str("10").unwrap()), None);
+        assert_eq!(grid.solve_gf2(&BitVec::from_bit_str("11").unwrap()).unwrap().to_bit_string(), "1");
+    }
+
+    #[test]
+    fn inverse_round_trip() {
+        let grid = wide_matrix();
+        let inverse = grid.invert_gf2().unwrap();
+        let product = multiply(&grid, &inverse);
+        for y in 0..70 {
+            assert_eq!(product.row_runs(y), vec![y..y + 1]);
+        }
+
+        assert_eq!(BitGrid::new(3, 2).invert_gf2(), None);
+        assert_eq!(BitGrid::from_cells(2, 2, [(0, 0), (1, 0)]).invert_gf2(), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! ## In-place logical operations
+//! AND, OR, and XOR of one BitVec into another, with an explicit policy for
+//! vectors of different lengths. The bits past the end of the shorter
+//! vector are treated as 0s, so the policy only decides the length of the
+//! result. The assignment operators are built on these.
+
+use crate::{BitVec, BitVecError};
+
+/// How the length of the result is chosen when two vectors differ in length
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LengthPolicy {
+    /// Truncate the result to the shorter length
+    Truncate,
+    /// Extend the result to the longer length, filling with 0s
+    Extend,
+    /// Reject vectors of different lengths
+    Reject,
+}
+
+impl BitVec {
+    /// Resizes the vector to combine with another according to a policy
+    pub(crate) fn resize_for(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        let len = match policy {
+            LengthPolicy::Truncate => self.len.min(other.len),
+            LengthPolicy::Extend => self.len.max(other.len),
+            LengthPolicy::Reject if self.len != other.len => {
+                return Err(BitVecError::LengthMismatch { expected: self.len, found: other.len });
+            }
+            LengthPolicy::Reject => self.len,
+        };
+        self.resize(len, false);
+        Ok(())
+    }
+
+    /// Resizes the vector according to a policy, then combines it with
+    /// another vector byte by byte, with 0s past the end of the other vector
+    fn combine_with(&mut self, other: &BitVec, policy: LengthPolicy, op: impl Fn(u8, u8) -> u8) -> Result<(), BitVecError> {
+        self.resize_for(other, policy)?;
+        for (index, byte) in self.data.iter_mut().enumerate() {
+            *byte = op(*byte, other.data.get(index).copied().unwrap_or(0));
+        }
+        self.clear_padding();
+        Ok(())
+    }
+
+    /// ANDs another vector into this one, in place. Returns an error only if
+    /// the lengths differ and the policy is `Reject`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, LengthPolicy};
+    ///
+    /// let mut bv = BitVec::from_bit_str("1111 1111 11").unwrap();
+    /// let mask = BitVec::from_bit_str("1010").unwrap();
+    /// bv.and_with(&mask, LengthPolicy::Extend).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "1010000000");
+    /// assert!(bv.and_with(&mask, LengthPolicy::Reject).is_err());
+    /// ```
+    pub fn and_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.combine_with(other, policy, |a, b| a & b)
+    }
+
+    /// ORs another vector into this one, in place. Returns an error only if
+    /// the lengths differ and the policy is `Reject`.
+    pub fn or_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.combine_with(other, policy, |a, b| a | b)
+    }
+
+    /// XORs another vector into this one, in place. Returns an error only if
+    /// the lengths differ and the policy is `Reject`.
+    pub fn xor_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.combine_with(other, policy, |a, b| a ^ b)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitvec(bits: &str) -> BitVec {
+        BitVec::from_bit_str(bits).unwrap()
+    }
+
+    #[test]
+    fn length_policies() {
+        let long = bitvec("1100 1010 1111 0000 1");
+        let short = bitvec("1010 11");
+
+        let mut bv = long.clone();
+        bv.and_with(&short, LengthPolicy::Truncate).unwrap();
+        assert_eq!(bv.to_bit_string(), "100010");
+        let mut bv = long.clone();
+        bv.and_with(&short, LengthPolicy::Extend).unwrap();
+        assert_eq!(bv.to_bit_string(), "10001000000000000");
+
+        let mut bv = short.clone();
+        bv.or_with(&long, LengthPolicy::Extend).unwrap();
+        assert_eq!(bv.to_bit_string(), "11101110111100001");
+        let mut bv = short.clone();
+        bv.xor_with(&long, LengthPolicy::Truncate).unwrap();
+        assert_eq!(bv.to_bit_string(), "011001");
+
+        let mut bv = short.clone();
+        assert_eq!(
+            bv.xor_with(&long, LengthPolicy::Reject),
+            Err(BitVecError::LengthMismatch { expected: 6, found: 17 })
+        );
+        assert_eq!(bv, short);
+    }
+
+    #[test]
+    fn long_tails_are_kept() {
+        // A tail of several bytes past the end of the shorter vector
+        let long = BitVec::from(&[0x00, 0x11, 0x22, 0x33]);
+        let short = BitVec::from(&[0xFF]);
+        let mut bv = short.clone();
+        bv.or_with(&long, LengthPolicy::Extend).unwrap();
+        assert_eq!(bv.as_bytes(), &[0xFF, 0x11, 0x22, 0x33]);
+        let mut bv = long.clone();
+        bv.xor_with(&short, LengthPolicy::Extend).unwrap();
+        assert_eq!(bv.as_bytes(), &[0xFF, 0x11, 0x22, 0x33]);
+    }
+}
@@ -0,0 +1,156 @@
+//! ## LOUDS trees
+//! The level-order unary degree sequence, a succinct encoding of an ordinal
+//! tree such as a trie in about 2 bits a node. Nodes are numbered from 0 at
+//! the root in breadth-first order, and each is written as its degree in
+//! unary, that many 1s then a 0, after a `10` for a super-root above the
+//! root. Node `v` is then the `v`th 1, and its children follow the `v`th 0,
+//! so the tree is navigated with rank and select alone.
+
+use crate::{BitVec, RankSelect};
+
+/// An ordinal tree in LOUDS form
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::Louds;
+///
+/// //     0
+/// //    / \
+/// //   1   2
+/// //  / \   \
+/// // 3   4   5
+/// let tree = Louds::from_tree([2, 2, 1, 0, 0, 0]);
+/// assert_eq!(tree.bits().to_bit_string(), "1011011010000");
+/// assert_eq!(tree.first_child(1), Some(3));
+/// assert_eq!(tree.next_sibling(3), Some(4));
+/// assert_eq!(tree.next_sibling(4), None);
+/// assert_eq!(tree.parent(5), Some(2));
+/// ```
+pub struct Louds {
+    rank: RankSelect,
+}
+
+impl Louds {
+    /// Builds a tree from the degree of each node, in breadth-first order.
+    /// The degrees are not checked to describe a tree: the children of all
+    /// the nodes should add up to one less than the number of nodes.
+    pub fn from_tree<I: IntoIterator<Item = usize>>(degrees: I) -> Self {
+        let mut bits = BitVec::from_bit_str("10").unwrap();
+        for degree in degrees {
+            bits.resize(bits.len + degree, true);
+            bits.push_bit(false);
+        }
+        Self::from_bits(bits)
+    }
+
+    /// Wraps bits already in LOUDS form, starting with the `10` of the
+    /// super-root
+    pub fn from_bits(bits: BitVec) -> Self {
+        Self { rank: RankSelect::new(bits) }
+    }
+
+    /// Returns the bits of the tree
+    pub fn bits(&self) -> &BitVec {
+        self.rank.bits()
+    }
+
+    /// Unwraps the tree, returning its bits
+    pub fn into_inner(self) -> BitVec {
+        self.rank.into_inner()
+    }
+
+    /// Get the number of nodes
+    pub fn num_nodes(&self) -> usize {
+        self.rank.count_ones()
+    }
+
+    /// Panics unless the node is in the tree
+    fn check(&self, node: usize) {
+        if node >= self.num_nodes() {
+            panic!("BitVec: node {} out of bounds for {} nodes", node, self.num_nodes())
+        }
+    }
+
+    /// Returns the index where a node's children are listed
+    fn children_start(&self, node: usize) -> usize {
+        self.rank.select0(node).map_or(self.bits().len, |zero| zero + 1)
+    }
+
+    /// Get the number of children of a node. Panics if the node is not in
+    /// the tree.
+    pub fn degree(&self, node: usize) -> usize {
+        self.check(node);
+        let start = self.children_start(node);
+        let end = self.rank.select0(node + 1).unwrap_or(self.bits().len);
+        end.saturating_sub(start)
+    }
+
+    /// Returns the first child of a node, or None if it is a leaf. Panics if
+    /// the node is not in the tree.
+    pub fn first_child(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        let start = self.children_start(node);
+        (start < self.bits().len && self.bits().get_bit(start)).then(|| self.rank.rank1(start))
+    }
+
+    /// Returns the next child of the same parent, or None if the node is the
+    /// last. Panics if the node is not in the tree.
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        let next = self.rank.select1(node).unwrap() + 1;
+        (next < self.bits().len && self.bits().get_bit(next)).then_some(node + 1)
+    }
+
+    /// Returns the parent of a node, or None for the root. Panics if the
+    /// node is not in the tree.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        self.rank.rank0(self.rank.select1(node).unwrap()).checked_sub(1)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn navigation() {
+        // A root with 3 children, the middle one with 2 of its own
+        let tree = Louds::from_tree([3, 0, 2, 0, 0, 0]);
+        assert_eq!(tree.num_nodes(), 6);
+        assert_eq!((0..6).map(|node| tree.degree(node)).collect::<Vec<_>>(), vec![3, 0, 2, 0, 0, 0]);
+        assert_eq!((0..6).map(|node| tree.parent(node)).collect::<Vec<_>>(), vec![None, Some(0), Some(0), Some(0), Some(2), Some(2)]);
+        assert_eq!((0..6).map(|node| tree.first_child(node)).collect::<Vec<_>>(), vec![Some(1), None, Some(4), None, None, None]);
+        assert_eq!((0..6).map(|node| tree.next_sibling(node)).collect::<Vec<_>>(), vec![None, Some(2), Some(3), None, Some(5), None]);
+
+        let bits = tree.into_inner();
+        assert_eq!(Louds::from_bits(bits.clone()).bits(), &bits);
+    }
+
+    #[test]
+    fn deep_and_single() {
+        // A path of 200 nodes crosses many blocks of the index
+        let path = Louds::from_tree((0..200).map(|node| (node < 199) as usize));
+        for node in 0..199 {
+            assert_eq!(path.first_child(node), Some(node + 1));
+            assert_eq!(path.parent(node + 1), Some(node));
+            assert_eq!(path.next_sibling(node + 1), None);
+        }
+        assert_eq!(path.first_child(199), None);
+
+        let root = Louds::from_tree([0]);
+        assert_eq!(root.num_nodes(), 1);
+        assert_eq!((root.degree(0), root.first_child(0), root.parent(0)), (0, None, None));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn node_out_of_bounds() {
+        Louds::from_tree([1, 0]).parent(2);
+    }
+}
@@ -0,0 +1,267 @@
+//! ## Selection masks
+//! Using a BitVec as a mask over a parallel slice of user data, where bit
+//! `i` says whether element `i` is selected.
+
+use alloc::{vec, vec::Vec};
+use crate::BitVec;
+
+impl BitVec {
+    /// Evaluates a predicate over every index `0..len`, returning bitmaps of
+    /// the indices where it holds and where it does not
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0x00]);
+    /// let (even, odd) = bv.partition_indices(|i| i % 2 == 0);
+    /// assert_eq!(even.as_binary(), "10101010");
+    /// assert_eq!(odd.as_binary(), "01010101");
+    /// ```
+    pub fn partition_indices<F: FnMut(usize) -> bool>(&self, mut pred: F) -> (BitVec, BitVec) {
+        let mut matching = BitVec::new();
+        let mut rest = BitVec::new();
+        for index in 0..self.len {
+            let value = pred(index);
+            matching.push_bit(value);
+            rest.push_bit(!value);
+        }
+
+        (matching, rest)
+    }
+
+    /// Returns references to the elements of a parallel slice whose bits are
+    /// set. Bits past the end of the slice are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01100000
+    /// let bv = BitVec::from(&[0x60]);
+    /// let names = ["a", "b", "c", "d"];
+    /// assert_eq!(bv.apply_mask_to_slice(&names), vec![&"b", &"c"]);
+    /// ```
+    pub fn apply_mask_to_slice<'a, T>(&self, items: &'a [T]) -> Vec<&'a T> {
+        self.iter_ones().map_while(|index| items.get(index)).collect()
+    }
+
+    /// Copies out the elements of a parallel slice whose bits are set. Bits
+    /// past the end of the slice are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b10010000
+    /// let bv = BitVec::from(&[0x90]);
+    /// assert_eq!(bv.gather_slice(&[5, 6, 7, 8]), vec![5, 8]);
+    /// ```
+    pub fn gather_slice<T: Copy>(&self, src: &[T]) -> Vec<T> {
+        self.iter_ones().map_while(|index| src.get(index).copied()).collect()
+    }
+
+    /// Writes values, in order, into the elements of a parallel slice whose
+    /// bits are set. Stops at whichever runs out first of the set bits, the
+    /// values, or the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b10010000
+    /// let bv = BitVec::from(&[0x90]);
+    /// let mut dst = [0; 4];
+    /// bv.scatter_slice(&mut dst, &[1, 2]);
+    /// assert_eq!(dst, [1, 0, 0, 2]);
+    /// ```
+    pub fn scatter_slice<T: Copy>(&self, dst: &mut [T], values: &[T]) {
+        let len = dst.len();
+        for (index, &value) in self.iter_ones().take_while(|&index| index < len).zip(values) {
+            dst[index] = value;
+        }
+    }
+
+    /// Returns the indices of the clear bits followed by the indices of the
+    /// set bits, each in ascending order, which is a stable sort of the
+    /// indices by bit value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01101000
+    /// let bv = BitVec::from(&[0x68]);
+    /// assert_eq!(bv.argsort(), vec![0, 3, 5, 6, 7, 1, 2, 4]);
+    /// ```
+    pub fn argsort(&self) -> Vec<usize> {
+        let mut indices = Vec::with_capacity(self.len);
+        indices.extend(self.iter_zeros());
+        indices.extend(self.iter_ones());
+        indices
+    }
+
+    /// Reorders a parallel slice in place so the elements at clear bits come
+    /// first and the elements at set bits come last, keeping their relative
+    /// order. Panics if the slice length does not match the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01101000
+    /// let bv = BitVec::from(&[0x68]);
+    /// let mut items = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+    /// bv.stable_partition_of(&mut items);
+    /// assert_eq!(items, ['a', 'd', 'f', 'g', 'h', 'b', 'c', 'e']);
+    /// ```
+    pub fn stable_partition_of<T>(&self, items: &mut [T]) {
+        if items.len() != self.len {
+            panic!("BitVec: slice length does not match")
+        }
+
+        // Position i takes the element from order[i], applied one cycle at a
+        // time so elements only need swapping
+        let order = self.argsort();
+        let mut placed = BitVec::from(vec![0u8; self.len.div_ceil(8)]);
+        for start in 0..order.len() {
+            if placed.get_bit(start) {
+                continue;
+            }
+            let mut current = start;
+            loop {
+                placed.set_bit(current, true);
+                let next = order[current];
+                if next == start {
+                    break;
+                }
+                items.swap(current, next);
+                current = next;
+            }
+        }
+    }
+
+    /// Splits a parallel slice into spans where the bits form a run of the
+    /// same value, paired with that value. Panics if the slice length does
+    /// not match the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b11100100
+    /// let bv = BitVec::from(&[0xE4]);
+    /// let samples = [9, 8, 7, 0, 0, 5, 0, 0];
+    /// let spans = bv.split_slice_by_runs(&samples);
+    /// assert_eq!(spans[0], (true, &samples[..3]));
+    /// assert_eq!(spans[1], (false, &samples[3..5]));
+    /// assert_eq!(spans.len(), 4);
+    /// ```
+    pub fn split_slice_by_runs<'a, T>(&self, data: &'a [T]) -> Vec<(bool, &'a [T])> {
+        if data.len() != self.len {
+            panic!("BitVec: slice length does not match")
+        }
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        while start < self.len {
+            let value = self.get_bit(start);
+            let end = if value {
+                self.next_clear_bit(start)
+            } else {
+                self.next_set_bit(start)
+            }
+            .unwrap_or(self.len);
+            spans.push((value, &data[start..end]));
+            start = end;
+        }
+
+        spans
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    #[test]
+    fn partition_and_apply() {
+        let bv = BitVec { data: vec![0, 0], len: 10, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let values = [3, 8, 1, 9, 4, 7, 2, 6, 5, 0];
+        let (big, small) = bv.partition_indices(|i| values[i] > 4);
+        assert_eq!(big.len_bits(), 10);
+        assert_eq!(big.apply_mask_to_slice(&values), vec![&8, &9, &7, &6, &5]);
+        assert_eq!(small.apply_mask_to_slice(&values), vec![&3, &1, &4, &2, &0]);
+        assert_eq!(big.count_ones() + small.count_ones(), 10);
+
+        // A short slice only picks up the bits it covers
+        assert_eq!(big.apply_mask_to_slice(&values[..4]), vec![&8, &9]);
+    }
+
+    #[test]
+    fn gather_then_scatter() {
+        // 0b01100101 0b1xxxxxxx
+        let bv = BitVec { data: vec![0x65, 0x80], len: 9, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let src: Vec<u32> = (10..19).collect();
+        let picked = bv.gather_slice(&src);
+        assert_eq!(picked, vec![11, 12, 15, 17, 18]);
+
+        let mut dst = [0u32; 9];
+        bv.scatter_slice(&mut dst, &picked);
+        assert_eq!(dst, [0, 11, 12, 0, 0, 15, 0, 17, 18]);
+
+        // Too few values, then too short a slice
+        let mut dst = [0u32; 9];
+        bv.scatter_slice(&mut dst, &[1, 2]);
+        assert_eq!(dst, [0, 1, 2, 0, 0, 0, 0, 0, 0]);
+        let mut dst = [0u32; 4];
+        bv.scatter_slice(&mut dst, &picked);
+        assert_eq!(dst, [0, 11, 12, 0]);
+    }
+
+    #[test]
+    fn stable_partition() {
+        // 0b10110010 0b011xxxxx
+        let bv = BitVec { data: vec![0xB2, 0x60], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let order = bv.argsort();
+        assert_eq!(order, vec![1, 4, 5, 7, 8, 0, 2, 3, 6, 9, 10]);
+
+        let mut items: Vec<String> = (0..11).map(|i| i.to_string()).collect();
+        bv.stable_partition_of(&mut items);
+        let expected: Vec<String> = order.iter().map(|i| i.to_string()).collect();
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn runs_of_a_parallel_slice() {
+        // 0b00011111 0b11111100 0b1xxxxxxx
+        let bv = BitVec { data: vec![0x1F, 0xFC, 0x80], len: 17, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let data: Vec<usize> = (0..17).collect();
+        let spans = bv.split_slice_by_runs(&data);
+        assert_eq!(spans, vec![
+            (false, &data[0..3]),
+            (true, &data[3..14]),
+            (false, &data[14..16]),
+            (true, &data[16..17]),
+        ]);
+        assert!(BitVec::new().split_slice_by_runs::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn stable_partition_length_mismatch() {
+        let bv = BitVec::from(&[0xB2]);
+        bv.stable_partition_of(&mut [0; 7]);
+    }
+}
@@ -0,0 +1,254 @@
+//! ## Bit matrices
+//! A rows × columns matrix of bits, such as an adjacency matrix, held row
+//! after row in a single BitVec with no padding between rows. Rows are
+//! contiguous, so they can be viewed as BitSlices and combined a byte at a
+//! time. Columns are not, so they are copied out. Transposing works on 8 × 8
+//! blocks, each transposed within a u64.
+
+use crate::slice::{load_byte, store_byte};
+use crate::{BitSlice, BitSliceMut, BitVec};
+
+/// A matrix of bits, stored row by row in a BitVec
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitMatrix;
+///
+/// // Edges 0 -> 1 and 1 -> 2
+/// let mut adjacency = BitMatrix::new(3, 3);
+/// adjacency.set(0, 1, true);
+/// adjacency.set(1, 2, true);
+/// // Add the nodes reachable in two steps from node 0
+/// adjacency.or_rows(0, 1);
+/// assert_eq!(adjacency.row(0).to_bitvec().to_bit_string(), "011");
+/// assert_eq!(adjacency.col(2).to_bit_string(), "110");
+/// assert!(adjacency.transpose().get(2, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    bits: BitVec,   // rows one after another
+    rows: usize,    // number of rows
+    cols: usize,    // bits in each row
+}
+
+impl BitMatrix {
+    /// Constructs a new BitMatrix of clear bits
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let mut bits = BitVec::new();
+        bits.resize(rows * cols, false);
+        Self { bits, rows, cols }
+    }
+
+    /// Builds a matrix from the rows of a BitVec, one after another with no
+    /// padding. Panics if the number of columns does not divide the length.
+    pub fn from_bitvec(bv: BitVec, cols: usize) -> Self {
+        if cols == 0 && bv.len > 0 || cols > 0 && !bv.len.is_multiple_of(cols) {
+            panic!("BitVec: invalid row width for the length")
+        }
+
+        let rows = bv.len.checked_div(cols).unwrap_or(0);
+        let mut bits = bv;
+        bits.reset_seq_read();
+        Self { bits, rows, cols }
+    }
+
+    /// Returns the BitVec holding the rows
+    pub fn as_bitvec(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Unwraps the matrix, returning the BitVec holding the rows
+    pub fn into_bitvec(self) -> BitVec {
+        self.bits
+    }
+
+    /// Get the number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the index into the BitVec of a bit
+    fn locate(&self, row: usize, col: usize) -> usize {
+        if row >= self.rows || col >= self.cols {
+            panic!("BitVec: index out of bounds")
+        }
+        row * self.cols + col
+    }
+
+    /// Returns the bit in a row and column. Panics if it is outside the
+    /// matrix.
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.bits.get_bit(self.locate(row, col))
+    }
+
+    /// Sets the bit in a row and column. Panics if it is outside the matrix.
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        let index = self.locate(row, col);
+        self.bits.set_bit(index, value);
+    }
+
+    /// Returns a view of a row. Panics if the row is outside the matrix.
+    pub fn row(&self, row: usize) -> BitSlice<'_> {
+        if row >= self.rows {
+            panic!("BitVec: index out of bounds")
+        }
+        self.bits.slice(row * self.cols..(row + 1) * self.cols)
+    }
+
+    /// Returns a mutable view of a row. Panics if the row is outside the
+    /// matrix.
+    pub fn row_mut(&mut self, row: usize) -> BitSliceMut<'_> {
+        if row >= self.rows {
+            panic!("BitVec: index out of bounds")
+        }
+        self.bits.slice_mut(row * self.cols..(row + 1) * self.cols)
+    }
+
+    /// Copies a column, from the top row down. Panics if the column is
+    /// outside the matrix.
+    pub fn col(&self, col: usize) -> BitVec {
+        if col >= self.cols {
+            panic!("BitVec: index out of bounds")
+        }
+        let mut column = BitVec::with_capacity(self.rows);
+        for row in 0..self.rows {
+            column.push_bit(self.bits.get_bit(row * self.cols + col));
+        }
+        column
+    }
+
+    /// Combines row `src` into row `dst`, a byte at a time
+    fn combine_rows(&mut self, dst: usize, src: usize, op: fn(u8, u8) -> u8) {
+        if dst >= self.rows || src >= self.rows {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let (dst, src) = (dst * self.cols, src * self.cols);
+        for index in (0..self.cols).step_by(8) {
+            let count = (self.cols - index).min(8);
+            let byte = op(
+                load_byte(&self.bits.data, dst + index, count),
+                load_byte(&self.bits.data, src + index, count),
+            );
+            store_byte(&mut self.bits.data, dst + index, count, byte);
+        }
+    }
+
+    /// ORs row `src` into row `dst`. Panics if either row is outside the
+    /// matrix.
+    pub fn or_rows(&mut self, dst: usize, src: usize) {
+        self.combine_rows(dst, src, |a, b| a | b);
+    }
+
+    /// ANDs row `src` into row `dst`. Panics if either row is outside the
+    /// matrix.
+    pub fn and_rows(&mut self, dst: usize, src: usize) {
+        self.combine_rows(dst, src, |a, b| a & b);
+    }
+
+    /// Returns the transpose, with a row for each column of this matrix
+    pub fn transpose(&self) -> BitMatrix {
+        let mut transposed = BitMatrix::new(self.cols, self.rows);
+        for block_row in (0..self.rows).step_by(8) {
+            let height = (self.rows - block_row).min(8);
+            for block_col in (0..self.cols).step_by(8) {
+                let width = (self.cols - block_col).min(8);
+
+                let mut block = 0;
+                for row in block_row..block_row + height {
+                    let byte = load_byte(&self.bits.data, row * self.cols + block_col, width);
+                    block |= (byte as u64) << (56 - 8 * (row - block_row));
+                }
+
+                let block = transpose_block(block);
+                for col in block_col..block_col + width {
+                    let byte = (block >> (56 - 8 * (col - block_col))) as u8;
+                    store_byte(&mut transposed.bits.data, col * self.rows + block_row, height, byte);
+                }
+            }
+        }
+        transposed
+    }
+}
+
+/// Transposes an 8 × 8 block of bits, held a row to each byte from the most
+/// significant, by swapping ever smaller sub-blocks across the diagonal
+fn transpose_block(mut x: u64) -> u64 {
+    let t = (x ^ (x >> 7)) & 0x00AA_00AA_00AA_00AA;
+    x ^= t ^ (t << 7);
+    let t = (x ^ (x >> 14)) & 0x0000_CCCC_0000_CCCC;
+    x ^= t ^ (t << 14);
+    let t = (x ^ (x >> 28)) & 0x0000_0000_F0F0_F0F0;
+    x ^ t ^ (t << 28)
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A matrix with a scattered pattern of set bits
+    fn pattern(rows: usize, cols: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::new(rows, cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                matrix.set(row, col, (row * 7 + col * 3) % 5 == 0 || row == col);
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn transpose_matches_naive() {
+        for (rows, cols) in [(0, 0), (1, 1), (3, 5), (8, 8), (13, 21), (24, 9), (1, 70)] {
+            let matrix = pattern(rows, cols);
+            let transposed = matrix.transpose();
+            assert_eq!((transposed.rows(), transposed.cols()), (cols, rows));
+            for row in 0..rows {
+                for col in 0..cols {
+                    assert_eq!(transposed.get(col, row), matrix.get(row, col));
+                }
+            }
+            assert_eq!(transposed.transpose(), matrix);
+        }
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let mut matrix = BitMatrix::from_bitvec(BitVec::from_bit_str("1100 1010 0111").unwrap(), 4);
+        assert_eq!((matrix.rows(), matrix.cols()), (3, 4));
+        assert_eq!(matrix.row(1).to_u64(), 0b1010);
+        assert_eq!(matrix.col(3).to_bit_string(), "001");
+
+        matrix.or_rows(0, 2);
+        assert_eq!(matrix.row(0).to_u64(), 0b1111);
+        matrix.and_rows(2, 1);
+        assert_eq!(matrix.row(2).to_u64(), 0b0010);
+        matrix.row_mut(1).fill(false);
+        assert_eq!(matrix.into_bitvec().to_bit_string(), "111100000010");
+    }
+
+    #[test]
+    fn wide_rows_combine_bytewise() {
+        // Rows of 13 bits start at every bit offset
+        let mut matrix = pattern(9, 13);
+        let expected: Vec<bool> = (0..13).map(|col| matrix.get(4, col) | matrix.get(7, col)).collect();
+        matrix.or_rows(7, 4);
+        assert_eq!(matrix.row(7).to_bitvec(), BitVec::from(expected));
+        assert_eq!(matrix.row(8).to_bitvec(), pattern(9, 13).row(8).to_bitvec());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid row width")]
+    fn ragged_rows() {
+        BitMatrix::from_bitvec(BitVec::from_bit_str("10101").unwrap(), 2);
+    }
+}
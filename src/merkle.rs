@@ -0,0 +1,202 @@
+//! ## Merkle hashing
+//! A hash tree over fixed-size pages of a bitmap, so two copies of a large
+//! bitmap can be compared by exchanging hashes rather than bits: equal roots
+//! mean equal bitmaps, and walking down only the subtrees whose hashes
+//! differ finds the changed pages.
+//!
+//! Pages are hashed with 64-bit FNV-1a over their length and bits, and each
+//! node over the hashes of its two children, or its one child at the end of
+//! an odd level, with leaves and nodes tagged apart. The hash finds changes,
+//! not tampering: it is not cryptographic.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// FNV-1a 64-bit offset basis
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+/// FNV-1a 64-bit prime
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+/// Tag of a page hash
+const LEAF: u8 = 0;
+/// Tag of a node hash
+const NODE: u8 = 1;
+
+/// Runs FNV-1a over some bytes after a tag
+fn fnv1a(tag: u8, parts: &[&[u8]]) -> u64 {
+    let mut hash = (FNV_OFFSET ^ tag as u64).wrapping_mul(FNV_PRIME);
+    for &byte in parts.iter().copied().flatten() {
+        hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Hashes one or two children into their parent
+fn node_hash(children: &[u64]) -> u64 {
+    let bytes: Vec<[u8; 8]> = children.iter().map(|child| child.to_le_bytes()).collect();
+    let parts: Vec<&[u8]> = bytes.iter().map(|bytes| &bytes[..]).collect();
+    fnv1a(NODE, &parts)
+}
+
+/// A hash tree over the pages of a bitmap
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let mut bv = BitVec::from(vec![0; 1 << 16]);
+/// let tree = bv.merkle_tree(4096);
+/// bv.set_bit(300_000, true);
+/// assert_ne!(bv.merkle_root(4096), tree.root());
+/// assert_eq!(bv.changed_pages_since(&tree), vec![73]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MerkleTree {
+    page_bits: usize,
+    levels:    Vec<Vec<u64>>,   // page hashes first, root last
+}
+
+impl MerkleTree {
+    /// Get the number of bits in each page
+    pub fn page_bits(&self) -> usize {
+        self.page_bits
+    }
+
+    /// Get the number of pages
+    pub fn num_pages(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns the hash of the whole bitmap
+    pub fn root(&self) -> u64 {
+        match self.levels.last() {
+            Some(top) if top.len() == 1 => top[0],
+            _ => node_hash(&[]),
+        }
+    }
+
+    /// Returns the hashes of the pages
+    pub fn page_hashes(&self) -> &[u64] {
+        &self.levels[0]
+    }
+
+    /// Returns the hashes at a level of the tree, from the pages at 0 up to
+    /// the root, or None past the root
+    pub fn level(&self, level: usize) -> Option<&[u64]> {
+        self.levels.get(level).map(|hashes| &hashes[..])
+    }
+
+    /// Returns the pages whose hashes differ from another tree's, in order,
+    /// walking down from the root only where the hashes differ. A page that
+    /// only one of the trees has counts as changed.
+    pub fn changed_pages(&self, other: &MerkleTree) -> Vec<usize> {
+        if self.page_bits != other.page_bits || self.num_pages() != other.num_pages() {
+            let pages = self.num_pages().max(other.num_pages());
+            return (0..pages)
+                .filter(|&page| self.levels[0].get(page) != other.levels[0].get(page))
+                .collect();
+        }
+
+        // The same shape, so the nodes pair up
+        let mut changed: Vec<usize> = (0..self.levels.last().unwrap().len()).collect();
+        for level in (0..self.levels.len()).rev() {
+            let (ours, theirs) = (&self.levels[level], &other.levels[level]);
+            changed.retain(|&index| ours[index] != theirs[index]);
+            if level > 0 {
+                let below = self.levels[level - 1].len();
+                changed = changed.iter().flat_map(|&index| [2 * index, 2 * index + 1]).filter(|&index| index < below).collect();
+            }
+        }
+        changed
+    }
+}
+
+impl BitVec {
+    /// Builds a hash tree over pages of `page_bits` bits, the last of which
+    /// may be shorter. Panics if the page size is 0.
+    pub fn merkle_tree(&self, page_bits: usize) -> MerkleTree {
+        if page_bits == 0 {
+            panic!("BitVec: page size cannot be 0")
+        }
+
+        let mut level: Vec<u64> = (0..self.len.div_ceil(page_bits))
+            .map(|page| {
+                let start = page * page_bits;
+                let bits = self.slice(start..(start + page_bits).min(self.len)).to_bitvec();
+                fnv1a(LEAF, &[&(bits.len as u64).to_le_bytes(), &bits.data])
+            })
+            .collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let next = level.chunks(2).map(node_hash).collect();
+            levels.push(level);
+            level = next;
+        }
+        levels.push(level);
+        MerkleTree { page_bits, levels }
+    }
+
+    /// Returns the root hash of the tree over pages of `page_bits` bits.
+    /// Panics if the page size is 0.
+    pub fn merkle_root(&self, page_bits: usize) -> u64 {
+        self.merkle_tree(page_bits).root()
+    }
+
+    /// Returns the pages that differ from those a tree was built over, with
+    /// the tree's page size
+    pub fn changed_pages_since(&self, tree: &MerkleTree) -> Vec<usize> {
+        self.merkle_tree(tree.page_bits).changed_pages(tree)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_changed_pages() {
+        let mut bv = BitVec::from(vec![0xA5; 125]);
+        let tree = bv.merkle_tree(64);
+        assert_eq!(tree.num_pages(), 16);
+        assert_eq!(tree.level(4).unwrap(), &[tree.root()]);
+        assert_eq!(tree.level(5), None);
+        assert!(bv.changed_pages_since(&tree).is_empty());
+
+        bv.set_bit(0, false);
+        bv.set_bit(498, false);
+        bv.set_bit(999, false);
+        assert_eq!(bv.changed_pages_since(&tree), vec![0, 7, 15]);
+        assert_ne!(bv.merkle_root(64), tree.root());
+
+        // Neighbouring pages under different nodes
+        let mut neighbours = BitVec::from(vec![0xA5; 125]);
+        neighbours.slice_mut(64..72).fill(false);
+        neighbours.slice_mut(128..136).fill(false);
+        assert_eq!(neighbours.changed_pages_since(&tree), vec![1, 2]);
+    }
+
+    #[test]
+    fn lengths_and_odd_levels() {
+        // 3 pages: the last page is short, and the top level is odd
+        let bv = BitVec::from_bit_str("1010 1010 10").unwrap();
+        let tree = bv.merkle_tree(4);
+        assert_eq!(tree.num_pages(), 3);
+        assert_eq!(tree.level(1).unwrap().len(), 2);
+
+        // A longer vector with the same bits differs in its last page, and
+        // has one more page
+        let mut longer = bv.clone();
+        longer.push_bit(false);
+        longer.resize(13, false);
+        assert_eq!(longer.changed_pages_since(&tree), vec![2, 3]);
+
+        // Clear padding bits still count through the length
+        assert_ne!(BitVec::from_bit_str("1").unwrap().merkle_root(8), BitVec::from_bit_str("10").unwrap().merkle_root(8));
+        assert_eq!(BitVec::new().merkle_tree(8).num_pages(), 0);
+        assert_eq!(BitVec::new().merkle_root(8), BitVec::new().merkle_root(16));
+    }
+}
@@ -0,0 +1,135 @@
+//! ## Similarity metrics
+//! Distances and similarities between two BitVecs of the same length, as
+//! used to compare binary feature hashes such as SimHash and MinHash
+//! signatures. Each counts bits a 64-bit word at a time.
+
+use core::iter::Sum;
+
+use crate::{BitVec, BitVecError};
+
+impl BitVec {
+    /// Counts the set bits of two vectors of the same length combined word
+    /// by word, or returns an error if the lengths differ
+    fn count_combined(&self, other: &BitVec, op: impl Fn(u64, u64) -> u64) -> Result<usize, BitVecError> {
+        if self.len != other.len {
+            return Err(BitVecError::LengthMismatch { expected: self.len, found: other.len });
+        }
+
+        let mut words = self.data.chunks_exact(8);
+        let mut other_words = other.data.chunks_exact(8);
+        let mut count = words
+            .by_ref()
+            .zip(other_words.by_ref())
+            .map(|(a, b)| op(u64::from_ne_bytes(a.try_into().unwrap()), u64::from_ne_bytes(b.try_into().unwrap())).count_ones() as usize)
+            .sum();
+        count += words
+            .remainder()
+            .iter()
+            .zip(other_words.remainder())
+            .map(|(&a, &b)| op(a as u64, b as u64).count_ones() as usize)
+            .sum::<usize>();
+        Ok(count)
+    }
+
+    /// Returns the number of places where two vectors differ, or an error if
+    /// the lengths differ
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let a = BitVec::from_bit_str("1100 1010").unwrap();
+    /// let b = BitVec::from_bit_str("1010 1001").unwrap();
+    /// assert_eq!(a.hamming_distance(&b), Ok(4));
+    /// assert_eq!(a.dot(&b), Ok(2));
+    /// assert_eq!(a.jaccard_similarity(&b), Ok(2.0 / 6.0));
+    /// ```
+    pub fn hamming_distance(&self, other: &BitVec) -> Result<usize, BitVecError> {
+        self.count_combined(other, |a, b| a ^ b)
+    }
+
+    /// Returns the number of places where both vectors are set, the
+    /// popcount of their AND, or an error if the lengths differ
+    pub fn dot(&self, other: &BitVec) -> Result<usize, BitVecError> {
+        self.count_combined(other, |a, b| a & b)
+    }
+
+    /// Returns the Jaccard similarity of the set bits of two vectors, the
+    /// size of their intersection over the size of their union, or an error
+    /// if the lengths differ. Two vectors with no set bits are identical, so
+    /// have a similarity of 1.
+    pub fn jaccard_similarity(&self, other: &BitVec) -> Result<f64, BitVecError> {
+        let union = self.count_combined(other, |a, b| a | b)?;
+        if union == 0 {
+            return Ok(1.0);
+        }
+
+        Ok(self.dot(other)? as f64 / union as f64)
+    }
+
+    /// Returns the sum of the weights at the set positions, the dot product
+    /// with a weight per bit, or an error unless there is one weight per
+    /// bit. Only the set bits are visited, so sparse selections are cheap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let chosen = BitVec::from_bit_str("1010").unwrap();
+    /// assert_eq!(chosen.weighted_sum(&[5u32, 7, 11, 13]), Ok(16));
+    /// assert_eq!(chosen.weighted_sum(&[0.5, 1.0, 0.25, 2.0]), Ok(0.75));
+    /// ```
+    pub fn weighted_sum<W: Copy + Sum<W>>(&self, weights: &[W]) -> Result<W, BitVecError> {
+        if weights.len() != self.len {
+            return Err(BitVecError::LengthMismatch { expected: self.len, found: weights.len() });
+        }
+
+        Ok(self.iter_ones().map(|index| weights[index]).sum())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_across_words() {
+        // 100 bits, so two full words and a ragged tail
+        let a = BitVec::repeat(&BitVec::from_bit_str("1100").unwrap(), 25);
+        let b = BitVec::repeat(&BitVec::from_bit_str("1010").unwrap(), 25);
+        assert_eq!(a.hamming_distance(&b), Ok(50));
+        assert_eq!(a.dot(&b), Ok(25));
+        assert_eq!(a.jaccard_similarity(&b), Ok(1.0 / 3.0));
+        assert_eq!(a.hamming_distance(&a), Ok(0));
+        assert_eq!(a.jaccard_similarity(&a), Ok(1.0));
+    }
+
+    #[test]
+    fn empty_and_mismatched() {
+        let zeros = BitVec::repeat_bit(false, 9);
+        assert_eq!(zeros.jaccard_similarity(&zeros), Ok(1.0));
+        assert_eq!(BitVec::new().hamming_distance(&BitVec::new()), Ok(0));
+
+        let short = BitVec::repeat_bit(true, 8);
+        assert_eq!(zeros.dot(&short), Err(BitVecError::LengthMismatch { expected: 9, found: 8 }));
+        assert!(zeros.jaccard_similarity(&short).is_err());
+    }
+
+    #[test]
+    fn weighted_sums() {
+        let weights: Vec<u32> = (0..100).collect();
+        let mut every_third = BitVec::new();
+        for index in 0..100 {
+            every_third.push_bit(index % 3 == 0);
+        }
+        assert_eq!(every_third.weighted_sum(&weights), Ok((0..100).step_by(3).sum()));
+        assert_eq!(BitVec::repeat_bit(false, 100).weighted_sum(&weights), Ok(0));
+        assert_eq!(BitVec::new().weighted_sum::<f64>(&[]), Ok(0.0));
+        assert_eq!(every_third.weighted_sum(&weights[..99]), Err(BitVecError::LengthMismatch { expected: 100, found: 99 }));
+    }
+}
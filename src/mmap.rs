@@ -0,0 +1,166 @@
+//! ## Memory-mapped bitmaps
+//! A bitmap kept in a file and mapped into memory, for presence bitmaps too
+//! large to hold in RAM. The file holds the bits packed MSB first, as
+//! `as_bytes` would give them, with nothing else, so the operating system
+//! pages them in and out as they are touched. Reads go through a BitSlice
+//! over the mapped bytes, and counting and iterating share their code with
+//! BitVec.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::ops::RangeBounds;
+use std::path::Path;
+
+use memmap2::MmapMut;
+
+use crate::iter::ones_in_bytes;
+use crate::range::count_ones_in_bytes;
+use crate::slice::resolve_range;
+use crate::{BitSlice, BitSliceMut, IterOnes};
+
+/// A fixed-length bitmap backed by a memory-mapped file
+///
+/// # Examples
+///
+/// ```no_run
+/// use bitvecs::FileBitVec;
+///
+/// let mut presence = FileBitVec::open("presence.bits", 1 << 33).unwrap();
+/// presence.set_bit(123_456_789, true);
+/// assert_eq!(presence.count_ones_in(..), 1);
+/// presence.flush().unwrap();
+/// ```
+pub struct FileBitVec {
+    map: MmapMut,   // the whole file, at least len bits
+    len: usize,     // length in bits
+}
+
+impl FileBitVec {
+    /// Opens a file as a bitmap of `len` bits, creating it if it does not
+    /// exist and growing it with clear bits if it is too short. A longer
+    /// file keeps its extra bytes, which the bitmap ignores.
+    pub fn open<P: AsRef<Path>>(path: P, len: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let bytes = len.div_ceil(8) as u64;
+        if file.metadata()?.len() < bytes {
+            file.set_len(bytes)?;
+        }
+
+        // SAFETY: the map is only sound while no other process or mapping
+        // changes the file, which is the caller's to ensure, as for any
+        // memory-mapped file
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        Ok(Self { map, len })
+    }
+
+    /// Get the number of bits in the bitmap
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the bitmap holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a view of the bitmap
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        BitSlice::from_bytes(&self.map, self.len).expect("BitVec: mapped file is shorter than the bitmap")
+    }
+
+    /// Returns a mutable view of the bitmap
+    pub fn as_mut_bitslice(&mut self) -> BitSliceMut<'_> {
+        BitSliceMut::from_bytes(&mut self.map, self.len).expect("BitVec: mapped file is shorter than the bitmap")
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// out of bounds.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.as_bitslice().get_bit(index)
+    }
+
+    /// Sets the bit at the desired index. Panics if the index is out of
+    /// bounds, as the bitmap cannot grow.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        self.as_mut_bitslice().set_bit(index, value);
+    }
+
+    /// Returns the number of set bits in a range, a word at a time. Panics
+    /// if the range runs past the end.
+    pub fn count_ones_in<R: RangeBounds<usize>>(&self, range: R) -> usize {
+        let (start, end) = resolve_range(range, self.len);
+        count_ones_in_bytes(&self.map, start, end)
+    }
+
+    /// Returns an iterator over the indices of the set bits
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        ones_in_bytes(&self.map, 0, self.len)
+    }
+
+    /// Returns an iterator over the indices of the set bits in a range.
+    /// Panics if the range runs past the end.
+    pub fn ones_in<R: RangeBounds<usize>>(&self, range: R) -> IterOnes<'_> {
+        let (start, end) = resolve_range(range, self.len);
+        ones_in_bytes(&self.map, start, end)
+    }
+
+    /// Writes every change back to the file, returning once it is on disk
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A path in the temporary directory for one test
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bitvecs-{}-{}.bits", name, std::process::id()))
+    }
+
+    #[test]
+    fn changes_reach_the_file() {
+        let path = temp_path("mmap");
+        {
+            let mut bitmap = FileBitVec::open(&path, 20).unwrap();
+            assert_eq!(bitmap.len(), 20);
+            assert_eq!(bitmap.count_ones_in(..), 0);
+            for index in [0, 9, 10, 19] {
+                bitmap.set_bit(index, true);
+            }
+            assert!(bitmap.get_bit(9));
+            assert_eq!(bitmap.count_ones_in(5..19), 2);
+            assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), vec![0, 9, 10, 19]);
+            assert_eq!(bitmap.ones_in(1..19).count(), 2);
+            bitmap.flush().unwrap();
+        }
+        assert_eq!(fs::read(&path).unwrap(), vec![0x80, 0x60, 0x10]);
+
+        // Reopening shorter ignores the bits past the new end
+        let bitmap = FileBitVec::open(&path, 12).unwrap();
+        assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), vec![0, 9, 10]);
+        assert_eq!(bitmap.as_bitslice().count_ones(), 3);
+        drop(bitmap);
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("mmap-empty");
+        let bitmap = FileBitVec::open(&path, 0).unwrap();
+        assert!(bitmap.is_empty());
+        assert_eq!(bitmap.iter_ones().next(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn cannot_grow() {
+        let path = temp_path("mmap-grow");
+        let mut bitmap = FileBitVec::open(&path, 8).unwrap();
+        fs::remove_file(&path).unwrap();
+        bitmap.set_bit(8, true);
+    }
+}
@@ -0,0 +1,99 @@
+//! ## Move-to-front
+//! The move-to-front transform over consecutive fields of a fixed width,
+//! read MSB first, as a compression pipeline runs after a Burrows-Wheeler
+//! transform. Each field is replaced by its place in a list of every value,
+//! and that value moved to the front, so runs of the same or recent values
+//! become runs of small numbers. A trailing field shorter than the width is
+//! passed through unchanged.
+
+use alloc::vec::Vec;
+
+use crate::symbols::TailPolicy;
+use crate::BitVec;
+
+/// Returns the list of every value of a width in order
+fn initial_list(width: usize) -> Vec<u16> {
+    (0..1u32 << width).map(|value| value as u16).collect()
+}
+
+impl BitVec {
+    /// Runs each value through a list, writing what `step` returns for it
+    fn move_to_front(&self, width: usize, mut step: impl FnMut(&mut Vec<u16>, u16) -> u16) -> BitVec {
+        let (symbols, tail) = self.to_symbols(width, TailPolicy::Keep).unwrap();
+        let mut list = initial_list(width);
+        let values: Vec<u16> = symbols.into_iter().map(|symbol| step(&mut list, symbol)).collect();
+        let mut out = BitVec::from_symbols(width, &values).unwrap();
+        out.extend(&tail);
+        out
+    }
+
+    /// Returns the move-to-front transform of the fields of `width` bits.
+    /// Panics if the width is not 1 to 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(b"bbbaab");
+    /// let encoded = bv.mtf_encode(8);
+    /// assert_eq!(encoded.as_bytes(), &[98, 0, 0, 98, 0, 1]);
+    /// assert_eq!(encoded.mtf_decode(8), bv);
+    /// ```
+    pub fn mtf_encode(&self, width: usize) -> BitVec {
+        self.move_to_front(width, |list, value| {
+            let place = list.iter().position(|&entry| entry == value).unwrap();
+            list.remove(place);
+            list.insert(0, value);
+            place as u16
+        })
+    }
+
+    /// Undoes `mtf_encode` with the same width. Panics if the width is not 1
+    /// to 16 bits.
+    pub fn mtf_decode(&self, width: usize) -> BitVec {
+        self.move_to_front(width, |list, place| {
+            let value = list.remove(place as usize);
+            list.insert(0, value);
+            value
+        })
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nibbles_and_bits() {
+        // Nibbles 3 3 1 3 0, then 2 bits left over
+        let bv = BitVec::from_bit_str("0011 0011 0001 0011 0000 10").unwrap();
+        let encoded = bv.mtf_encode(4);
+        assert_eq!(encoded.to_bit_string(), "0011000000100001001010");
+        assert_eq!(encoded.mtf_decode(4), bv);
+
+        // With single bits, 1 marks a change
+        let bits = BitVec::from_bit_str("0001 1101").unwrap();
+        assert_eq!(bits.mtf_encode(1).to_bit_string(), "00010011");
+        assert_eq!(bits.mtf_encode(1).mtf_decode(1), bits);
+    }
+
+    #[test]
+    fn round_trips() {
+        let bv = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD, 0x01]);
+        for width in [1, 2, 3, 5, 8, 12, 16] {
+            assert_eq!(bv.mtf_encode(width).len_bits(), bv.len_bits());
+            assert_eq!(bv.mtf_encode(width).mtf_decode(width), bv, "width {}", width);
+        }
+        assert_eq!(BitVec::new().mtf_encode(8).len_bits(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 to 16 bits")]
+    fn width_zero() {
+        BitVec::from(&[1]).mtf_encode(0);
+    }
+}
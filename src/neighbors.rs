@@ -0,0 +1,123 @@
+//! ## Hamming neighbourhoods
+//! Every vector one bit away from a vector, for local search over bit
+//! string solutions. The neighbours are visited in place: each step flips
+//! one bit back and the next one over, as a Gray code walks, so no
+//! neighbour is ever allocated. Since each neighbour borrows the vector
+//! until the next step, they come from `next` rather than an `Iterator`.
+
+use crate::BitVec;
+
+/// The neighbours at Hamming distance 1 of a vector, visited in place. The
+/// vector is restored when this is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let mut bv = BitVec::from_bit_str("101").unwrap();
+/// let mut neighbors = bv.neighbors_hamming1();
+/// let mut seen = Vec::new();
+/// while let Some((index, neighbor)) = neighbors.next() {
+///     seen.push((index, neighbor.to_bit_string()));
+/// }
+/// drop(neighbors);
+/// assert_eq!(seen, [(0, "001".to_string()), (1, "111".to_string()), (2, "100".to_string())]);
+/// assert_eq!(bv.to_bit_string(), "101");
+/// ```
+#[derive(Debug)]
+pub struct HammingNeighbors<'a> {
+    bv:      &'a mut BitVec,
+    next:    usize,            // the next bit to flip
+    flipped: Option<usize>,    // the bit flipped now, if any
+}
+
+/// Flips one bit of the packed bytes
+fn toggle(bv: &mut BitVec, index: usize) {
+    bv.data[index / 8] ^= 0x80 >> (index % 8);
+}
+
+impl HammingNeighbors<'_> {
+    /// Moves to the next neighbour, returning the bit flipped and the
+    /// vector with it flipped, or None once every bit has been
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(usize, &BitVec)> {
+        self.restore();
+        if self.next >= self.bv.len {
+            return None;
+        }
+        let index = self.next;
+        toggle(self.bv, index);
+        self.flipped = Some(index);
+        self.next += 1;
+        Some((index, &*self.bv))
+    }
+
+    /// Get the number of neighbours not yet visited
+    pub fn remaining(&self) -> usize {
+        self.bv.len - self.next
+    }
+
+    /// Flips back the bit flipped now, if any
+    fn restore(&mut self) {
+        if let Some(index) = self.flipped.take() {
+            toggle(self.bv, index);
+        }
+    }
+}
+
+impl Drop for HammingNeighbors<'_> {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+impl BitVec {
+    /// Visits every vector at Hamming distance 1, flipping bit 0 first,
+    /// with one flip back and one flip forward each step
+    pub fn neighbors_hamming1(&mut self) -> HammingNeighbors<'_> {
+        HammingNeighbors { bv: self, next: 0, flipped: None }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visits_every_neighbor() {
+        let original = BitVec::from_bit_str("1100 1010 01").unwrap();
+        let mut bv = original.clone();
+        let mut neighbors = bv.neighbors_hamming1();
+        assert_eq!(neighbors.remaining(), 10);
+
+        let mut count = 0;
+        while let Some((index, neighbor)) = neighbors.next() {
+            assert_eq!(neighbor.hamming_distance(&original), Ok(1));
+            assert_ne!(neighbor.get_bit(index), original.get_bit(index));
+            count += 1;
+        }
+        assert_eq!(count, 10);
+        assert!(neighbors.next().is_none());
+        drop(neighbors);
+        assert_eq!(bv, original);
+
+        assert!(BitVec::new().neighbors_hamming1().next().is_none());
+    }
+
+    #[test]
+    fn restores_when_dropped_early() {
+        let mut bv = BitVec::from(&[0x0F]);
+        {
+            let mut neighbors = bv.neighbors_hamming1();
+            neighbors.next();
+            let (index, neighbor) = neighbors.next().unwrap();
+            assert_eq!((index, neighbor.byte(0)), (1, 0x4F));
+            assert_eq!(neighbors.remaining(), 6);
+        }
+        assert_eq!(bv, BitVec::from(&[0x0F]));
+    }
+}
@@ -0,0 +1,70 @@
+//! ## NumPy packed bits
+//! The layout of `numpy.packbits` and `numpy.unpackbits` over a flat
+//! boolean array: eight elements to a byte, with the last byte padded with
+//! 0s. NumPy's `bitorder="big"`, the default, is `BitOrder::Msb0`, and
+//! `bitorder="little"` is `BitOrder::Lsb0`. The packed bytes do not carry
+//! the element count, which goes to `unpackbits` as `count`.
+
+use alloc::vec::Vec;
+
+use crate::{BitOrder, BitVec, BitVecError};
+
+impl BitVec {
+    /// Returns the bits packed as `numpy.packbits` packs a boolean array
+    /// with the given bit order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitOrder, BitVec};
+    ///
+    /// // numpy.packbits([1, 0, 1, 1, 0, 0, 0, 0, 1], bitorder="little")
+    /// let bv = BitVec::from_bit_str("101100001").unwrap();
+    /// let packed = bv.to_numpy_packed(BitOrder::Lsb0);
+    /// assert_eq!(packed, vec![0x0D, 0x01]);
+    /// assert_eq!(BitVec::from_numpy_packed(&packed, 9, BitOrder::Lsb0), Ok(bv));
+    /// ```
+    pub fn to_numpy_packed(&self, order: BitOrder) -> Vec<u8> {
+        self.data.iter().map(|&byte| order.convert(byte)).collect()
+    }
+
+    /// Reads `count` bits packed as by `numpy.packbits` with the given bit
+    /// order, as `numpy.unpackbits` with `count` does. Returns an error if
+    /// there are too few bytes; any bits after the count are ignored.
+    pub fn from_numpy_packed(bytes: &[u8], count: usize, order: BitOrder) -> Result<BitVec, BitVecError> {
+        if bytes.len() < count.div_ceil(8) {
+            return Err(BitVecError::NotEnoughBits { requested: count, available: bytes.len() * 8 });
+        }
+
+        let mut bv = BitVec::from_with_order(&bytes[..count.div_ceil(8)], order);
+        bv.truncate(count);
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_bit_orders() {
+        // numpy.packbits([[1, 1, 0, 0, 0, 0, 0, 1, 1, 0, 1]])
+        let bv = BitVec::from_bit_str("11000001 101").unwrap();
+        assert_eq!(bv.to_numpy_packed(BitOrder::Msb0), vec![0xC1, 0xA0]);
+        assert_eq!(bv.to_numpy_packed(BitOrder::Lsb0), vec![0x83, 0x05]);
+        for order in [BitOrder::Msb0, BitOrder::Lsb0] {
+            assert_eq!(BitVec::from_numpy_packed(&bv.to_numpy_packed(order), 11, order), Ok(bv.clone()));
+        }
+
+        // A shorter count drops the bits after it
+        assert_eq!(BitVec::from_numpy_packed(&[0xFF, 0xFF], 4, BitOrder::Msb0).unwrap().to_bit_string(), "1111");
+        assert_eq!(
+            BitVec::from_numpy_packed(&[0xFF], 9, BitOrder::Lsb0),
+            Err(BitVecError::NotEnoughBits { requested: 9, available: 8 })
+        );
+        assert!(BitVec::new().to_numpy_packed(BitOrder::Msb0).is_empty());
+    }
+}
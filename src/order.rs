@@ -0,0 +1,125 @@
+//! ## Bit order
+//! Many formats, such as DEFLATE and GIF, pack bits into bytes LSB first
+//! rather than MSB first. A BitVec always holds its bits in stream order, so
+//! bit 0 is the first bit pushed or read in either order, and `push_bit`,
+//! `get_bit`, `read_bit_seq`, and `extend` work the same way. The order decides
+//! how bits are packed into bytes, by `from_with_order`, `push_byte`,
+//! `read_byte`, `pop_byte`, `export`, and `write_to`.
+
+use alloc::vec::Vec;
+use crate::BitVec;
+
+/// How bits are packed into each byte
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first bit is the most significant bit of the byte
+    #[default]
+    Msb0,
+    /// The first bit is the least significant bit of the byte
+    Lsb0,
+}
+
+impl BitOrder {
+    /// Converts a byte between this order and MSB first. Reversing the bits
+    /// undoes itself, so this works in both directions.
+    pub(crate) fn convert(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::Msb0 => byte,
+            BitOrder::Lsb0 => byte.reverse_bits(),
+        }
+    }
+}
+
+impl BitVec {
+    /// Constructs a new, empty, BitVec which packs bytes in the given order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitOrder, BitVec};
+    ///
+    /// let mut bv = BitVec::with_order(BitOrder::Lsb0);
+    /// bv.push_byte(0x01);
+    /// assert_eq!(bv.get_bit(0), true);
+    /// ```
+    pub fn with_order(order: BitOrder) -> Self {
+        let mut bv = BitVec::new();
+        bv.order = order;
+        bv
+    }
+
+    /// Generates a new BitVec from bytes packed in the given order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitOrder, BitVec};
+    ///
+    /// // DEFLATE block header: BFINAL = 1, BTYPE = 01 (LSB first)
+    /// let mut bv = BitVec::from_with_order(&[0x03], BitOrder::Lsb0);
+    /// assert_eq!(bv.read_bit_seq(), Some(1));
+    /// assert_eq!(bv.read_bit_seq(), Some(1));
+    /// assert_eq!(bv.read_bit_seq(), Some(0));
+    /// ```
+    pub fn from_with_order(data: &[u8], order: BitOrder) -> Self {
+        let mut bv = BitVec::from(data.iter().map(|&byte| order.convert(byte)).collect::<Vec<u8>>());
+        bv.order = order;
+        bv
+    }
+
+    /// Get the order used to pack bits into bytes
+    pub fn bit_order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// Changes the order used to pack bits into bytes from now on. The bits
+    /// themselves are left as they are.
+    pub fn set_bit_order(&mut self, order: BitOrder) {
+        self.order = order;
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lsb_first_bytes() {
+        // 0b00000110 0b00001011 read LSB first
+        let mut bv = BitVec::from_with_order(&[0x06, 0x0B], BitOrder::Lsb0);
+        let bits: Vec<u8> = (0..8).map(|_| bv.read_bit_seq().unwrap()).collect();
+        assert_eq!(bits, vec![0, 1, 1, 0, 0, 0, 0, 0]);
+        assert!(bv.get_bit(8));
+        assert!(!bv.get_bit(10));
+
+        bv.reset_seq_read();
+        bv.read_bit_seq();
+        // 0b0000011 | 0b1 from the next byte, LSB first
+        assert_eq!(bv.read_byte(), Some(0x83));
+        assert_eq!(bv.pop_byte(), Some(0x0B));
+        assert_eq!(bv.export(), "\u{6}");
+    }
+
+    #[test]
+    fn pushing_lsb_first() {
+        let mut bv = BitVec::with_order(BitOrder::Lsb0);
+        bv.push_bit(true);
+        bv.push_bit(false);
+        bv.push_bit(true);
+        bv.push_byte(0xF0);
+        assert_eq!(bv.read_byte(), Some(0x85));
+        assert_eq!(bv.len_bits(), 11);
+
+        // Extending keeps the stream order whatever the orders
+        let mut msb = BitVec::from(&[0x80]);
+        msb.extend(&bv);
+        assert!(msb.get_bit(0));
+        assert!(msb.get_bit(8));
+        assert!(!msb.get_bit(9));
+        assert!(msb.get_bit(10));
+        assert_eq!(msb.len_bits(), 19);
+    }
+}
@@ -0,0 +1,108 @@
+//! ## Outer products
+//! Building a BitGrid from two BitVecs, where the cell in row `i` and
+//! column `j` combines bit `i` of the first with bit `j` of the second, as
+//! in constraint matrices and generated test patterns. The second vector is
+//! packed into row words once, and each row is then either a copy of it, its
+//! inversion, or a solid run, depending on the bit of the first vector.
+
+use alloc::vec::Vec;
+
+use crate::{BitGrid, BitVec};
+
+impl BitGrid {
+    /// Builds a grid with a row for each bit of `a` and a column for each
+    /// bit of `b`, setting each row from a bit of `a` and the words of `b`
+    fn outer(a: &BitVec, b: &BitVec, op: impl Fn(bool, u64) -> u64) -> BitGrid {
+        let columns: Vec<u64> = b.as_bytes()
+            .chunks(8)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u64::from_be_bytes(bytes)
+            })
+            .collect();
+
+        let mut grid = BitGrid::new(b.len_bits(), a.len_bits());
+        let masks: Vec<u64> = (0..columns.len()).map(|index| grid.word_mask(index)).collect();
+        for y in 0..a.len_bits() {
+            let bit = a.get_bit(y);
+            for (index, word) in grid.row_words_mut(y).iter_mut().enumerate() {
+                *word = op(bit, columns[index]) & masks[index];
+            }
+        }
+        grid
+    }
+
+    /// Returns the outer AND of two vectors, where cell (`j`, `i`) is set if
+    /// bits `a[i]` and `b[j]` are both set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitGrid, BitVec};
+    ///
+    /// let a = BitVec::from_bit_str("101").unwrap();
+    /// let b = BitVec::from_bit_str("0110").unwrap();
+    /// let grid = BitGrid::outer_and(&a, &b);
+    /// assert_eq!((grid.width(), grid.height()), (4, 3));
+    /// assert_eq!(grid.to_bitvec().to_bit_string(), "011000000110");
+    /// ```
+    pub fn outer_and(a: &BitVec, b: &BitVec) -> BitGrid {
+        Self::outer(a, b, |bit, word| if bit { word } else { 0 })
+    }
+
+    /// Returns the outer OR of two vectors, where cell (`j`, `i`) is set if
+    /// either bit `a[i]` or `b[j]` is set
+    pub fn outer_or(a: &BitVec, b: &BitVec) -> BitGrid {
+        Self::outer(a, b, |bit, word| if bit { u64::MAX } else { word })
+    }
+
+    /// Returns the outer XOR of two vectors, where cell (`j`, `i`) is set if
+    /// bits `a[i]` and `b[j]` differ
+    pub fn outer_xor(a: &BitVec, b: &BitVec) -> BitGrid {
+        Self::outer(a, b, |bit, word| if bit { !word } else { word })
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn products_match_cell_by_cell() {
+        // Columns across two words
+        let mut a = BitVec::from_bit_str("1101 0").unwrap();
+        let mut b = BitVec::new();
+        for index in 0..70 {
+            b.push_bit(index % 3 == 0);
+        }
+        a.push_bit(true);
+
+        let products = [
+            (BitGrid::outer_and(&a, &b), (|x, y| x && y) as fn(bool, bool) -> bool),
+            (BitGrid::outer_or(&a, &b), |x, y| x || y),
+            (BitGrid::outer_xor(&a, &b), |x, y| x != y),
+        ];
+        for (grid, op) in products {
+            assert_eq!((grid.width(), grid.height()), (70, 6));
+            for i in 0..6 {
+                for j in 0..70 {
+                    assert_eq!(grid.get(j, i), op(a.get_bit(i), b.get_bit(j)));
+                }
+            }
+        }
+        // Cells past the width stay clear
+        assert_eq!(BitGrid::outer_or(&a, &b).count_ones(), 4 * 70 + 2 * 24);
+    }
+
+    #[test]
+    fn empty_vectors() {
+        let a = BitVec::from_bit_str("11").unwrap();
+        let grid = BitGrid::outer_xor(&a, &BitVec::new());
+        assert_eq!((grid.width(), grid.height()), (0, 2));
+        assert_eq!(BitGrid::outer_and(&BitVec::new(), &a).height(), 0);
+    }
+}
@@ -0,0 +1,180 @@
+//! ## Packed bit-field records
+//! A schema of named fixed-width fields, such as the fields of a protocol
+//! header, that packs values into a BitVec and unpacks them again without
+//! any shift arithmetic. Fields are packed in the order they are declared,
+//! each value most significant bit first.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// A schema of named fixed-width fields, for packing records into BitVecs
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitPacker;
+///
+/// let header = BitPacker::new().field("version", 3).field("type", 5).field("length", 11);
+/// assert_eq!(header.width(), 19);
+///
+/// let mut bv = header.pack(&[4, 17, 1500]).unwrap();
+/// assert_eq!(bv.to_bit_string(), "1001000110111011100");
+///
+/// let fields = header.unpack(&mut bv).unwrap();
+/// assert_eq!(fields.get("type"), Some(17));
+/// assert_eq!(fields.get("length"), Some(1500));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitPacker<'a> {
+    fields: Vec<(&'a str, u32)>,
+}
+
+impl<'a> BitPacker<'a> {
+    /// Constructs a schema with no fields
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Adds a field of `width` bits after the fields declared so far. Panics
+    /// if the width is not 1 to 64 or the name is already used.
+    pub fn field(mut self, name: &'a str, width: u32) -> Self {
+        if !(1..=64).contains(&width) {
+            panic!("BitVec: field width must be 1 to 64")
+        }
+        if self.fields.iter().any(|&(field, _)| field == name) {
+            panic!("BitVec: field name is already used")
+        }
+        self.fields.push((name, width));
+        self
+    }
+
+    /// Get the total width of the fields in bits
+    pub fn width(&self) -> usize {
+        self.fields.iter().map(|&(_, width)| width as usize).sum()
+    }
+
+    /// Get the number of fields
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Checks whether the schema has no fields
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Packs one value for each field, in order, into a new BitVec. Returns
+    /// an error if the number of values does not match the number of fields,
+    /// or a value is too wide for its field.
+    pub fn pack(&self, values: &[u64]) -> Result<BitVec, BitVecError> {
+        let mut bv = BitVec::new();
+        self.pack_into(&mut bv, values)?;
+        Ok(bv)
+    }
+
+    /// Packs one value for each field onto the end of a BitVec, as `pack`.
+    /// The BitVec is left unchanged on an error.
+    pub fn pack_into(&self, bv: &mut BitVec, values: &[u64]) -> Result<(), BitVecError> {
+        if values.len() != self.fields.len() {
+            return Err(BitVecError::LengthMismatch { expected: self.fields.len(), found: values.len() });
+        }
+        for (&(_, width), &value) in self.fields.iter().zip(values) {
+            if width < 64 && value >> width != 0 {
+                return Err(BitVecError::ValueTooWide { value, width });
+            }
+        }
+
+        for (&(_, width), &value) in self.fields.iter().zip(values) {
+            bv.push_low_bits(value, width);
+        }
+        Ok(())
+    }
+
+    /// Unpacks a record from the reading position of a BitVec, moving the
+    /// reading position past it. Returns an error, without moving the
+    /// reading position, if fewer bits remain than the width of the schema.
+    pub fn unpack(&self, bv: &mut BitVec) -> Result<BitUnpacker<'a>, BitVecError> {
+        let available = bv.len_bits().saturating_sub(bv.get_read_position());
+        if available < self.width() {
+            return Err(BitVecError::NotEnoughBits { requested: self.width(), available });
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .map(|&(name, width)| {
+                let value = (0..width).fold(0, |value, _| value << 1 | bv.read_bit_seq().unwrap_or(0) as u64);
+                (name, value)
+            })
+            .collect();
+        Ok(BitUnpacker { fields })
+    }
+}
+
+/// The named field values of a record unpacked by a `BitPacker`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitUnpacker<'a> {
+    fields: Vec<(&'a str, u64)>,
+}
+
+impl<'a> BitUnpacker<'a> {
+    /// Get the value of a field by name, or None if there is no such field
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.fields.iter().find(|&&(field, _)| field == name).map(|&(_, value)| value)
+    }
+
+    /// Get the values of the fields in order
+    pub fn values(&self) -> Vec<u64> {
+        self.fields.iter().map(|&(_, value)| value).collect()
+    }
+
+    /// Returns an iterator over the names and values of the fields in order
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, u64)> + '_ {
+        self.fields.iter().copied()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trip() {
+        let header = BitPacker::new().field("flag", 1).field("id", 12).field("crc", 64);
+        assert_eq!((header.len(), header.width()), (3, 77));
+
+        // A header followed by a payload
+        let mut bv = header.pack(&[1, 0xABC, u64::MAX]).unwrap();
+        bv.push_bit(true);
+        let fields = header.unpack(&mut bv).unwrap();
+        assert_eq!(fields.values(), vec![1, 0xABC, u64::MAX]);
+        assert_eq!(fields.iter().map(|(name, _)| name).collect::<Vec<_>>(), vec!["flag", "id", "crc"]);
+        assert_eq!(fields.get("missing"), None);
+        assert_eq!(bv.get_read_position(), 77);
+        assert_eq!(bv.read_bit_seq(), Some(1));
+    }
+
+    #[test]
+    fn invalid_records() {
+        let header = BitPacker::new().field("a", 4).field("b", 4);
+        assert_eq!(header.pack(&[1]), Err(BitVecError::LengthMismatch { expected: 2, found: 1 }));
+        assert_eq!(header.pack(&[1, 16]), Err(BitVecError::ValueTooWide { value: 16, width: 4 }));
+
+        let mut bv = BitVec::from_bit_str("1111").unwrap();
+        assert!(header.pack_into(&mut bv, &[20, 1]).is_err());
+        assert_eq!(bv.len_bits(), 4);
+        bv.set_read_position(1);
+        assert_eq!(header.unpack(&mut bv), Err(BitVecError::NotEnoughBits { requested: 8, available: 3 }));
+        assert_eq!(bv.get_read_position(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "already used")]
+    fn duplicate_names_panic() {
+        let _ = BitPacker::new().field("a", 1).field("a", 2);
+    }
+}
@@ -0,0 +1,134 @@
+//! ## Parallel bulk operations
+//! Rayon versions of the bulk operations on very long vectors, behind the
+//! `rayon` feature. The bytes are split into blocks that are worked on by
+//! separate threads, so these only pay off on vectors of many megabits; the
+//! single-threaded methods remain the default.
+
+use rayon::prelude::*;
+
+use crate::{popcount, BitVec, BitVecError, LengthPolicy};
+
+/// Bytes worked on by each task
+const BLOCK_BYTES: usize = 1 << 16;
+
+impl BitVec {
+    /// Returns the number of set bits, counting blocks in parallel
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::repeat_bit(true, 1_000_000);
+    /// assert_eq!(bv.par_count_ones(), 1_000_000);
+    /// ```
+    pub fn par_count_ones(&self) -> usize {
+        self.data.par_chunks(BLOCK_BYTES).map(popcount).sum()
+    }
+
+    /// Sets every bit to the same value, filling blocks in parallel
+    pub fn par_fill(&mut self, value: bool) {
+        let byte = if value { 0xFF } else { 0 };
+        self.data.par_chunks_mut(BLOCK_BYTES).for_each(|block| block.fill(byte));
+        self.clear_padding();
+    }
+
+    /// Returns a parallel iterator over the indices of the set bits. The
+    /// indices come out of order unless collected.
+    pub fn par_iter_ones(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        self.data.par_chunks(BLOCK_BYTES).enumerate().flat_map_iter(|(block_idx, block)| {
+            block.iter().enumerate().flat_map(move |(offset, &byte)| {
+                let start = (block_idx * BLOCK_BYTES + offset) * 8;
+                (0..8).filter(move |bit| byte & (0x80 >> bit) != 0).map(move |bit| start + bit)
+            })
+        })
+    }
+
+    /// Resizes the vector according to a policy, then combines blocks of it
+    /// with another vector in parallel, with 0s past the end of the other
+    fn par_combine_with(&mut self, other: &BitVec, policy: LengthPolicy, op: impl Fn(u8, u8) -> u8 + Sync) -> Result<(), BitVecError> {
+        self.resize_for(other, policy)?;
+        self.data.par_chunks_mut(BLOCK_BYTES).enumerate().for_each(|(block_idx, block)| {
+            let start = block_idx * BLOCK_BYTES;
+            for (offset, byte) in block.iter_mut().enumerate() {
+                *byte = op(*byte, other.data.get(start + offset).copied().unwrap_or(0));
+            }
+        });
+        self.clear_padding();
+        Ok(())
+    }
+
+    /// ANDs another vector into this one in parallel, as `and_with`
+    pub fn par_and_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.par_combine_with(other, policy, |a, b| a & b)
+    }
+
+    /// ORs another vector into this one in parallel, as `or_with`
+    pub fn par_or_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.par_combine_with(other, policy, |a, b| a | b)
+    }
+
+    /// XORs another vector into this one in parallel, as `xor_with`
+    pub fn par_xor_with(&mut self, other: &BitVec, policy: LengthPolicy) -> Result<(), BitVecError> {
+        self.par_combine_with(other, policy, |a, b| a ^ b)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vector spanning several blocks, with a ragged end
+    fn long_bitvec() -> BitVec {
+        let pattern = BitVec::from_bit_str("1101 0010 0111 0").unwrap();
+        BitVec::repeat(&pattern, 100_003)
+    }
+
+    #[test]
+    fn counts_and_indices_match_serial() {
+        let bv = long_bitvec();
+        assert_eq!(bv.par_count_ones(), bv.count_ones());
+
+        let mut ones: Vec<usize> = bv.par_iter_ones().collect();
+        ones.sort_unstable();
+        assert_eq!(ones, bv.iter_ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn logical_ops_match_serial() {
+        let a = long_bitvec();
+        let mut b = a.clone();
+        b.shift_right(5);
+        b.truncate(900_000);
+
+        for policy in [LengthPolicy::Truncate, LengthPolicy::Extend] {
+            let (mut serial, mut parallel) = (a.clone(), a.clone());
+            serial.xor_with(&b, policy).unwrap();
+            parallel.par_xor_with(&b, policy).unwrap();
+            assert_eq!(parallel, serial);
+
+            let (mut serial, mut parallel) = (a.clone(), a.clone());
+            serial.and_with(&b, policy).unwrap();
+            parallel.par_and_with(&b, policy).unwrap();
+            assert_eq!(parallel, serial);
+
+            let (mut serial, mut parallel) = (b.clone(), b.clone());
+            serial.or_with(&a, policy).unwrap();
+            parallel.par_or_with(&a, policy).unwrap();
+            assert_eq!(parallel, serial);
+        }
+        assert!(a.clone().par_and_with(&b, LengthPolicy::Reject).is_err());
+    }
+
+    #[test]
+    fn fill_clears_padding() {
+        let mut bv = long_bitvec();
+        bv.par_fill(true);
+        assert_eq!(bv.par_count_ones(), bv.len_bits());
+        bv.par_fill(false);
+        assert_eq!(bv.count_ones(), 0);
+    }
+}
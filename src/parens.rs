@@ -0,0 +1,350 @@
+//! ## Balanced parentheses
+//! Navigation over a sequence of balanced parentheses, 1 for open and 0 for
+//! close, the usual succinct encoding of an ordinal tree in 2 bits a node.
+//! `excess(i)` is the opens minus the closes before index `i`, and
+//! `find_close`, `find_open` and `enclose` find matching and enclosing
+//! parentheses. The methods on BitVec scan the bits, while
+//! `BalancedParens` builds a range min-max tree over a frozen BitVec so each
+//! query skips whole blocks in logarithmic time.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{BitVec, RankSelect};
+
+/// Bits covered by each leaf of the range min-max tree
+const BLOCK_BITS: usize = 64;
+
+/// The change in excess from one parenthesis
+fn step(bits: &BitVec, index: usize) -> isize {
+    if bits.get_bit(index) { 1 } else { -1 }
+}
+
+/// Walks forward from `start` to `end`, returning the first index where the
+/// excess since `start`, counting that index, reaches `need`, or the excess
+/// over the whole range if it never does
+fn scan_forward(bits: &BitVec, start: usize, end: usize, need: isize) -> Result<usize, isize> {
+    let mut excess = 0;
+    for index in start..end {
+        excess += step(bits, index);
+        if excess == need {
+            return Ok(index);
+        }
+    }
+    Err(excess)
+}
+
+/// Walks back from `end` to `start`, returning the first index where the
+/// excess from that index up to `end` reaches `need`, or the excess over the
+/// whole range if it never does
+fn scan_backward(bits: &BitVec, start: usize, end: usize, need: isize) -> Result<usize, isize> {
+    let mut excess = 0;
+    for index in (start..end).rev() {
+        excess += step(bits, index);
+        if excess == need {
+            return Ok(index);
+        }
+    }
+    Err(excess)
+}
+
+impl BitVec {
+    /// Returns the opens minus the closes before the index. Panics if the
+    /// index is beyond the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // (()(()))
+    /// let bv = BitVec::from_bit_str("1101 1000").unwrap();
+    /// assert_eq!(bv.excess(4), 2);
+    /// assert_eq!(bv.find_close(0), Some(7));
+    /// assert_eq!(bv.find_open(6), Some(3));
+    /// assert_eq!(bv.enclose(4), Some(3));
+    /// ```
+    pub fn excess(&self, index: usize) -> isize {
+        2 * self.rank1(index) as isize - index as isize
+    }
+
+    /// Returns the index of the close matching the open at `index`, or None
+    /// if the bit there is a close or is never matched. Panics if the index
+    /// is out of bounds.
+    pub fn find_close(&self, index: usize) -> Option<usize> {
+        if !self.get_bit(index) {
+            return None;
+        }
+        scan_forward(self, index + 1, self.len, -1).ok()
+    }
+
+    /// Returns the index of the open matching the close at `index`, or None
+    /// if the bit there is an open or is never matched. Panics if the index
+    /// is out of bounds.
+    pub fn find_open(&self, index: usize) -> Option<usize> {
+        if self.get_bit(index) {
+            return None;
+        }
+        scan_backward(self, 0, index, 1).ok()
+    }
+
+    /// Returns the index of the open of the nearest pair enclosing the pair
+    /// opened at `index`, the parent of a node, or None if the bit there is
+    /// a close or no pair encloses it. Panics if the index is out of bounds.
+    pub fn enclose(&self, index: usize) -> Option<usize> {
+        if !self.get_bit(index) {
+            return None;
+        }
+        scan_backward(self, 0, index, 1).ok()
+    }
+}
+
+/// A range min-max tree over a frozen BitVec of parentheses, for fast
+/// `find_close`, `find_open` and `enclose`, with `RankSelect` for `excess`.
+///
+/// Each leaf covers a 64-bit block, and each node keeps the excess over its
+/// blocks with the lowest excess any prefix of them reaches and the highest
+/// any suffix does, so a search descends past whole subtrees the answer
+/// cannot lie in.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BalancedParens, BitVec};
+///
+/// // 1000 nested pairs
+/// let mut bv = BitVec::repeat_bit(true, 1000);
+/// bv.extend(&BitVec::repeat_bit(false, 1000));
+/// let parens = BalancedParens::new(bv);
+/// assert_eq!(parens.find_close(0), Some(1999));
+/// assert_eq!(parens.find_open(1000), Some(999));
+/// assert_eq!(parens.enclose(500), Some(499));
+/// ```
+pub struct BalancedParens {
+    rank:       RankSelect,
+    leaves:     usize,       // leaves in the tree, a power of 2
+    sums:       Vec<isize>,  // excess over each node, root at 1
+    min_prefix: Vec<isize>,  // lowest excess of a prefix of each node
+    max_suffix: Vec<isize>,  // highest excess of a suffix of each node
+}
+
+impl BalancedParens {
+    /// Builds the index over a BitVec
+    pub fn new(bits: BitVec) -> Self {
+        let blocks = bits.len.div_ceil(BLOCK_BITS);
+        let leaves = blocks.next_power_of_two();
+        // Empty leaves can never be searched into
+        let mut sums = vec![0; 2 * leaves];
+        let mut min_prefix = vec![isize::MAX; 2 * leaves];
+        let mut max_suffix = vec![isize::MIN; 2 * leaves];
+
+        for block in 0..blocks {
+            let (start, end) = (block * BLOCK_BITS, ((block + 1) * BLOCK_BITS).min(bits.len));
+            let (mut excess, mut lowest) = (0, isize::MAX);
+            for index in start..end {
+                excess += step(&bits, index);
+                lowest = lowest.min(excess);
+            }
+            let (mut suffix, mut highest) = (0, isize::MIN);
+            for index in (start..end).rev() {
+                suffix += step(&bits, index);
+                highest = highest.max(suffix);
+            }
+            (sums[leaves + block], min_prefix[leaves + block], max_suffix[leaves + block]) = (excess, lowest, highest);
+        }
+        for node in (1..leaves).rev() {
+            let (left, right) = (2 * node, 2 * node + 1);
+            sums[node] = sums[left] + sums[right];
+            min_prefix[node] = min_prefix[left].min(sums[left].saturating_add(min_prefix[right]));
+            max_suffix[node] = max_suffix[right].max(sums[right].saturating_add(max_suffix[left]));
+        }
+
+        Self { rank: RankSelect::new(bits), leaves, sums, min_prefix, max_suffix }
+    }
+
+    /// Returns the indexed BitVec
+    pub fn bits(&self) -> &BitVec {
+        self.rank.bits()
+    }
+
+    /// Unwraps the index, returning the BitVec
+    pub fn into_inner(self) -> BitVec {
+        self.rank.into_inner()
+    }
+
+    /// Finds the first block from `from` where the excess since its start
+    /// reaches `need`, taking off the excess of every block passed over
+    fn search_forward(&self, node: usize, span: (usize, usize), from: usize, need: &mut isize) -> Option<usize> {
+        let (lo, hi) = span;
+        if hi <= from {
+            return None;
+        }
+        if lo >= from && self.min_prefix[node] > *need {
+            *need -= self.sums[node];
+            return None;
+        }
+        if hi - lo == 1 {
+            return Some(lo);
+        }
+        let mid = (lo + hi) / 2;
+        self.search_forward(2 * node, (lo, mid), from, need)
+            .or_else(|| self.search_forward(2 * node + 1, (mid, hi), from, need))
+    }
+
+    /// Finds the last block before `to` where the excess from some index in
+    /// it up to its end reaches `need`, taking off the excess of every block
+    /// passed over
+    fn search_backward(&self, node: usize, span: (usize, usize), to: usize, need: &mut isize) -> Option<usize> {
+        let (lo, hi) = span;
+        if lo >= to {
+            return None;
+        }
+        if hi <= to && self.max_suffix[node] < *need {
+            *need -= self.sums[node];
+            return None;
+        }
+        if hi - lo == 1 {
+            return Some(lo);
+        }
+        let mid = (lo + hi) / 2;
+        self.search_backward(2 * node + 1, (mid, hi), to, need)
+            .or_else(|| self.search_backward(2 * node, (lo, mid), to, need))
+    }
+
+    /// Finds the first index from `start` where the excess reaches `need`
+    fn forward(&self, start: usize, need: isize) -> Option<usize> {
+        let bits = self.bits();
+        let block_end = (start / BLOCK_BITS + 1) * BLOCK_BITS;
+        let mut need = match scan_forward(bits, start, block_end.min(bits.len), need) {
+            Ok(index) => return Some(index),
+            Err(excess) => need - excess,
+        };
+        let block = self.search_forward(1, (0, self.leaves), block_end / BLOCK_BITS, &mut need)?;
+        let start = block * BLOCK_BITS;
+        scan_forward(bits, start, (start + BLOCK_BITS).min(bits.len), need).ok()
+    }
+
+    /// Finds the last index before `end` where the excess up to `end`
+    /// reaches `need`
+    fn backward(&self, end: usize, need: isize) -> Option<usize> {
+        let bits = self.bits();
+        let block_start = end / BLOCK_BITS * BLOCK_BITS;
+        let mut need = match scan_backward(bits, block_start, end, need) {
+            Ok(index) => return Some(index),
+            Err(excess) => need - excess,
+        };
+        let block = self.search_backward(1, (0, self.leaves), block_start / BLOCK_BITS, &mut need)?;
+        let start = block * BLOCK_BITS;
+        scan_backward(bits, start, start + BLOCK_BITS, need).ok()
+    }
+
+    /// Returns the opens minus the closes before the index. Panics if the
+    /// index is beyond the length of the vector.
+    pub fn excess(&self, index: usize) -> isize {
+        2 * self.rank.rank1(index) as isize - index as isize
+    }
+
+    /// Returns the index of the close matching the open at `index`, or None
+    /// if the bit there is a close or is never matched. Panics if the index
+    /// is out of bounds.
+    pub fn find_close(&self, index: usize) -> Option<usize> {
+        if !self.bits().get_bit(index) {
+            return None;
+        }
+        self.forward(index + 1, -1)
+    }
+
+    /// Returns the index of the open matching the close at `index`, or None
+    /// if the bit there is an open or is never matched. Panics if the index
+    /// is out of bounds.
+    pub fn find_open(&self, index: usize) -> Option<usize> {
+        if self.bits().get_bit(index) {
+            return None;
+        }
+        self.backward(index, 1)
+    }
+
+    /// Returns the index of the open of the nearest pair enclosing the pair
+    /// opened at `index`, or None if the bit there is a close or no pair
+    /// encloses it. Panics if the index is out of bounds.
+    pub fn enclose(&self, index: usize) -> Option<usize> {
+        if !self.bits().get_bit(index) {
+            return None;
+        }
+        self.backward(index, 1)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tree of nested and side by side pairs, unbalanced at the end, long
+    /// enough for the searches to cross many blocks
+    fn sample() -> BitVec {
+        let mut bv = BitVec::new();
+        for round in 0..40 {
+            for _ in 0..round {
+                bv.push_bit(true);
+            }
+            for _ in 0..round / 2 {
+                bv.extend(&BitVec::from_bit_str("10").unwrap());
+            }
+            for _ in 0..round {
+                bv.push_bit(false);
+            }
+        }
+        // Wraps everything, then opens a pair it never closes
+        let mut wrapped = BitVec::from_bit_str("1").unwrap();
+        wrapped.extend(&bv);
+        wrapped.extend(&BitVec::from_bit_str("0 11 0").unwrap());
+        wrapped
+    }
+
+    #[test]
+    fn scanning() {
+        // (()(()))
+        let bv = BitVec::from_bit_str("1101 1000").unwrap();
+        assert_eq!((0..=8).map(|index| bv.excess(index)).collect::<Vec<_>>(), vec![0, 1, 2, 1, 2, 3, 2, 1, 0]);
+        assert_eq!(bv.find_close(1), Some(2));
+        assert_eq!(bv.find_close(2), None);
+        assert_eq!(bv.find_open(7), Some(0));
+        assert_eq!(bv.find_open(0), None);
+        assert_eq!(bv.enclose(0), None);
+        assert_eq!(bv.enclose(1), Some(0));
+
+        // Unmatched parentheses
+        let open = BitVec::from_bit_str("110").unwrap();
+        assert_eq!(open.find_close(0), None);
+        assert_eq!(open.find_close(1), Some(2));
+        assert_eq!(BitVec::from_bit_str("0").unwrap().find_open(0), None);
+    }
+
+    #[test]
+    fn index_matches_scanning() {
+        let bv = sample();
+        assert!(bv.len_bits() > 20 * BLOCK_BITS);
+        let parens = BalancedParens::new(bv.clone());
+        for index in 0..bv.len_bits() {
+            assert_eq!(parens.excess(index), bv.excess(index));
+            assert_eq!(parens.find_close(index), bv.find_close(index), "find_close({})", index);
+            assert_eq!(parens.find_open(index), bv.find_open(index), "find_open({})", index);
+            assert_eq!(parens.enclose(index), bv.enclose(index), "enclose({})", index);
+        }
+        assert_eq!(parens.find_close(0), Some(bv.len_bits() - 4));
+        assert_eq!(parens.find_close(bv.len_bits() - 3), None);
+        assert_eq!(parens.into_inner(), bv);
+    }
+
+    #[test]
+    fn small_and_empty() {
+        let parens = BalancedParens::new(BitVec::from_bit_str("10").unwrap());
+        assert_eq!(parens.find_close(0), Some(1));
+        assert_eq!(parens.find_open(1), Some(0));
+        assert_eq!(parens.excess(2), 0);
+        assert_eq!(BalancedParens::new(BitVec::new()).excess(0), 0);
+    }
+}
@@ -0,0 +1,212 @@
+//! ## Parquet RLE/bit-packed hybrid
+//! The encoding Parquet uses for definition and repetition levels, and for
+//! dictionary indices. A run starts with a ULEB128 header: an even header
+//! is an RLE run of `header >> 1` copies of one value, stored in whole
+//! little-endian bytes, and an odd header is `header >> 1` groups of 8
+//! values, bit-packed LSB first.
+//!
+//! On the BitVec side, each value is `bit_width` bits, MSB first, one after
+//! another, so with a bit width of 1 the BitVec is simply the bitmap.
+
+use alloc::{vec, vec::Vec};
+use crate::BitVec;
+
+/// Pushes a run header, an unsigned LEB128 varint
+fn push_header(out: &mut Vec<u8>, header: usize) {
+    let mut varint = BitVec::new();
+    varint.push_varint(header as u64);
+    out.extend(varint.data);
+}
+
+impl BitVec {
+    /// Reads the value of `bit_width` bits at an index, MSB first
+    fn parquet_value(&self, index: usize, bit_width: usize) -> u32 {
+        (index * bit_width..(index + 1) * bit_width)
+            .fold(0, |value, bit| (value << 1) | self.get_bit(bit) as u32)
+    }
+
+    /// Decodes `count` values of `bit_width` bits (1 to 32) from the
+    /// RLE/bit-packed hybrid encoding. Returns None if the data runs out
+    /// before `count` values, or the bit width is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 3 defined values, then 2 nulls
+    /// let bv = BitVec::from_parquet_rle(&[0x06, 0x01, 0x04, 0x00], 1, 5).unwrap();
+    /// assert_eq!(bv.as_binary(), "11100000");
+    /// ```
+    pub fn from_parquet_rle(bytes: &[u8], bit_width: u8, count: usize) -> Option<BitVec> {
+        if !(1..=32).contains(&bit_width) {
+            return None;
+        }
+        let bit_width = bit_width as usize;
+        let value_bytes = bit_width.div_ceil(8);
+        let mut decoded = BitVec::new();
+        let mut remaining = count;
+        let mut position = 0;
+        // The run headers are read as varints from here
+        let mut headers = BitVec::from(bytes);
+
+        while remaining > 0 {
+            if !headers.set_read_position(position * 8) {
+                return None;
+            }
+            let header = usize::try_from(headers.read_varint()?).ok()?;
+            position = headers.get_read_position() / 8;
+            if header & 1 == 0 {
+                // RLE run
+                let run = (header >> 1).min(remaining);
+                let value_end = position.checked_add(value_bytes)?;
+                let value = bytes.get(position..value_end)?
+                    .iter()
+                    .rev()
+                    .fold(0u32, |value, &byte| (value << 8) | byte as u32);
+                position = value_end;
+                for _ in 0..run {
+                    decoded.push_low_bits(value as u64, bit_width as u32);
+                }
+                remaining -= run;
+            } else {
+                // Bit-packed groups of 8 values
+                let num_values = (header >> 1).checked_mul(8)?;
+                let packed_end = position.checked_add((header >> 1).checked_mul(bit_width)?)?;
+                let packed = bytes.get(position..packed_end)?;
+                position = packed_end;
+                for index in 0..num_values.min(remaining) {
+                    let value = (0..bit_width).fold(0u32, |value, bit| {
+                        let bit_pos = index * bit_width + bit;
+                        value | ((((packed[bit_pos / 8] >> (bit_pos % 8)) & 1) as u32) << bit)
+                    });
+                    decoded.push_low_bits(value as u64, bit_width as u32);
+                }
+                remaining -= num_values.min(remaining);
+            }
+        }
+
+        Some(decoded)
+    }
+
+    /// Encodes the vector as values of `bit_width` bits (1 to 32) in the
+    /// RLE/bit-packed hybrid encoding. Runs of 8 or more repeated values are
+    /// RLE coded and the rest are bit-packed, with the last group padded
+    /// with 0s. Panics if the bit width is out of range or does not divide
+    /// the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0xFF; 4]);
+    /// assert_eq!(bv.to_parquet_rle(1), vec![0x40, 0x01]);
+    /// ```
+    pub fn to_parquet_rle(&self, bit_width: u8) -> Vec<u8> {
+        if !(1..=32).contains(&bit_width) || !self.len.is_multiple_of(bit_width as usize) {
+            panic!("BitVec: invalid bit width for the length")
+        }
+        let bit_width = bit_width as usize;
+        let value_bytes = bit_width.div_ceil(8);
+        let num_values = self.len / bit_width;
+        let mut out = Vec::new();
+        let mut literals: Vec<u32> = Vec::new();
+
+        // Writes out the pending literals, which must be whole groups of 8
+        // unless they are the last values
+        let flush = |out: &mut Vec<u8>, literals: &mut Vec<u32>| {
+            if literals.is_empty() {
+                return;
+            }
+            let groups = literals.len().div_ceil(8);
+            push_header(out, (groups << 1) | 1);
+            let mut packed = vec![0u8; groups * bit_width];
+            for (index, &value) in literals.iter().enumerate() {
+                for bit in 0..bit_width {
+                    let bit_pos = index * bit_width + bit;
+                    packed[bit_pos / 8] |= (((value >> bit) & 1) as u8) << (bit_pos % 8);
+                }
+            }
+            out.extend(packed);
+            literals.clear();
+        };
+
+        let mut index = 0;
+        while index < num_values {
+            let value = self.parquet_value(index, bit_width);
+            let mut run = 1;
+            while index + run < num_values && self.parquet_value(index + run, bit_width) == value {
+                run += 1;
+            }
+
+            // Top the literals up to a whole group from the run first
+            let borrowed = if run >= 8 { (8 - literals.len() % 8) % 8 } else { 0 };
+            if run - borrowed >= 8 {
+                literals.extend(core::iter::repeat_n(value, borrowed));
+                flush(&mut out, &mut literals);
+                push_header(&mut out, (run - borrowed) << 1);
+                out.extend(&value.to_le_bytes()[..value_bytes]);
+            } else {
+                literals.extend(core::iter::repeat_n(value, run));
+            }
+            index += run;
+        }
+        flush(&mut out, &mut literals);
+
+        out
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_example() {
+        // Values 0 to 7 with a bit width of 3, from the Parquet documentation
+        let bv = BitVec::from_parquet_rle(&[0x03, 0x88, 0xC6, 0xFA], 3, 8).unwrap();
+        let values: Vec<u32> = (0..8).map(|i| bv.parquet_value(i, 3)).collect();
+        assert_eq!(values, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(bv.to_parquet_rle(3), vec![0x03, 0x88, 0xC6, 0xFA]);
+    }
+
+    #[test]
+    fn mixed_runs_round_trip() {
+        let mut bv = BitVec::new();
+        let values = [5, 1, 1, 9, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 3, 0, 300, 300];
+        for &value in &values {
+            bv.push_low_bits(value as u64, 10);
+        }
+        let encoded = bv.to_parquet_rle(10);
+        // Literals topped up to a group of 8, then an RLE run of 8
+        assert_eq!(encoded[0], 0x03);
+        assert_eq!(&encoded[11..14], &[0x10, 0x01, 0x00]);
+        assert_eq!(BitVec::from_parquet_rle(&encoded, 10, values.len()).unwrap(), bv);
+    }
+
+    #[test]
+    fn long_bitmaps() {
+        let mut bv = BitVec::from(&[0xFF; 40]);
+        bv.extend(&BitVec::from(&[0x5A; 3]));
+        let encoded = bv.to_parquet_rle(1);
+        // An RLE run of 320 set bits, then 3 groups of literals
+        assert_eq!(&encoded[..3], &[0x80, 0x05, 0x01]);
+        assert_eq!(&encoded[3..], &[0x07, 0x5A, 0x5A, 0x5A]);
+        assert_eq!(BitVec::from_parquet_rle(&encoded, 1, 344).unwrap(), bv);
+    }
+
+    #[test]
+    fn truncated_input() {
+        assert!(BitVec::from_parquet_rle(&[0x03, 0x88, 0xC6], 3, 8).is_none());
+        assert!(BitVec::from_parquet_rle(&[0x10], 1, 8).is_none());
+        assert!(BitVec::from_parquet_rle(&[0x80], 1, 8).is_none());
+        // A whole run of 2, then nothing left for the third value
+        assert!(BitVec::from_parquet_rle(&[0x04, 0x01], 1, 3).is_none());
+        assert!(BitVec::from_parquet_rle(&[0x02, 0x01], 33, 1).is_none());
+        assert_eq!(BitVec::from_parquet_rle(&[], 1, 0).unwrap().len_bits(), 0);
+    }
+}
@@ -0,0 +1,298 @@
+//! ## Persistent bitmaps
+//! A bitmap held in memory and backed by storage split into fixed-size
+//! pages, such as a file or an SQLite blob, so a large mutable bitmap
+//! survives restarts. The whole bitmap is loaded when opened, and each
+//! change marks its page dirty, so `flush` writes back only the pages that
+//! changed rather than the whole bitmap.
+//!
+//! With a journal, from `open_with_journal`, changes are also recorded
+//! there, and reach it before any page is written back, so a crash part way
+//! through a flush cannot leave a mix of old and new pages behind.
+//!
+//! The storage holds the bits packed MSB first, as `as_bytes` would give
+//! them, page after page, with the last page padded with 0s. Pages past the
+//! end of the storage read as clear.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::RangeBounds;
+
+use crate::slice::resolve_range;
+use crate::{BitSlice, BitVec, JournalStorage};
+
+/// Storage read and written a page at a time
+pub trait PageStorage {
+    /// Reads page `index` into `page`, which is a whole page, returning the
+    /// number of bytes read: fewer than the page size only if the storage
+    /// ends part way through it, and 0 if the page is past the end
+    fn read_page(&mut self, index: usize, page: &mut [u8]) -> io::Result<usize>;
+
+    /// Writes page `index`, which is a whole page, growing the storage if
+    /// needed
+    fn write_page(&mut self, index: usize, page: &[u8]) -> io::Result<()>;
+
+    /// Makes the pages written so far durable
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl PageStorage for Vec<u8> {
+    fn read_page(&mut self, index: usize, page: &mut [u8]) -> io::Result<usize> {
+        let start = (index * page.len()).min(self.len());
+        let read = (self.len() - start).min(page.len());
+        page[..read].copy_from_slice(&self[start..start + read]);
+        Ok(read)
+    }
+
+    fn write_page(&mut self, index: usize, page: &[u8]) -> io::Result<()> {
+        let start = index * page.len();
+        if self.len() < start + page.len() {
+            self.resize(start + page.len(), 0);
+        }
+        self[start..start + page.len()].copy_from_slice(page);
+        Ok(())
+    }
+}
+
+impl PageStorage for File {
+    fn read_page(&mut self, index: usize, page: &mut [u8]) -> io::Result<usize> {
+        self.seek(SeekFrom::Start((index * page.len()) as u64))?;
+        let mut read = 0;
+        while read < page.len() {
+            match self.read(&mut page[read..])? {
+                0 => break,
+                count => read += count,
+            }
+        }
+        Ok(read)
+    }
+
+    fn write_page(&mut self, index: usize, page: &[u8]) -> io::Result<()> {
+        self.seek(SeekFrom::Start((index * page.len()) as u64))?;
+        self.write_all(page)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+/// A fixed-length bitmap loaded from paged storage, writing back only the
+/// pages changed
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::PersistentBitVec;
+///
+/// let mut bitmap = PersistentBitVec::open(Vec::new(), 100_000, 4096).unwrap();
+/// bitmap.set_bit(70_000, true);
+/// assert_eq!(bitmap.dirty_pages(), 1);
+/// bitmap.flush().unwrap();
+///
+/// let bitmap = PersistentBitVec::open(bitmap.into_storage(), 100_000, 4096).unwrap();
+/// assert!(bitmap.get_bit(70_000));
+/// ```
+pub struct PersistentBitVec<S: PageStorage> {
+    pub(crate) storage:    S,
+    pub(crate) bits:       BitVec,
+    pub(crate) page_bytes: usize,
+    pub(crate) dirty:      BitVec,     // one bit for each page
+    pub(crate) journal:    Option<Box<dyn JournalStorage>>,
+    pub(crate) pending:    Vec<u8>,    // records not yet in the journal
+}
+
+impl<S: PageStorage> PersistentBitVec<S> {
+    /// Loads a bitmap of `len` bits from storage in pages of `page_bytes`
+    /// bytes. Panics if the page size is 0.
+    pub fn open(mut storage: S, len: usize, page_bytes: usize) -> io::Result<Self> {
+        if page_bytes == 0 {
+            panic!("BitVec: page size cannot be 0")
+        }
+
+        let mut bits = BitVec::with_capacity(len);
+        bits.resize(len, false);
+        let pages = bits.data.len().div_ceil(page_bytes);
+        let mut page = vec![0; page_bytes];
+        for (index, bytes) in bits.data.chunks_mut(page_bytes).enumerate() {
+            // A short read leaves the rest of the page clear
+            let read = storage.read_page(index, &mut page)?;
+            page[read..].fill(0);
+            bytes.copy_from_slice(&page[..bytes.len()]);
+        }
+        bits.clear_padding();
+
+        let mut dirty = BitVec::with_capacity(pages);
+        dirty.resize(pages, false);
+        Ok(Self { storage, bits, page_bytes, dirty, journal: None, pending: Vec::new() })
+    }
+
+    /// Get the number of bits in the bitmap
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    /// Checks if the bitmap holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.bits.len == 0
+    }
+
+    /// Returns a view of the bitmap
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        self.bits.as_bitslice()
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// out of bounds.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.bits.as_bitslice().get_bit(index)
+    }
+
+    /// Sets the bit at the desired index, marking its page dirty. Panics if
+    /// the index is out of bounds, as the bitmap cannot grow.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        self.bits.slice_mut(..).set_bit(index, value);
+        self.mark_dirty(index, index + 1);
+        self.record(index, index + 1, value);
+    }
+
+    /// Sets every bit in a range, marking their pages dirty. Panics if the
+    /// range runs past the end.
+    pub fn fill_range<R: RangeBounds<usize>>(&mut self, range: R, value: bool) {
+        let (start, end) = resolve_range(range, self.bits.len);
+        self.bits.slice_mut(start..end).fill(value);
+        self.mark_dirty(start, end);
+        self.record(start, end, value);
+    }
+
+    /// Marks the pages holding the bits from `start` to `end` dirty
+    pub(crate) fn mark_dirty(&mut self, start: usize, end: usize) {
+        if start < end {
+            let pages = start / 8 / self.page_bytes..(end - 1) / 8 / self.page_bytes + 1;
+            self.dirty.slice_mut(pages).fill(true);
+        }
+    }
+
+    /// Get the number of pages changed since the last flush
+    pub fn dirty_pages(&self) -> usize {
+        self.dirty.count_ones_in(..)
+    }
+
+    /// Writes every dirty page back to the storage, then flushes the storage.
+    /// With a journal, this is a checkpoint: the changes are committed to
+    /// the journal first, and the journal is emptied once the pages are
+    /// durable.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.commit()?;
+        let dirty: Vec<usize> = self.dirty.iter_ones().collect();
+        let mut page = vec![0; self.page_bytes];
+        for index in dirty {
+            let bytes = self.bits.data.chunks(self.page_bytes).nth(index).unwrap_or_default();
+            page[..bytes.len()].copy_from_slice(bytes);
+            page[bytes.len()..].fill(0);
+            self.storage.write_page(index, &page)?;
+            self.dirty.set_bit(index, false);
+        }
+        self.storage.flush()?;
+        self.clear_journal()
+    }
+
+    /// Returns the storage
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Returns the storage, dropping any changes not flushed
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+}
+
+impl<S: PageStorage + fmt::Debug> fmt::Debug for PersistentBitVec<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentBitVec")
+            .field("storage", &self.storage)
+            .field("bits", &self.bits)
+            .field("page_bytes", &self.page_bytes)
+            .field("dirty", &self.dirty)
+            .finish_non_exhaustive()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Storage counting the pages written
+    struct Counting {
+        bytes:  Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl PageStorage for Counting {
+        fn read_page(&mut self, index: usize, page: &mut [u8]) -> io::Result<usize> {
+            self.bytes.read_page(index, page)
+        }
+
+        fn write_page(&mut self, index: usize, page: &[u8]) -> io::Result<()> {
+            self.writes.push(index);
+            self.bytes.write_page(index, page)
+        }
+    }
+
+    #[test]
+    fn writes_back_dirty_pages() {
+        let storage = Counting { bytes: vec![0x80, 0, 0, 0, 0, 0, 0, 0, 0xFF], writes: Vec::new() };
+        // 5 pages of 2 bytes, the last holding 1 bit
+        let mut bitmap = PersistentBitVec::open(storage, 65, 2).unwrap();
+        assert_eq!(bitmap.len(), 65);
+        assert_eq!(bitmap.as_bitslice().count_ones(), 2);
+
+        bitmap.set_bit(0, false);
+        bitmap.set_bit(1, true);
+        bitmap.fill_range(30..34, true);
+        bitmap.fill_range(40..40, true);
+        assert_eq!(bitmap.dirty_pages(), 3);
+        bitmap.flush().unwrap();
+        assert_eq!(bitmap.dirty_pages(), 0);
+        assert_eq!(bitmap.storage().writes, vec![0, 1, 2]);
+
+        bitmap.set_bit(64, false);
+        bitmap.flush().unwrap();
+        let storage = bitmap.into_storage();
+        assert_eq!(storage.writes, vec![0, 1, 2, 4]);
+        assert_eq!(storage.bytes, vec![0x40, 0, 0, 0x03, 0xC0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn pages_past_the_end_read_clear() {
+        let mut bitmap = PersistentBitVec::open(Vec::new(), 20, 4).unwrap();
+        assert_eq!(bitmap.as_bitslice().count_ones(), 0);
+        bitmap.set_bit(19, true);
+        bitmap.flush().unwrap();
+        assert_eq!(bitmap.storage(), &vec![0, 0, 0x10, 0]);
+        assert!(PersistentBitVec::open(Vec::new(), 0, 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn file_storage() {
+        let path = std::env::temp_dir().join(format!("bitvecs-persist-{}.bits", std::process::id()));
+        let file = File::options().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+        let mut bitmap = PersistentBitVec::open(file, 1000, 16).unwrap();
+        bitmap.fill_range(500..520, true);
+        bitmap.flush().unwrap();
+        drop(bitmap);
+
+        let file = File::options().read(true).write(true).open(&path).unwrap();
+        let bitmap = PersistentBitVec::open(file, 1000, 16).unwrap();
+        assert_eq!(bitmap.as_bitslice().count_ones(), 20);
+        assert!(bitmap.get_bit(519));
+        drop(bitmap);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
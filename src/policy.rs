@@ -0,0 +1,168 @@
+//! ## Misuse policy
+//! Embedded hot paths want misuse, such as an index out of bounds, to panic
+//! and return plain values, while defensive server code wants a Result it
+//! can handle. A misuse policy picks one as a type parameter, so code
+//! generic over the policy is written once and serves both. Each operation
+//! is implemented once by its fallible `try_` form, which the policy either
+//! unwraps or passes through. The trait is sealed: `Panic` and `Checked` are
+//! the only policies.
+
+use core::marker::PhantomData;
+
+use crate::{BitVec, BitVecError};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// How an operation reports misuse
+pub trait MisusePolicy: sealed::Sealed {
+    /// What an operation producing a `T` returns
+    type Output<T>;
+
+    /// Turns the result of an operation into its output
+    fn apply<T>(result: Result<T, BitVecError>) -> Self::Output<T>;
+}
+
+/// Misuse panics, and operations return plain values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Panic {}
+
+/// Misuse is an error, and operations return a Result
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Checked {}
+
+impl sealed::Sealed for Panic {}
+impl sealed::Sealed for Checked {}
+
+impl MisusePolicy for Panic {
+    type Output<T> = T;
+
+    #[inline]
+    fn apply<T>(result: Result<T, BitVecError>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(err) => panic!("BitVec: {}", err),
+        }
+    }
+}
+
+impl MisusePolicy for Checked {
+    type Output<T> = Result<T, BitVecError>;
+
+    #[inline]
+    fn apply<T>(result: Result<T, BitVecError>) -> Result<T, BitVecError> {
+        result
+    }
+}
+
+/// A BitVec borrowed under a misuse policy
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, Checked, MisusePolicy, Panic};
+///
+/// // Written once, for either policy
+/// fn first_byte<P: MisusePolicy>(bv: &mut BitVec) -> P::Output<u8> {
+///     bv.with_policy::<P>().read_byte()
+/// }
+///
+/// let mut bv = BitVec::from(&[0xA5]);
+/// assert_eq!(first_byte::<Panic>(&mut bv), 0xA5);
+/// assert!(first_byte::<Checked>(&mut bv).is_err());
+/// ```
+#[derive(Debug)]
+pub struct WithPolicy<'a, P: MisusePolicy> {
+    bv:     &'a mut BitVec,
+    policy: PhantomData<P>,
+}
+
+impl<P: MisusePolicy> WithPolicy<'_, P> {
+    /// Returns the bit value at the desired index
+    pub fn get_bit(&self, index: usize) -> P::Output<bool> {
+        P::apply(self.bv.try_get_bit(index))
+    }
+
+    /// Sets the bit at the desired index. Whatever the growth policy, the
+    /// vector is not grown, and an index beyond the end is misuse.
+    pub fn set_bit(&mut self, index: usize, value: bool) -> P::Output<()> {
+        P::apply(self.bv.try_set_bit(index, value))
+    }
+
+    /// Removes and returns the last bit, where an empty vector is misuse
+    pub fn pop_bit(&mut self) -> P::Output<bool> {
+        P::apply(self.bv.try_pop_bit())
+    }
+
+    /// Removes and returns the last contiguous byte, as `pop_byte` does,
+    /// where an empty vector is misuse
+    pub fn pop_byte(&mut self) -> P::Output<u8> {
+        P::apply(self.bv.try_pop_byte())
+    }
+
+    /// Sequentially reads the next 8 bits as a byte, where fewer than 8
+    /// bits left is misuse
+    pub fn read_byte(&mut self) -> P::Output<u8> {
+        P::apply(self.bv.try_read_byte())
+    }
+
+    /// Sets the reading position in bits, where a position beyond the end
+    /// is misuse
+    pub fn set_read_position(&mut self, bit_idx: usize) -> P::Output<()> {
+        P::apply(self.bv.try_set_read_position(bit_idx))
+    }
+}
+
+impl BitVec {
+    /// Borrows the vector under a misuse policy, `Panic` or `Checked`
+    pub fn with_policy<P: MisusePolicy>(&mut self) -> WithPolicy<'_, P> {
+        WithPolicy { bv: self, policy: PhantomData }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads one flag, written once for both policies
+    fn flag<P: MisusePolicy>(bv: &mut BitVec, index: usize) -> P::Output<bool> {
+        bv.with_policy::<P>().get_bit(index)
+    }
+
+    #[test]
+    fn checked() {
+        let mut bv = BitVec::from_bit_str("101").unwrap();
+        assert_eq!(flag::<Checked>(&mut bv, 2), Ok(true));
+        assert_eq!(flag::<Checked>(&mut bv, 3), Err(BitVecError::IndexOutOfBounds { index: 3, len: 3 }));
+
+        let mut checked = bv.with_policy::<Checked>();
+        assert_eq!(checked.set_bit(5, true), Err(BitVecError::IndexOutOfBounds { index: 5, len: 3 }));
+        assert_eq!(checked.set_bit(1, true), Ok(()));
+        assert_eq!(checked.set_read_position(4), Err(BitVecError::IndexOutOfBounds { index: 4, len: 3 }));
+        assert_eq!(checked.read_byte(), Err(BitVecError::NotEnoughBits { requested: 8, available: 3 }));
+        assert_eq!(checked.pop_bit(), Ok(true));
+        assert_eq!(checked.pop_byte(), Ok(0xC0));
+        assert_eq!(checked.pop_bit(), Err(BitVecError::Empty));
+        assert_eq!(bv.len_bits(), 0);
+    }
+
+    #[test]
+    fn panicking() {
+        let mut bv = BitVec::from(&[0x0F]);
+        assert!(!flag::<Panic>(&mut bv, 0));
+        let mut plain = bv.with_policy::<Panic>();
+        plain.set_bit(0, true);
+        assert_eq!(plain.read_byte(), 0x8F);
+        assert!(plain.pop_bit());
+    }
+
+    #[test]
+    #[should_panic(expected = "BitVec: index 8 out of bounds for length 8")]
+    fn panics_with_the_error() {
+        flag::<Panic>(&mut BitVec::from(&[0]), 8);
+    }
+}
@@ -0,0 +1,250 @@
+//! ## Polynomials over GF(2)
+//! Treating a BitVec as a binary polynomial, as in CRC and LFSR design. The
+//! first bit is the coefficient of the highest power and the last bit is the
+//! constant term, so `100000111` is `x^8 + x^2 + x + 1`.
+//!
+//! The coefficients are packed into 64-bit words, lowest power first, and
+//! multiplied a word at a time with carry-less multiplication, using the
+//! PCLMULQDQ instruction when the target has it.
+
+use alloc::{vec, vec::Vec};
+
+use crate::BitVec;
+
+/// Bits in each word of a polynomial
+const WORD_BITS: usize = 64;
+
+/// Multiplies two words without carries
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", target_feature = "pclmulqdq"))]
+fn clmul(a: u64, b: u64) -> u128 {
+    use core::arch::x86_64::{_mm_clmulepi64_si128, _mm_cvtsi128_si64, _mm_set_epi64x, _mm_srli_si128};
+
+    // SAFETY: the build enables both target features, as checked by the cfg
+    let (low, high) = unsafe {
+        let product = _mm_clmulepi64_si128(_mm_set_epi64x(0, a as i64), _mm_set_epi64x(0, b as i64), 0);
+        (_mm_cvtsi128_si64(product) as u64, _mm_cvtsi128_si64(_mm_srli_si128(product, 8)) as u64)
+    };
+    (high as u128) << 64 | low as u128
+}
+
+/// Multiplies two words without carries, one set bit at a time
+#[cfg(not(all(target_arch = "x86_64", target_feature = "sse2", target_feature = "pclmulqdq")))]
+fn clmul(a: u64, b: u64) -> u128 {
+    let mut product = 0;
+    let mut remaining = b;
+    while remaining != 0 {
+        product ^= (a as u128) << remaining.trailing_zeros();
+        remaining &= remaining - 1;
+    }
+    product
+}
+
+/// Returns the degree of a polynomial, or None for the zero polynomial
+fn degree(poly: &[u64]) -> Option<usize> {
+    let index = poly.iter().rposition(|&word| word != 0)?;
+    Some(index * WORD_BITS + WORD_BITS - 1 - poly[index].leading_zeros() as usize)
+}
+
+/// Multiplies two polynomials a word at a time
+fn multiply(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut product = vec![0; a.len() + b.len()];
+    for (i, &a_word) in a.iter().enumerate() {
+        for (j, &b_word) in b.iter().enumerate() {
+            let partial = clmul(a_word, b_word);
+            product[i + j] ^= partial as u64;
+            product[i + j + 1] ^= (partial >> WORD_BITS) as u64;
+        }
+    }
+    product
+}
+
+/// Reduces a polynomial modulo a non-zero modulus, in place
+fn reduce(poly: &mut [u64], modulus: &[u64]) {
+    let modulus_degree = degree(modulus).unwrap();
+    // Words above the leading term would be shifted past the end
+    let modulus = &modulus[..modulus_degree / WORD_BITS + 1];
+    while let Some(poly_degree) = degree(poly) {
+        if poly_degree < modulus_degree {
+            break;
+        }
+
+        // XOR in the modulus shifted up to the leading term
+        let shift = poly_degree - modulus_degree;
+        let (word_shift, bit_shift) = (shift / WORD_BITS, shift % WORD_BITS);
+        for (index, &word) in modulus.iter().enumerate() {
+            if index + word_shift >= poly.len() {
+                break;
+            }
+            poly[index + word_shift] ^= word << bit_shift;
+            if bit_shift > 0 && index + word_shift + 1 < poly.len() {
+                poly[index + word_shift + 1] ^= word >> (WORD_BITS - bit_shift);
+            }
+        }
+    }
+}
+
+impl BitVec {
+    /// Packs the coefficients into words, lowest power first
+    fn to_poly(&self) -> Vec<u64> {
+        let mut poly = vec![0; self.len.div_ceil(WORD_BITS)];
+        for index in self.iter_ones() {
+            let power = self.len - 1 - index;
+            poly[power / WORD_BITS] |= 1 << (power % WORD_BITS);
+        }
+        poly
+    }
+
+    /// Unpacks the coefficients of the powers below `len`, highest first
+    fn from_poly(poly: &[u64], len: usize) -> BitVec {
+        let mut bv = BitVec::new();
+        bv.resize(len, false);
+        for power in 0..len.min(poly.len() * WORD_BITS) {
+            if poly[power / WORD_BITS] & (1 << (power % WORD_BITS)) != 0 {
+                bv.set_bit(len - 1 - power, true);
+            }
+        }
+        bv
+    }
+
+    /// Returns the product of two polynomials, with one bit fewer than the
+    /// two lengths together, or no bits if either is empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // (x + 1)(x + 1) = x^2 + 1
+    /// let a = BitVec::from_bit_str("11").unwrap();
+    /// assert_eq!(a.gf2_mul(&a).to_bit_string(), "101");
+    /// ```
+    pub fn gf2_mul(&self, other: &BitVec) -> BitVec {
+        if self.len == 0 || other.len == 0 {
+            return BitVec::new();
+        }
+        BitVec::from_poly(&multiply(&self.to_poly(), &other.to_poly()), self.len + other.len - 1)
+    }
+
+    /// Returns the remainder after dividing by a modulus, with one bit for
+    /// each power below the degree of the modulus, as for a CRC. Panics if
+    /// the modulus is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // The CRC-3 of 1101 with x^3 + x + 1 is the remainder of 1101 000
+    /// let message = BitVec::from_bit_str("1101 000").unwrap();
+    /// let modulus = BitVec::from_bit_str("1011").unwrap();
+    /// assert_eq!(message.gf2_mod(&modulus).to_bit_string(), "001");
+    /// ```
+    pub fn gf2_mod(&self, modulus: &BitVec) -> BitVec {
+        let modulus_poly = modulus.to_poly();
+        let Some(modulus_degree) = degree(&modulus_poly) else {
+            panic!("BitVec: modulus must not be zero")
+        };
+
+        let mut poly = self.to_poly();
+        reduce(&mut poly, &modulus_poly);
+        BitVec::from_poly(&poly, modulus_degree)
+    }
+
+    /// Returns the product of two polynomials modulo a third, with one bit
+    /// for each power below the degree of the modulus. Panics if the modulus
+    /// is zero.
+    pub fn gf2_mulmod(&self, other: &BitVec, modulus: &BitVec) -> BitVec {
+        self.gf2_mul(other).gf2_mod(modulus)
+    }
+
+    /// Returns the greatest common divisor of two polynomials, with no
+    /// leading 0s, or no bits if both are zero
+    pub fn gf2_gcd(&self, other: &BitVec) -> BitVec {
+        let (mut a, mut b) = (self.to_poly(), other.to_poly());
+        while degree(&b).is_some() {
+            reduce(&mut a, &b);
+            (a, b) = (b, a);
+        }
+        BitVec::from_poly(&a, degree(&a).map_or(0, |degree| degree + 1))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitvec(bits: &str) -> BitVec {
+        BitVec::from_bit_str(bits).unwrap()
+    }
+
+    #[test]
+    fn carry_less_words() {
+        assert_eq!(clmul(0b11, 0b11), 0b101);
+        assert_eq!(clmul(u64::MAX, 2), (u64::MAX as u128) << 1);
+        assert_eq!(clmul(1 << 63, 1 << 63), 1 << 126);
+    }
+
+    #[test]
+    fn products_across_words() {
+        // (x^100 + 1)(x^70 + x) = x^170 + x^101 + x^70 + x
+        let mut a = bitvec("1");
+        a.resize(101, false);
+        a.set_bit(100, true);
+        let mut b = bitvec("1");
+        b.resize(71, false);
+        b.set_bit(69, true);
+
+        let product = a.gf2_mul(&b);
+        assert_eq!(product.len_bits(), 171);
+        let powers: Vec<usize> = product.iter_ones().map(|index| 170 - index).collect();
+        assert_eq!(powers, vec![170, 101, 70, 1]);
+        assert_eq!(a.gf2_mul(&BitVec::new()).len_bits(), 0);
+    }
+
+    #[test]
+    fn remainders() {
+        // x^8 + x^2 + x + 1, the CRC-8 polynomial
+        let crc8 = bitvec("100000111");
+        let message = BitVec::from(&[0x31, 0x32, 0x33, 0x00]);
+        assert_eq!(message.gf2_mod(&crc8).as_binary(), "11000000");
+
+        let mut product = BitVec::from(&[0xA5, 0x5A, 0xC3]).gf2_mul(&crc8);
+        assert_eq!(product.gf2_mod(&crc8).count_ones(), 0);
+        product.flip_range(3..4);
+        assert_ne!(product.gf2_mod(&crc8).count_ones(), 0);
+
+        // x^2 * x^7 mod x^8 + x^4 + x^3 + x + 1, as in AES
+        let aes = bitvec("100011011");
+        assert_eq!(bitvec("100").gf2_mulmod(&bitvec("10000000"), &aes).to_bit_string(), "00110110");
+    }
+
+    #[test]
+    fn greatest_common_divisors() {
+        // (x + 1)(x^2 + x + 1) and (x + 1)(x^3 + x + 1)
+        let a = bitvec("11").gf2_mul(&bitvec("111"));
+        let b = bitvec("11").gf2_mul(&bitvec("1011"));
+        assert_eq!(a.gf2_gcd(&b).to_bit_string(), "11");
+        assert_eq!(bitvec("0001011").gf2_gcd(&BitVec::new()).to_bit_string(), "1011");
+        assert_eq!(bitvec("000").gf2_gcd(&bitvec("0")).len_bits(), 0);
+    }
+
+    #[test]
+    fn modulus_with_high_zero_words() {
+        // x^3 + x + 1 after 130 zero coefficients, three words long
+        let mut modulus = BitVec::repeat_bit(false, 130);
+        modulus.extend(&bitvec("1011"));
+        let message = bitvec("1101 000");
+        assert_eq!(message.gf2_mod(&modulus).to_bit_string(), "001");
+        assert_eq!(bitvec("1011").gf2_mod(&modulus).count_ones(), 0);
+        assert_eq!(bitvec("1111").gf2_gcd(&modulus).to_bit_string(), "1");
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_modulus() {
+        bitvec("1011").gf2_mod(&bitvec("000"));
+    }
+}
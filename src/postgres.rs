@@ -0,0 +1,149 @@
+//! ## PostgreSQL bit strings
+//! The text and binary forms of PostgreSQL `bit` and `bit varying` values.
+//!
+//! - Text: the server sends the digits alone, such as `1010`, and accepts
+//!   them with a leading `B` for binary or `X` for hex, 4 bits a digit. In
+//!   SQL they are written as the literals `B'1010'` and `X'A'`.
+//! - Binary: the length in bits as a big-endian i32, then the bits packed
+//!   MSB first, with the last byte padded with 0s, as `varbit_send` writes.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+impl BitVec {
+    /// Parses a bit string as PostgreSQL does: binary digits, optionally
+    /// after a `B`, or hex digits after an `X`, either in either case and
+    /// either optionally quoted as in an SQL literal. Unlike `from_bit_str`,
+    /// no separators are allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_pg_text("B'1010'").unwrap();
+    /// assert_eq!(bv.to_pg_text(), "1010");
+    /// assert_eq!(bv.to_pg_literal(), "B'1010'");
+    /// assert_eq!(BitVec::from_pg_text("x'a'"), Ok(bv));
+    /// ```
+    pub fn from_pg_text(s: &str) -> Result<BitVec, BitVecError> {
+        let (radix, body, offset) = match s.as_bytes().first() {
+            Some(b'B' | b'b') => (2, &s[1..], 1),
+            Some(b'X' | b'x') => (16, &s[1..], 1),
+            _ => (2, s, 0),
+        };
+        let (digits, offset) = match body.strip_prefix('\'').and_then(|body| body.strip_suffix('\'')) {
+            Some(digits) if offset == 1 => (digits, 2),
+            _ => (body, offset),
+        };
+
+        let width = if radix == 2 { 1 } else { 4 };
+        let mut bv = BitVec::with_capacity(digits.len() * width);
+        for (position, digit) in digits.char_indices() {
+            let Some(value) = digit.to_digit(radix) else {
+                return Err(BitVecError::InvalidDigit { position: offset + position, digit });
+            };
+            bv.push_low_bits(value as u64, width as u32);
+        }
+        Ok(bv)
+    }
+
+    /// Formats the bits as the server sends them in text: the binary digits
+    /// alone
+    pub fn to_pg_text(&self) -> String {
+        self.to_bit_string()
+    }
+
+    /// Formats the bits as an SQL bit string literal, such as `B'1010'`
+    pub fn to_pg_literal(&self) -> String {
+        let mut literal = String::with_capacity(self.len + 3);
+        literal.push_str("B'");
+        literal.push_str(&self.to_bit_string());
+        literal.push('\'');
+        literal
+    }
+
+    /// Returns the bits in the binary wire format. Panics if there are more
+    /// than `i32::MAX` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("101").unwrap();
+    /// assert_eq!(bv.to_pg_binary(), vec![0, 0, 0, 3, 0xA0]);
+    /// assert_eq!(BitVec::from_pg_binary(&[0, 0, 0, 3, 0xA0]), Ok(bv));
+    /// ```
+    pub fn to_pg_binary(&self) -> Vec<u8> {
+        let Ok(len) = i32::try_from(self.len) else {
+            panic!("BitVec: too many bits for a PostgreSQL bit string")
+        };
+        let mut out = Vec::with_capacity(4 + self.data.len());
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    /// Reads a bit string in the binary wire format. Returns an error if the
+    /// length is negative or does not match the number of bytes, as the
+    /// server would; padding bits set in the last byte are cleared.
+    pub fn from_pg_binary(bytes: &[u8]) -> Result<BitVec, BitVecError> {
+        let Some((len, data)) = bytes.split_first_chunk::<4>() else {
+            return Err(BitVecError::InvalidEncoding);
+        };
+        let Ok(len) = usize::try_from(i32::from_be_bytes(*len)) else {
+            return Err(BitVecError::InvalidEncoding);
+        };
+        if data.len() != len.div_ceil(8) {
+            return Err(BitVecError::InvalidEncoding);
+        }
+
+        let mut bv = BitVec::from(data);
+        bv.len = len;
+        bv.clear_padding();
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_forms() {
+        let bv = BitVec::from_bit_str("0110 1").unwrap();
+        for text in ["01101", "B01101", "b'01101'"] {
+            assert_eq!(BitVec::from_pg_text(text), Ok(bv.clone()));
+        }
+        assert_eq!(BitVec::from_pg_text("X'1F'").unwrap().to_pg_text(), "00011111");
+        assert_eq!(BitVec::from_pg_text("B''"), Ok(BitVec::new()));
+        assert_eq!(BitVec::from_pg_text(""), Ok(BitVec::new()));
+        assert_eq!(BitVec::new().to_pg_literal(), "B''");
+
+        // No separators, and positions count from the start of the string
+        assert_eq!(BitVec::from_pg_text("B'01 1'"), Err(BitVecError::InvalidDigit { position: 4, digit: ' ' }));
+        assert_eq!(BitVec::from_pg_text("102"), Err(BitVecError::InvalidDigit { position: 2, digit: '2' }));
+        assert_eq!(BitVec::from_pg_text("'101'"), Err(BitVecError::InvalidDigit { position: 0, digit: '\'' }));
+    }
+
+    #[test]
+    fn binary_form() {
+        let bv = BitVec::from_bit_str("1111 0000 11").unwrap();
+        let bytes = bv.to_pg_binary();
+        assert_eq!(bytes, vec![0, 0, 0, 10, 0xF0, 0xC0]);
+        assert_eq!(BitVec::from_pg_binary(&bytes), Ok(bv));
+
+        // Padding bits are cleared
+        assert_eq!(BitVec::from_pg_binary(&[0, 0, 0, 2, 0xFF]).unwrap().as_bytes(), &[0xC0]);
+        assert_eq!(BitVec::from_pg_binary(&[0, 0, 0, 0]), Ok(BitVec::new()));
+        assert_eq!(BitVec::from_pg_binary(&[0, 0, 0, 9, 0xFF]), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_pg_binary(&[0xFF, 0xFF, 0xFF, 0xFF]), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_pg_binary(&[0, 0]), Err(BitVecError::InvalidEncoding));
+    }
+}
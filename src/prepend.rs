@@ -0,0 +1,143 @@
+//! ## Prepending
+//! Adding bits to the front of a vector. On a BitVec, `insert_bits` at index
+//! 0 shifts every bit already there, so it costs O(n) each time. Code that
+//! builds a vector from the back, such as assembling a Huffman code from the
+//! leaf up to the root, uses a `BitPrepender`, which keeps its bits at the
+//! end of a buffer behind a head offset and grows towards the front, so each
+//! prepend costs only the bits added, amortized over the doublings of the
+//! buffer.
+
+use crate::{BitSlice, BitVec};
+
+/// Smallest buffer a BitPrepender grows to, in bits
+const MIN_BUFFER: usize = 64;
+
+/// The low `count` bits of a value, most significant first. Panics if the
+/// count is more than 64.
+fn value_bits(value: u64, count: u32) -> BitVec {
+    let mut bits = BitVec::with_capacity(count.min(64) as usize);
+    bits.push_low_bits(value, count);
+    bits
+}
+
+/// A vector built from the back, with bits added to the front in amortized
+/// O(1) a bit
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitPrepender;
+///
+/// // Walk from a leaf up to the root, prepending each branch taken
+/// let mut code = BitPrepender::new();
+/// for branch in [true, true, false] {
+///     code.push_front_bit(branch);
+/// }
+/// assert_eq!(code.into_bitvec().to_bit_string(), "011");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BitPrepender {
+    buffer: BitVec,     // the bits are the last len bits
+    head:   usize,      // index in the buffer of the first bit
+}
+
+impl BitPrepender {
+    /// Constructs a new, empty, BitPrepender
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of bits
+    pub fn len(&self) -> usize {
+        self.buffer.len - self.head
+    }
+
+    /// Checks if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Makes room for at least `additional` more bits in front of the head,
+    /// at least doubling the buffer if it has to grow
+    fn reserve_front(&mut self, additional: usize) {
+        if additional <= self.head {
+            return;
+        }
+
+        let len = self.len();
+        let size = (len + additional).max(2 * self.buffer.len).max(MIN_BUFFER);
+        let mut buffer = BitVec::with_capacity(size);
+        buffer.resize(size, false);
+        buffer.copy_bits_from(&self.buffer, self.head, size - len, len);
+        self.buffer = buffer;
+        self.head = size - len;
+    }
+
+    /// Pushes a bit onto the front
+    pub fn push_front_bit(&mut self, value: bool) {
+        self.reserve_front(1);
+        self.head -= 1;
+        self.buffer.set_bit(self.head, value);
+    }
+
+    /// Pushes the low `count` bits of a value onto the front, most
+    /// significant first. Panics if the count is more than 64.
+    pub fn push_front_bits(&mut self, value: u64, count: u32) {
+        self.prepend(&value_bits(value, count));
+    }
+
+    /// Pushes every bit of a vector onto the front
+    pub fn prepend(&mut self, bits: &BitVec) {
+        self.reserve_front(bits.len);
+        self.head -= bits.len;
+        self.buffer.copy_bits_from(bits, 0, self.head, bits.len);
+    }
+
+    /// Returns a view of the bits
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        self.buffer.slice(self.head..)
+    }
+
+    /// Returns the bits as a BitVec
+    pub fn into_bitvec(self) -> BitVec {
+        self.as_bitslice().to_bitvec()
+    }
+}
+
+impl From<BitVec> for BitPrepender {
+    fn from(bv: BitVec) -> Self {
+        Self { buffer: bv, head: 0 }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepender_matches_bitvec() {
+        let mut expected = BitVec::from_bit_str("1").unwrap();
+        let mut prepender = BitPrepender::from(expected.clone());
+        // Enough to grow the buffer several times
+        for round in 0..100u64 {
+            let count = (round % 13) as u32;
+            prepender.push_front_bits(round * 0x9E37, count);
+            expected.insert_bits(0, &value_bits(round * 0x9E37, count));
+            prepender.push_front_bit(round % 3 == 0);
+            expected.insert_bits(0, &value_bits((round % 3 == 0) as u64, 1));
+            if round % 10 == 0 {
+                let bits = BitVec::from(&[round as u8, 0x5A]);
+                prepender.prepend(&bits);
+                expected.insert_bits(0, &bits);
+            }
+        }
+
+        assert_eq!(prepender.len(), expected.len_bits());
+        assert_eq!(prepender.as_bitslice(), expected.as_bitslice());
+        assert_eq!(prepender.into_bitvec(), expected);
+        assert!(BitPrepender::new().is_empty());
+    }
+}
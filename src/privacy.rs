@@ -0,0 +1,155 @@
+//! ## Randomized response
+//! Local differential privacy for boolean flags: each bit is flipped with a
+//! known probability before it leaves the client, behind the `rand`
+//! feature, so no single report can be trusted, yet counts over many bits or
+//! reports can still be estimated without bias.
+//!
+//! With flip probability `p`, a bit that was set reads set with probability
+//! `1 - p` and a clear one with probability `p`, so from `o` ones seen among
+//! `n` bits the estimate of the true ones is `(o - n * p) / (1 - 2p)`.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::BitVec;
+
+/// Panics unless the flip probability is in `0..=1` and not 1/2, where the
+/// reports carry no information
+fn check_flip_probability(flip_probability: f64) {
+    if !(0.0..=1.0).contains(&flip_probability) || flip_probability == 0.5 {
+        panic!("BitVec: flip probability must be between 0 and 1, and not 1/2")
+    }
+}
+
+impl BitVec {
+    /// Returns a copy with each bit flipped independently with probability
+    /// `flip_probability`. Panics if the probability is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(3);
+    /// let flags = BitVec::repeat_bit(true, 10_000);
+    /// let report = flags.randomized_response(&mut rng, 0.25);
+    /// let estimate = report.estimate_ones(0.25);
+    /// assert!((9_500.0..10_500.0).contains(&estimate));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn randomized_response<R: Rng + ?Sized>(&self, rng: &mut R, flip_probability: f64) -> BitVec {
+        if !(0.0..=1.0).contains(&flip_probability) {
+            panic!("BitVec: flip probability must be between 0 and 1")
+        }
+
+        let mut report = self.clone();
+        for index in 0..self.len {
+            if rng.random_bool(flip_probability) {
+                report.flip_range(index..index + 1);
+            }
+        }
+        report
+    }
+
+    /// Estimates how many of `total` bits were set before flipping, from the
+    /// `observed_ones` seen after. The estimate is unbiased, so it may fall
+    /// outside `0..=total`. Panics if the probability is not between 0 and
+    /// 1, or is 1/2.
+    pub fn debias_ones(observed_ones: usize, total: usize, flip_probability: f64) -> f64 {
+        check_flip_probability(flip_probability);
+        (observed_ones as f64 - total as f64 * flip_probability) / (1.0 - 2.0 * flip_probability)
+    }
+
+    /// Returns the variance of `debias_ones` over `total` bits, whatever
+    /// they were: `n * p * (1 - p) / (1 - 2p)^2`. Panics if the probability
+    /// is not between 0 and 1, or is 1/2.
+    pub fn debias_variance(total: usize, flip_probability: f64) -> f64 {
+        check_flip_probability(flip_probability);
+        let scale = 1.0 - 2.0 * flip_probability;
+        total as f64 * flip_probability * (1.0 - flip_probability) / (scale * scale)
+    }
+
+    /// Estimates how many bits of this report were set before flipping
+    pub fn estimate_ones(&self, flip_probability: f64) -> f64 {
+        BitVec::debias_ones(self.count_ones(), self.len, flip_probability)
+    }
+
+    /// Estimates, for each flag, how many reports had it set before
+    /// flipping, where bit `i` of every report is flag `i`. Reports shorter
+    /// than the longest count as missing for the flags they lack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let reports = [BitVec::from_bit_str("10").unwrap(), BitVec::from_bit_str("11").unwrap()];
+    /// assert_eq!(BitVec::estimate_flag_counts(&reports, 0.0), vec![2.0, 1.0]);
+    /// ```
+    pub fn estimate_flag_counts(reports: &[BitVec], flip_probability: f64) -> Vec<f64> {
+        check_flip_probability(flip_probability);
+        let flags = reports.iter().map(|report| report.len).max().unwrap_or(0);
+        (0..flags)
+            .map(|flag| {
+                let present = reports.iter().filter(|report| flag < report.len);
+                let (total, ones) = present.fold((0, 0), |(total, ones), report| {
+                    (total + 1, ones + report.as_bitslice().get_bit(flag) as usize)
+                });
+                BitVec::debias_ones(ones, total, flip_probability)
+            })
+            .collect()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimators() {
+        // p = 1/4: 100 true ones of 400 are seen as 75 + 75 = 150
+        assert_eq!(BitVec::debias_ones(150, 400, 0.25), 100.0);
+        assert_eq!(BitVec::debias_variance(400, 0.25), 300.0);
+        // Always flipping is a relabelling, not noise
+        assert_eq!(BitVec::debias_ones(3, 10, 1.0), 7.0);
+        assert_eq!(BitVec::debias_variance(10, 0.0), 0.0);
+
+        let report = BitVec::from_bit_str("1100 0000").unwrap();
+        assert_eq!(report.estimate_ones(0.0), 2.0);
+        // Unbiased, so it can go negative
+        assert!(report.estimate_ones(0.4) < 0.0);
+
+        let reports = [BitVec::from_bit_str("111").unwrap(), BitVec::from_bit_str("0").unwrap()];
+        assert_eq!(BitVec::estimate_flag_counts(&reports, 1.0), vec![1.0, 0.0, 0.0]);
+        assert!(BitVec::estimate_flag_counts(&[], 0.1).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "not 1/2")]
+    fn half_carries_nothing() {
+        BitVec::debias_ones(5, 10, 0.5);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn responses_debias() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64(11);
+        let mut flags = BitVec::repeat_bit(false, 20_000);
+        flags.slice_mut(..5_000).fill(true);
+        assert_eq!(flags.randomized_response(&mut rng, 0.0), flags);
+
+        let report = flags.randomized_response(&mut rng, 0.2);
+        assert_eq!(report.len_bits(), flags.len_bits());
+        // Within 4 standard deviations, about 4 * 116
+        let estimate = report.estimate_ones(0.2);
+        assert!((estimate - 5_000.0).abs() < 4.0 * BitVec::debias_variance(20_000, 0.2).sqrt());
+    }
+}
@@ -0,0 +1,176 @@
+//! ## Region quadtrees
+//! A compact encoding of a BitGrid as a region quadtree, which suits sparse
+//! occupancy maps with large empty or full areas. The grid is covered by a
+//! square block with a power of two side, which is split into four quarters
+//! until each block is all one value.
+//!
+//! The nodes are stored depth first as flags in a BitVec. A `0` flag is a
+//! leaf, followed by its value. A `1` flag is a split block, followed by its
+//! quarters in the order top left, top right, bottom left, bottom right. A
+//! split block at the maximum depth is followed by its cells instead, row by
+//! row. Blocks wholly outside the grid are left out, and the cells outside
+//! the grid are ignored.
+
+use core::ops::Range;
+
+use crate::{BitCursor, BitGrid, BitVec};
+
+/// The size and depth of the square block covering a grid
+struct Cover {
+    width:     usize,
+    height:    usize,
+    max_depth: usize,
+}
+
+impl Cover {
+    /// Get the side of the root block
+    fn root_side(&self) -> usize {
+        self.width.max(self.height).max(1).next_power_of_two()
+    }
+
+    /// Returns the columns and rows of a block that are inside the grid
+    fn clip(&self, x: usize, y: usize, side: usize) -> (Range<usize>, Range<usize>) {
+        (x.min(self.width)..(x + side).min(self.width), y.min(self.height)..(y + side).min(self.height))
+    }
+
+    /// Returns the corners of the quarters of a block
+    fn quarters(x: usize, y: usize, half: usize) -> [(usize, usize); 4] {
+        [(x, y), (x + half, y), (x, y + half), (x + half, y + half)]
+    }
+}
+
+impl BitGrid {
+    /// Pushes the nodes of a block, depth first
+    fn encode_block(&self, cover: &Cover, out: &mut BitVec, (x, y, side, depth): (usize, usize, usize, usize)) {
+        let (columns, rows) = cover.clip(x, y, side);
+        if columns.is_empty() || rows.is_empty() {
+            return;
+        }
+
+        let ones = self.count_ones_in_block(columns.clone(), rows.clone());
+        if ones == 0 || ones == columns.len() * rows.len() {
+            out.push_bit(false);
+            out.push_bit(ones > 0);
+            return;
+        }
+
+        out.push_bit(true);
+        if depth == cover.max_depth {
+            for cell_y in rows {
+                for cell_x in columns.clone() {
+                    out.push_bit(self.get(cell_x, cell_y));
+                }
+            }
+        } else {
+            for (quarter_x, quarter_y) in Cover::quarters(x, y, side / 2) {
+                self.encode_block(cover, out, (quarter_x, quarter_y, side / 2, depth + 1));
+            }
+        }
+    }
+
+    /// Reads the nodes of a block, depth first. Returns None if the data
+    /// runs out or a split block is 1 cell.
+    fn decode_block(&mut self, cover: &Cover, cursor: &mut BitCursor<&BitVec>, (x, y, side, depth): (usize, usize, usize, usize)) -> Option<()> {
+        let (columns, rows) = cover.clip(x, y, side);
+        if columns.is_empty() || rows.is_empty() {
+            return Some(());
+        }
+
+        if !cursor.read_bit()? {
+            let value = cursor.read_bit()?;
+            for cell_y in rows {
+                self.set_run(cell_y, columns.clone(), value);
+            }
+        } else if side == 1 {
+            return None;
+        } else if depth == cover.max_depth {
+            for cell_y in rows {
+                for cell_x in columns.clone() {
+                    self.set(cell_x, cell_y, cursor.read_bit()?);
+                }
+            }
+        } else {
+            for (quarter_x, quarter_y) in Cover::quarters(x, y, side / 2) {
+                self.decode_block(cover, cursor, (quarter_x, quarter_y, side / 2, depth + 1))?;
+            }
+        }
+
+        Some(())
+    }
+
+    /// Encodes the grid as a region quadtree, splitting blocks at most
+    /// `max_depth` times before storing their cells directly
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitGrid;
+    ///
+    /// // One set cell in the bottom right of a 4x4 grid
+    /// let mut grid = BitGrid::new(4, 4);
+    /// grid.set(3, 3, true);
+    /// let tree = grid.to_quadtree(8);
+    /// assert_eq!(tree.to_bit_string(), "1 00 00 00 1 00 00 00 01".replace(' ', ""));
+    /// assert_eq!(BitGrid::from_quadtree(&tree, 4, 4, 8), Some(grid));
+    /// ```
+    pub fn to_quadtree(&self, max_depth: usize) -> BitVec {
+        let cover = Cover { width: self.width(), height: self.height(), max_depth };
+        let mut out = BitVec::new();
+        self.encode_block(&cover, &mut out, (0, 0, cover.root_side(), 0));
+        out
+    }
+
+    /// Decodes a region quadtree made by `to_quadtree` with the same grid
+    /// size and maximum depth. Returns None if the data is invalid or runs
+    /// out.
+    pub fn from_quadtree(tree: &BitVec, width: usize, height: usize, max_depth: usize) -> Option<BitGrid> {
+        let cover = Cover { width, height, max_depth };
+        let mut grid = BitGrid::new(width, height);
+        let mut cursor = BitCursor::new(tree);
+        grid.decode_block(&cover, &mut cursor, (0, 0, cover.root_side(), 0))?;
+        Some(grid)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_map_round_trip() {
+        // A filled square and a lone cell in a 100x70 map
+        let mut grid = BitGrid::new(100, 70);
+        for y in 32..64 {
+            for x in 64..96 {
+                grid.set(x, y, true);
+            }
+        }
+        grid.set(5, 66, true);
+
+        let tree = grid.to_quadtree(16);
+        assert!(tree.len_bits() < 100);
+        assert_eq!(BitGrid::from_quadtree(&tree, 100, 70, 16), Some(grid.clone()));
+
+        // Limiting the depth stores the mixed blocks as cells
+        let shallow = grid.to_quadtree(2);
+        assert_eq!(BitGrid::from_quadtree(&shallow, 100, 70, 2), Some(grid));
+    }
+
+    #[test]
+    fn uniform_and_invalid_trees() {
+        let mut full = BitGrid::new(3, 5);
+        for y in 0..5 {
+            full.set_run(y, 0..3, true);
+        }
+        assert_eq!(full.to_quadtree(4).to_bit_string(), "01");
+        assert_eq!(BitGrid::new(0, 0).to_quadtree(4).len_bits(), 0);
+
+        let tree = BitVec::from_bit_str("1 00 01").unwrap();
+        assert_eq!(BitGrid::from_quadtree(&tree, 2, 2, 4), None);
+        let tree = BitVec::from_bit_str("1 1").unwrap();
+        assert_eq!(BitGrid::from_quadtree(&tree, 1, 1, 4), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! ## Random generation
+//! Random BitVecs of any length, behind the `rand` feature, for property
+//! tests and benchmarks. Any `Rng` can be used, so a seeded generator gives
+//! the same vector every run.
+
+use alloc::vec;
+
+use rand::Rng;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Constructs a BitVec of `len` bits, each set with probability 1/2
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(1);
+    /// let bv = BitVec::random(13, &mut rng);
+    /// assert_eq!(bv.len_bits(), 13);
+    /// assert_eq!(bv, BitVec::random(13, &mut SmallRng::seed_from_u64(1)));
+    /// ```
+    pub fn random<R: Rng + ?Sized>(len: usize, rng: &mut R) -> Self {
+        let mut data = vec![0; len.div_ceil(8)];
+        rng.fill_bytes(&mut data);
+
+        let mut bv = BitVec::from(data);
+        bv.truncate(len);
+        bv
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    #[test]
+    fn random_lengths() {
+        let mut rng = SmallRng::seed_from_u64(42);
+        for len in [0, 1, 7, 8, 9, 1000] {
+            let bv = BitVec::random(len, &mut rng);
+            assert_eq!(bv.len_bits(), len);
+            assert_eq!(bv.as_bytes().len(), len.div_ceil(8));
+            assert_eq!(bv.count_ones(), bv.iter().filter(|&bit| bit).count());
+        }
+
+        // Roughly half the bits are set
+        let ones = BitVec::random(100_000, &mut rng).count_ones();
+        assert!((48_000..52_000).contains(&ones));
+    }
+}
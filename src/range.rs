@@ -0,0 +1,208 @@
+//! ## Range operations
+//! Setting, clearing, flipping, counting, and copying contiguous ranges of
+//! bits. The bytes wholly inside the range are worked on a byte (or word) at
+//! a time, and only the bytes at either edge are masked, so long ranges cost
+//! far less than the equivalent loop of `set_bit` or `get_bit`.
+
+use core::ops::RangeBounds;
+
+use crate::slice::resolve_range;
+use crate::{popcount, BitVec};
+
+/// Counts the set bits from `start` to `end` of bytes packed MSB first,
+/// masking only the bytes at either edge
+pub(crate) fn count_ones_in_bytes(data: &[u8], start: usize, end: usize) -> usize {
+    if start == end {
+        return 0;
+    }
+
+    let (first, last) = (start / 8, (end - 1) / 8);
+    let first_mask = 0xFF >> (start % 8);
+    let last_mask = BitVec::mask_msb(end % 8);
+    if first == last {
+        return (data[first] & first_mask & last_mask).count_ones() as usize;
+    }
+
+    (data[first] & first_mask).count_ones() as usize
+        + popcount(&data[first + 1..last])
+        + (data[last] & last_mask).count_ones() as usize
+}
+
+impl BitVec {
+    /// Applies an operation to every byte overlapping a range, along with
+    /// the mask of the bits of the byte inside the range
+    fn edit_range(&mut self, start: usize, end: usize, op: impl Fn(u8, u8) -> u8) {
+        if start == end {
+            return;
+        }
+
+        let (first, last) = (start / 8, (end - 1) / 8);
+        for byte_idx in first..=last {
+            let mut mask = 0xFF;
+            if byte_idx == first {
+                mask &= 0xFF >> (start % 8);
+            }
+            if byte_idx == last {
+                mask &= BitVec::mask_msb(end % 8);
+            }
+            self.data[byte_idx] = op(self.data[byte_idx], mask);
+        }
+    }
+
+    /// Sets every bit in a range to the same value. Panics if the range runs
+    /// past the end of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x00, 0x00]);
+    /// bv.set_range(3..13, true);
+    /// assert_eq!(bv.as_binary(), "00011111 11111000");
+    /// ```
+    pub fn set_range<R: RangeBounds<usize>>(&mut self, range: R, value: bool) {
+        let (start, end) = resolve_range(range, self.len);
+        if value {
+            self.edit_range(start, end, |byte, mask| byte | mask);
+        } else {
+            self.edit_range(start, end, |byte, mask| byte & !mask);
+        }
+    }
+
+    /// Clears every bit in a range. Panics if the range runs past the end of
+    /// the vector.
+    pub fn clear_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.set_range(range, false);
+    }
+
+    /// Inverts every bit in a range. Panics if the range runs past the end
+    /// of the vector.
+    pub fn flip_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = resolve_range(range, self.len);
+        self.edit_range(start, end, |byte, mask| byte ^ mask);
+    }
+
+    /// Returns the number of set bits in a range. Panics if the range runs
+    /// past the end of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0x0F, 0xFF, 0xF0]);
+    /// assert_eq!(bv.count_ones_in(2..22), 16);
+    /// ```
+    pub fn count_ones_in<R: RangeBounds<usize>>(&self, range: R) -> usize {
+        let (start, end) = resolve_range(range, self.len);
+        count_ones_in_bytes(&self.data, start, end)
+    }
+
+    /// Copies `len` bits of another vector from `src_start` over the bits
+    /// from `dst_start`, shifting them into place a byte at a time. Panics if
+    /// either range runs past the end of its vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut frame = BitVec::from(&[0x00, 0x00]);
+    /// let stream = BitVec::from(&[0xFF]);
+    /// frame.copy_bits_from(&stream, 2, 5, 4);
+    /// assert_eq!(frame.as_binary(), "00000111 10000000");
+    /// ```
+    pub fn copy_bits_from(&mut self, src: &BitVec, src_start: usize, dst_start: usize, len: usize) {
+        let src = src.slice(src_start..src_start + len);
+        self.slice_mut(dst_start..dst_start + len).copy_from(&src);
+    }
+
+    /// Copies a range of bits to start at `dst`, within the same vector,
+    /// which is correct even when the ranges overlap. Panics if either range
+    /// runs past the end of the vector.
+    pub fn copy_within<R: RangeBounds<usize>>(&mut self, src: R, dst: usize) {
+        let (start, end) = resolve_range(src, self.len);
+        let bits = self.slice(start..end).to_bitvec();
+        self.copy_bits_from(&bits, 0, dst, end - start);
+    }
+
+    /// Swaps two bits. Panics if either index is beyond the end of the
+    /// vector.
+    pub fn swap_bits(&mut self, i: usize, j: usize) {
+        let (a, b) = (self.get_bit(i), self.get_bit(j));
+        self.set_bit(i, b);
+        self.set_bit(j, a);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_clear_and_flip() {
+        let mut bv = BitVec::new();
+        bv.resize(100, false);
+        bv.set_range(5..95, true);
+        assert_eq!(bv.count_ones(), 90);
+        assert_eq!(bv.next_set_bit(0), Some(5));
+        assert_eq!(bv.next_clear_bit(5), Some(95));
+
+        bv.clear_range(10..=20);
+        assert_eq!(bv.count_ones(), 79);
+        assert!(bv.get_bit(9) && !bv.get_bit(20) && bv.get_bit(21));
+
+        bv.flip_range(..);
+        assert_eq!(bv.count_ones(), 21);
+        // Within a single byte, and empty
+        bv.flip_range(1..3);
+        bv.set_range(50..50, false);
+        assert!(!bv.get_bit(1) && !bv.get_bit(2) && bv.get_bit(3));
+        assert_eq!(bv.count_ones(), 19);
+    }
+
+    #[test]
+    fn copying_between_vectors() {
+        // Assembling a frame from two streams at odd offsets
+        let a = BitVec::from(&[0xFF, 0xFF, 0xFF]);
+        let b = BitVec::from(&[0x5A, 0x5A, 0x5A]);
+        let mut frame = BitVec::new();
+        frame.resize(40, false);
+        frame.copy_bits_from(&a, 3, 1, 17);
+        frame.copy_bits_from(&b, 5, 20, 19);
+        assert_eq!(frame.slice(1..18).count_ones(), 17);
+        assert_eq!(frame.slice(20..39).to_bitvec(), b.slice(5..24).to_bitvec());
+        assert!(!frame.get_bit(0) && !frame.get_bit(18) && !frame.get_bit(39));
+
+        // Overlapping copies within one vector, both ways
+        let mut bv = BitVec::from_bit_str("1100 1010 0").unwrap();
+        bv.copy_within(0..6, 3);
+        assert_eq!(bv.to_bit_string(), "110110010");
+        bv.copy_within(3.., 0);
+        assert_eq!(bv.to_bit_string(), "110010010");
+
+        bv.swap_bits(0, 8);
+        bv.swap_bits(1, 1);
+        assert_eq!(bv.to_bit_string(), "010010011");
+    }
+
+    #[test]
+    fn counting_ranges() {
+        let bv = BitVec::from(&[0xA5; 40]);
+        for (start, end) in [(0, 320), (3, 5), (7, 9), (13, 300), (320, 320), (1, 8)] {
+            let expected = (start..end).filter(|&index| bv.get_bit(index)).count();
+            assert_eq!(bv.count_ones_in(start..end), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_past_the_end() {
+        let mut bv = BitVec::from(&[0xFF]);
+        bv.set_range(4..9, false);
+    }
+}
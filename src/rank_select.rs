@@ -0,0 +1,388 @@
+//! ## Rank and select
+//! `rank1(i)` counts the set bits before index `i`, and `select1(k)` finds
+//! the index of the `k`th set bit (counting from 0), with `rank0`/`select0`
+//! doing the same for clear bits. The methods on BitVec scan the data, while
+//! `RankSelect` builds an index over a frozen BitVec for fast repeated
+//! queries on large vectors.
+
+use alloc::vec::Vec;
+use crate::{popcount, BitVec};
+
+/// Bits covered by each block of the index
+const BLOCK_BITS: usize = 64;
+/// Blocks in each superblock of the index
+const BLOCKS_PER_SUPER: usize = 8;
+
+/// Finds the position of the `rank`th set bit (from 0) in a byte, MSB first
+fn select_in_byte(byte: u8, rank: usize) -> usize {
+    let mut byte = byte;
+    for _ in 0..rank {
+        byte &= !(0x80 >> byte.leading_zeros());
+    }
+    byte.leading_zeros() as usize
+}
+
+/// Finds the position of the `rank`th set bit (from 0) in a word, MSB first
+fn select_in_word(word: u64, rank: usize) -> usize {
+    let mut word = word;
+    for _ in 0..rank {
+        word &= !(1 << (63 - word.leading_zeros()));
+    }
+    word.leading_zeros() as usize
+}
+
+impl BitVec {
+    /// Returns the number of set bits before the index. Panics if the index
+    /// is beyond the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01101000
+    /// let bv = BitVec::from(&[0x68]);
+    /// assert_eq!(bv.rank1(3), 2);
+    /// assert_eq!(bv.rank0(3), 1);
+    /// ```
+    pub fn rank1(&self, index: usize) -> usize {
+        if index > self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let full_bytes = index / 8;
+        let mut count = popcount(&self.data[..full_bytes]);
+        let offset = index % 8;
+        if offset > 0 {
+            count += (self.data[full_bytes] & BitVec::mask_msb(offset)).count_ones() as usize;
+        }
+
+        count
+    }
+
+    /// Returns the number of clear bits before the index. Panics if the
+    /// index is beyond the length of the vector.
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Returns the index of the `k`th set bit, counting from 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b01101000
+    /// let bv = BitVec::from(&[0x68]);
+    /// assert_eq!(bv.select1(2), Some(4));
+    /// assert_eq!(bv.select1(3), None);
+    /// ```
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        self.nth_with(k, 0)
+    }
+
+    /// Returns the index of the `k`th clear bit, counting from 0
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        self.nth_with(k, 0xFF)
+    }
+
+    /// Returns the index of the `k`th set bit, counting from 0, skipping a
+    /// word of 64 bits at a time by its popcount. The same as `select1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut occupancy = BitVec::from(&[0; 100]);
+    /// occupancy.set_bit(517, true);
+    /// occupancy.set_bit(790, true);
+    /// assert_eq!(occupancy.nth_one(1), Some(790));
+    /// assert_eq!(occupancy.nth_zero(517), Some(518));
+    /// ```
+    pub fn nth_one(&self, k: usize) -> Option<usize> {
+        self.nth_with(k, 0)
+    }
+
+    /// Returns the index of the `k`th clear bit, counting from 0, skipping
+    /// a word of 64 bits at a time by its popcount. The same as `select0`.
+    pub fn nth_zero(&self, k: usize) -> Option<usize> {
+        self.nth_with(k, 0xFF)
+    }
+
+    /// Finds the `k`th bit that is set after XORing with the flip mask,
+    /// a word at a time, then a byte at a time in the last partial word
+    fn nth_with(&self, k: usize, flip: u8) -> Option<usize> {
+        let mut remaining = k;
+        let full_words = self.len / 64;
+        for (word_idx, word) in self.data[..full_words * 8].chunks_exact(8).enumerate() {
+            let word = u64::from_be_bytes(word.try_into().unwrap()) ^ u64::from_ne_bytes([flip; 8]);
+            let ones = word.count_ones() as usize;
+            if remaining < ones {
+                return Some(word_idx * 64 + select_in_word(word, remaining));
+            }
+            remaining -= ones;
+        }
+
+        let tail = &self.data[full_words * 8..self.len.div_ceil(8)];
+        for (byte_idx, &byte) in tail.iter().enumerate() {
+            let base = full_words * 64 + byte_idx * 8;
+            // Padding bits at the end of the vector are not counted
+            let valid = (self.len - base).min(8);
+            let byte = (byte ^ flip) & BitVec::mask_msb(valid % 8);
+            let ones = byte.count_ones() as usize;
+            if remaining < ones {
+                return Some(base + select_in_byte(byte, remaining));
+            }
+            remaining -= ones;
+        }
+
+        None
+    }
+}
+
+/// An index over a frozen BitVec for constant-time rank and fast select.
+///
+/// Cumulative counts are kept for every 512-bit superblock, and counts
+/// relative to the superblock for every 64-bit block, adding roughly 6% to
+/// the size of the vector. Select binary searches the superblocks and then
+/// scans at most 8 blocks.
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, RankSelect};
+///
+/// let index = RankSelect::new(BitVec::from(&[0x68; 1000]));
+/// assert_eq!(index.rank1(8000), 3000);
+/// assert_eq!(index.select1(2999), Some(7996));
+/// ```
+pub struct RankSelect {
+    bits:        BitVec,
+    superblocks: Vec<u64>,  // set bits before each superblock
+    blocks:      Vec<u16>,  // set bits before each block, within its superblock
+    ones:        usize,     // total set bits
+}
+
+impl RankSelect {
+    /// Builds the index over a BitVec
+    pub fn new(bits: BitVec) -> Self {
+        let num_words = bits.len.div_ceil(BLOCK_BITS);
+        let mut superblocks = Vec::with_capacity(num_words.div_ceil(BLOCKS_PER_SUPER));
+        let mut blocks = Vec::with_capacity(num_words);
+        let mut ones = 0;
+        let mut super_base = 0;
+
+        for word_idx in 0..num_words {
+            if word_idx % BLOCKS_PER_SUPER == 0 {
+                super_base = ones;
+                superblocks.push(ones as u64);
+            }
+            blocks.push((ones - super_base) as u16);
+            ones += Self::word(&bits, word_idx).count_ones() as usize;
+        }
+
+        Self { bits, superblocks, blocks, ones }
+    }
+
+    /// Reads 64 bits from a whole block, padding with 0s past the end
+    fn word(bits: &BitVec, word_idx: usize) -> u64 {
+        let start = word_idx * 8;
+        let end = (start + 8).min(bits.data.len());
+        let mut bytes = [0; 8];
+        bytes[..end - start].copy_from_slice(&bits.data[start..end]);
+        u64::from_be_bytes(bytes)
+    }
+
+    /// Returns the indexed BitVec
+    pub fn bits(&self) -> &BitVec {
+        &self.bits
+    }
+
+    /// Unwraps the index, returning the BitVec
+    pub fn into_inner(self) -> BitVec {
+        self.bits
+    }
+
+    /// Get the total number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Get the total number of clear bits
+    pub fn count_zeros(&self) -> usize {
+        self.bits.len - self.ones
+    }
+
+    /// Set bits before a block
+    fn ones_before_block(&self, word_idx: usize) -> usize {
+        self.superblocks[word_idx / BLOCKS_PER_SUPER] as usize + self.blocks[word_idx] as usize
+    }
+
+    /// Returns the number of set bits before the index. Panics if the index
+    /// is beyond the length of the vector.
+    pub fn rank1(&self, index: usize) -> usize {
+        if index > self.bits.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let word_idx = index / BLOCK_BITS;
+        if word_idx == self.blocks.len() {
+            return self.ones;
+        }
+
+        let offset = index % BLOCK_BITS;
+        let partial = if offset == 0 {
+            0
+        } else {
+            (Self::word(&self.bits, word_idx) >> (BLOCK_BITS - offset)).count_ones() as usize
+        };
+
+        self.ones_before_block(word_idx) + partial
+    }
+
+    /// Returns the number of clear bits before the index. Panics if the
+    /// index is beyond the length of the vector.
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Finds the block holding the `k`th counted bit, given the number of
+    /// counted bits before each superblock and each block
+    fn select_block(&self, k: usize, before_super: impl Fn(usize) -> usize, before_block: impl Fn(usize) -> usize) -> usize {
+        // Last superblock starting at or before the kth bit
+        let (mut low, mut high) = (0, self.superblocks.len());
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if before_super(mid) <= k {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let first = low * BLOCKS_PER_SUPER;
+        let last = (first + BLOCKS_PER_SUPER).min(self.blocks.len());
+        (first + 1..last)
+            .take_while(|&word_idx| before_block(word_idx) <= k)
+            .last()
+            .unwrap_or(first)
+    }
+
+    /// Returns the index of the `k`th set bit, counting from 0
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+
+        let word_idx = self.select_block(
+            k,
+            |super_idx| self.superblocks[super_idx] as usize,
+            |word_idx| self.ones_before_block(word_idx),
+        );
+        let rank = k - self.ones_before_block(word_idx);
+
+        Some(word_idx * BLOCK_BITS + select_in_word(Self::word(&self.bits, word_idx), rank))
+    }
+
+    /// Returns the index of the `k`th clear bit, counting from 0
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+
+        let zeros_before_block = |word_idx: usize| word_idx * BLOCK_BITS - self.ones_before_block(word_idx);
+        let word_idx = self.select_block(
+            k,
+            |super_idx| super_idx * BLOCKS_PER_SUPER * BLOCK_BITS - self.superblocks[super_idx] as usize,
+            zeros_before_block,
+        );
+        let rank = k - zeros_before_block(word_idx);
+
+        Some(word_idx * BLOCK_BITS + select_in_word(!Self::word(&self.bits, word_idx), rank))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    /// Builds a pseudo-random vector with a non-aligned length
+    fn pseudo_random(len: usize) -> BitVec {
+        let mut bv = BitVec::new();
+        let mut state: u32 = 0x1234_5678;
+        for _ in 0..len {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            bv.push_bit(state >> 28 < 5);
+        }
+        bv
+    }
+
+    #[test]
+    fn naive_rank_select() {
+        // 0b01101011 0b101xxxxx
+        let bv = BitVec { data: vec![0x6B, 0xA0], len: 11, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.rank1(0), 0);
+        assert_eq!(bv.rank1(8), 5);
+        assert_eq!(bv.rank1(11), 7);
+        assert_eq!(bv.rank0(11), 4);
+        assert_eq!(bv.select1(0), Some(1));
+        assert_eq!(bv.select1(6), Some(10));
+        assert_eq!(bv.select1(7), None);
+        assert_eq!(bv.select0(3), Some(9));
+        // Padding bits are not clear bits
+        assert_eq!(bv.select0(4), None);
+    }
+
+    #[test]
+    fn index_matches_naive() {
+        let bv = pseudo_random(3001);
+        let ones = bv.rank1(3001);
+        let zeros = 3001 - ones;
+        let naive_select1: Vec<_> = (0..=ones).map(|k| bv.select1(k)).collect();
+        let naive_select0: Vec<_> = (0..=zeros).map(|k| bv.select0(k)).collect();
+        let naive_rank: Vec<_> = (0..=3001).map(|i| bv.rank1(i)).collect();
+
+        let index = RankSelect::new(bv);
+        assert_eq!(index.count_ones(), ones);
+        for (i, &rank) in naive_rank.iter().enumerate() {
+            assert_eq!(index.rank1(i), rank);
+            assert_eq!(index.rank0(i), i - rank);
+        }
+        for (k, &select) in naive_select1.iter().enumerate() {
+            assert_eq!(index.select1(k), select);
+        }
+        for (k, &select) in naive_select0.iter().enumerate() {
+            assert_eq!(index.select0(k), select);
+        }
+    }
+
+    #[test]
+    fn nth_matches_iterators() {
+        // Whole words, then a partial word with a partial byte
+        let bv = pseudo_random(203);
+        let ones: Vec<_> = bv.iter_ones().collect();
+        let zeros: Vec<_> = bv.iter_zeros().collect();
+        for (k, &index) in ones.iter().enumerate() {
+            assert_eq!(bv.nth_one(k), Some(index));
+        }
+        for (k, &index) in zeros.iter().enumerate() {
+            assert_eq!(bv.nth_zero(k), Some(index));
+        }
+        assert_eq!(bv.nth_one(ones.len()), None);
+        assert_eq!(bv.nth_zero(zeros.len()), None);
+        assert_eq!(BitVec::new().nth_one(0), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rank_out_of_bounds() {
+        let index = RankSelect::new(BitVec::from(&[0xFF]));
+        index.rank1(9);
+    }
+}
@@ -0,0 +1,143 @@
+//! ## Packed rasters
+//! 1-bit image formats, such as TIFF bilevel images and DICOM overlays, pad
+//! each row out to a row stride in bytes, so the rows cannot simply be read
+//! as one run of bits. Here the rows are unpacked into one BitVec, row after
+//! row with no padding, and packed back again. TIFF packs the first pixel
+//! into the MSB of each byte by default, while DICOM overlays use the LSB.
+
+use alloc::vec::Vec;
+use crate::{BitOrder, BitVec};
+
+impl BitVec {
+    /// Unpacks `rows` rows of `width_bits` pixels each, starting every
+    /// `row_stride_bytes` bytes, with the first pixel in the MSB of each
+    /// byte. Returns None if the stride is too short for the width or there
+    /// are not enough bytes. Panics if the rows would span more than
+    /// `usize::MAX` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // Two 10-pixel rows, each padded to a stride of 4 bytes
+    /// let bytes = [0xFF, 0xC0, 0, 0, 0x80, 0x40, 0, 0];
+    /// let bv = BitVec::from_packed_rows(&bytes, 10, 4, 2).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "11111111111000000001");
+    /// ```
+    pub fn from_packed_rows(bytes: &[u8], width_bits: usize, row_stride_bytes: usize, rows: usize) -> Option<BitVec> {
+        BitVec::from_packed_rows_with_order(bytes, width_bits, row_stride_bytes, rows, BitOrder::Msb0)
+    }
+
+    /// Unpacks padded rows as with `from_packed_rows`, with the pixels
+    /// packed into each byte in the given order. The new vector keeps that
+    /// order for packing the rows back up.
+    pub fn from_packed_rows_with_order(
+        bytes: &[u8],
+        width_bits: usize,
+        row_stride_bytes: usize,
+        rows: usize,
+        order: BitOrder,
+    ) -> Option<BitVec> {
+        let row_bytes = width_bits.div_ceil(8);
+        if row_stride_bytes < row_bytes {
+            return None;
+        }
+        let needed = match rows {
+            0 => 0,
+            _ => (rows - 1)
+                .checked_mul(row_stride_bytes)
+                .and_then(|start| start.checked_add(row_bytes))
+                .unwrap_or_else(|| panic!("BitVec: {} rows of {} bytes are too large", rows, row_stride_bytes)),
+        };
+        if bytes.len() < needed {
+            return None;
+        }
+
+        let mut bv = BitVec::with_order(order);
+        for row in 0..rows {
+            let start = row * row_stride_bytes;
+            let mut pixels = BitVec::from_with_order(&bytes[start..start + row_bytes], order);
+            pixels.truncate(width_bits);
+            bv.append_bits(&pixels);
+        }
+
+        Some(bv)
+    }
+
+    /// Packs the vector into rows of `width_bits` pixels, each padded with
+    /// 0s to `row_stride_bytes` bytes, in the vector's bit order. Panics if
+    /// the width does not divide the length, the stride is too short, or the
+    /// rows would span more than `usize::MAX` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("111 000 101").unwrap();
+    /// assert_eq!(bv.to_packed_rows(3, 2), vec![0xE0, 0, 0x00, 0, 0xA0, 0]);
+    /// ```
+    pub fn to_packed_rows(&self, width_bits: usize, row_stride_bytes: usize) -> Vec<u8> {
+        if width_bits == 0 || !self.len.is_multiple_of(width_bits) || row_stride_bytes < width_bits.div_ceil(8) {
+            panic!("BitVec: invalid row geometry for the length")
+        }
+
+        let rows = self.len / width_bits;
+        let size = rows
+            .checked_mul(row_stride_bytes)
+            .unwrap_or_else(|| panic!("BitVec: {} rows of {} bytes are too large", rows, row_stride_bytes));
+        let mut bytes = Vec::with_capacity(size);
+        for row in 0..rows {
+            let pixels = self.slice(row * width_bits..(row + 1) * width_bits).to_bitvec();
+            bytes.extend(pixels.data.iter().map(|&byte| self.order.convert(byte)));
+            bytes.resize((row + 1) * row_stride_bytes, 0);
+        }
+
+        bytes
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip() {
+        // Three 13-pixel rows with a stride of 3 bytes and junk in the padding
+        let bytes = [0xAB, 0xCF, 0xEE, 0x12, 0x34, 0x56, 0xFF, 0xF8, 0x01];
+        let bv = BitVec::from_packed_rows(&bytes, 13, 3, 3).unwrap();
+        assert_eq!(bv.len_bits(), 39);
+        assert_eq!(bv.slice(0..13).to_bitvec().to_bit_string(), "1010101111001");
+        assert_eq!(bv.slice(13..26).to_bitvec().to_bit_string(), "0001001000110");
+        assert_eq!(bv.count_ones(), 8 + 4 + 13);
+
+        let packed = bv.to_packed_rows(13, 3);
+        assert_eq!(packed, vec![0xAB, 0xC8, 0, 0x12, 0x30, 0, 0xFF, 0xF8, 0]);
+    }
+
+    #[test]
+    fn lsb_first_rows() {
+        // An 8x2 overlay with a 2 byte stride, first pixel in the LSB
+        let bytes = [0x01, 0x00, 0x80, 0x00];
+        let bv = BitVec::from_packed_rows_with_order(&bytes, 8, 2, 2, BitOrder::Lsb0).unwrap();
+        assert_eq!(bv.to_bit_string(), "1000000000000001");
+        assert_eq!(bv.to_packed_rows(8, 2), bytes);
+    }
+
+    #[test]
+    fn bad_geometry() {
+        assert!(BitVec::from_packed_rows(&[0; 5], 13, 1, 2).is_none());
+        assert!(BitVec::from_packed_rows(&[0; 4], 13, 3, 2).is_none());
+        assert_eq!(BitVec::from_packed_rows(&[], 13, 3, 0).unwrap().len_bits(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "too large")]
+    fn rows_too_large() {
+        BitVec::from_packed_rows(&[0; 4], 8, usize::MAX / 2, 4);
+    }
+}
@@ -0,0 +1,199 @@
+//! ## Redis bitmaps
+//! BITCOUNT, BITPOS and BITOP with the semantics of Redis, so bitmap logic
+//! can be mirrored locally or checked against a server. Redis stores a
+//! bitmap as a string of bytes, MSB first as a BitVec is, so these take the
+//! vector as its bytes, padding bits and all.
+//!
+//! Ranges are in bytes and inclusive at both ends, and a negative index
+//! counts back from the end, -1 being the last byte. An index past either
+//! end is clamped to it, and a range that ends before it starts is empty.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// The operation of a BITOP command
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RedisBitOp {
+    /// Set where every vector has a set bit
+    And,
+    /// Set where any vector has a set bit
+    Or,
+    /// Set where an odd number of vectors have a set bit
+    Xor,
+    /// Set where the one vector has a clear bit
+    Not,
+}
+
+impl BitVec {
+    /// Resolves a Redis byte range over the bytes of the vector, returning
+    /// the first and last bytes, or None if the range is empty
+    fn redis_range(&self, start: i64, end: i64) -> Option<(usize, usize)> {
+        let bytes = self.data.len() as i64;
+        let resolve = |index: i64| if index < 0 { (bytes + index).max(0) } else { index };
+        let (start, end) = (resolve(start), resolve(end).min(bytes - 1));
+        (start <= end).then_some((start as usize, end as usize))
+    }
+
+    /// Returns the number of set bits, as BITCOUNT does, in the whole vector
+    /// or in an inclusive range of bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(b"foobar");
+    /// assert_eq!(bv.bitcount(None), 26);
+    /// assert_eq!(bv.bitcount(Some((1, 1))), 6);
+    /// assert_eq!(bv.bitcount(Some((-2, -1))), 7);
+    /// ```
+    pub fn bitcount(&self, range: Option<(i64, i64)>) -> usize {
+        let (start, end) = range.unwrap_or((0, -1));
+        match self.redis_range(start, end) {
+            Some((start, end)) => self.data[start..=end].iter().map(|byte| byte.count_ones() as usize).sum(),
+            None => 0,
+        }
+    }
+
+    /// Returns the index of the first bit with the given value, as BITPOS
+    /// does, searching from byte `start` to byte `end`, or the last byte
+    /// if there is no end. Returns -1 if there is no such bit, except that
+    /// with no end the bytes are taken as followed by clear bits, so looking
+    /// for a clear bit in set bytes finds the first bit after them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0xFF, 0xF0, 0x00]);
+    /// assert_eq!(bv.bitpos(false, 0, None), 12);
+    /// assert_eq!(bv.bitpos(true, 2, None), -1);
+    /// assert_eq!(BitVec::from(&[0xFF]).bitpos(false, 0, None), 8);
+    /// assert_eq!(BitVec::from(&[0xFF]).bitpos(false, 0, Some(-1)), -1);
+    /// ```
+    pub fn bitpos(&self, bit: bool, start: i64, end: Option<i64>) -> i64 {
+        if self.data.is_empty() {
+            return if bit { -1 } else { 0 };
+        }
+        let Some((first, last)) = self.redis_range(start, end.unwrap_or(-1)) else {
+            return -1;
+        };
+
+        let flip = if bit { 0 } else { 0xFF };
+        for (index, &byte) in self.data[first..=last].iter().enumerate() {
+            let byte = byte ^ flip;
+            if byte != 0 {
+                return ((first + index) * 8 + byte.leading_zeros() as usize) as i64;
+            }
+        }
+        match (bit, end) {
+            (false, None) => ((last + 1) * 8) as i64,
+            _ => -1,
+        }
+    }
+
+    /// Combines vectors byte by byte, as BITOP does. The result is as long
+    /// as the longest vector in whole bytes, with shorter vectors taken as
+    /// followed by clear bytes. Panics if the operation is NOT and there is
+    /// not exactly one vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, RedisBitOp};
+    ///
+    /// let a = BitVec::from(&[0xF0, 0x0F]);
+    /// let b = BitVec::from(&[0x3C]);
+    /// assert_eq!(BitVec::bitop(RedisBitOp::And, &[&a, &b]).as_bytes(), &[0x30, 0x00]);
+    /// assert_eq!(BitVec::bitop(RedisBitOp::Not, &[&b]).as_bytes(), &[0xC3]);
+    /// ```
+    pub fn bitop(op: RedisBitOp, sources: &[&BitVec]) -> BitVec {
+        if op == RedisBitOp::Not && sources.len() != 1 {
+            panic!("BitVec: BITOP NOT takes exactly one vector")
+        }
+
+        let bytes = sources.iter().map(|bv| bv.data.len()).max().unwrap_or(0);
+        let mut data = Vec::with_capacity(bytes);
+        for index in 0..bytes {
+            let mut byte_of = sources.iter().map(|bv| bv.data.get(index).copied().unwrap_or(0));
+            let first = byte_of.next().unwrap_or(0);
+            data.push(match op {
+                RedisBitOp::And => byte_of.fold(first, |acc, byte| acc & byte),
+                RedisBitOp::Or => byte_of.fold(first, |acc, byte| acc | byte),
+                RedisBitOp::Xor => byte_of.fold(first, |acc, byte| acc ^ byte),
+                RedisBitOp::Not => !first,
+            });
+        }
+        BitVec::from(data)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting() {
+        // The examples from the Redis documentation
+        let bv = BitVec::from(b"foobar");
+        assert_eq!(bv.bitcount(None), 26);
+        assert_eq!(bv.bitcount(Some((0, 0))), 4);
+        assert_eq!(bv.bitcount(Some((1, 1))), 6);
+        assert_eq!(bv.bitcount(Some((-100, 100))), 26);
+        assert_eq!(bv.bitcount(Some((3, 2))), 0);
+        assert_eq!(bv.bitcount(Some((-1, -2))), 0);
+        assert_eq!(bv.bitcount(Some((6, 10))), 0);
+        assert_eq!(BitVec::new().bitcount(None), 0);
+    }
+
+    #[test]
+    fn positions() {
+        // The examples from the Redis documentation
+        let bv = BitVec::from(&[0xFF, 0xF0, 0x00]);
+        assert_eq!(bv.bitpos(false, 0, None), 12);
+        let bv = BitVec::from(&[0x00, 0xFF, 0xF0]);
+        assert_eq!(bv.bitpos(true, 0, None), 8);
+        assert_eq!(bv.bitpos(true, 2, None), 16);
+        assert_eq!(bv.bitpos(true, 2, Some(-1)), 16);
+        assert_eq!(bv.bitpos(true, -3, Some(0)), -1);
+        assert_eq!(BitVec::from(&[0x00; 3]).bitpos(true, 0, None), -1);
+
+        // Clear bits past the end only without an end
+        let ones = BitVec::from(&[0xFF, 0xFF]);
+        assert_eq!(ones.bitpos(false, 1, None), 16);
+        assert_eq!(ones.bitpos(false, 0, Some(5)), -1);
+        assert_eq!(ones.bitpos(false, 3, None), -1);
+
+        // A missing key
+        assert_eq!(BitVec::new().bitpos(false, 0, None), 0);
+        assert_eq!(BitVec::new().bitpos(true, 0, None), -1);
+
+        // Padding bits are clear bits of the last byte
+        assert_eq!(BitVec::from_bit_str("111").unwrap().bitpos(false, 0, Some(0)), 3);
+    }
+
+    #[test]
+    fn operations() {
+        let a = BitVec::from_bit_str("1100 1010 1").unwrap();
+        let b = BitVec::from(&[0x0F]);
+        let c = BitVec::from(&[0xFF, 0x00, 0xAA]);
+        assert_eq!(BitVec::bitop(RedisBitOp::And, &[&a, &b, &c]).as_bytes(), &[0x0A, 0x00, 0x00]);
+        assert_eq!(BitVec::bitop(RedisBitOp::Or, &[&a, &b]).as_bytes(), &[0xCF, 0x80]);
+        assert_eq!(BitVec::bitop(RedisBitOp::Xor, &[&a, &c]).as_bytes(), &[0x35, 0x80, 0xAA]);
+        assert_eq!(BitVec::bitop(RedisBitOp::Not, &[&a]).as_bytes(), &[0x35, 0x7F]);
+        assert_eq!(BitVec::bitop(RedisBitOp::Or, &[&a, &b]).len_bits(), 16);
+        assert_eq!(BitVec::bitop(RedisBitOp::Xor, &[]).len_bits(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "exactly one")]
+    fn not_takes_one_vector() {
+        let a = BitVec::from(&[0x0F]);
+        BitVec::bitop(RedisBitOp::Not, &[&a, &a]);
+    }
+}
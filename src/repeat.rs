@@ -0,0 +1,127 @@
+//! ## Repeated patterns
+//! Building a BitVec from many copies of a short pattern, as for test
+//! patterns. Each copy starts at one of only 8 bit offsets within a byte, so
+//! the pattern is shifted to each offset once up front, and every copy is
+//! then one OR into the last partial byte and a copy of the rest.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Constructs a BitVec of `n` bits, all with the same value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::repeat_bit(true, 10);
+    /// assert_eq!(bv.as_binary(), "11111111 11000000");
+    /// ```
+    pub fn repeat_bit(value: bool, n: usize) -> BitVec {
+        let mut bv = BitVec::new();
+        bv.resize(n, value);
+        bv
+    }
+
+    /// Constructs a BitVec from a pattern repeated a number of times, in the
+    /// bit order of the pattern. Panics if the length would be more than
+    /// `usize::MAX` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let pattern = BitVec::from_bit_str("110").unwrap();
+    /// let bv = BitVec::repeat(&pattern, 4);
+    /// assert_eq!(bv.to_bit_string(), "110110110110");
+    /// ```
+    pub fn repeat(pattern: &BitVec, times: usize) -> BitVec {
+        let mut bv = BitVec::with_order(pattern.order);
+        let len = pattern
+            .len
+            .checked_mul(times)
+            .unwrap_or_else(|| panic!("BitVec: {} copies of {} bits are too long", times, pattern.len));
+        if len == 0 {
+            return bv;
+        }
+
+        // The pattern after each number of leading 0s, with clear padding
+        let variants: Vec<Vec<u8>> = (0..8)
+            .map(|shift| {
+                let mut shifted = pattern.data[..pattern.len.div_ceil(8)].to_vec();
+                if let Some(last) = shifted.last_mut() {
+                    *last &= BitVec::mask_msb(pattern.len % 8);
+                }
+                shifted.push(0);
+                for index in (0..shifted.len()).rev() {
+                    let carry = if index > 0 && shift > 0 { shifted[index - 1] << (8 - shift) } else { 0 };
+                    shifted[index] = (shifted[index] >> shift) | carry;
+                }
+                shifted.truncate((shift + pattern.len).div_ceil(8));
+                shifted
+            })
+            .collect();
+
+        bv.data.reserve(len.div_ceil(8));
+        for copy in 0..times {
+            let position = copy * pattern.len;
+            let variant = &variants[position % 8];
+            if position.is_multiple_of(8) {
+                bv.data.extend_from_slice(variant);
+            } else {
+                *bv.data.last_mut().unwrap() |= variant[0];
+                bv.data.extend_from_slice(&variant[1..]);
+            }
+        }
+        bv.len = len;
+        bv.clear_padding();
+        bv
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    #[test]
+    fn repeats_match_naive() {
+        for pattern_len in [1, 3, 8, 11, 17] {
+            let mut pattern = BitVec::new();
+            for index in 0..pattern_len {
+                pattern.push_bit(index % 2 == 0 || index % 5 == 3);
+            }
+            for times in [0, 1, 2, 9, 100] {
+                let mut expected = BitVec::new();
+                for _ in 0..times {
+                    expected.extend(&pattern);
+                }
+                assert_eq!(BitVec::repeat(&pattern, times), expected, "{} x {}", pattern_len, times);
+            }
+        }
+        assert_eq!(BitVec::repeat(&BitVec::new(), 5).len_bits(), 0);
+    }
+
+    #[test]
+    fn repeated_bits() {
+        assert_eq!(BitVec::repeat_bit(false, 13).as_binary(), "00000000 00000000");
+        assert_eq!(BitVec::repeat_bit(true, 13).count_ones(), 13);
+        assert_eq!(BitVec::repeat_bit(true, 0).len_bits(), 0);
+
+        // Padding bits of the pattern never leak into the copies
+        let pattern = BitVec { data: vec![0xFF], len: 2, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(BitVec::repeat(&pattern, 3).as_binary(), "11111100");
+    }
+
+    #[test]
+    #[should_panic(expected = "too long")]
+    fn repeat_too_long() {
+        BitVec::repeat(&BitVec::from_bit_str("101").unwrap(), usize::MAX / 2);
+    }
+}
@@ -0,0 +1,106 @@
+//! ## Bit reversal
+//! Reversing the order of the bits of a vector or a range, for protocols
+//! that send some fields bit-reversed. The bytes are swapped end for end and
+//! each byte reversed with `u8::reverse_bits`, then the result is shifted to
+//! drop the padding bits that end up at the start.
+
+use core::ops::RangeBounds;
+
+use crate::slice::resolve_range;
+use crate::BitVec;
+
+impl BitVec {
+    /// Reverses the order of the bits, in place
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bit_str("1100 1010 111").unwrap();
+    /// bv.reverse();
+    /// assert_eq!(bv.to_bit_string(), "11101010011");
+    /// ```
+    pub fn reverse(&mut self) {
+        self.clear_padding();
+        self.data.reverse();
+        for byte in self.data.iter_mut() {
+            *byte = byte.reverse_bits();
+        }
+
+        // The padding bits are now at the start, so shift them out over the
+        // whole of the bytes
+        let len = self.len;
+        self.len = self.data.len() * 8;
+        self.shift_left(self.len - len);
+        self.len = len;
+    }
+
+    /// Returns a new BitVec with the bits in reverse order
+    pub fn reversed(&self) -> BitVec {
+        let mut bv = self.clone();
+        bv.reverse();
+        bv
+    }
+
+    /// Reverses the order of the bits in a range, in place. Panics if the
+    /// range runs past the end of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bit_str("0001 1010 00").unwrap();
+    /// bv.reverse_range(3..8);
+    /// assert_eq!(bv.to_bit_string(), "0000101100");
+    /// ```
+    pub fn reverse_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = resolve_range(range, self.len);
+        let mut part = self.slice(start..end).to_bitvec();
+        part.reverse();
+        self.slice_mut(start..end).copy_from(&part.as_bitslice());
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(bv: &BitVec) -> Vec<bool> {
+        (0..bv.len_bits()).map(|i| bv.get_bit(i)).collect()
+    }
+
+    #[test]
+    fn reverse_matches_naive() {
+        for len in [0, 1, 7, 8, 9, 19, 64, 77] {
+            let mut bv = BitVec::new();
+            for index in 0..len {
+                bv.push_bit(index % 3 == 0 || index % 5 == 1);
+            }
+            let mut expected = bits(&bv);
+            expected.reverse();
+
+            let reversed = bv.reversed();
+            assert_eq!(bits(&reversed), expected, "length {}", len);
+            assert_eq!(reversed.reversed(), bv);
+        }
+    }
+
+    #[test]
+    fn reverse_ranges() {
+        let mut bv = BitVec::from(&[0x0F, 0x33, 0x81]);
+        let original = bits(&bv);
+        bv.reverse_range(5..19);
+        for (index, &bit) in original.iter().enumerate() {
+            let source = if (5..19).contains(&index) { 23 - index } else { index };
+            assert_eq!(bv.get_bit(source), bit, "index {}", index);
+        }
+        bv.reverse_range(0..0);
+        bv.reverse_range(5..19);
+        assert_eq!(bits(&bv), original);
+    }
+}
@@ -0,0 +1,312 @@
+//! ## Roaring serialization
+//! The portable serialization format of Roaring bitmaps, as read and written
+//! by CRoaring, roaring-rs, and the Java library, behind the `roaring`
+//! feature. Set bits are 32-bit values, split by their high 16 bits into
+//! containers of up to 65536 values each, stored as one of:
+//!
+//! - an array of the low 16 bits of each value, for at most 4096 values
+//! - a bitmap of 65536 bits, as 1024 little-endian u64s, LSB first
+//! - a list of runs, as a start and a length less one
+//!
+//! After a cookie, a header gives the key and cardinality of each container,
+//! and offsets to the containers, then the containers follow in order. All
+//! numbers are little-endian.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// Cookie of a stream with no run containers, followed by the count
+const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+/// Cookie of a stream with run containers, with the count less one in the
+/// high 16 bits
+const SERIAL_COOKIE: u16 = 12347;
+/// Fewest containers for a stream with run containers to have offsets
+const NO_OFFSET_THRESHOLD: usize = 4;
+/// Most values in an array container
+const MAX_ARRAY: usize = 4096;
+/// Bytes in a bitmap container
+const BITMAP_BYTES: usize = 8192;
+
+/// How a container is stored
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Array,
+    Bitmap,
+    Run,
+}
+
+/// A container of values sharing their high 16 bits
+struct Container {
+    key:    u16,
+    values: Vec<u16>,   // low 16 bits of each value, in order
+    runs:   usize,
+}
+
+impl Container {
+    /// Picks the smallest form, with runs only where strictly smaller, as
+    /// `run_optimize` does
+    fn kind(&self) -> Kind {
+        let (kind, size) = match self.values.len() {
+            card if card <= MAX_ARRAY => (Kind::Array, 2 * card),
+            _ => (Kind::Bitmap, BITMAP_BYTES),
+        };
+        if 2 + 4 * self.runs < size { Kind::Run } else { kind }
+    }
+
+    /// Gets the number of bytes the container takes
+    fn size(&self) -> usize {
+        match self.kind() {
+            Kind::Array => 2 * self.values.len(),
+            Kind::Bitmap => BITMAP_BYTES,
+            Kind::Run => 2 + 4 * self.runs,
+        }
+    }
+
+    /// Writes the body of the container
+    fn write(&self, out: &mut Vec<u8>) {
+        match self.kind() {
+            Kind::Array => {
+                for value in &self.values {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            Kind::Bitmap => {
+                let mut words = [0u64; BITMAP_BYTES / 8];
+                for &value in &self.values {
+                    words[value as usize / 64] |= 1 << (value % 64);
+                }
+                for word in words {
+                    out.extend_from_slice(&word.to_le_bytes());
+                }
+            }
+            Kind::Run => {
+                out.extend_from_slice(&(self.runs as u16).to_le_bytes());
+                let mut index = 0;
+                while index < self.values.len() {
+                    let start = self.values[index];
+                    let mut length = 0;
+                    while index + 1 < self.values.len() && self.values[index + 1] == self.values[index] + 1 {
+                        index += 1;
+                        length += 1;
+                    }
+                    out.extend_from_slice(&start.to_le_bytes());
+                    out.extend_from_slice(&(length as u16).to_le_bytes());
+                    index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Reads little-endian numbers from the front of a byte slice
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], BitVecError> {
+        if self.bytes.len() < count {
+            return Err(BitVecError::InvalidEncoding);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16, BitVecError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, BitVecError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+impl BitVec {
+    /// Returns the set bits in the portable Roaring serialization, with
+    /// each container in its smallest form. The length of the vector is
+    /// not kept. Panics if a set bit is at an index beyond `u32::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(vec![0; 10_000]);
+    /// bv.slice_mut(100..200).fill(true);
+    /// bv.set_bit(70_000, true);
+    /// let bytes = bv.to_roaring_bytes();
+    /// assert_eq!(BitVec::from_roaring_bytes(&bytes).unwrap().iter_ones().count(), 101);
+    /// ```
+    pub fn to_roaring_bytes(&self) -> Vec<u8> {
+        let mut containers: Vec<Container> = Vec::new();
+        for index in self.iter_ones() {
+            let Ok(value) = u32::try_from(index) else {
+                panic!("BitVec: index too large for a Roaring bitmap")
+            };
+            let (key, low) = ((value >> 16) as u16, value as u16);
+            match containers.last_mut() {
+                Some(container) if container.key == key => {
+                    if container.values.last() != Some(&low.wrapping_sub(1)) {
+                        container.runs += 1;
+                    }
+                    container.values.push(low);
+                }
+                _ => containers.push(Container { key, values: alloc::vec![low], runs: 1 }),
+            }
+        }
+
+        let count = containers.len();
+        let has_runs = containers.iter().any(|container| container.kind() == Kind::Run);
+        let mut out = Vec::new();
+        if has_runs {
+            out.extend_from_slice(&SERIAL_COOKIE.to_le_bytes());
+            out.extend_from_slice(&((count - 1) as u16).to_le_bytes());
+            let mut flags = alloc::vec![0u8; count.div_ceil(8)];
+            for (index, container) in containers.iter().enumerate() {
+                if container.kind() == Kind::Run {
+                    flags[index / 8] |= 1 << (index % 8);
+                }
+            }
+            out.extend_from_slice(&flags);
+        } else {
+            out.extend_from_slice(&SERIAL_COOKIE_NO_RUNCONTAINER.to_le_bytes());
+            out.extend_from_slice(&(count as u32).to_le_bytes());
+        }
+
+        for container in &containers {
+            out.extend_from_slice(&container.key.to_le_bytes());
+            out.extend_from_slice(&((container.values.len() - 1) as u16).to_le_bytes());
+        }
+        if !has_runs || count >= NO_OFFSET_THRESHOLD {
+            let mut offset = out.len() + 4 * count;
+            for container in &containers {
+                out.extend_from_slice(&(offset as u32).to_le_bytes());
+                offset += container.size();
+            }
+        }
+        for container in &containers {
+            container.write(&mut out);
+        }
+        out
+    }
+
+    /// Reads a bitmap in the portable Roaring serialization, as long as
+    /// needed to hold its last set bit. Returns an error if the data is
+    /// truncated or not in the format; any bytes after it are ignored.
+    pub fn from_roaring_bytes(bytes: &[u8]) -> Result<BitVec, BitVecError> {
+        let mut reader = Reader { bytes };
+        let cookie = reader.u32()?;
+        let (count, run_flags) = if cookie as u16 == SERIAL_COOKIE {
+            let count = (cookie >> 16) as usize + 1;
+            (count, reader.take(count.div_ceil(8))?)
+        } else if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+            (reader.u32()? as usize, &[][..])
+        } else {
+            return Err(BitVecError::InvalidEncoding);
+        };
+
+        let header = reader.take(count.checked_mul(4).ok_or(BitVecError::InvalidEncoding)?)?;
+        if run_flags.is_empty() || count >= NO_OFFSET_THRESHOLD {
+            reader.take(4 * count)?;
+        }
+
+        let mut bv = BitVec::new();
+        for (index, entry) in header.chunks_exact(4).enumerate() {
+            let base = (u16::from_le_bytes([entry[0], entry[1]]) as usize) << 16;
+            let card = u16::from_le_bytes([entry[2], entry[3]]) as usize + 1;
+            let is_run = run_flags.get(index / 8).is_some_and(|flags| flags >> (index % 8) & 1 == 1);
+
+            if is_run {
+                let runs = reader.u16()?;
+                for _ in 0..runs {
+                    let start = base + reader.u16()? as usize;
+                    let end = start + reader.u16()? as usize + 1;
+                    if end > base + (1 << 16) {
+                        return Err(BitVecError::InvalidEncoding);
+                    }
+                    if bv.len < end {
+                        bv.resize(end, false);
+                    }
+                    bv.slice_mut(start..end).fill(true);
+                }
+            } else if card <= MAX_ARRAY {
+                for _ in 0..card {
+                    bv.set_bit(base + reader.u16()? as usize, true);
+                }
+            } else {
+                for (word_index, word) in reader.take(BITMAP_BYTES)?.chunks_exact(8).enumerate() {
+                    let mut word = u64::from_le_bytes(word.try_into().unwrap());
+                    while word != 0 {
+                        bv.set_bit(base + word_index * 64 + word.trailing_zeros() as usize, true);
+                        word &= word - 1;
+                    }
+                }
+            }
+        }
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_containers() {
+        let mut bv = BitVec::new();
+        for index in [1, 5, 65_536 + 7] {
+            bv.set_bit(index, true);
+        }
+        let bytes = bv.to_roaring_bytes();
+        assert_eq!(
+            bytes,
+            vec![
+                0x3A, 0x30, 0, 0, 2, 0, 0, 0,       // cookie and count
+                0, 0, 1, 0, 1, 0, 0, 0,             // keys and cardinalities
+                24, 0, 0, 0, 28, 0, 0, 0,           // offsets
+                1, 0, 5, 0, 7, 0,                   // values
+            ]
+        );
+        assert_eq!(BitVec::from_roaring_bytes(&bytes), Ok(bv));
+    }
+
+    #[test]
+    fn run_and_bitmap_containers() {
+        // A run, then a container too dense for an array but with too many
+        // runs for a run container
+        let mut bv = BitVec::from(vec![0; 1 << 13]);
+        bv.slice_mut(10..3000).fill(true);
+        for index in (65_536..131_072).step_by(3) {
+            bv.set_bit(index, true);
+        }
+        bv.set_bit(131_072, true);
+        let bytes = bv.to_roaring_bytes();
+        // Run cookie with 3 containers, the first a run, and no offsets
+        assert_eq!(&bytes[..5], &[0x3B, 0x30, 2, 0, 0x01]);
+        assert_eq!(bytes.len(), 5 + 12 + 6 + BITMAP_BYTES + 2);
+
+        let decoded = BitVec::from_roaring_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len_bits(), 131_073);
+        assert_eq!(decoded.iter_ones().collect::<Vec<_>>(), bv.iter_ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert_eq!(BitVec::new().to_roaring_bytes(), vec![0x3A, 0x30, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(BitVec::from_roaring_bytes(&[0x3A, 0x30, 0, 0, 0, 0, 0, 0]), Ok(BitVec::new()));
+
+        let bytes = BitVec::from(&[0x40]).to_roaring_bytes();
+        assert_eq!(BitVec::from_roaring_bytes(&bytes[..bytes.len() - 1]), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_roaring_bytes(&[0x39, 0x30, 0, 0]), Err(BitVecError::InvalidEncoding));
+        // A run past the end of its container
+        let run = [0x3B, 0x30, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0xFF, 0xFF, 1, 0];
+        assert_eq!(BitVec::from_roaring_bytes(&run), Err(BitVecError::InvalidEncoding));
+    }
+}
@@ -0,0 +1,112 @@
+//! ## Runs
+//! The vector as a sequence of maximal runs of equal bits, for run-length
+//! analysis. Each run is found with the same scan as `next_set_bit` and
+//! `next_clear_bit`, which skips whole words and bytes inside a run.
+
+use core::iter::FusedIterator;
+
+use crate::BitVec;
+
+/// Iterator over the maximal runs of equal bits of a BitVec, as the value
+/// and length of each run
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// let bv = BitVec::from_bit_str("0001 1011").unwrap();
+/// let runs: Vec<(bool, usize)> = bv.iter_runs().collect();
+/// assert_eq!(runs, vec![(false, 3), (true, 2), (false, 1), (true, 2)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Runs<'a> {
+    bv:       &'a BitVec,
+    position: usize,    // start of the next run
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (bool, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.bv.len {
+            return None;
+        }
+
+        let value = self.bv.get_bit(self.position);
+        let end = if value { self.bv.next_clear_bit(self.position) } else { self.bv.next_set_bit(self.position) };
+        let end = end.unwrap_or(self.bv.len);
+        let len = end - self.position;
+        self.position = end;
+        Some((value, len))
+    }
+}
+
+impl FusedIterator for Runs<'_> {}
+
+impl BitVec {
+    /// Returns an iterator over the maximal runs of equal bits, as the value
+    /// and length of each run
+    pub fn iter_runs(&self) -> Runs<'_> {
+        Runs { bv: self, position: 0 }
+    }
+
+    /// Returns the number of maximal runs of equal bits
+    pub fn num_runs(&self) -> usize {
+        self.iter_runs().count()
+    }
+
+    /// Returns the length of the longest run of a value, or 0 if the value
+    /// does not appear
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("0111 0011 1100").unwrap();
+    /// assert_eq!(bv.longest_run(true), 4);
+    /// assert_eq!(bv.longest_run(false), 2);
+    /// ```
+    pub fn longest_run(&self, value: bool) -> usize {
+        self.iter_runs().filter(|&(run_value, _)| run_value == value).map(|(_, len)| len).max().unwrap_or(0)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_across_words() {
+        // A long run of 1s spanning several words, ending part way through a byte
+        let mut bv = BitVec::new();
+        bv.resize(300, false);
+        bv.set_range(3..203, true);
+        bv.set_range(299.., true);
+        let runs: Vec<(bool, usize)> = bv.iter_runs().collect();
+        assert_eq!(runs, vec![(false, 3), (true, 200), (false, 96), (true, 1)]);
+        assert_eq!(bv.num_runs(), 4);
+        assert_eq!(bv.longest_run(true), 200);
+        assert_eq!(bv.longest_run(false), 96);
+        assert_eq!(runs.iter().map(|&(_, len)| len).sum::<usize>(), 300);
+    }
+
+    #[test]
+    fn trivial_vectors() {
+        assert_eq!(BitVec::new().iter_runs().next(), None);
+        assert_eq!(BitVec::new().longest_run(true), 0);
+
+        let ones = BitVec::repeat_bit(true, 77);
+        assert_eq!(ones.iter_runs().collect::<Vec<_>>(), vec![(true, 77)]);
+        assert_eq!(ones.longest_run(false), 0);
+
+        // Alternating bits are all runs of one
+        let alternating = BitVec::from(&[0x55; 9]);
+        assert_eq!(alternating.num_runs(), 72);
+        assert_eq!(alternating.longest_run(true), 1);
+    }
+}
@@ -0,0 +1,134 @@
+//! ## Schema bool lists
+//! The layouts serialization schemas use for lists of booleans.
+//!
+//! - Cap'n Proto: a `List(Bool)` packs one bit per element, LSB first within
+//!   each byte, and the list body is padded with 0s to a whole 64-bit word.
+//!   The element count lives in the list pointer, not the body.
+//! - FlatBuffers: a `[bool]` vector stores one byte per element, 0 or 1,
+//!   after a little-endian u32 count. Any nonzero byte reads as true.
+
+use alloc::vec::Vec;
+
+use crate::{BitOrder, BitVec, BitVecError};
+
+impl BitVec {
+    /// Returns the body of a Cap'n Proto `List(Bool)` holding the bits, LSB
+    /// first and padded to a whole word
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1101").unwrap();
+    /// let body = bv.to_capnp_bool_list();
+    /// assert_eq!(body, vec![0x0B, 0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(BitVec::from_capnp_bool_list(&body, 4), Ok(bv));
+    /// ```
+    pub fn to_capnp_bool_list(&self) -> Vec<u8> {
+        let mut body: Vec<u8> = self.data.iter().map(|&byte| BitOrder::Lsb0.convert(byte)).collect();
+        body.resize(self.len.div_ceil(64) * 8, 0);
+        body
+    }
+
+    /// Reads `len` bits from the body of a Cap'n Proto `List(Bool)`, taking
+    /// the count from the list pointer. Returns an error if the body is too
+    /// short; padding after the bits is ignored.
+    pub fn from_capnp_bool_list(body: &[u8], len: usize) -> Result<BitVec, BitVecError> {
+        let bytes = len.div_ceil(8);
+        if body.len() < bytes {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: body.len() * 8 });
+        }
+
+        let mut bv = BitVec::from_with_order(&body[..bytes], BitOrder::Lsb0);
+        bv.truncate(len);
+        Ok(bv)
+    }
+
+    /// Returns a FlatBuffers `[bool]` vector holding the bits: the count as a
+    /// little-endian u32, then a byte of 0 or 1 for each bit. Panics if there
+    /// are more than `u32::MAX` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("101").unwrap();
+    /// let vector = bv.to_flatbuffers_bools();
+    /// assert_eq!(vector, vec![3, 0, 0, 0, 1, 0, 1]);
+    /// assert_eq!(BitVec::from_flatbuffers_bools(&vector), Ok(bv));
+    /// ```
+    pub fn to_flatbuffers_bools(&self) -> Vec<u8> {
+        let Ok(count) = u32::try_from(self.len) else {
+            panic!("BitVec: too many bits for a FlatBuffers vector")
+        };
+        let mut vector = Vec::with_capacity(4 + self.len);
+        vector.extend_from_slice(&count.to_le_bytes());
+        vector.extend((0..self.len).map(|index| self.get_bit(index) as u8));
+        vector
+    }
+
+    /// Reads a FlatBuffers `[bool]` vector, its count then a byte for each
+    /// bit, with any nonzero byte a set bit. Returns an error if there are
+    /// fewer bytes than the count says; any bytes after them are ignored.
+    pub fn from_flatbuffers_bools(vector: &[u8]) -> Result<BitVec, BitVecError> {
+        let Some((count, bools)) = vector.split_first_chunk::<4>() else {
+            return Err(BitVecError::InvalidEncoding);
+        };
+        let count = u32::from_le_bytes(*count) as usize;
+        if bools.len() < count {
+            return Err(BitVecError::InvalidEncoding);
+        }
+
+        let mut bv = BitVec::with_capacity(count);
+        for &byte in &bools[..count] {
+            bv.push_bit(byte != 0);
+        }
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capnp_bool_lists() {
+        let bv = BitVec::from_bit_str("10000000 01").unwrap();
+        let body = bv.to_capnp_bool_list();
+        assert_eq!(body, vec![0x01, 0x02, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(BitVec::from_capnp_bool_list(&body, 10), Ok(bv.clone()));
+
+        // Bits set past the count are dropped
+        let mut dirty = body.clone();
+        dirty[1] = 0xFE;
+        assert_eq!(BitVec::from_capnp_bool_list(&dirty, 10), Ok(bv));
+        assert_eq!(
+            BitVec::from_capnp_bool_list(&body[..1], 10),
+            Err(BitVecError::NotEnoughBits { requested: 10, available: 8 })
+        );
+
+        // Exactly one word, and no words at all
+        assert_eq!(BitVec::from(&[0xFF; 8]).to_capnp_bool_list().len(), 8);
+        assert!(BitVec::new().to_capnp_bool_list().is_empty());
+    }
+
+    #[test]
+    fn flatbuffers_bool_vectors() {
+        let bv = BitVec::from(&[0x81, 0x40]);
+        let vector = bv.to_flatbuffers_bools();
+        assert_eq!(vector.len(), 20);
+        assert_eq!(&vector[..6], &[16, 0, 0, 0, 1, 0]);
+        assert_eq!(BitVec::from_flatbuffers_bools(&vector), Ok(bv));
+
+        // Any nonzero byte is true
+        assert_eq!(BitVec::from_flatbuffers_bools(&[2, 0, 0, 0, 0xFF, 0, 9]).unwrap().to_bit_string(), "10");
+        assert_eq!(BitVec::from_flatbuffers_bools(&[3, 0, 0, 0, 1, 1]), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::from_flatbuffers_bools(&[0, 0]), Err(BitVecError::InvalidEncoding));
+        assert_eq!(BitVec::new().to_flatbuffers_bools(), vec![0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,109 @@
+//! ## Pattern search
+//! Finding a pattern of bits, such as a sync word or frame marker, at any
+//! bit offset rather than only on byte boundaries. The candidate bits at
+//! each position are loaded 64 at a time and compared with the pattern a
+//! word at a time, instead of bit by bit.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// Bits compared at a time
+const WORD_BITS: usize = 64;
+
+impl BitVec {
+    /// Loads the 64 bits starting at any position, MSB first, with 0s past
+    /// the end of the data
+    fn load_word(&self, position: usize) -> u64 {
+        let byte_idx = position / 8;
+        let mut bytes = [0u8; 16];
+        let available = self.data.len().saturating_sub(byte_idx).min(9);
+        bytes[..available].copy_from_slice(&self.data[byte_idx..byte_idx + available]);
+        (u128::from_be_bytes(bytes) << (position % 8) >> 64) as u64
+    }
+
+    /// Checks if the pattern appears at a position, which must leave room
+    /// for the whole pattern
+    fn matches_at(&self, pattern: &BitVec, position: usize) -> bool {
+        (0..pattern.len).step_by(WORD_BITS).all(|offset| {
+            let count = (pattern.len - offset).min(WORD_BITS);
+            let mask = u64::MAX << (WORD_BITS - count);
+            (self.load_word(position + offset) ^ pattern.load_word(offset)) & mask == 0
+        })
+    }
+
+    /// Finds the first position at or after `start` where a pattern
+    /// appears, at any bit offset. An empty pattern matches at `start`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // An 11-bit marker, 3 bits into the stream
+    /// let bv = BitVec::from_bit_str("010 11111111110 0110").unwrap();
+    /// let marker = BitVec::from_bit_str("11111111110").unwrap();
+    /// assert_eq!(bv.find(&marker, 0), Some(3));
+    /// assert_eq!(bv.find(&marker, 4), None);
+    /// ```
+    pub fn find(&self, pattern: &BitVec, start: usize) -> Option<usize> {
+        if start > self.len || pattern.len > self.len - start {
+            return None;
+        }
+        (start..=self.len - pattern.len).find(|&position| self.matches_at(pattern, position))
+    }
+
+    /// Finds every position where a pattern appears, at any bit offset,
+    /// including matches that overlap
+    pub fn find_all(&self, pattern: &BitVec) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut start = 0;
+        while let Some(position) = self.find(pattern, start) {
+            found.push(position);
+            start = position + 1;
+        }
+        found
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitvec(bits: &str) -> BitVec {
+        BitVec::from_bit_str(bits).unwrap()
+    }
+
+    #[test]
+    fn unaligned_matches() {
+        let bv = bitvec("1011 0110 1101 1011 0");
+        let pattern = bitvec("1101");
+        assert_eq!(bv.find(&pattern, 0), Some(2));
+        assert_eq!(bv.find_all(&pattern), vec![2, 5, 8, 11]);
+        assert_eq!(bv.find(&bitvec("0000"), 0), None);
+        assert_eq!(bv.find(&bv, 0), Some(0));
+        assert_eq!(bv.find(&bitvec(""), 17), Some(17));
+        assert_eq!(bv.find(&pattern, 18), None);
+    }
+
+    #[test]
+    fn long_patterns() {
+        // A 100-bit pattern placed 37 bits into a run of 0s
+        let mut pattern = BitVec::from(&[0xC3, 0x5A, 0x0F, 0xF0, 0x99, 0x66, 0xA5, 0x3C, 0x81, 0x7E, 0xDB, 0x24, 0x55]);
+        pattern.truncate(100);
+        let mut bv = BitVec::new();
+        bv.resize(37, false);
+        bv.extend(&pattern);
+        bv.resize(300, false);
+
+        assert_eq!(bv.find(&pattern, 0), Some(37));
+        assert_eq!(bv.find_all(&pattern), vec![37]);
+        // A difference in the last bit spoils the match
+        let mut near = pattern.clone();
+        near.flip_range(99..);
+        assert_eq!(bv.find(&near, 0), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! ## Seeking the reading position
+//! Moving the sequential reading position of a BitVec in the manner of
+//! `io::Seek`, and peeking at the bits after it without moving it, as
+//! decoders do for table lookups. The position is a bit index, which may
+//! equal the length once everything has been read.
+//!
+//! Methods which change the length treat the position as an index into the
+//! bits. Pushing and extending leave it alone. Popping, truncating, draining
+//! and removing leave it at the same index, pulled back to the new end if the
+//! vector shrinks past it, and inserting leaves it at the same index too, so
+//! bits inserted before it move the unread bits along. Clearing the vector
+//! resets it to the start.
+
+use crate::BitVec;
+
+/// A place to seek the reading position to, as with `io::SeekFrom`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// A bit index from the start of the vector
+    Start(usize),
+    /// A number of bits from the end of the vector
+    End(isize),
+    /// A number of bits from the current reading position
+    Current(isize),
+}
+
+impl BitVec {
+    /// Moves the reading position, which must be within the length, without
+    /// checking it
+    pub(crate) fn place_read_position(&mut self, position: usize) {
+        self.byte_idx = position / 8;
+        self.bit_idx = (position % 8) as u8;
+    }
+
+    /// Pulls the reading position back to the end if the vector has shrunk
+    /// past it
+    pub(crate) fn clamp_read_position(&mut self) {
+        if self.get_read_position() > self.len {
+            self.place_read_position(self.len);
+        }
+    }
+
+    /// Moves the reading position, returning the new position. Returns None,
+    /// without moving, if the target is before the start or past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, SeekFrom};
+    ///
+    /// let mut bv = BitVec::from(&[0x0F, 0xF0]);
+    /// assert_eq!(bv.seek(SeekFrom::End(-12)), Some(4));
+    /// assert_eq!(bv.read_byte(), Some(0xFF));
+    /// assert_eq!(bv.seek(SeekFrom::Current(-3)), Some(9));
+    /// assert_eq!(bv.seek(SeekFrom::Current(-10)), None);
+    /// ```
+    pub fn seek(&mut self, pos: SeekFrom) -> Option<usize> {
+        let position = match pos {
+            SeekFrom::Start(index) => Some(index),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.get_read_position().checked_add_signed(offset),
+        }
+        .filter(|&position| position <= self.len)?;
+
+        self.place_read_position(position);
+        Some(position)
+    }
+
+    /// Moves the reading position forward a number of bits. Returns false,
+    /// without moving, if fewer bits than that remain.
+    pub fn skip(&mut self, count: usize) -> bool {
+        let position = self.get_read_position();
+        if count > self.len - position {
+            return false;
+        }
+        self.place_read_position(position + count);
+        true
+    }
+
+    /// Moves the reading position back a number of bits, to read them
+    /// again. Returns false, without moving, if fewer bits than that have
+    /// been read.
+    pub fn rewind(&mut self, count: usize) -> bool {
+        let position = self.get_read_position();
+        if count > position {
+            return false;
+        }
+        self.place_read_position(position - count);
+        true
+    }
+
+    /// Returns the next bit in sequence without moving the reading position,
+    /// or None at the end
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0xA5, 0x0F]);
+    /// assert_eq!(bv.peek_bits(3), Some(0b101));
+    /// assert_eq!(bv.read_bit_seq(), Some(1));
+    /// assert_eq!(bv.peek_bit(), Some(0));
+    /// assert_eq!(bv.peek_byte(), Some(0x4A));
+    /// assert!(bv.skip(15));
+    /// assert_eq!(bv.peek_bit(), None);
+    /// assert!(bv.rewind(4));
+    /// assert_eq!(bv.peek_bits(4), Some(0xF));
+    /// ```
+    pub fn peek_bit(&self) -> Option<u8> {
+        let position = self.get_read_position();
+        (position < self.len).then(|| self.get_bit(position) as u8)
+    }
+
+    /// Returns the next `count` bits in sequence, MSB first, without moving
+    /// the reading position, or None if fewer than that remain. Panics if
+    /// the count is more than 64.
+    pub fn peek_bits(&self, count: u32) -> Option<u64> {
+        if count > u64::BITS {
+            panic!("BitVec: cannot read more than 64 bits at once")
+        }
+        let start = self.get_read_position();
+        let end = start + count as usize;
+        (end <= self.len).then(|| self.slice(start..end).to_u64())
+    }
+
+    /// Returns the next 8 bits in sequence as a byte, in the bit order of
+    /// the vector as with `read_byte`, without moving the reading position,
+    /// or None if fewer than that remain
+    pub fn peek_byte(&self) -> Option<u8> {
+        self.peek_bits(8).map(|byte| self.order.convert(byte as u8))
+    }
+
+    /// Moves the reading position forward to the next byte boundary,
+    /// stopping at the end of the vector
+    pub fn align_to_byte(&mut self) {
+        let position = self.get_read_position().next_multiple_of(8).min(self.len);
+        self.place_read_position(position);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeking() {
+        let mut bv = BitVec::from_bit_str("1010 1100 11").unwrap();
+        assert_eq!(bv.seek(SeekFrom::Start(10)), Some(10));
+        assert_eq!(bv.read_bit_seq(), None);
+        assert_eq!(bv.seek(SeekFrom::Start(11)), None);
+        assert_eq!(bv.seek(SeekFrom::End(1)), None);
+        assert_eq!(bv.seek(SeekFrom::End(-10)), Some(0));
+        assert_eq!(bv.seek(SeekFrom::Current(-1)), None);
+        assert_eq!(bv.get_read_position(), 0);
+
+        assert!(bv.skip(3));
+        assert_eq!(bv.read_bit_seq(), Some(0));
+        bv.align_to_byte();
+        assert_eq!(bv.get_read_position(), 8);
+        assert!(!bv.skip(3));
+        assert!(bv.skip(2));
+        bv.align_to_byte();
+        assert_eq!(bv.get_read_position(), 10);
+    }
+
+    #[test]
+    fn peeking_and_rewinding() {
+        let mut bv = BitVec::from_bit_str("1100 1010 0111").unwrap();
+        assert_eq!(bv.peek_bit(), Some(1));
+        assert_eq!(bv.peek_bits(0), Some(0));
+        assert_eq!(bv.peek_bits(12), Some(0xCA7));
+        assert_eq!(bv.peek_bits(13), None);
+        assert_eq!(bv.get_read_position(), 0);
+
+        assert!(bv.skip(2));
+        assert_eq!(bv.peek_byte(), bv.clone().read_byte());
+        assert_eq!(bv.peek_byte(), Some(0x29));
+        assert!(!bv.rewind(3));
+        assert!(bv.rewind(2));
+        assert_eq!(bv.read_bit_seq(), Some(1));
+
+        bv.seek(SeekFrom::End(-7));
+        assert_eq!(bv.peek_byte(), None);
+        assert!(bv.rewind(1));
+        assert_eq!(bv.read_byte(), Some(0xA7));
+        assert_eq!(bv.peek_bit(), None);
+    }
+
+    #[test]
+    fn shrinking_clamps_the_position() {
+        let mut bv = BitVec::from(&[0xAB, 0xCD, 0xEF]);
+        bv.seek(SeekFrom::End(0));
+        bv.pop_bit();
+        assert_eq!(bv.get_read_position(), 23);
+        bv.pop_byte();
+        assert_eq!(bv.get_read_position(), 15);
+        assert_eq!(bv.pop_vec_byte(), Some(0xCC));
+        assert_eq!((bv.len_bits(), bv.get_read_position()), (8, 8));
+        assert_eq!(bv.read_bit_seq(), None);
+
+        // Popping behind the position leaves it alone
+        bv.seek(SeekFrom::Start(2));
+        bv.pop_bit();
+        assert_eq!(bv.get_read_position(), 2);
+        bv.truncate(1);
+        assert_eq!(bv.get_read_position(), 1);
+    }
+
+    #[test]
+    fn position_stays_at_its_index() {
+        let mut bv = BitVec::from(&[0x0F]);
+        bv.seek(SeekFrom::Start(4));
+        bv.insert_bit(0, true);
+        assert_eq!(bv.get_read_position(), 4);
+        assert_eq!(bv.read_bit_seq(), Some(0));
+        bv.remove_bit(0);
+        bv.push_bit(true);
+        assert_eq!(bv.get_read_position(), 5);
+        bv.clear();
+        assert_eq!(bv.get_read_position(), 0);
+    }
+
+    #[test]
+    fn position_stays_across_splices() {
+        let mut bv = BitVec::from(&[0x0F, 0xF0]);
+        bv.seek(SeekFrom::Start(6));
+        bv.insert_bits(0, &BitVec::from_bit_str("111").unwrap());
+        assert_eq!(bv.get_read_position(), 6);
+        bv.drain(2..4);
+        assert_eq!(bv.get_read_position(), 6);
+        assert_eq!(bv.read_bit_seq(), Some(1));
+
+        // Draining past the position pulls it back to the new end
+        bv.drain(3..);
+        assert_eq!(bv.get_read_position(), 3);
+    }
+}
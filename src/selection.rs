@@ -0,0 +1,125 @@
+//! ## Selection vectors
+//! Vectorized query engines pass the rows that survive a filter either as a
+//! bitmap or as a selection vector, the u32 indices of the selected rows,
+//! and switch between them as the density changes. A selection vector costs
+//! 32 bits a row selected and a bitmap one bit a row, so below one row in
+//! 32 the selection vector is the smaller, and also the quicker to walk.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// The density below which a selection vector is smaller than a bitmap
+pub const SELECTION_DENSITY: f64 = 1.0 / 32.0;
+
+/// A way of passing the selected rows of a batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Representation {
+    /// One bit for every row
+    Bitmap,
+    /// The index of every selected row
+    SelectionVector,
+}
+
+impl Representation {
+    /// Returns the smaller representation for a fraction of rows selected
+    pub fn for_density(density: f64) -> Self {
+        if density < SELECTION_DENSITY {
+            Representation::SelectionVector
+        } else {
+            Representation::Bitmap
+        }
+    }
+}
+
+impl BitVec {
+    /// Returns the fraction of bits that are set, or 0 if the vector is
+    /// empty
+    pub fn density(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        self.count_ones_in(..) as f64 / self.len as f64
+    }
+
+    /// Returns the smaller way of passing the set bits, by their density
+    pub fn recommended_representation(&self) -> Representation {
+        Representation::for_density(self.density())
+    }
+
+    /// Returns the indices of the set bits, in order. Panics if a set bit is
+    /// at an index beyond `u32::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, Representation};
+    ///
+    /// let mut filter = BitVec::from(&[0; 16]);
+    /// filter.set_bit(3, true);
+    /// filter.set_bit(97, true);
+    /// assert_eq!(filter.recommended_representation(), Representation::SelectionVector);
+    /// let selection = filter.to_selection_vector();
+    /// assert_eq!(selection, vec![3, 97]);
+    /// assert_eq!(BitVec::from_selection_vector(128, &selection), Ok(filter));
+    /// ```
+    pub fn to_selection_vector(&self) -> Vec<u32> {
+        let mut selection = Vec::with_capacity(self.count_ones_in(..));
+        for index in self.iter_ones() {
+            let Ok(index) = u32::try_from(index) else {
+                panic!("BitVec: index too large for a selection vector")
+            };
+            selection.push(index);
+        }
+        selection
+    }
+
+    /// Builds a vector of `len` bits with the bits at the indices set, in any
+    /// order. Returns an error if an index is out of bounds.
+    pub fn from_selection_vector(len: usize, selection: &[u32]) -> Result<BitVec, BitVecError> {
+        let mut bv = BitVec::with_capacity(len);
+        bv.resize(len, false);
+        for &index in selection {
+            bv.try_set_bit(index as usize, true)?;
+        }
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bv = BitVec::from_bit_str("0100 0000 0011 0000 1").unwrap();
+        let selection = bv.to_selection_vector();
+        assert_eq!(selection, vec![1, 10, 11, 16]);
+        assert_eq!(BitVec::from_selection_vector(17, &selection), Ok(bv));
+
+        // Order and repeats do not matter
+        let unordered = BitVec::from_selection_vector(17, &[16, 1, 11, 10, 1]).unwrap();
+        assert_eq!(unordered.to_selection_vector(), selection);
+        assert_eq!(
+            BitVec::from_selection_vector(17, &[3, 17]),
+            Err(BitVecError::IndexOutOfBounds { index: 17, len: 17 })
+        );
+        assert!(BitVec::new().to_selection_vector().is_empty());
+    }
+
+    #[test]
+    fn density_threshold() {
+        let mut bv = BitVec::from(&[0; 8]);
+        assert_eq!(bv.density(), 0.0);
+        assert_eq!(BitVec::new().density(), 0.0);
+        bv.set_bit(5, true);
+        assert_eq!(bv.recommended_representation(), Representation::SelectionVector);
+        // 2 in 64 is exactly the threshold
+        bv.set_bit(50, true);
+        assert_eq!(bv.density(), 1.0 / 32.0);
+        assert_eq!(bv.recommended_representation(), Representation::Bitmap);
+    }
+}
@@ -0,0 +1,203 @@
+//! ## Serde support
+//! Human-readable formats store a BitVec as a string of `0`s and `1`s, while
+//! binary formats store a `(bit length, packed bytes)` tuple. Both forms
+//! round-trip the exact bit length, including non-byte-aligned lengths.
+//!
+//! Only the bits are stored. The bit order, growth policy and reading
+//! position are lost, so a deserialized BitVec packs bytes MSB first, grows
+//! freely, and reads from the start; call `set_bit_order` on it to restore
+//! an LSB-first order.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+use crate::{BitOrder, BitVec, GrowthPolicy};
+
+/// Packed bytes, serialized with `serialize_bytes` rather than as a sequence
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Owned packed bytes, accepted as bytes or as a sequence of u8s
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ByteBufVisitor;
+
+        impl<'de> Visitor<'de> for ByteBufVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("packed bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(ByteBufVisitor)
+    }
+}
+
+impl Serialize for BitVec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let bits: String = (0..self.len)
+                .map(|index| if self.get_bit(index) { '1' } else { '0' })
+                .collect();
+            serializer.serialize_str(&bits)
+        } else {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&(self.len as u64))?;
+            tuple.serialize_element(&Bytes(&self.data[..self.len.div_ceil(8)]))?;
+            tuple.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BitVec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BitVecVisitor;
+
+        impl<'de> Visitor<'de> for BitVecVisitor {
+            type Value = BitVec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string of 0s and 1s, or a (bit length, bytes) tuple")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<BitVec, E> {
+                let mut bv = BitVec::new();
+                for c in v.chars() {
+                    match c {
+                        '0' => bv.push_bit(false),
+                        '1' => bv.push_bit(true),
+                        _ => return Err(E::invalid_value(de::Unexpected::Char(c), &self)),
+                    }
+                }
+                Ok(bv)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BitVec, A::Error> {
+                let len: u64 = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let ByteBuf(mut data) = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                let len = usize::try_from(len)
+                    .map_err(|_| de::Error::custom("bit length does not fit in usize"))?;
+                if data.len() != len.div_ceil(8) {
+                    return Err(de::Error::invalid_length(data.len(), &"packed bytes matching the bit length"));
+                }
+                // Padding bits must stay 0
+                if let Some(last_byte) = data.last_mut() {
+                    *last_byte &= BitVec::mask_msb(len % 8);
+                }
+
+                Ok(BitVec { data, len, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow })
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BitVecVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, BitVecVisitor)
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Configure, Token};
+
+    /// Compares BitVecs by length and data for the token assertions
+    struct Exact(BitVec);
+
+    impl PartialEq for Exact {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.len == other.0.len && self.0.data == other.0.data
+        }
+    }
+
+    impl fmt::Debug for Exact {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(&self.0, f)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Exact {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            BitVec::deserialize(deserializer).map(Exact)
+        }
+    }
+
+    fn sample() -> BitVec {
+        // 0b01101011 0b10100xxx
+        BitVec { data: vec![0x6B, 0xA0], len: 13, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow }
+    }
+
+    #[test]
+    fn readable_round_trip() {
+        assert_ser_tokens(&sample().readable(), &[Token::Str("0110101110100")]);
+        assert_de_tokens(&Exact(sample()).readable(), &[Token::Str("0110101110100")]);
+        assert_de_tokens_error::<serde_test::Readable<Exact>>(
+            &[Token::Str("0120")],
+            "invalid value: character `2`, expected a string of 0s and 1s, or a (bit length, bytes) tuple",
+        );
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let tokens = [
+            Token::Tuple { len: 2 },
+            Token::U64(13),
+            Token::Bytes(&[0x6B, 0xA0]),
+            Token::TupleEnd,
+        ];
+        assert_ser_tokens(&sample().compact(), &tokens);
+        assert_de_tokens(&Exact(sample()).compact(), &tokens);
+
+        // The bit order is not stored, only the bits
+        let mut lsb_first = sample();
+        lsb_first.set_bit_order(BitOrder::Lsb0);
+        assert_ser_tokens(&lsb_first.compact(), &tokens);
+    }
+
+    #[test]
+    fn compact_rejects_mismatched_length() {
+        assert_de_tokens_error::<serde_test::Compact<Exact>>(
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(17),
+                Token::Bytes(&[0x6B, 0xA0]),
+                Token::TupleEnd,
+            ],
+            "invalid length 2, expected packed bytes matching the bit length",
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! ## Set operations
+//! A BitVec as a set of indices, the indices of its set bits. Vectors of
+//! different lengths are compared as though the shorter were padded with 0s,
+//! so a set does not change by growing the vector. The predicates work a
+//! word at a time and stop at the first word that settles the answer.
+
+use crate::{BitVec, LengthPolicy};
+
+/// Loads 8 bytes from a start index as a word, with 0s past the end
+fn load_word(data: &[u8], start: usize) -> u64 {
+    let mut word = [0; 8];
+    let bytes = data.get(start..).unwrap_or(&[]);
+    let count = bytes.len().min(8);
+    word[..count].copy_from_slice(&bytes[..count]);
+    u64::from_be_bytes(word)
+}
+
+impl BitVec {
+    /// Checks whether any word of the two vectors combines to a non-zero
+    /// word, stopping at the first
+    fn any_combined(&self, other: &BitVec, op: impl Fn(u64, u64) -> u64) -> bool {
+        let len = self.data.len().max(other.data.len());
+        (0..len).step_by(8).any(|start| op(load_word(&self.data, start), load_word(&other.data, start)) != 0)
+    }
+
+    /// Returns the bits set here but not in another vector (aka AND NOT),
+    /// with the length of this vector
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let a = BitVec::from_bit_str("1110 1").unwrap();
+    /// let b = BitVec::from_bit_str("0110").unwrap();
+    /// assert_eq!(a.difference(&b).to_bit_string(), "10001");
+    /// assert!(b.is_subset(&a));
+    /// assert!(b.is_disjoint(&a.difference(&b)));
+    /// ```
+    pub fn difference(&self, other: &BitVec) -> BitVec {
+        let mut result = self.logic_copy();
+        let _ = result.and_with(&other.complement(), LengthPolicy::Truncate);
+        if other.len < self.len {
+            result.append_bits(&self.slice(other.len..).to_bitvec());
+        }
+        result
+    }
+
+    /// Checks whether every bit set here is also set in another vector
+    pub fn is_subset(&self, other: &BitVec) -> bool {
+        !self.any_combined(other, |a, b| a & !b)
+    }
+
+    /// Checks whether every bit set in another vector is also set here
+    pub fn is_superset(&self, other: &BitVec) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Checks whether no bit is set in both vectors
+    pub fn is_disjoint(&self, other: &BitVec) -> bool {
+        !self.any_combined(other, |a, b| a & b)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A set of indices in a vector of a given length
+    fn set(len: usize, indices: &[usize]) -> BitVec {
+        let mut bv = BitVec::new();
+        bv.resize(len, false);
+        for &index in indices {
+            bv.set_bit(index, true);
+        }
+        bv
+    }
+
+    #[test]
+    fn differences() {
+        let a = set(100, &[1, 5, 64, 70, 99]);
+        let b = set(80, &[5, 70, 79]);
+        assert_eq!(a.difference(&b), set(100, &[1, 64, 99]));
+        assert_eq!(b.difference(&a), set(80, &[79]));
+        assert_eq!(a.symmetric_difference(&b), set(100, &[1, 64, 79, 99]));
+        assert_eq!(a.difference(&a).count_ones(), 0);
+    }
+
+    #[test]
+    fn predicates() {
+        let small = set(70, &[3, 66]);
+        let large = set(200, &[3, 10, 66, 150]);
+        assert!(small.is_subset(&large));
+        assert!(large.is_superset(&small));
+        assert!(!large.is_subset(&small));
+        assert!(!small.is_disjoint(&large));
+        assert!(small.is_disjoint(&set(200, &[4, 150])));
+
+        // Trailing 0s past the end make no difference
+        assert!(set(10, &[]).is_subset(&BitVec::new()));
+        assert!(large.is_subset(&set(300, &[3, 10, 66, 150, 250])));
+        assert!(!set(300, &[250]).is_subset(&large));
+    }
+}
@@ -0,0 +1,265 @@
+//! ## Shifts and rotations
+//! Whole-vector shifts and rotations that keep the length. Bit 0 is the
+//! leftmost bit, so shifting left moves bits towards index 0 and shifting
+//! right moves them towards the end, as for a big-endian integer.
+
+use core::ops;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Clears the padding bits after the last bit of the vector
+    pub(crate) fn clear_padding(&mut self) {
+        let tail = self.len % 8;
+        if tail > 0 {
+            self.data[self.len / 8] &= BitVec::mask_msb(tail);
+        }
+    }
+
+    /// Shifts every bit `k` places towards the start, filling the end with
+    /// 0s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x0F, 0x81]);
+    /// bv.shift_left(3);
+    /// assert_eq!(bv.as_binary(), "01111100 00001000");
+    /// ```
+    pub fn shift_left(&mut self, k: usize) {
+        let num_bytes = self.len.div_ceil(8);
+        if k >= self.len {
+            self.data[..num_bytes].fill(0);
+            return;
+        }
+
+        self.clear_padding();
+        let byte_shift = k / 8;
+        let bit_shift = k % 8;
+        for i in 0..num_bytes - byte_shift {
+            let src = i + byte_shift;
+            let mut byte = self.data[src] << bit_shift;
+            if bit_shift > 0 && src + 1 < num_bytes {
+                byte |= self.data[src + 1] >> (8 - bit_shift);
+            }
+            self.data[i] = byte;
+        }
+        self.data[num_bytes - byte_shift..num_bytes].fill(0);
+    }
+
+    /// Shifts every bit `k` places towards the end, filling the start with
+    /// 0s. Bits shifted past the end are lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x0F, 0x81]);
+    /// bv.shift_right(3);
+    /// assert_eq!(bv.as_binary(), "00000001 11110000");
+    /// ```
+    pub fn shift_right(&mut self, k: usize) {
+        let num_bytes = self.len.div_ceil(8);
+        if k >= self.len {
+            self.data[..num_bytes].fill(0);
+            return;
+        }
+
+        let byte_shift = k / 8;
+        let bit_shift = k % 8;
+        for i in (byte_shift..num_bytes).rev() {
+            let src = i - byte_shift;
+            let mut byte = self.data[src] >> bit_shift;
+            if bit_shift > 0 && src > 0 {
+                byte |= self.data[src - 1] << (8 - bit_shift);
+            }
+            self.data[i] = byte;
+        }
+        self.data[..byte_shift].fill(0);
+        self.clear_padding();
+    }
+
+    /// Shifts every bit `k` places towards the end, filling the start with
+    /// copies of the first bit, so a vector holding a two's complement value
+    /// keeps its sign, as with `>>` on a signed integer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // -12 as 8 bits, halved twice
+    /// let mut bv = BitVec::from(&[0xF4]);
+    /// bv.arithmetic_shr(2);
+    /// assert_eq!(bv.as_binary(), "11111101");
+    /// ```
+    pub fn arithmetic_shr(&mut self, k: usize) {
+        if self.len == 0 {
+            return;
+        }
+
+        let sign = self.get_bit(0);
+        self.shift_right(k);
+        if sign {
+            self.set_range(..k.min(self.len), true);
+        }
+    }
+
+    /// Rotates every bit `k` places towards the start, with the bits shifted
+    /// off the start coming back in at the end
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x0F, 0x81]);
+    /// bv.rotate_left(3);
+    /// assert_eq!(bv.as_binary(), "01111100 00001000");
+    /// bv.rotate_left(5);
+    /// assert_eq!(bv.as_binary(), "10000001 00001111");
+    /// ```
+    pub fn rotate_left(&mut self, k: usize) {
+        if self.len == 0 {
+            return;
+        }
+        let k = k % self.len;
+        if k == 0 {
+            return;
+        }
+
+        let mut wrapped = self.clone();
+        wrapped.shift_right(self.len - k);
+        self.shift_left(k);
+        for (byte, wrapped) in self.data.iter_mut().zip(&wrapped.data[..self.len.div_ceil(8)]) {
+            *byte |= wrapped;
+        }
+    }
+
+    /// Rotates every bit `k` places towards the end, with the bits shifted
+    /// off the end coming back in at the start
+    pub fn rotate_right(&mut self, k: usize) {
+        if self.len == 0 {
+            return;
+        }
+        self.rotate_left(self.len - k % self.len);
+    }
+}
+
+// ############################################################################
+// Custom ops
+// ############################################################################
+
+impl ops::Shl<usize> for BitVec {
+    type Output = Self;
+
+    fn shl(mut self, rhs: usize) -> Self::Output {
+        self.shift_left(rhs);
+        self
+    }
+}
+
+impl ops::ShlAssign<usize> for BitVec {
+    fn shl_assign(&mut self, rhs: usize) {
+        self.shift_left(rhs);
+    }
+}
+
+impl ops::Shr<usize> for BitVec {
+    type Output = Self;
+
+    fn shr(mut self, rhs: usize) -> Self::Output {
+        self.shift_right(rhs);
+        self
+    }
+}
+
+impl ops::ShrAssign<usize> for BitVec {
+    fn shr_assign(&mut self, rhs: usize) {
+        self.shift_right(rhs);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    /// Shifts one bit at a time, for checking the byte-level versions
+    fn naive_shift(bv: &BitVec, k: isize) -> Vec<bool> {
+        (0..bv.len_bits() as isize)
+            .map(|i| {
+                let src = i + k;
+                src >= 0 && src < bv.len_bits() as isize && bv.get_bit(src as usize)
+            })
+            .collect()
+    }
+
+    fn bits(bv: &BitVec) -> Vec<bool> {
+        (0..bv.len_bits()).map(|i| bv.get_bit(i)).collect()
+    }
+
+    #[test]
+    fn shifts_match_naive() {
+        // Padding bits are set to check they never shift in
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        for k in 0..22 {
+            let left = bv.clone() << k;
+            assert_eq!(bits(&left), naive_shift(&bv, k as isize), "shift left {}", k);
+            let right = bv.clone() >> k;
+            assert_eq!(bits(&right), naive_shift(&bv, -(k as isize)), "shift right {}", k);
+            assert_eq!(right.data[2] & 0x1F, 0);
+        }
+    }
+
+    #[test]
+    fn arithmetic_shifts() {
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        for k in 0..22 {
+            // Positive, so the same as a logical shift
+            let mut positive = bv.clone();
+            positive.arithmetic_shr(k);
+            assert_eq!(positive, bv.clone() >> k);
+
+            // Negative, the vacated bits set
+            let mut negative = bv.complement();
+            negative.arithmetic_shr(k);
+            let logical = bv.complement() >> k;
+            let expected: Vec<bool> = (0..19).map(|i| i < k || logical.get_bit(i)).collect();
+            assert_eq!(bits(&negative), expected, "shift {}", k);
+            assert_eq!(negative.data[2] & 0x1F, 0);
+        }
+
+        // Matches a signed integer
+        let mut bv = BitVec::from(&(-1000i16).to_be_bytes());
+        bv.arithmetic_shr(3);
+        assert_eq!(bv.as_bytes(), &(-1000i16 >> 3).to_be_bytes());
+        BitVec::new().arithmetic_shr(1);
+    }
+
+    #[test]
+    fn rotations() {
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let original = bits(&bv);
+        for k in 0..40 {
+            let mut rotated = bv.clone();
+            rotated.rotate_left(k);
+            let expected: Vec<bool> = (0..19).map(|i| original[(i + k) % 19]).collect();
+            assert_eq!(bits(&rotated), expected, "rotate left {}", k);
+
+            rotated.rotate_right(k);
+            assert_eq!(rotated, bv);
+        }
+
+        let mut empty = BitVec::new();
+        empty.rotate_left(3);
+        empty <<= 2;
+        assert_eq!(empty.len_bits(), 0);
+    }
+}
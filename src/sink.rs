@@ -0,0 +1,239 @@
+//! ## Bit sinks
+//! A trait for anything bits can be pushed to, so encoders can be written
+//! once for a BitVec, a stream through `BitWriter` or `StreamBitWriter`, or a
+//! `CountingSink` that only measures how many bits the encoding takes.
+
+use core::convert::Infallible;
+
+use crate::{BitVec, BitVecError};
+
+/// What happens when a value pushed as a field has more bits than the field
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WidthPolicy {
+    /// Keep only the low bits of the value, as `push_bits` does
+    #[default]
+    Truncate,
+    /// Reject the value with an error
+    Error,
+    /// Panic in debug builds, where tests run, and truncate in release builds
+    DebugAssert,
+}
+
+impl WidthPolicy {
+    /// Checks that a value fits in `count` bits, returning its low `count`
+    /// bits, or an error if it does not fit and the policy is `Error`.
+    /// Panics if the count is more than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitSink, BitVec, BitVecError, WidthPolicy};
+    ///
+    /// fn encode(bv: &mut BitVec, value: u64) -> Result<(), BitVecError> {
+    ///     let value = WidthPolicy::Error.apply(value, 4)?;
+    ///     bv.push_bits(value, 4).unwrap();
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut bv = BitVec::new();
+    /// assert!(encode(&mut bv, 15).is_ok());
+    /// assert!(encode(&mut bv, 16).is_err());
+    /// assert_eq!(WidthPolicy::Truncate.apply(0x1F, 4), Ok(0xF));
+    /// ```
+    pub fn apply(self, value: u64, count: u32) -> Result<u64, BitVecError> {
+        if count > 64 {
+            panic!("BitVec: cannot push more than 64 bits of a value")
+        }
+        let low = value & u64::MAX.checked_shr(64 - count).unwrap_or(0);
+        if low == value {
+            return Ok(value);
+        }
+
+        match self {
+            WidthPolicy::Truncate => Ok(low),
+            WidthPolicy::Error => Err(BitVecError::ValueTooWide { value, width: count }),
+            WidthPolicy::DebugAssert => {
+                debug_assert!(false, "BitVec: value {} does not fit in {} bits", value, count);
+                Ok(low)
+            }
+        }
+    }
+}
+
+/// A destination for a sequence of bits
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitSink, BitVec, CountingSink};
+///
+/// fn encode<S: BitSink>(sink: &mut S, values: &[u8]) -> Result<(), S::Error> {
+///     for &value in values {
+///         sink.push_bits(value as u64, 3)?;
+///     }
+///     Ok(())
+/// }
+///
+/// let mut counter = CountingSink::new();
+/// encode(&mut counter, &[1, 2, 3]).unwrap();
+/// assert_eq!(counter.len_bits(), 9);
+///
+/// let mut bv = BitVec::new();
+/// encode(&mut bv, &[1, 2, 3]).unwrap();
+/// assert_eq!(bv.to_bit_string(), "001010011");
+/// ```
+pub trait BitSink {
+    /// The error from a failed push
+    type Error;
+
+    /// Pushes one bit
+    fn push_bit(&mut self, value: bool) -> Result<(), Self::Error>;
+
+    /// Pushes the low `count` bits of a value, most significant first.
+    /// Panics if the count is more than 64.
+    fn push_bits(&mut self, value: u64, count: u32) -> Result<(), Self::Error> {
+        if count > 64 {
+            panic!("BitVec: cannot push more than 64 bits of a value")
+        }
+        for shift in (0..count).rev() {
+            self.push_bit(value >> shift & 1 == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Pushes every bit of some bytes, most significant first
+    fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        for &byte in bytes {
+            self.push_bits(byte as u64, 8)?;
+        }
+        Ok(())
+    }
+}
+
+impl BitVec {
+    /// Pushes the low `count` bits of a value, most significant first, with
+    /// a policy for values that do not fit. Returns an error, pushing
+    /// nothing, only if the value does not fit and the policy is `Error`.
+    /// Panics if the count is more than 64.
+    pub fn push_bits_with(&mut self, value: u64, count: u32, policy: WidthPolicy) -> Result<(), BitVecError> {
+        let value = policy.apply(value, count)?;
+        self.push_low_bits(value, count);
+        Ok(())
+    }
+}
+
+impl BitSink for BitVec {
+    type Error = Infallible;
+
+    fn push_bit(&mut self, value: bool) -> Result<(), Infallible> {
+        BitVec::push_bit(self, value);
+        Ok(())
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), Infallible> {
+        self.extend(&BitVec::from(bytes));
+        Ok(())
+    }
+}
+
+/// A sink that stores nothing, only counting the bits pushed to it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CountingSink {
+    len: usize,
+}
+
+impl CountingSink {
+    /// Constructs a new CountingSink with no bits counted
+    pub fn new() -> Self {
+        Self { len: 0 }
+    }
+
+    /// Get the number of bits pushed
+    pub fn len_bits(&self) -> usize {
+        self.len
+    }
+}
+
+impl BitSink for CountingSink {
+    type Error = Infallible;
+
+    fn push_bit(&mut self, _value: bool) -> Result<(), Infallible> {
+        self.len += 1;
+        Ok(())
+    }
+
+    fn push_bits(&mut self, _value: u64, count: u32) -> Result<(), Infallible> {
+        if count > 64 {
+            panic!("BitVec: cannot push more than 64 bits of a value")
+        }
+        self.len += count as usize;
+        Ok(())
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), Infallible> {
+        self.len += bytes.len() * 8;
+        Ok(())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a few fields through any sink
+    fn encode<S: BitSink>(sink: &mut S) -> Result<(), S::Error> {
+        sink.push_bit(true)?;
+        sink.push_bits(0x2A, 6)?;
+        sink.push_bytes(&[0xDE, 0xAD])?;
+        sink.push_bits(u64::MAX, 64)?;
+        sink.push_bits(0, 0)
+    }
+
+    #[test]
+    fn sinks_agree() {
+        let mut bv = BitVec::new();
+        encode(&mut bv).unwrap();
+        let mut counter = CountingSink::new();
+        encode(&mut counter).unwrap();
+
+        assert_eq!(counter.len_bits(), 87);
+        assert_eq!(bv.len_bits(), 87);
+        assert_eq!(bv.slice(..23).to_bitvec().to_bit_string(), "11010101101111010101101");
+        assert_eq!(bv.slice(23..).count_ones(), 64);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writer_sink() {
+        let mut writer = crate::BitWriter::new(Vec::new());
+        encode(&mut writer).unwrap();
+        let mut bv = BitVec::new();
+        encode(&mut bv).unwrap();
+        assert_eq!(writer.finish().unwrap(), bv.as_bytes());
+    }
+
+    #[test]
+    fn width_policies() {
+        let mut bv = BitVec::new();
+        bv.push_bits_with(0x1F, 4, WidthPolicy::Truncate).unwrap();
+        assert_eq!(
+            bv.push_bits_with(0x1F, 4, WidthPolicy::Error),
+            Err(BitVecError::ValueTooWide { value: 0x1F, width: 4 })
+        );
+        bv.push_bits_with(0x0F, 4, WidthPolicy::Error).unwrap();
+        bv.push_bits_with(u64::MAX, 64, WidthPolicy::Error).unwrap();
+        assert!(bv.push_bits_with(1, 0, WidthPolicy::Error).is_err());
+        bv.push_bits_with(0, 0, WidthPolicy::Error).unwrap();
+        assert_eq!(bv.len_bits(), 72);
+        assert_eq!(bv.count_ones(), 72);
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "does not fit in 3 bits"))]
+    fn debug_assert_policy() {
+        assert_eq!(WidthPolicy::DebugAssert.apply(9, 3), Ok(1));
+    }
+}
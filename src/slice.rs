@@ -0,0 +1,627 @@
+//! ## Bit slices
+//! Borrowed views into a range of bits of a BitVec, analogous to `&[T]` and
+//! `&mut [T]` for a `Vec<T>`. A view may start and end part way through a
+//! byte, so it keeps the bit offset of its first bit within its first byte.
+//! A view can also be made straight over borrowed bytes.
+
+use alloc::vec::Vec;
+use core::ops::{Bound, RangeBounds};
+use core::{fmt, ops};
+
+use crate::{BitOrder, BitStr, BitVec, BitVecError, GrowthPolicy};
+
+/// Converts any range of bit indices into start and end indices, panicking
+/// if the range is reversed or runs past the length
+pub(crate) fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        panic!("BitVec: range {}..{} out of bounds for length {}", start, end, len)
+    }
+
+    (start, end)
+}
+
+/// Reads up to 8 bits starting at any bit position, returned in the high
+/// bits of the byte with the rest cleared
+pub(crate) fn load_byte(data: &[u8], position: usize, count: usize) -> u8 {
+    let byte_idx = position / 8;
+    let shift = position % 8;
+
+    let mut byte = data[byte_idx] << shift;
+    if shift > 0 && byte_idx + 1 < data.len() {
+        byte |= data[byte_idx + 1] >> (8 - shift);
+    }
+
+    byte & BitVec::mask_msb(count)
+}
+
+/// Writes the high `count` bits of a byte starting at any bit position,
+/// leaving the surrounding bits untouched
+pub(crate) fn store_byte(data: &mut [u8], position: usize, count: usize, byte: u8) {
+    let byte_idx = position / 8;
+    let shift = position % 8;
+    let mask = BitVec::mask_msb(count);
+    let byte = byte & mask;
+
+    data[byte_idx] = (data[byte_idx] & !(mask >> shift)) | (byte >> shift);
+    if shift + count > 8 {
+        let spill = 8 - shift;
+        data[byte_idx + 1] = (data[byte_idx + 1] & !(mask << spill)) | (byte << spill);
+    }
+}
+
+/// An immutable view into a range of bits
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// // 0b01101011 0b10100000
+/// let bv = BitVec::from(&[0x6B, 0xA0]);
+/// let slice = bv.slice(3..11);
+/// assert_eq!(slice.len(), 8);
+/// assert_eq!(slice.to_bitvec().pop_byte(), Some(0x5D));
+/// ```
+#[derive(Clone, Copy)]
+pub struct BitSlice<'a> {
+    data:   &'a [u8],   // bytes holding the bits of the view
+    offset: usize,      // bit offset of the first bit in the first byte
+    len:    usize,      // length in bits
+}
+
+/// A mutable view into a range of bits. Bits can be changed, but the length
+/// of the underlying vector cannot.
+pub struct BitSliceMut<'a> {
+    data:   &'a mut [u8],   // bytes holding the bits of the view
+    offset: usize,          // bit offset of the first bit in the first byte
+    len:    usize,          // length in bits
+}
+
+/// A read-only view of bits packed MSB first in borrowed bytes, such as a
+/// memory-mapped file, made without copying by `BitSlice::from_bytes`. It
+/// is a BitSlice, so everything a BitSlice does works on it.
+pub type BitVecRef<'a> = BitSlice<'a>;
+
+impl<'a> BitSlice<'a> {
+    /// Views the first `len` bits of some bytes, packed MSB first, without
+    /// copying them. Returns an error if the bytes hold fewer bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitSlice, BitSource};
+    ///
+    /// let bytes = [0b1011_0000, 0xFF];
+    /// let mut view = BitSlice::from_bytes(&bytes, 12).unwrap();
+    /// assert_eq!(view.count_ones(), 7);
+    /// assert_eq!(view.read_bits(4), Ok(Some(0b1011)));
+    /// assert!(BitSlice::from_bytes(&bytes, 17).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &'a [u8], len: usize) -> Result<Self, BitVecError> {
+        if len > bytes.len() * 8 {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: bytes.len() * 8 });
+        }
+        Ok(BitSlice { data: &bytes[..len.div_ceil(8)], offset: 0, len })
+    }
+
+    /// Builds a view from bytes holding exactly the bits from `offset`
+    pub(crate) fn from_parts(data: &'a [u8], offset: usize, len: usize) -> Self {
+        BitSlice { data, offset, len }
+    }
+
+    /// Returns the bits of the view as a `&BitStr`, borrowing the same bytes
+    pub fn as_bitstr(self) -> &'a BitStr {
+        BitStr::from_parts(self.data, self.offset, self.len)
+    }
+
+    /// Get the number of bits in the view
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the view contains no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the offset of the first bit within its byte of the vector
+    pub(crate) fn bit_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// beyond the end of the view.
+    pub fn get_bit(&self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("BitSlice: index out of bounds")
+        }
+
+        let position = self.offset + index;
+        self.data[position / 8] & (0x80 >> (position % 8)) != 0
+    }
+
+    /// Returns the bit value at the desired index, or None if the index is
+    /// beyond the end of the view
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.get_bit(index))
+    }
+
+    /// Returns a narrower view over a range of this view
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> BitSlice<'a> {
+        let (start, end) = resolve_range(range, self.len);
+        let first = self.offset + start;
+        let last = self.offset + end;
+
+        BitSlice {
+            data: &self.data[first / 8..last.div_ceil(8)],
+            offset: first % 8,
+            len: end - start,
+        }
+    }
+
+    /// Splits the view into two at an index
+    pub fn split_at(&self, mid: usize) -> (BitSlice<'a>, BitSlice<'a>) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Returns an iterator over the bits of the view
+    pub fn iter(&self) -> Bits<'a> {
+        Bits { slice: *self, front: 0, back: self.len }
+    }
+
+    /// Reads up to 8 bits from the view, in the high bits of a byte
+    fn load(&self, index: usize, count: usize) -> u8 {
+        load_byte(self.data, self.offset + index, count)
+    }
+
+    /// Returns the bits of the view as an unsigned integer, the first bit
+    /// being the most significant. Panics if the view is longer than 64 bits.
+    pub fn to_u64(&self) -> u64 {
+        if self.len > 64 {
+            panic!("BitSlice: {} bits do not fit in a u64", self.len)
+        }
+        (0..self.len).step_by(8).fold(0, |value, index| {
+            let count = (self.len - index).min(8);
+            (value << count) | (self.load(index, count) >> (8 - count)) as u64
+        })
+    }
+
+    /// Returns the number of set bits in the view
+    pub fn count_ones(&self) -> usize {
+        (0..self.len).step_by(8)
+            .map(|index| self.load(index, (self.len - index).min(8)).count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of clear bits in the view
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Copies the view into a new BitVec
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut data = Vec::with_capacity(self.len.div_ceil(8));
+        for index in (0..self.len).step_by(8) {
+            data.push(self.load(index, (self.len - index).min(8)));
+        }
+
+        BitVec { data, len: self.len, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow }
+    }
+
+    /// Combines two views of equal length byte by byte into a new BitVec
+    fn combine(&self, other: &BitSlice, op: impl Fn(u8, u8) -> u8) -> BitVec {
+        if self.len != other.len {
+            panic!("BitSlice: logical operations need views of equal length")
+        }
+
+        let mut data = Vec::with_capacity(self.len.div_ceil(8));
+        for index in (0..self.len).step_by(8) {
+            let count = (self.len - index).min(8);
+            data.push(op(self.load(index, count), other.load(index, count)) & BitVec::mask_msb(count));
+        }
+
+        BitVec { data, len: self.len, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow }
+    }
+
+    /// Returns a new BitVec holding the AND of two views of equal length
+    pub fn and(&self, other: &BitSlice) -> BitVec {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Returns a new BitVec holding the OR of two views of equal length
+    pub fn or(&self, other: &BitSlice) -> BitVec {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Returns a new BitVec holding the XOR of two views of equal length
+    pub fn xor(&self, other: &BitSlice) -> BitVec {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Returns a new BitVec holding the inversion of the view (aka NOT)
+    pub fn not(&self) -> BitVec {
+        self.combine(self, |a, _| !a)
+    }
+}
+
+impl<'a> BitSliceMut<'a> {
+    /// Views the first `len` bits of some bytes, packed MSB first, mutably
+    /// and without copying them. Returns an error if the bytes hold fewer
+    /// bits.
+    pub fn from_bytes(bytes: &'a mut [u8], len: usize) -> Result<Self, BitVecError> {
+        if len > bytes.len() * 8 {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: bytes.len() * 8 });
+        }
+        Ok(BitSliceMut { data: &mut bytes[..len.div_ceil(8)], offset: 0, len })
+    }
+
+    /// Get the number of bits in the view
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the view contains no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reborrows the view as an immutable view
+    pub fn as_slice(&self) -> BitSlice<'_> {
+        BitSlice { data: self.data, offset: self.offset, len: self.len }
+    }
+
+    /// Returns the bit value at the desired index. Panics if the index is
+    /// beyond the end of the view.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.as_slice().get_bit(index)
+    }
+
+    /// Sets the bit at the desired index. Panics if the index is beyond the
+    /// end of the view, as a view cannot grow.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.len {
+            panic!("BitSlice: index out of bounds")
+        }
+
+        let position = self.offset + index;
+        if value {
+            self.data[position / 8] |= 0x80 >> (position % 8);
+        } else {
+            self.data[position / 8] &= !(0x80 >> (position % 8));
+        }
+    }
+
+    /// Returns a narrower mutable view over a range of this view
+    pub fn slice_mut<R: RangeBounds<usize>>(&mut self, range: R) -> BitSliceMut<'_> {
+        let (start, end) = resolve_range(range, self.len);
+        let first = self.offset + start;
+        let last = self.offset + end;
+
+        BitSliceMut {
+            data: &mut self.data[first / 8..last.div_ceil(8)],
+            offset: first % 8,
+            len: end - start,
+        }
+    }
+
+    /// Sets every bit in the view to the same value
+    pub fn fill(&mut self, value: bool) {
+        let byte = if value { 0xFF } else { 0 };
+        for index in (0..self.len).step_by(8) {
+            store_byte(self.data, self.offset + index, (self.len - index).min(8), byte);
+        }
+    }
+
+    /// Overwrites the view with the bits of another view of equal length
+    pub fn copy_from(&mut self, src: &BitSlice) {
+        self.apply(src, |_, b| b);
+    }
+
+    /// Combines another view of equal length into this one, byte by byte
+    fn apply(&mut self, other: &BitSlice, op: impl Fn(u8, u8) -> u8) {
+        if self.len != other.len {
+            panic!("BitSlice: logical operations need views of equal length")
+        }
+
+        for index in (0..self.len).step_by(8) {
+            let count = (self.len - index).min(8);
+            let position = self.offset + index;
+            let byte = op(load_byte(self.data, position, count), other.load(index, count));
+            store_byte(self.data, position, count, byte);
+        }
+    }
+
+    /// Inverts every bit in the view
+    pub fn invert(&mut self) {
+        for index in (0..self.len).step_by(8) {
+            let count = (self.len - index).min(8);
+            let position = self.offset + index;
+            let byte = !load_byte(self.data, position, count);
+            store_byte(self.data, position, count, byte);
+        }
+    }
+}
+
+impl BitVec {
+    /// Returns an immutable view over a range of bits. Panics if the range
+    /// runs past the end of the vector.
+    ///
+    /// Indexing with a range, as `bv[4..12]`, gives the same bits as a
+    /// `&BitStr`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0x0F, 0xF0]);
+    /// assert_eq!(bv.slice(4..12).count_ones(), 8);
+    /// assert_eq!(bv.slice(..4).count_ones(), 0);
+    /// ```
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> BitSlice<'_> {
+        let (start, end) = resolve_range(range, self.len);
+
+        BitSlice {
+            data: &self.data[start / 8..end.div_ceil(8)],
+            offset: start % 8,
+            len: end - start,
+        }
+    }
+
+    /// Returns a mutable view over a range of bits. Panics if the range runs
+    /// past the end of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x00, 0x00]);
+    /// bv.slice_mut(6..10).fill(true);
+    /// assert_eq!(bv.as_binary(), "00000011 11000000");
+    /// ```
+    pub fn slice_mut<R: RangeBounds<usize>>(&mut self, range: R) -> BitSliceMut<'_> {
+        let (start, end) = resolve_range(range, self.len);
+
+        BitSliceMut {
+            data: &mut self.data[start / 8..end.div_ceil(8)],
+            offset: start % 8,
+            len: end - start,
+        }
+    }
+
+    /// Returns an immutable view over the whole vector
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        self.slice(..)
+    }
+
+    /// Returns a mutable view over the whole vector
+    pub fn as_mut_bitslice(&mut self) -> BitSliceMut<'_> {
+        self.slice_mut(..)
+    }
+}
+
+/// Iterator over the bits of a BitSlice, created by `BitSlice::iter`
+pub struct Bits<'a> {
+    slice: BitSlice<'a>,
+    front: usize,
+    back:  usize,
+}
+
+impl Iterator for Bits<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.slice.get_bit(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for Bits<'_> {
+    fn next_back(&mut self) -> Option<bool> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.slice.get_bit(self.back))
+    }
+}
+
+impl ExactSizeIterator for Bits<'_> {}
+
+impl<'a> IntoIterator for BitSlice<'a> {
+    type Item = bool;
+    type IntoIter = Bits<'a>;
+
+    fn into_iter(self) -> Bits<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &BitSlice<'a> {
+    type Item = bool;
+    type IntoIter = Bits<'a>;
+
+    fn into_iter(self) -> Bits<'a> {
+        self.iter()
+    }
+}
+
+// ############################################################################
+// Standard traits
+// ############################################################################
+
+/// Views are equal when they hold the same bits, wherever they start
+impl PartialEq for BitSlice<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (0..self.len).step_by(8).all(|index| {
+                let count = (self.len - index).min(8);
+                self.load(index, count) == other.load(index, count)
+            })
+    }
+}
+
+impl Eq for BitSlice<'_> {}
+
+impl fmt::Debug for BitSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BitSlice[")?;
+        for bit in self.iter() {
+            write!(f, "{}", bit as u8)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl fmt::Debug for BitSliceMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+// ############################################################################
+// Custom ops
+// ############################################################################
+
+impl ops::BitAnd for BitSlice<'_> {
+    type Output = BitVec;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.and(&rhs)
+    }
+}
+
+impl ops::BitOr for BitSlice<'_> {
+    type Output = BitVec;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.or(&rhs)
+    }
+}
+
+impl ops::BitXor for BitSlice<'_> {
+    type Output = BitVec;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.xor(&rhs)
+    }
+}
+
+impl ops::Not for BitSlice<'_> {
+    type Output = BitVec;
+
+    fn not(self) -> Self::Output {
+        BitSlice::not(&self)
+    }
+}
+
+impl ops::BitAndAssign<BitSlice<'_>> for BitSliceMut<'_> {
+    fn bitand_assign(&mut self, rhs: BitSlice<'_>) {
+        self.apply(&rhs, |a, b| a & b);
+    }
+}
+
+impl ops::BitOrAssign<BitSlice<'_>> for BitSliceMut<'_> {
+    fn bitor_assign(&mut self, rhs: BitSlice<'_>) {
+        self.apply(&rhs, |a, b| a | b);
+    }
+}
+
+impl ops::BitXorAssign<BitSlice<'_>> for BitSliceMut<'_> {
+    fn bitxor_assign(&mut self, rhs: BitSlice<'_>) {
+        self.apply(&rhs, |a, b| a ^ b);
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unaligned_views() {
+        // bv = 0b01101011 0b10100101 0b1100xxxx
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC0], len: 20, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let slice = bv.slice(3..17);
+        assert_eq!(slice.len(), 14);
+        assert!(!slice.get_bit(0));
+        assert!(slice.get_bit(1));
+        assert_eq!(slice.get(14), None);
+        assert_eq!(slice.count_ones(), 8);
+        // 0b01011101 0b001011xx
+        assert_eq!(slice.to_bitvec().data, vec![0x5D, 0x2C]);
+
+        let inner = slice.slice(5..);
+        assert_eq!(inner, bv.slice(8..17));
+        assert_eq!(inner.iter().rev().take(3).collect::<Vec<_>>(), vec![true, true, false]);
+        let (left, right) = slice.split_at(6);
+        assert_eq!(left.len() + right.len(), 14);
+        assert_eq!(right, inner.slice(1..));
+    }
+
+    #[test]
+    fn logical_ops_between_views() {
+        let bv = BitVec::from(&[0b1100_1010, 0b0110_0101]);
+        let a = bv.slice(2..10);
+        let b = bv.slice(5..13);
+        // a = 0b00101001, b = 0b01001100
+        assert_eq!((a & b).data, vec![0b0000_1000]);
+        assert_eq!((a | b).data, vec![0b0110_1101]);
+        assert_eq!((a ^ b).data, vec![0b0110_0101]);
+        assert_eq!((!bv.slice(4..7)).data, vec![0b0100_0000]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mismatched_view_lengths() {
+        let bv = BitVec::from(&[0xFF]);
+        let _ = bv.slice(0..3) & bv.slice(0..4);
+    }
+
+    #[test]
+    fn mutable_views() {
+        let mut bv = BitVec::from(&[0x00, 0x00, 0x00]);
+        bv.slice_mut(5..19).fill(true);
+        assert_eq!(bv.data, vec![0x07, 0xFF, 0xE0]);
+
+        let mut view = bv.slice_mut(4..12);
+        view.set_bit(0, true);
+        view.set_bit(7, false);
+        view.slice_mut(2..4).invert();
+        assert_eq!(bv.data, vec![0x0C, 0xEF, 0xE0]);
+
+        let src = BitVec::from(&[0b1010_0000]);
+        let mut view = bv.slice_mut(20..23);
+        view ^= src.slice(..3);
+        assert_eq!(bv.data, vec![0x0C, 0xEF, 0xEA]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn view_past_end() {
+        let bv = BitVec::from(&[0xFF]);
+        bv.slice(4..9);
+    }
+}
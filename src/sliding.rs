@@ -0,0 +1,140 @@
+//! ## Sliding windows of bitmaps
+//! Membership over the last few time buckets, for rate limiters and recent
+//! visitor tracking: a ring of bitmaps, one per bucket, where ids seen now
+//! are set in the current bucket, and moving to the next bucket forgets the
+//! oldest. Asking whether an id was seen within the last `n` buckets checks
+//! one bit in each.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// A ring of bitmaps, one per time bucket
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::SlidingWindowBits;
+///
+/// let mut window = SlidingWindowBits::new(3);
+/// window.insert_now(42);
+/// window.advance();
+/// window.insert_now(7);
+/// assert!(window.seen_within(42, 2));
+/// assert!(!window.seen_within(42, 1));
+///
+/// // After 3 buckets, 42 has aged out
+/// window.advance();
+/// window.advance();
+/// assert!(!window.seen_within(42, 3));
+/// assert!(window.seen_within(7, 3));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlidingWindowBits {
+    buckets: Vec<BitVec>,   // a ring, oldest after current
+    current: usize,         // the bucket being filled
+}
+
+impl SlidingWindowBits {
+    /// Constructs a window of `buckets` empty buckets. Panics if there are
+    /// none.
+    pub fn new(buckets: usize) -> Self {
+        if buckets == 0 {
+            panic!("BitVec: a sliding window needs at least one bucket")
+        }
+        Self { buckets: (0..buckets).map(|_| BitVec::new()).collect(), current: 0 }
+    }
+
+    /// Get the number of buckets
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns the bucket `age` buckets back, 0 for the current one
+    fn bucket(&self, age: usize) -> &BitVec {
+        let len = self.buckets.len();
+        &self.buckets[(self.current + len - age) % len]
+    }
+
+    /// Panics unless `buckets` is 1 to the number of buckets
+    fn check(&self, buckets: usize) {
+        if !(1..=self.buckets.len()).contains(&buckets) {
+            panic!("BitVec: window of {} buckets out of 1 to {}", buckets, self.buckets.len())
+        }
+    }
+
+    /// Marks an id as seen in the current bucket
+    pub fn insert_now(&mut self, id: usize) {
+        let bucket = &mut self.buckets[self.current];
+        if id >= bucket.len {
+            bucket.resize(id + 1, false);
+        }
+        bucket.set_bit(id, true);
+    }
+
+    /// Checks if an id was seen in the last `buckets` buckets, counting the
+    /// current one. Panics if `buckets` is 0 or more than there are.
+    pub fn seen_within(&self, id: usize, buckets: usize) -> bool {
+        self.check(buckets);
+        (0..buckets).any(|age| {
+            let bucket = self.bucket(age);
+            id < bucket.len && bucket.get_bit(id)
+        })
+    }
+
+    /// Returns every id seen in the last `buckets` buckets, counting the
+    /// current one. Panics if `buckets` is 0 or more than there are.
+    pub fn union_within(&self, buckets: usize) -> BitVec {
+        self.check(buckets);
+        (0..buckets).fold(BitVec::new(), |seen, age| seen.union(self.bucket(age)))
+    }
+
+    /// Moves to a new, empty, bucket, forgetting the oldest
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % self.buckets.len();
+        self.buckets[self.current].clear();
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_out() {
+        let mut window = SlidingWindowBits::new(4);
+        for step in 0..10 {
+            window.insert_now(step * 3);
+            window.advance();
+        }
+        // Inserted in the last 4 buckets, all now past the current one
+        for step in 0..10 {
+            assert_eq!(window.seen_within(step * 3, 4), step >= 7, "step {}", step);
+        }
+        assert!(window.seen_within(27, 2));
+        assert!(!window.seen_within(24, 2));
+        assert!(!window.seen_within(1000, 4));
+        assert_eq!(window.union_within(4).iter_ones().collect::<Vec<_>>(), vec![21, 24, 27]);
+        assert_eq!(window.union_within(1).count_ones(), 0);
+    }
+
+    #[test]
+    fn single_bucket() {
+        let mut window = SlidingWindowBits::new(1);
+        window.insert_now(5);
+        window.insert_now(5);
+        assert!(window.seen_within(5, 1));
+        window.advance();
+        assert!(!window.seen_within(5, 1));
+        assert_eq!(window.num_buckets(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of 1 to 2")]
+    fn window_too_wide() {
+        SlidingWindowBits::new(2).seen_within(0, 3);
+    }
+}
@@ -0,0 +1,163 @@
+//! ## Soft bits
+//! A companion to BitVec for demodulated data, keeping a log-likelihood
+//! ratio (LLR) for each bit, so decoders can weigh how sure the demodulator
+//! was. A positive LLR favours a 0 and a negative one a 1, with the size
+//! giving the confidence; 0 is an erasure.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// A sequence of soft bits, stored as one i8 LLR per bit
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, SoftBits};
+///
+/// let soft = SoftBits::from(vec![90, -12, 5, -127]);
+/// let hard = soft.hard_decision();
+/// assert_eq!(hard.to_bit_string(), "0101");
+///
+/// // The third bit was the least sure, so disagreeing with it costs little
+/// let decoded = BitVec::from_bit_str("0111").unwrap();
+/// assert_eq!(soft.hamming_vs_soft_agreement(&decoded), Ok((1, 224)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SoftBits {
+    llrs: Vec<i8>,
+}
+
+impl SoftBits {
+    /// Constructs a new, empty SoftBits
+    pub fn new() -> Self {
+        Self { llrs: Vec::new() }
+    }
+
+    /// Constructs SoftBits from the bits of a BitVec, each with the same
+    /// confidence. A negative confidence is taken as its size.
+    pub fn from_bitvec(bv: &BitVec, confidence: i8) -> Self {
+        let confidence = confidence.saturating_abs();
+        Self { llrs: bv.iter().map(|bit| if bit { -confidence } else { confidence }).collect() }
+    }
+
+    /// Get the number of soft bits
+    pub fn len(&self) -> usize {
+        self.llrs.len()
+    }
+
+    /// Checks if there are no soft bits
+    pub fn is_empty(&self) -> bool {
+        self.llrs.is_empty()
+    }
+
+    /// Returns the LLRs, one for each bit
+    pub fn llrs(&self) -> &[i8] {
+        &self.llrs
+    }
+
+    /// Returns the LLR at the desired index, or None if the index is beyond
+    /// the end
+    pub fn get(&self, index: usize) -> Option<i8> {
+        self.llrs.get(index).copied()
+    }
+
+    /// Pushes an LLR onto the end
+    pub fn push(&mut self, llr: i8) {
+        self.llrs.push(llr);
+    }
+
+    /// Returns the hard decision for each bit, 1 where the LLR is negative
+    /// and 0 otherwise, so erasures decide 0
+    pub fn hard_decision(&self) -> BitVec {
+        let mut bv = BitVec::new();
+        for &llr in &self.llrs {
+            bv.push_bit(llr < 0);
+        }
+        bv
+    }
+
+    /// Compares a BitVec with the soft bits. Returns the Hamming distance
+    /// from the hard decisions, and the soft agreement, the sum of the LLR
+    /// sizes where the bits agree less the sum where they differ. Returns an
+    /// error if the lengths differ.
+    pub fn hamming_vs_soft_agreement(&self, bv: &BitVec) -> Result<(usize, i64), BitVecError> {
+        if bv.len_bits() != self.llrs.len() {
+            return Err(BitVecError::LengthMismatch { expected: self.llrs.len(), found: bv.len_bits() });
+        }
+
+        let mut distance = 0;
+        let mut agreement = 0;
+        for (&llr, bit) in self.llrs.iter().zip(bv.iter()) {
+            if (llr < 0) != bit {
+                distance += 1;
+            }
+            // A positive LLR agrees with a 0 and a negative one with a 1
+            let size = (llr as i64).abs();
+            agreement += if (llr > 0 && !bit) || (llr < 0 && bit) { size } else { -size };
+        }
+        Ok((distance, agreement))
+    }
+}
+
+impl From<Vec<i8>> for SoftBits {
+    fn from(llrs: Vec<i8>) -> Self {
+        Self { llrs }
+    }
+}
+
+impl From<&BitVec> for SoftBits {
+    /// Takes each bit with full confidence
+    fn from(bv: &BitVec) -> Self {
+        Self::from_bitvec(bv, i8::MAX)
+    }
+}
+
+impl From<&SoftBits> for BitVec {
+    /// Takes the hard decision for each bit
+    fn from(soft: &SoftBits) -> Self {
+        soft.hard_decision()
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_decisions() {
+        let bv = BitVec::from(&[0xA5, 0x3C]).slice(..13).to_bitvec();
+        let soft = SoftBits::from(&bv);
+        assert_eq!(soft.len(), 13);
+        assert_eq!(soft.get(0), Some(-127));
+        assert_eq!(soft.get(1), Some(127));
+        assert_eq!(BitVec::from(&soft), bv);
+
+        let low = SoftBits::from_bitvec(&bv, -3);
+        assert_eq!(low.get(0), Some(-3));
+        assert_eq!(low.hamming_vs_soft_agreement(&bv), Ok((0, 39)));
+
+        // An erasure decides 0 but agrees with neither bit
+        let mut erased = SoftBits::new();
+        erased.push(0);
+        assert_eq!(erased.hard_decision().to_bit_string(), "0");
+        assert_eq!(erased.hamming_vs_soft_agreement(&BitVec::from_bit_str("1").unwrap()), Ok((1, 0)));
+    }
+
+    #[test]
+    fn agreement_weighs_confidence() {
+        let soft = SoftBits::from(vec![100, -100, 2, -2]);
+        let received = soft.hard_decision();
+        assert_eq!(soft.hamming_vs_soft_agreement(&received), Ok((0, 204)));
+
+        // Flipping an unsure bit costs less than flipping a sure one
+        let unsure = BitVec::from_bit_str("0100").unwrap();
+        let sure = BitVec::from_bit_str("1101").unwrap();
+        assert_eq!(soft.hamming_vs_soft_agreement(&unsure), Ok((1, 200)));
+        assert_eq!(soft.hamming_vs_soft_agreement(&sure), Ok((1, 4)));
+        assert!(soft.hamming_vs_soft_agreement(&BitVec::new()).is_err());
+    }
+}
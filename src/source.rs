@@ -0,0 +1,204 @@
+//! ## Bit sources
+//! A trait for anything bits can be read from in sequence, so decoders can
+//! be written once for the built-in reading position of a BitVec, a
+//! `BitCursor`, a BitSlice, or a stream through `BitReader` or
+//! `StreamBitReader`. Values of several bits are read MSB first, and a read
+//! that runs past the end returns None without consuming anything.
+
+use core::borrow::Borrow;
+use core::convert::Infallible;
+
+use crate::{BitCursor, BitSlice, BitVec};
+
+/// A sequence of bits read in order
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitSource, BitVec};
+///
+/// fn decode<S: BitSource>(source: &mut S) -> Result<Option<(u64, u64)>, S::Error> {
+///     let Some(kind) = source.read_bits(3)? else { return Ok(None) };
+///     source.align()?;
+///     Ok(source.read_bits(8)?.map(|value| (kind, value)))
+/// }
+///
+/// let mut bv = BitVec::from(&[0b1010_0000, 0xAB]);
+/// assert_eq!(decode(&mut bv), Ok(Some((0b101, 0xAB))));
+/// assert_eq!(decode(&mut bv.slice(..)), Ok(Some((0b101, 0xAB))));
+/// ```
+pub trait BitSource {
+    /// The error from a failed read
+    type Error;
+
+    /// Reads the next `count` bits (up to 64) without consuming them.
+    /// Returns None if there are not enough bits left.
+    fn peek_bits(&mut self, count: u32) -> Result<Option<u64>, Self::Error>;
+
+    /// Skips `count` bits, which must already have been peeked
+    fn consume(&mut self, count: u32) -> Result<(), Self::Error>;
+
+    /// Moves forward to the next byte boundary, stopping at the end
+    fn align(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns the number of bits left, or None if that is not known, as for
+    /// a stream
+    fn remaining(&self) -> Option<usize>;
+
+    /// Reads the next `count` bits (up to 64). Returns None, without
+    /// consuming anything, if there are not enough bits left.
+    fn read_bits(&mut self, count: u32) -> Result<Option<u64>, Self::Error> {
+        let value = self.peek_bits(count)?;
+        if value.is_some() {
+            self.consume(count)?;
+        }
+        Ok(value)
+    }
+
+    /// Reads the next bit, or None at the end
+    fn read_bit(&mut self) -> Result<Option<bool>, Self::Error> {
+        Ok(self.read_bits(1)?.map(|bit| bit == 1))
+    }
+}
+
+/// Panics if a read is wider than a u64
+fn check_count(count: u32) {
+    if count > u64::BITS {
+        panic!("BitVec: cannot read more than 64 bits at once")
+    }
+}
+
+/// Reads from the built-in reading position
+impl BitSource for BitVec {
+    type Error = Infallible;
+
+    fn peek_bits(&mut self, count: u32) -> Result<Option<u64>, Infallible> {
+        Ok(BitVec::peek_bits(self, count))
+    }
+
+    fn consume(&mut self, count: u32) -> Result<(), Infallible> {
+        let position = (self.get_read_position() + count as usize).min(self.len);
+        self.place_read_position(position);
+        Ok(())
+    }
+
+    fn align(&mut self) -> Result<(), Infallible> {
+        self.align_to_byte();
+        Ok(())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len.saturating_sub(self.get_read_position()))
+    }
+}
+
+impl<T: Borrow<BitVec>> BitSource for BitCursor<T> {
+    type Error = Infallible;
+
+    fn peek_bits(&mut self, count: u32) -> Result<Option<u64>, Infallible> {
+        Ok(BitCursor::peek_bits(self, count))
+    }
+
+    fn consume(&mut self, count: u32) -> Result<(), Infallible> {
+        let position = self.position() + (count as usize).min(self.bits_remaining());
+        self.set_position(position);
+        Ok(())
+    }
+
+    fn align(&mut self) -> Result<(), Infallible> {
+        self.align_to_byte();
+        Ok(())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.bits_remaining())
+    }
+}
+
+/// Reads from the front of the view, shrinking it, as `Read` does for
+/// `&[u8]`. Alignment is to the bytes of the underlying vector.
+impl BitSource for BitSlice<'_> {
+    type Error = Infallible;
+
+    fn peek_bits(&mut self, count: u32) -> Result<Option<u64>, Infallible> {
+        check_count(count);
+        Ok((count as usize <= self.len()).then(|| self.slice(..count as usize).to_u64()))
+    }
+
+    fn consume(&mut self, count: u32) -> Result<(), Infallible> {
+        *self = self.slice((count as usize).min(self.len())..);
+        Ok(())
+    }
+
+    fn align(&mut self) -> Result<(), Infallible> {
+        let skip = (8 - self.bit_offset()) % 8;
+        self.consume(skip as u32)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads a 5-bit field, aligns, then reads 16 bits and a bit
+    fn decode<S: BitSource>(source: &mut S) -> Result<Vec<u64>, S::Error> {
+        let mut values = Vec::new();
+        values.extend(source.read_bits(5)?);
+        source.align()?;
+        values.extend(source.peek_bits(16)?);
+        values.extend(source.read_bits(16)?);
+        values.extend(source.read_bit()?.map(|bit| bit as u64));
+        // Past the end, without consuming
+        values.extend(source.read_bits(64)?);
+        Ok(values)
+    }
+
+    #[test]
+    fn sources_agree() {
+        let bv = BitVec::from(&[0b1011_0111, 0xBE, 0xEF, 0x80]).slice(..25).to_bitvec();
+        let expected = vec![0b10110, 0xBEEF, 0xBEEF, 1];
+
+        let mut own = bv.clone();
+        assert_eq!(decode(&mut own), Ok(expected.clone()));
+        assert_eq!(BitSource::remaining(&own), Some(0));
+
+        let mut cursor = BitCursor::new(&bv);
+        assert_eq!(decode(&mut cursor), Ok(expected.clone()));
+        assert_eq!(BitSource::remaining(&cursor), Some(0));
+
+        let mut slice = bv.slice(..);
+        assert_eq!(decode(&mut slice), Ok(expected));
+        assert!(slice.is_empty());
+    }
+
+    #[test]
+    fn slice_aligns_to_the_vector() {
+        // A view starting at bit 3 aligns at bit 8 of the vector
+        let bv = BitVec::from(&[0x00, 0xFF]);
+        let mut slice = bv.slice(3..);
+        slice.align().unwrap();
+        assert_eq!(slice.remaining(), Some(8));
+        assert_eq!(slice.read_bits(8), Ok(Some(0xFF)));
+        slice.align().unwrap();
+        assert_eq!(slice.read_bit(), Ok(None));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stream_source() {
+        let bytes = [0b1011_0111, 0xBE, 0xEF, 0x80];
+        let mut reader = crate::BitReader::new(&bytes[..]);
+        assert_eq!(decode(&mut reader).unwrap(), vec![0b10110, 0xBEEF, 0xBEEF, 1]);
+        assert_eq!(reader.remaining(), None);
+        // Only the padding of the last byte is left
+        assert_eq!(reader.read_bits(7).unwrap(), Some(0));
+        assert_eq!(reader.read_bit().unwrap(), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! ## Spare capacity
+//! Direct access to the reserved but unused bytes after the data, so a
+//! decoder that produces whole words can write them in place rather than
+//! pushing them a bit at a time. As with `Vec::spare_capacity_mut`, the
+//! spare bytes are handed out uninitialized, and the unsafe
+//! `assume_bits_init` then takes the bits written into the vector.
+//! `extend_words` wraps both safely.
+//!
+//! The spare bytes follow the last byte of the data, so on a vector whose
+//! length is not a multiple of 8 the padding bits of that byte come between
+//! the old bits and the first spare byte.
+
+use core::mem::MaybeUninit;
+
+use crate::BitVec;
+
+impl BitVec {
+    /// Returns the spare capacity after the last byte of the data, packed
+    /// MSB first like the data, to be written before `assume_bits_init`
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        self.data.spare_capacity_mut()
+    }
+
+    /// Lengthens the vector by `count` bits, taking first the padding bits
+    /// of the last byte, which are clear, then the spare bytes in order.
+    /// Any bits written past the new end of the last byte are cleared.
+    ///
+    /// # Safety
+    ///
+    /// The spare bytes holding the new bits must have been initialized, and
+    /// must be within the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0xAB]);
+    /// bv.reserve(12);
+    /// let spare = bv.spare_capacity_mut();
+    /// spare[0].write(0xCD);
+    /// spare[1].write(0xEF);
+    /// // SAFETY: both bytes holding the new bits were written
+    /// unsafe { bv.assume_bits_init(12) };
+    /// assert_eq!(bv.as_bytes(), &[0xAB, 0xCD, 0xE0]);
+    /// ```
+    pub unsafe fn assume_bits_init(&mut self, count: usize) {
+        let len = self.len + count;
+        debug_assert!(len.div_ceil(8) <= self.data.capacity());
+        // SAFETY: the caller guarantees the bytes up to the new length are
+        // initialized and within the capacity
+        unsafe { self.data.set_len(len.div_ceil(8)) };
+        self.len = len;
+        self.clear_padding();
+    }
+
+    /// Lengthens the vector by `words` words of 64 bits, handing `fill` the
+    /// new bytes, 8 for each word and clear to start with, to write the bits
+    /// into, MSB first. On a vector whose length is a multiple of 8 the
+    /// bytes are written in place; otherwise they are copied into place
+    /// afterwards, a byte at a time. Panics if the number of bits overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.extend_words(2, |bytes| {
+    ///     for (word, chunk) in [1u64, u64::MAX].iter().zip(bytes.chunks_exact_mut(8)) {
+    ///         chunk.copy_from_slice(&word.to_be_bytes());
+    ///     }
+    /// });
+    /// assert_eq!(bv.len_bits(), 128);
+    /// assert_eq!(bv.slice(..64).to_u64(), 1);
+    /// ```
+    pub fn extend_words(&mut self, words: usize, fill: impl FnOnce(&mut [u8])) {
+        let Some(bits) = words.checked_mul(64) else {
+            panic!("BitVec: capacity overflow")
+        };
+        let bytes = bits / 8;
+        if !self.len.is_multiple_of(8) {
+            let mut buffer = alloc::vec![0; bytes];
+            fill(&mut buffer);
+            self.reserve(bits);
+            for byte in buffer {
+                self.push_msb_byte(byte);
+            }
+            return;
+        }
+
+        self.reserve(bits);
+        let start = self.data.len();
+        self.data.resize(start + bytes, 0);
+        fill(&mut self.data[start..]);
+        self.len += bits;
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assume_written_bits() {
+        // Starting part way into a byte, the padding bits come first
+        let mut bv = BitVec::from_bit_str("101").unwrap();
+        bv.reserve(21);
+        let spare = bv.spare_capacity_mut();
+        assert!(spare.len() >= 2);
+        spare[0].write(0xFF);
+        spare[1].write(0xFF);
+        // SAFETY: the two bytes holding the new bits were written
+        unsafe { bv.assume_bits_init(17) };
+        assert_eq!(bv.len_bits(), 20);
+        assert_eq!(bv.to_bit_string(), "10100000111111111111");
+
+        // Nothing to add
+        // SAFETY: no new bytes are taken
+        unsafe { bv.assume_bits_init(0) };
+        assert_eq!(bv.len_bits(), 20);
+    }
+
+    #[test]
+    fn extending_by_words() {
+        let words = [0x0123_4567_89AB_CDEF_u64, 0xFEDC_BA98_7654_3210];
+        let fill = |bytes: &mut [u8]| {
+            assert!(bytes.iter().all(|&byte| byte == 0));
+            for (word, chunk) in words.iter().zip(bytes.chunks_exact_mut(8)) {
+                chunk.copy_from_slice(&word.to_be_bytes());
+            }
+        };
+
+        let mut aligned = BitVec::from(&[0xAA]);
+        aligned.extend_words(2, fill);
+        let mut unaligned = BitVec::from_bit_str("1").unwrap();
+        unaligned.extend_words(2, fill);
+
+        assert_eq!(aligned.len_bits(), 136);
+        assert_eq!(aligned.slice(8..72).to_u64(), words[0]);
+        assert_eq!(aligned.slice(72..).to_u64(), words[1]);
+        assert_eq!(unaligned.len_bits(), 129);
+        assert_eq!(unaligned.slice(1..65).to_u64(), words[0]);
+        assert_eq!(unaligned.slice(65..).to_u64(), words[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn extending_by_too_many_words() {
+        let mut bv = BitVec::new();
+        bv.extend_words(usize::MAX / 32, |_| ());
+    }
+}
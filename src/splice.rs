@@ -0,0 +1,325 @@
+//! ## Splicing and resizing
+//! Inserting and removing bits at any position, shifting the bits after it
+//! along, which is useful for editing an encoded stream in place. Also
+//! changing the length from the end. Padding bits are always left cleared,
+//! and the reading position is pulled back if the vector shrinks past it.
+
+use core::ops::RangeBounds;
+
+use crate::BitVec;
+use crate::slice::resolve_range;
+
+impl BitVec {
+    /// Shortens the vector to a number of bits, dropping any spare bytes,
+    /// clearing the padding bits, and clamping the reading position
+    pub(crate) fn truncate_bits(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+        self.data.truncate(self.len.div_ceil(8));
+        self.clear_padding();
+        self.clamp_read_position();
+    }
+
+    /// Pushes all the bits of another vector, a byte at a time where possible
+    pub(crate) fn append_bits(&mut self, other: &BitVec) {
+        let full_bytes = other.len / 8;
+        for &byte in &other.data[..full_bytes] {
+            self.push_msb_byte(byte);
+        }
+        for index in full_bytes * 8..other.len {
+            self.push_bit(other.get_bit(index));
+        }
+    }
+
+    /// Inserts a bit at an index, shifting every bit after it one place
+    /// towards the end. Panics if the index is beyond the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x00]);
+    /// bv.insert_bit(2, true);
+    /// assert_eq!(bv.as_binary(), "00100000 00000000");
+    /// assert_eq!(bv.len_bits(), 9);
+    /// ```
+    pub fn insert_bit(&mut self, index: usize, value: bool) {
+        if index > self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        self.truncate_bits(self.len);
+        self.push_bit(false);
+
+        let byte_idx = index / 8;
+        let offset = index % 8;
+        let byte = self.data[byte_idx];
+        let mut carry = byte & 1;
+        let high = byte & !(0xFF >> offset);
+        let low = (byte & (0xFF >> offset)) >> 1;
+        self.data[byte_idx] = high | ((value as u8) << (7 - offset)) | low;
+
+        for byte in &mut self.data[byte_idx + 1..] {
+            let next_carry = *byte & 1;
+            *byte = (*byte >> 1) | (carry << 7);
+            carry = next_carry;
+        }
+        self.clear_padding();
+    }
+
+    /// Removes and returns the bit at an index, shifting every bit after it
+    /// one place towards the start. Panics if the index is beyond the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // 0b00100001
+    /// let mut bv = BitVec::from(&[0x21]);
+    /// assert!(bv.remove_bit(2));
+    /// assert_eq!(bv.as_binary(), "00000010");
+    /// assert_eq!(bv.len_bits(), 7);
+    /// ```
+    pub fn remove_bit(&mut self, index: usize) -> bool {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        self.truncate_bits(self.len);
+        let value = self.get_bit(index);
+        let num_bytes = self.data.len();
+        let byte_idx = index / 8;
+        let offset = index % 8;
+        let carry_in = |data: &[u8], next: usize| if next < num_bytes { data[next] >> 7 } else { 0 };
+
+        let byte = self.data[byte_idx];
+        let high = byte & !(0xFF >> offset);
+        let low = (byte & (0x7F >> offset)) << 1;
+        self.data[byte_idx] = high | low | carry_in(&self.data, byte_idx + 1);
+
+        for i in byte_idx + 1..num_bytes {
+            self.data[i] = (self.data[i] << 1) | carry_in(&self.data, i + 1);
+        }
+
+        self.truncate_bits(self.len - 1);
+        value
+    }
+
+    /// Inserts all the bits of another vector at an index, shifting the bits
+    /// after it towards the end. Panics if the index is beyond the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x00]);
+    /// bv.insert_bits(4, &BitVec::from(&[0xFF]));
+    /// assert_eq!(bv.as_binary(), "00001111 11110000");
+    /// ```
+    pub fn insert_bits(&mut self, index: usize, bits: &BitVec) {
+        if index > self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let position = self.get_read_position();
+        let tail = self.slice(index..).to_bitvec();
+        self.truncate_bits(index);
+        self.append_bits(bits);
+        self.append_bits(&tail);
+        self.place_read_position(position);
+    }
+
+    /// Removes a range of bits, returning them as a new BitVec and shifting
+    /// the bits after the range towards the start. Panics if the range runs
+    /// past the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x0F, 0xF0]);
+    /// let drained = bv.drain(4..12);
+    /// assert_eq!(drained.as_binary(), "11111111");
+    /// assert_eq!(bv.as_binary(), "00000000");
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> BitVec {
+        let (start, end) = resolve_range(range, self.len);
+        let drained = self.slice(start..end).to_bitvec();
+        let position = self.get_read_position();
+        let tail = self.slice(end..).to_bitvec();
+        self.truncate_bits(start);
+        self.append_bits(&tail);
+        self.place_read_position(position);
+        self.clamp_read_position();
+
+        drained
+    }
+
+    /// Shortens the vector to `len` bits. Has no effect if the vector is
+    /// already that short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0xFF, 0xFF]);
+    /// bv.truncate(5);
+    /// assert_eq!(bv.as_binary(), "11111000");
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        self.truncate_bits(len);
+    }
+
+    /// Changes the length to `len` bits, either truncating or filling the
+    /// new bits with a value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x00]);
+    /// bv.resize(12, true);
+    /// assert_eq!(bv.as_binary(), "00000000 11110000");
+    /// ```
+    pub fn resize(&mut self, len: usize, value: bool) {
+        self.truncate_bits(len);
+        if len <= self.len {
+            return;
+        }
+
+        let tail = self.len % 8;
+        if value && tail > 0 {
+            self.data[self.len / 8] |= 0xFF >> tail;
+        }
+        self.data.resize(len.div_ceil(8), if value { 0xFF } else { 0 });
+        self.len = len;
+        self.clear_padding();
+    }
+
+    /// Splits the vector in two at an index, returning the bits from the
+    /// index onwards. Panics if the index is beyond the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from(&[0x0F]);
+    /// let tail = bv.split_off(3);
+    /// assert_eq!(bv.len_bits(), 3);
+    /// assert_eq!(tail.as_binary(), "01111000");
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> BitVec {
+        if at > self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let mut tail = self.slice(at..).to_bitvec();
+        tail.order = self.order;
+        self.truncate_bits(at);
+
+        tail
+    }
+
+    /// Removes every bit and resets the reading position
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.len = 0;
+        self.reset_seq_read();
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    fn bits(bv: &BitVec) -> Vec<bool> {
+        (0..bv.len_bits()).map(|i| bv.get_bit(i)).collect()
+    }
+
+    #[test]
+    fn insert_and_remove_single_bits() {
+        // Padding bits are set to check they never shift in
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let original = bits(&bv);
+
+        for index in 0..=19 {
+            for value in [false, true] {
+                let mut inserted = bv.clone();
+                inserted.insert_bit(index, value);
+                let mut expected = original.clone();
+                expected.insert(index, value);
+                assert_eq!(bits(&inserted), expected, "insert {} at {}", value, index);
+
+                assert_eq!(inserted.remove_bit(index), value);
+                assert_eq!(inserted, bv);
+            }
+        }
+    }
+
+    #[test]
+    fn insert_and_drain_ranges() {
+        let bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let extra = BitVec { data: vec![0xF0, 0x0F], len: 13, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+
+        for index in 0..=19 {
+            let mut spliced = bv.clone();
+            spliced.insert_bits(index, &extra);
+            assert_eq!(spliced.len_bits(), 32);
+            let mut expected = bits(&bv);
+            expected.splice(index..index, bits(&extra));
+            assert_eq!(bits(&spliced), expected);
+
+            assert_eq!(spliced.drain(index..index + 13), extra);
+            assert_eq!(spliced, bv);
+        }
+
+        let mut all = bv.clone();
+        assert_eq!(all.drain(..), bv);
+        assert_eq!(all.len_bits(), 0);
+    }
+
+    #[test]
+    fn resizing() {
+        // Padding bits are set to check they are cleared
+        let mut bv = BitVec { data: vec![0x6B, 0xA5, 0xC7], len: 19, byte_idx: 2, bit_idx: 2, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        bv.truncate(20);
+        assert_eq!(bv.len_bits(), 19);
+        bv.truncate(13);
+        assert_eq!(bv.data, vec![0x6B, 0xA0]);
+        assert_eq!(bv.get_read_position(), 13);
+        assert_eq!(bv.read_bit_seq(), None);
+
+        bv.resize(21, true);
+        assert_eq!(bv.data, vec![0x6B, 0xA7, 0xF8]);
+        bv.resize(30, false);
+        assert_eq!(bv.data, vec![0x6B, 0xA7, 0xF8, 0x00]);
+        bv.resize(4, true);
+        assert_eq!(bv.data, vec![0x60]);
+
+        let tail = bv.split_off(1);
+        assert_eq!(tail.to_bit_string(), "110");
+        assert_eq!(bv.to_bit_string(), "0");
+        bv.clear();
+        assert_eq!(bv.len_bits(), 0);
+        assert_eq!(bv, BitVec::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_past_end() {
+        let mut bv = BitVec::from(&[0xFF]);
+        bv.remove_bit(8);
+    }
+}
@@ -0,0 +1,161 @@
+//! ## String fields
+//! Helpers for the fixed-width and NUL-terminated (C-style) string
+//! conventions commonly found in packed binary records. Strings are written
+//! and read byte by byte at the current bit position, so they need not be
+//! byte aligned.
+
+use alloc::{string::String, vec::Vec};
+use crate::BitVec;
+
+impl BitVec {
+    /// Pushes a string into a fixed-width field of `n_bytes` bytes. Strings
+    /// longer than the field are truncated at the last complete character
+    /// that fits, and shorter strings are padded with `pad`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_str_fixed("ABC", 8, 0);
+    /// assert_eq!(bv.len_bits(), 64);
+    /// ```
+    pub fn push_str_fixed(&mut self, s: &str, n_bytes: usize, pad: u8) {
+        let mut end = s.len().min(n_bytes);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        for &byte in &s.as_bytes()[..end] {
+            self.push_byte(byte);
+        }
+        for _ in end..n_bytes {
+            self.push_byte(pad);
+        }
+    }
+
+    /// Reads a fixed-width field of `n_bytes` bytes from the current reading
+    /// position. The string ends at the first NUL byte, if any; other padding
+    /// is left in place for the caller to trim. Returns None, without moving
+    /// the reading position, if there are not enough bits remaining or the
+    /// field is not valid UTF-8.
+    pub fn read_str_fixed(&mut self, n_bytes: usize) -> Option<String> {
+        let start = self.get_read_position();
+        let end = n_bytes.checked_mul(8).and_then(|bits| start.checked_add(bits))?;
+        if end > self.len {
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity(n_bytes);
+        for _ in 0..n_bytes {
+            bytes.push(self.read_byte()?);
+        }
+        if let Some(nul) = bytes.iter().position(|&byte| byte == 0) {
+            bytes.truncate(nul);
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(s) => Some(s),
+            Err(_) => {
+                self.set_read_position(start);
+                None
+            }
+        }
+    }
+
+    /// Pushes a string followed by a NUL terminator. Any interior NUL bytes
+    /// are written as-is, and will end the string when it is read back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_cstr("hi");
+    /// assert_eq!(bv.read_cstr(), Some("hi".to_string()));
+    /// ```
+    pub fn push_cstr(&mut self, s: &str) {
+        for &byte in s.as_bytes() {
+            self.push_byte(byte);
+        }
+        self.push_byte(0);
+    }
+
+    /// Reads a NUL-terminated string from the current reading position,
+    /// consuming the terminator. Returns None, without moving the reading
+    /// position, if no terminator is found or the string is not valid UTF-8.
+    pub fn read_cstr(&mut self) -> Option<String> {
+        let start = self.get_read_position();
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.read_byte() {
+                Some(0) => break,
+                Some(byte) => bytes.push(byte),
+                None => {
+                    self.set_read_position(start);
+                    return None;
+                }
+            }
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(s) => Some(s),
+            Err(_) => {
+                self.set_read_position(start);
+                None
+            }
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_width_round_trip() {
+        let mut bv = BitVec::new();
+        bv.push_bit(true);
+        bv.push_str_fixed("name", 6, 0);
+        bv.push_str_fixed("truncated", 5, 0);
+        assert_eq!(bv.len_bits(), 1 + 6 * 8 + 5 * 8);
+
+        bv.set_read_position(1);
+        assert_eq!(bv.read_str_fixed(6).unwrap(), "name");
+        assert_eq!(bv.read_str_fixed(5).unwrap(), "trunc");
+        assert_eq!(bv.read_str_fixed(1), None);
+        assert_eq!(bv.read_str_fixed(usize::MAX / 4), None);
+    }
+
+    #[test]
+    fn fixed_width_keeps_char_boundaries() {
+        let mut bv = BitVec::new();
+        // 'é' is two bytes, so only "a" fits in a 2 byte field
+        bv.push_str_fixed("aé", 2, b' ');
+        assert_eq!(bv.read_str_fixed(2).unwrap(), "a ");
+    }
+
+    #[test]
+    fn c_string_round_trip() {
+        let mut bv = BitVec::new();
+        bv.push_bit(false);
+        bv.push_bit(true);
+        bv.push_cstr("first");
+        bv.push_cstr("");
+        bv.push_byte(b'x');
+
+        bv.set_read_position(2);
+        assert_eq!(bv.read_cstr().unwrap(), "first");
+        assert_eq!(bv.read_cstr().unwrap(), "");
+        // Unterminated string leaves the position untouched
+        let position = bv.get_read_position();
+        assert_eq!(bv.read_cstr(), None);
+        assert_eq!(bv.get_read_position(), position);
+    }
+}
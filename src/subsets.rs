@@ -0,0 +1,199 @@
+//! ## Fixed-weight enumeration
+//! Stepping through every pattern with a given number of set bits, for
+//! enumerating the k-subsets of a set as bitmaps. This is Gosper's hack,
+//! for any length rather than just 64 bits: the vector is read as a number
+//! MSB first, bit 0 the most significant, and each step moves to the next
+//! larger number with the same popcount.
+//!
+//! The position of a pattern in that order is its combinadic rank: with the
+//! set bits at places `c1 < c2 < ... < ck` counted up from the last bit,
+//! the rank is `C(c1, 1) + C(c2, 2) + ... + C(ck, k)`, so k-of-n subsets
+//! can be stored as a single number.
+
+use alloc::vec::Vec;
+
+use crate::BitVec;
+
+/// Returns the binomial coefficient `C(n, k)`, or None if it does not fit
+fn binomial(n: usize, k: usize) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for j in 1..=k {
+        // Exact at every step, as the product of j consecutive numbers
+        result = result.checked_mul((n - k + j) as u128)? / j as u128;
+    }
+    Some(result)
+}
+
+impl BitVec {
+    /// Constructs the first pattern of `len` bits with `weight` set, the
+    /// smallest as a number: the set bits all at the end. Panics if the
+    /// weight is more than the length.
+    pub fn first_of_weight(len: usize, weight: usize) -> BitVec {
+        if weight > len {
+            panic!("BitVec: weight {} is more than length {}", weight, len)
+        }
+        let mut bv = BitVec::repeat_bit(false, len);
+        bv.slice_mut(len - weight..).fill(true);
+        bv
+    }
+
+    /// Advances to the next pattern with the same number of set bits,
+    /// returning false, and leaving the vector alone, if this is the last:
+    /// the set bits all at the start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::first_of_weight(4, 2);
+    /// let mut subsets = vec![bv.to_bit_string()];
+    /// while bv.next_same_weight() {
+    ///     subsets.push(bv.to_bit_string());
+    /// }
+    /// assert_eq!(subsets, ["0011", "0101", "0110", "1001", "1010", "1100"]);
+    /// ```
+    pub fn next_same_weight(&mut self) -> bool {
+        // The lowest run of ones, between `start` and `end`
+        let Some(end) = (0..self.len).rev().find(|&index| self.get_bit(index)).map(|index| index + 1) else {
+            return false;
+        };
+        let Some(start) = (0..end).rev().find(|&index| !self.get_bit(index)).map(|index| index + 1) else {
+            return false;
+        };
+
+        // Carry the run's top bit up one place, and the rest of the run down
+        // to the end
+        let ones = end - start;
+        self.set_bit(start - 1, true);
+        self.slice_mut(start..).fill(false);
+        self.slice_mut(self.len - (ones - 1)..).fill(true);
+        true
+    }
+
+    /// Returns the position of this pattern among those of its length and
+    /// popcount, in the order `next_same_weight` steps through them. Panics
+    /// if the number of such patterns does not fit in a u128.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1010").unwrap();
+    /// assert_eq!(bv.rank_combinadic(), 4);
+    /// assert_eq!(BitVec::from_combinadic_rank(4, 2, 4), Some(bv));
+    /// ```
+    pub fn rank_combinadic(&self) -> u128 {
+        if binomial(self.len, self.count_ones()).is_none() {
+            panic!("BitVec: too many patterns to rank in a u128")
+        }
+        // Places counted up from the last bit, lowest first
+        let ones: Vec<usize> = self.iter_ones().collect();
+        ones.iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &index)| binomial(self.len - 1 - index, i + 1).unwrap())
+            .sum()
+    }
+
+    /// Constructs the pattern of `n` bits with `k` set at position `rank`
+    /// in the order `next_same_weight` steps through them, or None if there
+    /// are not that many patterns. Panics if `k` is more than `n`, or if the
+    /// number of patterns does not fit in a u128.
+    pub fn from_combinadic_rank(n: usize, k: usize, mut rank: u128) -> Option<BitVec> {
+        if k > n {
+            panic!("BitVec: weight {} is more than length {}", k, n)
+        }
+        let Some(count) = binomial(n, k) else {
+            panic!("BitVec: too many patterns to rank in a u128")
+        };
+        if rank >= count {
+            return None;
+        }
+
+        // Place the highest set bit first, as far up as the rank allows
+        let mut bv = BitVec::repeat_bit(false, n);
+        let mut place = n;
+        for i in (1..=k).rev() {
+            place = (i - 1..place).rev().find(|&c| binomial(c, i).unwrap() <= rank).unwrap();
+            rank -= binomial(place, i).unwrap();
+            bv.set_bit(n - 1 - place, true);
+        }
+        Some(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_gosper() {
+        // Against Gosper's hack on a u64, for 3 of 7 bits
+        let mut bv = BitVec::first_of_weight(7, 3);
+        let mut word: u64 = 0b111;
+        let mut count = 1;
+        while bv.next_same_weight() {
+            let lowest = word & word.wrapping_neg();
+            let ripple = word + lowest;
+            word = (((ripple ^ word) >> 2) / lowest) | ripple;
+            assert_eq!(bv.as_bitslice().to_u64(), word);
+            count += 1;
+        }
+        assert_eq!(count, 35);
+        assert_eq!(bv.to_bit_string(), "1110000");
+    }
+
+    #[test]
+    fn long_and_edge_cases() {
+        // Beyond 64 bits, the carry crosses bytes
+        let mut bv = BitVec::first_of_weight(100, 1);
+        let mut steps = 0;
+        while bv.next_same_weight() {
+            steps += 1;
+            assert_eq!(bv.count_ones(), 1);
+        }
+        assert_eq!(steps, 99);
+        assert!(bv.get_bit(0));
+
+        let mut bv = BitVec::from_bit_str("0111 1111 1000").unwrap();
+        assert!(bv.next_same_weight());
+        assert_eq!(bv.to_bit_string(), "100001111111");
+
+        // No bits set, or all of them, have no next pattern
+        for mut bv in [BitVec::first_of_weight(5, 0), BitVec::first_of_weight(5, 5), BitVec::new()] {
+            let before = bv.clone();
+            assert!(!bv.next_same_weight());
+            assert_eq!(bv, before);
+        }
+    }
+    #[test]
+    fn combinadic_ranks() {
+        // Ranks follow next_same_weight, from 0
+        let mut bv = BitVec::first_of_weight(10, 4);
+        for rank in 0..210 {
+            assert_eq!(bv.rank_combinadic(), rank);
+            assert_eq!(BitVec::from_combinadic_rank(10, 4, rank).as_ref(), Some(&bv));
+            bv.next_same_weight();
+        }
+        assert_eq!(BitVec::from_combinadic_rank(10, 4, 210), None);
+
+        // Long vectors, and the edges
+        let mut wide = BitVec::repeat_bit(false, 200);
+        wide.set_bit(0, true);
+        wide.set_bit(199, true);
+        assert_eq!(wide.rank_combinadic(), 199 * 198 / 2);
+        assert_eq!(BitVec::from_combinadic_rank(200, 2, 199 * 198 / 2), Some(wide));
+        assert_eq!(BitVec::from_combinadic_rank(0, 0, 0), Some(BitVec::new()));
+        assert_eq!(BitVec::from_combinadic_rank(5, 5, 0), Some(BitVec::repeat_bit(true, 5)));
+        assert_eq!(binomial(200, 100), None);
+    }
+}
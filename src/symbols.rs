@@ -0,0 +1,126 @@
+//! ## Symbols
+//! Splitting a BitVec into symbols of any width from 1 to 16 bits, read MSB
+//! first, and joining them back, as every transcoder between alphabets
+//! does. When the length is not a multiple of the width, a policy says what
+//! happens to the final partial symbol: pad it with 0s, reject it, or hand
+//! its bits back separately.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, BitVecError};
+
+/// What is done with a final symbol shorter than the width
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TailPolicy {
+    /// Pad the final symbol with 0s after its bits
+    Pad,
+    /// Reject a length that is not a multiple of the width
+    Reject,
+    /// Return the bits of the final symbol separately
+    Keep,
+}
+
+/// Panics unless the width is 1 to 16 bits, as every symbol width must be
+pub(crate) fn check_width(width: usize) {
+    if !(1..=16).contains(&width) {
+        panic!("BitVec: symbol width must be 1 to 16 bits")
+    }
+}
+
+impl BitVec {
+    /// Splits the vector into symbols of `width` bits, returning them with
+    /// any bits of a final partial symbol kept by `TailPolicy::Keep`, or an
+    /// error if `TailPolicy::Reject` finds one. Panics if the width is not 1
+    /// to 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::{BitVec, TailPolicy};
+    ///
+    /// let bv = BitVec::from_bit_str("10110 01101 011").unwrap();
+    /// let (symbols, tail) = bv.to_symbols(5, TailPolicy::Pad).unwrap();
+    /// assert_eq!(symbols, vec![22, 13, 12]);
+    /// assert_eq!(tail.len_bits(), 0);
+    ///
+    /// let (symbols, tail) = bv.to_symbols(5, TailPolicy::Keep).unwrap();
+    /// assert_eq!(symbols, vec![22, 13]);
+    /// assert_eq!(tail.to_bit_string(), "011");
+    /// assert!(bv.to_symbols(5, TailPolicy::Reject).is_err());
+    /// ```
+    pub fn to_symbols(&self, width: usize, tail: TailPolicy) -> Result<(Vec<u16>, BitVec), BitVecError> {
+        check_width(width);
+        let whole = self.len / width * width;
+        let rest = self.slice(whole..);
+        if !rest.is_empty() && tail == TailPolicy::Reject {
+            return Err(BitVecError::NotEnoughBits { requested: width, available: rest.len() });
+        }
+
+        let mut symbols: Vec<u16> = self.chunks_exact(width).map(|symbol| symbol.to_u64() as u16).collect();
+        match tail {
+            TailPolicy::Pad if !rest.is_empty() => {
+                symbols.push((rest.to_u64() << (width - rest.len())) as u16);
+                Ok((symbols, BitVec::new()))
+            }
+            TailPolicy::Keep => Ok((symbols, rest.to_bitvec())),
+            _ => Ok((symbols, BitVec::new())),
+        }
+    }
+
+    /// Joins symbols of `width` bits into a new BitVec, or returns an error
+    /// if a symbol does not fit in the width. Panics if the width is not 1
+    /// to 16 bits.
+    pub fn from_symbols(width: usize, symbols: &[u16]) -> Result<BitVec, BitVecError> {
+        check_width(width);
+        let mut bv = BitVec::with_capacity(symbols.len() * width);
+        for &symbol in symbols {
+            bv.push_int(symbol, width as u32)?;
+        }
+        Ok(bv)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let bv = BitVec::from(&[0xDE, 0xAD, 0xBE, 0xEF, 0x42]);
+        for width in 1..=16 {
+            let (symbols, tail) = bv.to_symbols(width, TailPolicy::Keep).unwrap();
+            assert_eq!(symbols.len(), 40 / width);
+            let mut joined = BitVec::from_symbols(width, &symbols).unwrap();
+            joined.extend(&tail);
+            assert_eq!(joined, bv, "width {}", width);
+
+            // Padding adds 0s to the end, and no more
+            let (padded, _) = bv.to_symbols(width, TailPolicy::Pad).unwrap();
+            let joined = BitVec::from_symbols(width, &padded).unwrap();
+            assert_eq!(joined.len_bits(), 40usize.div_ceil(width) * width);
+            assert_eq!(joined.slice(..40).to_bitvec(), bv);
+            assert_eq!(joined.count_ones(), bv.count_ones());
+        }
+    }
+
+    #[test]
+    fn tails() {
+        let bv = BitVec::from_bit_str("111 1").unwrap();
+        assert_eq!(bv.to_symbols(3, TailPolicy::Reject), Err(BitVecError::NotEnoughBits { requested: 3, available: 1 }));
+        assert_eq!(bv.to_symbols(3, TailPolicy::Pad).unwrap().0, vec![7, 4]);
+        assert_eq!(bv.to_symbols(2, TailPolicy::Reject).unwrap().0, vec![3, 3]);
+        assert_eq!(BitVec::new().to_symbols(4, TailPolicy::Pad).unwrap(), (vec![], BitVec::new()));
+
+        assert_eq!(BitVec::from_symbols(3, &[7, 8]), Err(BitVecError::ValueTooWide { value: 8, width: 3 }));
+        assert_eq!(BitVec::from_symbols(16, &[0xFFFF]).unwrap(), BitVec::from(&[0xFF, 0xFF]));
+    }
+
+    #[test]
+    #[should_panic(expected = "1 to 16 bits")]
+    fn width_too_wide() {
+        BitVec::new().to_symbols(17, TailPolicy::Pad).unwrap();
+    }
+}
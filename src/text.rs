@@ -0,0 +1,300 @@
+//! ## Bit and hex strings
+//! Parsing and formatting BitVecs as strings of binary or hex digits, such
+//! as `"0b1011_0010"` or `"0xa3f"`. Parsing accepts an optional prefix and
+//! skips `_` and whitespace between digits. Hex digits are 4 bits each,
+//! with any partial final nibble padded with 0s when formatting.
+
+use alloc::string::String;
+use core::{fmt, str::FromStr};
+
+use crate::{BitVec, BitVecError};
+
+/// Strips an optional prefix, such as `0b`, in either case
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> &'a str {
+    match s.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => &s[prefix.len()..],
+        _ => s,
+    }
+}
+
+/// Runs through the digits of a string after an optional prefix, skipping
+/// separators and reporting the first character that is not a valid digit
+fn parse_digits(s: &str, prefix: &str, radix: u32, mut push: impl FnMut(u32)) -> Result<(), BitVecError> {
+    let digits = strip_prefix(s.trim_start(), prefix);
+    let offset = s.len() - digits.len();
+
+    for (position, digit) in digits.char_indices() {
+        let position = offset + position;
+        if digit == '_' || digit.is_whitespace() {
+            continue;
+        }
+        match digit.to_digit(radix) {
+            Some(value) => push(value),
+            None => return Err(BitVecError::InvalidDigit { position, digit }),
+        }
+    }
+
+    Ok(())
+}
+
+impl BitVec {
+    /// Parses a string of binary digits, one bit per digit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("0b1011_0010 1").unwrap();
+    /// assert_eq!(bv.len_bits(), 9);
+    /// assert_eq!(bv.as_binary(), "10110010 10000000");
+    /// ```
+    pub fn from_bit_str(s: &str) -> Result<Self, BitVecError> {
+        let mut bv = BitVec::new();
+        parse_digits(s, "0b", 2, |digit| bv.push_bit(digit == 1))?;
+
+        Ok(bv)
+    }
+
+    /// Parses a string of hex digits, four bits per digit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_hex_str("0xa3f").unwrap();
+    /// assert_eq!(bv.len_bits(), 12);
+    /// assert_eq!(bv.as_binary(), "10100011 11110000");
+    /// ```
+    pub fn from_hex_str(s: &str) -> Result<Self, BitVecError> {
+        let mut bv = BitVec::new();
+        parse_digits(s, "0x", 16, |digit| {
+            for shift in (0..4).rev() {
+                bv.push_bit((digit >> shift) & 1 == 1);
+            }
+        })?;
+
+        Ok(bv)
+    }
+
+    /// Parses a string of hex digits into a vector of `len` bits, for when
+    /// the last digit only holds part of a nibble, as written by
+    /// `to_hex_string`. The bits of the digits past the length are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_hex_str_with_len("a3e", 11).unwrap();
+    /// assert_eq!(bv.to_bit_string(), "10100011111");
+    /// ```
+    pub fn from_hex_str_with_len(s: &str, len: usize) -> Result<Self, BitVecError> {
+        let mut bv = BitVec::from_hex_str(s)?;
+        if len > bv.len {
+            return Err(BitVecError::NotEnoughBits { requested: len, available: bv.len });
+        }
+        bv.truncate_bits(len);
+
+        Ok(bv)
+    }
+
+    /// Formats the vector as a string of binary digits, without a prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from(&[0xA5]);
+    /// assert_eq!(bv.to_bit_string(), "10100101");
+    /// ```
+    pub fn to_bit_string(&self) -> String {
+        (0..self.len)
+            .map(|index| if self.get_bit(index) { '1' } else { '0' })
+            .collect()
+    }
+
+    /// Formats the vector as a string of lower case hex digits, without a
+    /// prefix. A partial final nibble is padded with 0s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1010 0011 111").unwrap();
+    /// assert_eq!(bv.to_hex_string(), "a3e");
+    /// ```
+    pub fn to_hex_string(&self) -> String {
+        self.to_digit_string(4, false)
+    }
+
+    /// Formats the vector with `width` bits per digit, the final digit padded
+    /// with 0s, in lower or upper case
+    fn to_digit_string(&self, width: usize, upper: bool) -> String {
+        (0..self.len.div_ceil(width))
+            .map(|digit| {
+                let start = digit * width;
+                let value = (start..start + width)
+                    .fold(0, |value, index| value << 1 | (index < self.len && self.get_bit(index)) as u32);
+                let digit = char::from_digit(value, 1 << width).unwrap();
+                if upper { digit.to_ascii_uppercase() } else { digit }
+            })
+            .collect()
+    }
+
+    /// Returns an adapter which formats the bits up to the length as 0s and
+    /// 1s, in groups of `group_size` bits separated by spaces. A group size
+    /// of 0 puts all the bits in one group. Width, fill, and alignment flags
+    /// apply to the whole string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bit_str("1010 0011 111").unwrap();
+    /// assert_eq!(bv.display_bits(4).to_string(), "1010 0011 111");
+    /// assert_eq!(format!("[{:>8}]", bv.slice(..6).to_bitvec().display_bits(3)), "[ 101 000]");
+    /// ```
+    pub fn display_bits(&self, group_size: usize) -> DisplayBits<'_> {
+        DisplayBits { bv: self, group_size }
+    }
+}
+
+/// Formats the bits of a BitVec in groups, as returned by `display_bits`
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayBits<'a> {
+    bv:         &'a BitVec,
+    group_size: usize,
+}
+
+impl fmt::Display for DisplayBits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bits = String::with_capacity(self.bv.len * 2);
+        for index in 0..self.bv.len {
+            if index > 0 && self.group_size > 0 && index.is_multiple_of(self.group_size) {
+                bits.push(' ');
+            }
+            bits.push(if self.bv.get_bit(index) { '1' } else { '0' });
+        }
+        f.pad(&bits)
+    }
+}
+
+// Each format gives the digits of the bits up to the length, in order, with
+// the final digit padded with 0s, so `{:x}` matches `to_hex_string`. The
+// alternate forms add a `0b`, `0o` or `0x` prefix.
+
+impl fmt::Binary for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0b", &self.to_digit_string(1, false))
+    }
+}
+
+impl fmt::Octal for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0o", &self.to_digit_string(3, false))
+    }
+}
+
+impl fmt::LowerHex for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_digit_string(4, false))
+    }
+}
+
+impl fmt::UpperHex for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_digit_string(4, true))
+    }
+}
+
+/// Parses binary digits, or hex digits when prefixed with `0x`
+impl FromStr for BitVec {
+    type Err = BitVecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim_start();
+        if strip_prefix(trimmed, "0x").len() < trimmed.len() {
+            BitVec::from_hex_str(s)
+        } else {
+            BitVec::from_bit_str(s)
+        }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    #[test]
+    fn bit_strings() {
+        let bv: BitVec = "10110".parse().unwrap();
+        assert_eq!(bv.len_bits(), 5);
+        assert_eq!(bv.data, vec![0xB0]);
+        assert_eq!(bv.to_bit_string(), "10110");
+        assert_eq!("0B1_0110".parse::<BitVec>().unwrap(), bv);
+        assert_eq!("".parse::<BitVec>().unwrap(), BitVec::new());
+
+        assert_eq!(
+            BitVec::from_bit_str("0b10201"),
+            Err(BitVecError::InvalidDigit { position: 4, digit: '2' })
+        );
+    }
+
+    #[test]
+    fn hex_strings() {
+        let bv: BitVec = "0XA3_f".parse().unwrap();
+        assert_eq!(bv.len_bits(), 12);
+        assert_eq!(bv.to_hex_string(), "a3f");
+        assert_eq!(BitVec::from_hex_str("a3f").unwrap(), bv);
+        assert!(matches!(BitVec::from_hex_str("a3g"), Err(BitVecError::InvalidDigit { digit: 'g', .. })));
+
+        // Partial nibbles with set padding bits
+        let bv = BitVec { data: vec![0xA3, 0xFF], len: 10, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(bv.to_hex_string(), "a3c");
+        assert_eq!(BitVec::from_hex_str_with_len("a3c", 10).unwrap(), bv);
+        assert_eq!(
+            BitVec::from_hex_str_with_len("a3c", 13),
+            Err(BitVecError::NotEnoughBits { requested: 13, available: 12 })
+        );
+    }
+
+    #[test]
+    fn fmt_traits() {
+        let bv = BitVec::from_bit_str("1010 0011 111").unwrap();
+        assert_eq!(format!("{:b}", bv), "10100011111");
+        assert_eq!(format!("{:o}", bv), "5076");
+        assert_eq!(format!("{:x}", bv), bv.to_hex_string());
+        assert_eq!(format!("{:X}", bv), "A3E");
+        assert_eq!(format!("{:#x}", bv), "0xa3e");
+        assert_eq!(format!("{:#08X}", bv), "0x000A3E");
+        assert_eq!(format!("{:>5x}|", bv), "  a3e|");
+        assert_eq!(format!("{:x}", BitVec::new()), "");
+    }
+
+    #[test]
+    fn grouped_bits() {
+        // Set padding bits are never shown
+        let bv = BitVec { data: vec![0xA3, 0xFF], len: 10, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        assert_eq!(format!("{}", bv.display_bits(8)), "10100011 11");
+        assert_eq!(format!("{}", bv.display_bits(0)), "1010001111");
+        assert_eq!(format!("{}", bv.display_bits(10)), "1010001111");
+        assert_eq!(format!("{:_<13}|", bv.display_bits(4)), "1010 0011 11_|");
+        assert_eq!(format!("{:^4}", BitVec::new().display_bits(4)), "    ");
+    }
+
+    #[test]
+    fn from_string_counts_bits() {
+        let bv = BitVec::from_string(&"AB".to_string());
+        assert_eq!(bv.len_bits(), 16);
+        assert_eq!(bv.to_hex_string(), "4142");
+    }
+}
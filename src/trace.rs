@@ -0,0 +1,188 @@
+//! ## Read traces
+//! Golden-file testing for codecs. A recording reader wraps the sequential
+//! reading position of a BitVec and notes the offset, width and value of
+//! every read, giving a trace that can be saved from a known good run. After
+//! a refactor, the same stream is decoded again and its trace verified
+//! against the golden one, read for read, pointing at the first read that
+//! differs.
+
+use alloc::vec::Vec;
+use core::{error, fmt};
+
+use crate::BitVec;
+
+/// One read: where it started, how many bits it took, and what it gave
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TracedRead {
+    pub offset: usize,
+    pub width:  u32,
+    pub value:  u64,
+}
+
+/// The first read where a trace differs from a golden one. A read missing
+/// from either trace is None.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceMismatch {
+    pub index:    usize,
+    pub expected: Option<TracedRead>,
+    pub found:    Option<TracedRead>,
+}
+
+impl fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "read {} differs: expected {:?}, found {:?}", self.index, self.expected, self.found)
+    }
+}
+
+impl error::Error for TraceMismatch {}
+
+/// A reader over a BitVec that records every read it makes
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, TracedRead};
+///
+/// let mut bv = BitVec::from_bit_str("101 0001001").unwrap();
+/// let mut reader = bv.recording_reader();
+/// assert_eq!(reader.read_bits(3), Some(5));
+/// assert_eq!(reader.read_with(|bv| bv.read_gamma()), Some(9));
+/// let golden = reader.into_trace();
+/// assert_eq!(golden[1], TracedRead { offset: 3, width: 7, value: 9 });
+///
+/// // Reading the header in two parts differs from the first read
+/// bv.set_read_position(0);
+/// let mut reader = bv.recording_reader();
+/// reader.read_bit();
+/// reader.read_bits(2);
+/// reader.read_with(|bv| bv.read_gamma());
+/// assert_eq!(reader.verify_against_trace(&golden).unwrap_err().index, 0);
+/// ```
+#[derive(Debug)]
+pub struct RecordingReader<'a> {
+    bv:    &'a mut BitVec,
+    trace: Vec<TracedRead>,
+}
+
+impl RecordingReader<'_> {
+    /// Runs a read, recording it if it succeeds
+    fn record(&mut self, value: Option<u64>, offset: usize) -> Option<u64> {
+        let value = value?;
+        let width = (self.bv.get_read_position() - offset) as u32;
+        self.trace.push(TracedRead { offset, width, value });
+        Some(value)
+    }
+
+    /// Sequentially reads the next bit, or None at the end
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let offset = self.bv.get_read_position();
+        let bit = self.bv.read_bit_seq().map(u64::from);
+        self.record(bit, offset).map(|bit| bit == 1)
+    }
+
+    /// Sequentially reads the next `count` bits (up to 64), MSB first.
+    /// Returns None, without moving the reading position, if there are not
+    /// enough bits left.
+    pub fn read_bits(&mut self, count: u32) -> Option<u64> {
+        let offset = self.bv.get_read_position();
+        let value = self.bv.read_int::<u64>(count);
+        self.record(value, offset)
+    }
+
+    /// Runs any read from the reading position, such as a gamma or Rice
+    /// code, recording the bits it moved past as its width. A read that
+    /// fails is not recorded.
+    pub fn read_with(&mut self, read: impl FnOnce(&mut BitVec) -> Option<u64>) -> Option<u64> {
+        let offset = self.bv.get_read_position();
+        let value = read(self.bv);
+        self.record(value, offset)
+    }
+
+    /// Returns the reads so far
+    pub fn trace(&self) -> &[TracedRead] {
+        &self.trace
+    }
+
+    /// Consumes the reader, returning the reads it made
+    pub fn into_trace(self) -> Vec<TracedRead> {
+        self.trace
+    }
+
+    /// Checks the reads so far against a golden trace, read for read, or
+    /// returns the first that differs
+    pub fn verify_against_trace(&self, golden: &[TracedRead]) -> Result<(), TraceMismatch> {
+        for index in 0..self.trace.len().max(golden.len()) {
+            let expected = golden.get(index).copied();
+            let found = self.trace.get(index).copied();
+            if expected != found {
+                return Err(TraceMismatch { index, expected, found });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitVec {
+    /// Returns a reader recording every read from the reading position
+    pub fn recording_reader(&mut self) -> RecordingReader<'_> {
+        RecordingReader { bv: self, trace: Vec::new() }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a header of a 4-bit count, then that many Rice codes
+    fn decode(reader: &mut RecordingReader, k: u32) {
+        let count = reader.read_bits(4).unwrap();
+        for _ in 0..count {
+            reader.read_with(|bv| bv.read_rice(k));
+        }
+    }
+
+    #[test]
+    fn golden_run() {
+        let mut bv = BitVec::new();
+        bv.push_int(3u8, 4).unwrap();
+        for value in [5, 17, 2] {
+            bv.write_rice(2, value);
+        }
+        let mut reader = bv.recording_reader();
+        decode(&mut reader, 2);
+        let golden = reader.into_trace();
+        assert_eq!(golden.len(), 4);
+        assert_eq!(golden[2], TracedRead { offset: 8, width: 7, value: 17 });
+
+        bv.set_read_position(0);
+        let mut reader = bv.recording_reader();
+        decode(&mut reader, 2);
+        assert_eq!(reader.verify_against_trace(&golden), Ok(()));
+
+        // A wrong parameter reads the same header, then goes astray
+        bv.set_read_position(0);
+        let mut reader = bv.recording_reader();
+        decode(&mut reader, 1);
+        let mismatch = reader.verify_against_trace(&golden).unwrap_err();
+        assert_eq!((mismatch.index, mismatch.expected), (1, Some(golden[1])));
+        assert_eq!(mismatch.found.unwrap().offset, 4);
+    }
+
+    #[test]
+    fn missing_and_failed_reads() {
+        let mut bv = BitVec::from_bit_str("1011").unwrap();
+        let mut reader = bv.recording_reader();
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bits(8), None);
+        assert_eq!(reader.trace().len(), 1);
+        let golden = [TracedRead { offset: 0, width: 1, value: 1 }, TracedRead { offset: 1, width: 3, value: 3 }];
+        let mismatch = reader.verify_against_trace(&golden).unwrap_err();
+        assert_eq!((mismatch.index, mismatch.found), (1, None));
+        assert_eq!(reader.read_bits(3), Some(3));
+        assert_eq!(reader.verify_against_trace(&golden), Ok(()));
+        assert!(reader.verify_against_trace(&golden[..1]).is_err());
+    }
+}
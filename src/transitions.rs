@@ -0,0 +1,129 @@
+//! ## Transition statistics
+//! How often each bit pattern is followed by a 0 or a 1, in the spirit of an
+//! eye diagram, for characterizing captured line data before choosing a
+//! decoder. Line codes and inter-symbol interference show up as contexts
+//! whose next bit is far from a coin toss.
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+use crate::BitVec;
+
+/// Counts of the bit following each pattern of `window` bits
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::BitVec;
+///
+/// // Manchester coded data never has three equal bits in a row
+/// let bv = BitVec::from_bit_str("1001 1010 0101 1001").unwrap();
+/// let matrix = bv.transition_matrix(2);
+/// assert_eq!(matrix.count(0b00, true), 3);
+/// assert_eq!(matrix.count(0b00, false), 0);
+/// assert_eq!(matrix.probability_one(0b11), Some(0.0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitionMatrix {
+    window: usize,
+    counts: Vec<[usize; 2]>,    // counts of the next bit, by context
+}
+
+impl TransitionMatrix {
+    /// Get the number of bits in each context
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Get the number of times a context, as a number MSB first, was
+    /// followed by a bit. Panics if the context is wider than the window.
+    pub fn count(&self, context: usize, next: bool) -> usize {
+        self.counts[context][next as usize]
+    }
+
+    /// Get the number of times a context was seen with a bit after it.
+    /// Panics if the context is wider than the window.
+    pub fn total(&self, context: usize) -> usize {
+        self.counts[context][0] + self.counts[context][1]
+    }
+
+    /// Returns the fraction of the times a context was followed by a 1, or
+    /// None if it was never seen. Panics if the context is wider than the
+    /// window.
+    pub fn probability_one(&self, context: usize) -> Option<f64> {
+        let total = self.total(context);
+        (total > 0).then(|| self.counts[context][1] as f64 / total as f64)
+    }
+
+    /// Exports the counts as CSV, with a header row and one row for each
+    /// context, written as bits. The probability of a 1 is left empty for
+    /// contexts that were never seen.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("context,next_0,next_1,p_1\n");
+        for (context, &[zeros, ones]) in self.counts.iter().enumerate() {
+            let _ = write!(csv, "{:0width$b},{},{},", context, zeros, ones, width = self.window);
+            if let Some(p) = self.probability_one(context) {
+                let _ = write!(csv, "{}", p);
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl BitVec {
+    /// Counts the bit following every pattern of `window` bits, sliding
+    /// along one bit at a time. Panics if the window is not 1 to 16 bits.
+    pub fn transition_matrix(&self, window: usize) -> TransitionMatrix {
+        if !(1..=16).contains(&window) {
+            panic!("BitVec: transition window must be 1 to 16 bits")
+        }
+
+        let mask = (1 << window) - 1;
+        let mut counts = vec![[0; 2]; 1 << window];
+        let mut context = 0;
+        for index in 0..self.len {
+            let bit = self.get_bit(index) as usize;
+            if index >= window {
+                counts[context][bit] += 1;
+            }
+            context = (context << 1 | bit) & mask;
+        }
+        TransitionMatrix { window, counts }
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_every_context() {
+        // A repeating 0011 pattern is fully determined by two bits
+        let bv = BitVec::repeat(&BitVec::from_bit_str("0011").unwrap(), 50);
+        let matrix = bv.transition_matrix(2);
+        assert_eq!(matrix.window(), 2);
+        assert_eq!((0..4).map(|context| matrix.total(context)).sum::<usize>(), 198);
+        assert_eq!(matrix.probability_one(0b00), Some(1.0));
+        assert_eq!(matrix.probability_one(0b01), Some(1.0));
+        assert_eq!(matrix.probability_one(0b11), Some(0.0));
+        assert_eq!(matrix.probability_one(0b10), Some(0.0));
+
+        // One bit of context can't tell which half of the pattern it is in
+        assert_eq!(bv.transition_matrix(1).probability_one(0), Some(0.5));
+
+        // Too short for any context
+        let short = BitVec::from_bit_str("101").unwrap().transition_matrix(3);
+        assert_eq!(short.probability_one(0b101), None);
+    }
+
+    #[test]
+    fn csv_export() {
+        let bv = BitVec::from_bit_str("0001 1111").unwrap();
+        let csv = bv.transition_matrix(2).to_csv();
+        assert_eq!(csv, "context,next_0,next_1,p_1\n00,1,1,0.5\n01,0,1,1\n10,0,0,\n11,0,3,1\n");
+    }
+}
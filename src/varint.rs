@@ -0,0 +1,142 @@
+//! ## Varints
+//! LEB128 variable-length integers, as used by protobuf, written byte by
+//! byte at the current bit position, so they need not be byte aligned. Each
+//! byte holds 7 bits of the value, least significant group first, with the
+//! top bit set on every byte but the last. Signed values are zigzag encoded
+//! first, so small negative numbers stay short.
+
+use crate::BitVec;
+
+/// Longest encoding of a u64, in bytes
+const MAX_VARINT_BYTES: usize = 10;
+
+impl BitVec {
+    /// Pushes an unsigned LEB128 varint
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_varint(300);
+    /// assert_eq!(bv.as_binary(), "10101100 00000010");
+    /// ```
+    pub fn push_varint(&mut self, value: u64) {
+        let mut value = value;
+        while value >= 0x80 {
+            self.push_byte((value as u8 & 0x7F) | 0x80);
+            value >>= 7;
+        }
+        self.push_byte(value as u8);
+    }
+
+    /// Reads an unsigned LEB128 varint from the current reading position.
+    /// Returns None, without moving the reading position, if the varint is
+    /// truncated, longer than 10 bytes, or too large for a u64.
+    pub fn read_varint(&mut self) -> Option<u64> {
+        let start = self.get_read_position();
+        let mut value = 0;
+
+        for index in 0..MAX_VARINT_BYTES {
+            let Some(byte) = self.read_byte() else {
+                break;
+            };
+            // The 10th byte holds only the top bit of a u64
+            if index == MAX_VARINT_BYTES - 1 && byte > 1 {
+                break;
+            }
+            value |= ((byte & 0x7F) as u64) << (7 * index);
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        self.set_read_position(start);
+        None
+    }
+
+    /// Pushes a signed varint, zigzag encoded so that 0, -1, 1, -2, ...
+    /// become 0, 1, 2, 3, ...
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// bv.push_zigzag(-2);
+    /// assert_eq!(bv.as_binary(), "00000011");
+    /// assert_eq!(bv.read_zigzag(), Some(-2));
+    /// ```
+    pub fn push_zigzag(&mut self, value: i64) {
+        self.push_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    /// Reads a zigzag encoded signed varint from the current reading
+    /// position. Returns None, without moving the reading position, if the
+    /// varint is truncated or too long.
+    pub fn read_zigzag(&mut self) -> Option<i64> {
+        let value = self.read_varint()?;
+        Some((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trip() {
+        let mut bv = BitVec::new();
+        // Start off byte alignment
+        bv.push_bit(true);
+        let values = [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX];
+        for &value in &values {
+            bv.push_varint(value);
+        }
+        assert_eq!(bv.len_bits(), 1 + 8 * (1 + 1 + 1 + 2 + 2 + 5 + 10));
+
+        bv.read_bit_seq();
+        for &value in &values {
+            assert_eq!(bv.read_varint(), Some(value));
+        }
+        assert_eq!(bv.read_varint(), None);
+    }
+
+    #[test]
+    fn zigzag_round_trip() {
+        let mut bv = BitVec::new();
+        let values = [0, -1, 1, -64, 64, i64::MIN, i64::MAX];
+        for &value in &values {
+            bv.push_zigzag(value);
+        }
+        // -64 still fits in one byte
+        assert_eq!(bv.data[3], 0x7F);
+        for &value in &values {
+            assert_eq!(bv.read_zigzag(), Some(value));
+        }
+    }
+
+    #[test]
+    fn truncated_varint() {
+        let mut bv = BitVec::from(&[0x05, 0xAC]);
+        assert_eq!(bv.read_varint(), Some(5));
+        assert_eq!(bv.read_varint(), None);
+        assert_eq!(bv.get_read_position(), 8);
+
+        let mut overlong = BitVec::from(&[0x80; 11]);
+        assert_eq!(overlong.read_varint(), None);
+        assert_eq!(overlong.get_read_position(), 0);
+
+        // A 10th byte of 2 would set bit 64
+        let mut bytes = [0xFF; 10];
+        bytes[9] = 0x02;
+        let mut too_large = BitVec::from(&bytes);
+        assert_eq!(too_large.read_varint(), None);
+        assert_eq!(too_large.get_read_position(), 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! ## Variable-length code tables
+//! Maps symbols to prefix-free bit codes, such as Huffman, varicode, or
+//! hand-built Morse-style tables, and encodes and decodes them over a
+//! BitVec. Decoding walks a binary trie built from the codes, reading from
+//! the sequential reading position.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::{error, fmt};
+
+use crate::{BitVec, BitVecError};
+
+/// Reasons a set of codes cannot form a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarLenCodeError {
+    /// The code for the entry at this index has no bits
+    EmptyCode(usize),
+    /// The symbol for the entry at this index appears more than once
+    DuplicateSymbol(usize),
+    /// The code of the first entry is a prefix of (or equal to) the code
+    /// of the second
+    PrefixConflict(usize, usize),
+}
+
+impl fmt::Display for VarLenCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyCode(index) => write!(f, "code {} is empty", index),
+            Self::DuplicateSymbol(index) => write!(f, "symbol {} is duplicated", index),
+            Self::PrefixConflict(prefix, code) => write!(f, "code {} is a prefix of code {}", prefix, code),
+        }
+    }
+}
+
+impl error::Error for VarLenCodeError {}
+
+/// A node of the decoding trie. Index 0 is the root, so it doubles as "no
+/// child".
+#[derive(Default)]
+struct Node {
+    children: [usize; 2],
+    symbol:   Option<usize>,
+}
+
+/// A table of prefix-free variable-length codes
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::{BitVec, VarLenCodeTable};
+///
+/// let mut code_a = BitVec::new();
+/// code_a.push_bit(false);
+/// let mut code_b = BitVec::new();
+/// code_b.push_bit(true);
+/// code_b.push_bit(false);
+///
+/// let table = VarLenCodeTable::new([('a', code_a), ('b', code_b)]).unwrap();
+/// let mut encoded = table.encode(&['b', 'a', 'b']).unwrap();
+/// assert_eq!(encoded.len_bits(), 5);
+/// assert_eq!(table.decode_all(&mut encoded), Some(vec!['b', 'a', 'b']));
+/// ```
+pub struct VarLenCodeTable<S> {
+    symbols: Vec<S>,
+    codes:   Vec<BitVec>,
+    lookup:  HashMap<S, usize>,
+    trie:    Vec<Node>,
+}
+
+impl<S: Clone + Eq + Hash> VarLenCodeTable<S> {
+    /// Builds a table from symbol and code pairs, checking that every code
+    /// is non-empty, every symbol is unique, and no code is a prefix of
+    /// another
+    pub fn new<I: IntoIterator<Item = (S, BitVec)>>(entries: I) -> Result<Self, VarLenCodeError> {
+        let mut table = Self {
+            symbols: Vec::new(),
+            codes: Vec::new(),
+            lookup: HashMap::new(),
+            trie: vec![Node::default()],
+        };
+
+        for (index, (symbol, code)) in entries.into_iter().enumerate() {
+            if code.len_bits() == 0 {
+                return Err(VarLenCodeError::EmptyCode(index));
+            }
+            if table.lookup.insert(symbol.clone(), index).is_some() {
+                return Err(VarLenCodeError::DuplicateSymbol(index));
+            }
+            table.insert_code(index, &code)?;
+            table.symbols.push(symbol);
+            table.codes.push(code);
+        }
+
+        Ok(table)
+    }
+
+    /// Adds a code to the trie, rejecting any prefix conflict
+    fn insert_code(&mut self, index: usize, code: &BitVec) -> Result<(), VarLenCodeError> {
+        let mut node = 0;
+        for bit_idx in 0..code.len_bits() {
+            if let Some(existing) = self.trie[node].symbol {
+                return Err(VarLenCodeError::PrefixConflict(existing, index));
+            }
+
+            let branch = code.get_bit(bit_idx) as usize;
+            if self.trie[node].children[branch] == 0 {
+                self.trie.push(Node::default());
+                self.trie[node].children[branch] = self.trie.len() - 1;
+            }
+            node = self.trie[node].children[branch];
+        }
+
+        if let Some(existing) = self.trie[node].symbol {
+            return Err(VarLenCodeError::PrefixConflict(existing, index));
+        }
+        if let Some(existing) = self.first_symbol_below(node) {
+            return Err(VarLenCodeError::PrefixConflict(index, existing));
+        }
+
+        self.trie[node].symbol = Some(index);
+        Ok(())
+    }
+
+    /// Finds any symbol in the subtree below a node
+    fn first_symbol_below(&self, node: usize) -> Option<usize> {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if let Some(symbol) = self.trie[node].symbol {
+                return Some(symbol);
+            }
+            stack.extend(self.trie[node].children.iter().filter(|&&child| child != 0));
+        }
+        None
+    }
+
+    /// Get the number of symbols in the table
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Checks if the table has no symbols
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the code for a symbol
+    pub fn code(&self, symbol: &S) -> Option<&BitVec> {
+        self.lookup.get(symbol).map(|&index| &self.codes[index])
+    }
+
+    /// Pushes the code for a symbol onto a BitVec. Returns false, leaving the
+    /// BitVec untouched, if the symbol is not in the table.
+    pub fn push_symbol(&self, symbol: &S, bv: &mut BitVec) -> bool {
+        match self.code(symbol) {
+            Some(code) => {
+                for bit_idx in 0..code.len_bits() {
+                    bv.push_bit(code.get_bit(bit_idx));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Encodes a sequence of symbols into a new BitVec. Returns None if any
+    /// symbol is not in the table.
+    pub fn encode<'a, I>(&self, symbols: I) -> Option<BitVec>
+    where
+        I: IntoIterator<Item = &'a S>,
+        S: 'a,
+    {
+        let mut bv = BitVec::new();
+        for symbol in symbols {
+            if !self.push_symbol(symbol, &mut bv) {
+                return None;
+            }
+        }
+        Some(bv)
+    }
+
+    /// Decodes one symbol from the reading position of a BitVec. Returns
+    /// None, without moving the reading position, if the bits do not form a
+    /// code before the end of the vector.
+    pub fn decode_symbol(&self, bv: &mut BitVec) -> Option<&S> {
+        self.try_decode_symbol(bv).ok()
+    }
+
+    /// Decodes one symbol as `decode_symbol` does, returning an error with
+    /// the offset where the bits stopped matching a code, or the end of the
+    /// vector
+    pub fn try_decode_symbol(&self, bv: &mut BitVec) -> Result<&S, BitVecError> {
+        let start = bv.get_read_position();
+        let mut node = 0;
+
+        while let Some(branch) = bv.read_bit_seq() {
+            node = self.trie[node].children[branch as usize];
+            if node == 0 {
+                break;
+            }
+            if let Some(index) = self.trie[node].symbol {
+                return Ok(&self.symbols[index]);
+            }
+        }
+
+        let offset = bv.get_read_position();
+        bv.set_read_position(start);
+        Err(BitVecError::Undecodable { offset })
+    }
+
+    /// Decodes symbols from the reading position to the end of a BitVec.
+    /// Returns None if the remaining bits are not a whole number of codes.
+    pub fn decode_all(&self, bv: &mut BitVec) -> Option<Vec<S>> {
+        self.try_decode_all(bv).ok()
+    }
+
+    /// Decodes symbols as `decode_all` does, returning an error with the
+    /// offset where a code could not be decoded
+    pub fn try_decode_all(&self, bv: &mut BitVec) -> Result<Vec<S>, BitVecError> {
+        let mut symbols = Vec::new();
+        while bv.get_read_position() < bv.len_bits() {
+            symbols.push(self.try_decode_symbol(bv)?.clone());
+        }
+        Ok(symbols)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(bits: &str) -> BitVec {
+        let mut bv = BitVec::new();
+        for c in bits.chars() {
+            bv.push_bit(c == '1');
+        }
+        bv
+    }
+
+    #[test]
+    fn prefix_validation() {
+        let table = VarLenCodeTable::new([(1, code("0")), (2, code("01"))]);
+        assert_eq!(table.err(), Some(VarLenCodeError::PrefixConflict(0, 1)));
+        let table = VarLenCodeTable::new([(1, code("01")), (2, code("0"))]);
+        assert_eq!(table.err(), Some(VarLenCodeError::PrefixConflict(1, 0)));
+        let table = VarLenCodeTable::new([(1, code("01")), (2, code("01"))]);
+        assert_eq!(table.err(), Some(VarLenCodeError::PrefixConflict(0, 1)));
+        let table = VarLenCodeTable::new([(1, code("01")), (1, code("1"))]);
+        assert_eq!(table.err(), Some(VarLenCodeError::DuplicateSymbol(1)));
+        let table = VarLenCodeTable::new([(1, code(""))]);
+        assert_eq!(table.err(), Some(VarLenCodeError::EmptyCode(0)));
+    }
+
+    #[test]
+    fn varicode_style_round_trip() {
+        // PSK31 varicode for e, t, a, o with the "00" separator appended
+        let table = VarLenCodeTable::new([
+            ('e', code("1100")),
+            ('t', code("10100")),
+            ('a', code("101100")),
+            ('o', code("11100")),
+        ]).unwrap();
+        assert_eq!(table.len(), 4);
+
+        let mut bv = table.encode(&['t', 'o', 'e', 'a']).unwrap();
+        assert_eq!(bv.len_bits(), 20);
+        assert_eq!(table.decode_all(&mut bv).unwrap(), vec!['t', 'o', 'e', 'a']);
+        assert!(table.encode(&['z']).is_none());
+    }
+
+    #[test]
+    fn decode_failure_keeps_position() {
+        let table = VarLenCodeTable::new([('x', code("00")), ('y', code("01"))]).unwrap();
+        // A lone 1 is not a code, and a trailing 0 is incomplete
+        let mut bv = code("0001100");
+        assert_eq!(table.decode_symbol(&mut bv), Some(&'x'));
+        assert_eq!(table.decode_symbol(&mut bv), Some(&'y'));
+        assert_eq!(table.decode_symbol(&mut bv), None);
+        assert_eq!(bv.get_read_position(), 4);
+        assert_eq!(table.try_decode_symbol(&mut bv), Err(BitVecError::Undecodable { offset: 5 }));
+        bv.set_read_position(5);
+        assert_eq!(table.decode_symbol(&mut bv), Some(&'x'));
+        assert_eq!(table.decode_all(&mut code("010")), None);
+        assert_eq!(table.try_decode_all(&mut code("010")), Err(BitVecError::Undecodable { offset: 3 }));
+    }
+}
@@ -0,0 +1,254 @@
+//! ## Value change dumps
+//! Import and export of single-bit signals in the Value Change Dump (VCD)
+//! format produced by logic analysers and HDL simulators. A signal is held
+//! as one bit per timescale tick, starting at time 0.
+
+use std::io::{self, BufRead, Write};
+
+use crate::BitVec;
+
+/// Parser state for the VCD sections we care about
+enum Section {
+    Header,
+    Data,
+}
+
+impl BitVec {
+    /// Extracts a single-bit signal from a VCD capture. The signal may be
+    /// given by its identifier code (e.g. `!`) or by its reference name (e.g.
+    /// `clk`). Each bit holds the signal value at one timescale tick, from
+    /// time 0 up to and including the last timestamp in the dump. Unknown
+    /// (`x`) and high impedance (`z`) values are read as 0, as are the ticks
+    /// before the first timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// let vcd = "$var wire 1 ! clk $end $enddefinitions $end #0 0! #2 1! #3";
+    /// let bv = BitVec::from_vcd_signal(vcd.as_bytes(), "clk").unwrap();
+    /// assert_eq!(bv.len_bits(), 4);
+    /// ```
+    pub fn from_vcd_signal<R: BufRead>(reader: R, signal_id: &str) -> io::Result<Self> {
+        let mut bv = BitVec::new();
+        let mut section = Section::Header;
+        let mut code: Option<String> = None;
+        let mut value = false;
+        let mut last_time: Option<usize> = None;
+
+        // Tokens may span lines, so keep the ones belonging to an unfinished
+        // `$keyword ... $end` block until the block is complete
+        let mut block: Vec<String> = Vec::new();
+        let mut in_block = false;
+        // Vector and real changes name their signal in the following token
+        let mut pending_vector = false;
+
+        for line in reader.lines() {
+            let line = line?;
+            for token in line.split_whitespace() {
+                if in_block {
+                    if token == "$end" {
+                        in_block = false;
+                        match block.first().map(String::as_str) {
+                            // $var type size code reference [index] $end
+                            Some("$var") if block.len() >= 5 && (block[3] == signal_id || block[4] == signal_id) => {
+                                if block[2] != "1" {
+                                    return Err(io::Error::new(
+                                        io::ErrorKind::InvalidData,
+                                        format!("VCD signal {} is {} bits wide", signal_id, block[2]),
+                                    ));
+                                }
+                                code = Some(block[3].clone());
+                            }
+                            Some("$enddefinitions") => section = Section::Data,
+                            _ => (),
+                        }
+                    } else {
+                        block.push(token.to_string());
+                    }
+                    continue;
+                }
+
+                match section {
+                    Section::Header => {
+                        if token.starts_with('$') {
+                            in_block = true;
+                            block.clear();
+                            block.push(token.to_string());
+                        }
+                    }
+                    Section::Data => {
+                        if pending_vector {
+                            pending_vector = false;
+                            continue;
+                        }
+                        match token.as_bytes()[0] {
+                            b'$' => {
+                                // Dump sections hold ordinary value changes,
+                                // so only comments need skipping
+                                if token == "$comment" {
+                                    in_block = true;
+                                    block.clear();
+                                    block.push(token.to_string());
+                                }
+                            }
+                            b'#' => {
+                                let time = token[1..].parse::<usize>().map_err(|_| {
+                                    io::Error::new(io::ErrorKind::InvalidData, format!("invalid VCD timestamp {}", token))
+                                })?;
+                                match last_time {
+                                    Some(last) => {
+                                        while bv.len_bits() < last.max(time) {
+                                            bv.push_bit(value);
+                                        }
+                                    }
+                                    // Nothing is known before the first timestamp
+                                    None => bv.resize(time, false),
+                                }
+                                last_time = Some(time);
+                            }
+                            b'0' | b'1' | b'x' | b'X' | b'z' | b'Z' => {
+                                if code.as_deref() == Some(&token[1..]) {
+                                    value = token.as_bytes()[0] == b'1';
+                                }
+                            }
+                            b'b' | b'B' | b'r' | b'R' => pending_vector = true,
+                            _ => {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    format!("unexpected VCD token {}", token),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if code.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("VCD signal {} not found", signal_id),
+            ));
+        }
+        if let Some(last) = last_time {
+            while bv.len_bits() <= last {
+                bv.push_bit(value);
+            }
+        }
+
+        Ok(bv)
+    }
+
+    /// Writes the BitVec as a single-bit signal in VCD format, one bit per
+    /// timescale tick (e.g. `"1ns"`). Only value changes are recorded, and a
+    /// closing timestamp marks the final bit so that the exact length is
+    /// restored by `from_vcd_signal`.
+    pub fn write_vcd_signal<W: Write>(&self, mut writer: W, signal_name: &str, timescale: &str) -> io::Result<()> {
+        writeln!(writer, "$timescale {} $end", timescale)?;
+        writeln!(writer, "$scope module bitvecs $end")?;
+        writeln!(writer, "$var wire 1 ! {} $end", signal_name)?;
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        let mut value = self.get_bit(0);
+        writeln!(writer, "#0")?;
+        writeln!(writer, "$dumpvars")?;
+        writeln!(writer, "{}!", value as u8)?;
+        writeln!(writer, "$end")?;
+
+        let mut last_time = 0;
+        for time in 1..self.len {
+            let bit = self.get_bit(time);
+            if bit != value {
+                value = bit;
+                last_time = time;
+                writeln!(writer, "#{}", time)?;
+                writeln!(writer, "{}!", value as u8)?;
+            }
+        }
+        if last_time != self.len - 1 {
+            writeln!(writer, "#{}", self.len - 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitOrder, GrowthPolicy};
+
+    const CAPTURE: &str = "\
+$date today $end
+$timescale 1us $end
+$scope module top $end
+$var wire 1 ! clk $end
+$var wire 4 \" bus [3:0] $end
+$var wire 1 # data $end
+$upscope $end
+$enddefinitions $end
+$comment initial values $end
+#0
+$dumpvars
+0!
+b0000 \"
+1#
+$end
+#3
+1!
+b1010 \"
+#5
+0#
+#6
+0!
+#9
+";
+
+    #[test]
+    fn import_signal_by_name_and_code() {
+        let clk = BitVec::from_vcd_signal(CAPTURE.as_bytes(), "clk").unwrap();
+        assert_eq!(clk.len_bits(), 10);
+        // 0001110000
+        assert_eq!(clk.data, vec![0x1C, 0x00]);
+
+        let data = BitVec::from_vcd_signal(CAPTURE.as_bytes(), "#").unwrap();
+        // 1111100000
+        assert_eq!(data.data, vec![0xF8, 0x00]);
+    }
+
+    #[test]
+    fn ticks_before_the_first_timestamp() {
+        let vcd = "$var wire 1 ! clk $end $enddefinitions $end #5 1! #7 0! #8";
+        let clk = BitVec::from_vcd_signal(vcd.as_bytes(), "clk").unwrap();
+        assert_eq!(clk.to_bit_string(), "000001100");
+    }
+
+    #[test]
+    fn reject_missing_or_wide_signals() {
+        let missing = BitVec::from_vcd_signal(CAPTURE.as_bytes(), "reset");
+        assert!(matches!(missing, Err(e) if e.kind() == io::ErrorKind::NotFound));
+        let wide = BitVec::from_vcd_signal(CAPTURE.as_bytes(), "bus");
+        assert!(matches!(wide, Err(e) if e.kind() == io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let bv = BitVec { data: vec![0x6B, 0xA0], len: 13, byte_idx: 0, bit_idx: 0, order: BitOrder::Msb0, growth: GrowthPolicy::Grow };
+        let mut out = Vec::new();
+        bv.write_vcd_signal(&mut out, "sig", "1ns").unwrap();
+
+        let back = BitVec::from_vcd_signal(out.as_slice(), "sig").unwrap();
+        assert_eq!(back.len_bits(), 13);
+        assert_eq!(back.data, bv.data);
+    }
+}
@@ -0,0 +1,187 @@
+//! ## Wavelet matrix
+//! A sequence of small symbols, such as bytes or 16-bit codes, held as one
+//! bit vector per bit of the symbols, answering what symbol is at an index
+//! and how often and where a symbol occurs without storing the sequence.
+//! Level `l` holds bit `l`, from the most significant, of every symbol,
+//! after a stable sort by the bits above it, so each query walks down the
+//! levels with rank, or up them with select, on a `RankSelect` each.
+
+use alloc::vec::Vec;
+
+use crate::{BitVec, RankSelect};
+
+/// A sequence of symbols of up to 16 bits, as a wavelet matrix
+///
+/// # Examples
+///
+/// ```
+/// use bitvecs::WaveletMatrix;
+///
+/// let text = WaveletMatrix::new(*b"abracadabra");
+/// assert_eq!(text.access(3), b'a' as u16);
+/// assert_eq!(text.rank(b'a' as u16, 8), 4);
+/// assert_eq!(text.select(b'r' as u16, 1), Some(9));
+/// ```
+pub struct WaveletMatrix {
+    levels: Vec<RankSelect>,   // most significant bit first
+    zeros:  Vec<usize>,        // clear bits in each level
+    len:    usize,             // symbols in the sequence
+}
+
+impl WaveletMatrix {
+    /// Builds the matrix over a sequence of symbols, with as many levels as
+    /// the widest symbol has bits
+    pub fn new<S: Into<u16>, I: IntoIterator<Item = S>>(symbols: I) -> Self {
+        let mut symbols: Vec<u16> = symbols.into_iter().map(Into::into).collect();
+        let width = symbols.iter().map(|&symbol| 16 - symbol.leading_zeros()).max().unwrap_or(0);
+
+        let mut levels = Vec::with_capacity(width as usize);
+        let mut zeros = Vec::with_capacity(width as usize);
+        for shift in (0..width).rev() {
+            let mut bits = BitVec::with_capacity(symbols.len());
+            for &symbol in &symbols {
+                bits.push_bit(symbol >> shift & 1 == 1);
+            }
+            // The symbols with the bit clear go first, in order
+            let (mut low, high): (Vec<u16>, Vec<u16>) = symbols.iter().partition(|&&symbol| symbol >> shift & 1 == 0);
+            zeros.push(low.len());
+            low.extend(high);
+            symbols = low;
+            levels.push(RankSelect::new(bits));
+        }
+
+        Self { levels, zeros, len: symbols.len() }
+    }
+
+    /// Get the number of symbols
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the sequence holds no symbols
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of levels, the bits in the widest symbol
+    pub fn width(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Checks if a symbol fits in the levels
+    fn fits(&self, symbol: u16) -> bool {
+        (symbol as u32) >> self.levels.len() == 0
+    }
+
+    /// Returns the symbol at an index. Panics if the index is out of bounds.
+    pub fn access(&self, index: usize) -> u16 {
+        if index >= self.len {
+            panic!("BitVec: index out of bounds")
+        }
+
+        let mut index = index;
+        let mut symbol = 0;
+        for (level, &zeros) in self.levels.iter().zip(&self.zeros) {
+            let bit = level.bits().get_bit(index);
+            symbol = symbol << 1 | bit as u16;
+            index = if bit { zeros + level.rank1(index) } else { level.rank0(index) };
+        }
+        symbol
+    }
+
+    /// Follows the range `start..end` down the levels along a symbol's bits,
+    /// to where the symbol's occurrences in it sit at the bottom
+    fn descend(&self, symbol: u16, mut start: usize, mut end: usize) -> (usize, usize) {
+        for (depth, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate() {
+            if symbol >> (self.levels.len() - 1 - depth) & 1 == 1 {
+                (start, end) = (zeros + level.rank1(start), zeros + level.rank1(end));
+            } else {
+                (start, end) = (level.rank0(start), level.rank0(end));
+            }
+        }
+        (start, end)
+    }
+
+    /// Returns the number of times a symbol occurs before an index. Panics
+    /// if the index is beyond the length of the sequence.
+    pub fn rank(&self, symbol: u16, index: usize) -> usize {
+        if index > self.len {
+            panic!("BitVec: index out of bounds")
+        }
+        if !self.fits(symbol) {
+            return 0;
+        }
+        let (start, end) = self.descend(symbol, 0, index);
+        end - start
+    }
+
+    /// Returns the index of the `k`th occurrence of a symbol, counting from
+    /// 0, or None if it occurs no more than `k` times
+    pub fn select(&self, symbol: u16, k: usize) -> Option<usize> {
+        if !self.fits(symbol) {
+            return None;
+        }
+        let (start, end) = self.descend(symbol, 0, self.len);
+        if k >= end - start {
+            return None;
+        }
+
+        let mut index = start + k;
+        for (depth, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate().rev() {
+            index = if symbol >> (self.levels.len() - 1 - depth) & 1 == 1 {
+                level.select1(index - zeros)?
+            } else {
+                level.select0(index)?
+            };
+        }
+        Some(index)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_sequence() {
+        let symbols: Vec<u16> = (0..500u32).map(|i| ((i * 7919) % 37 + (i % 3) * 1000) as u16).collect();
+        let matrix = WaveletMatrix::new(symbols.iter().copied());
+        assert_eq!((matrix.len(), matrix.width()), (500, 11));
+
+        for (index, &symbol) in symbols.iter().enumerate() {
+            assert_eq!(matrix.access(index), symbol);
+        }
+        for &symbol in &[0, 5, 36, 1005, 2036, 3000] {
+            let positions: Vec<usize> = (0..500).filter(|&index| symbols[index] == symbol).collect();
+            for index in [0, 1, 250, 499, 500] {
+                assert_eq!(matrix.rank(symbol, index), positions.iter().filter(|&&p| p < index).count());
+            }
+            for (k, &position) in positions.iter().enumerate() {
+                assert_eq!(matrix.select(symbol, k), Some(position));
+            }
+            assert_eq!(matrix.select(symbol, positions.len()), None);
+        }
+    }
+
+    #[test]
+    fn edges() {
+        // Symbols wider than the levels never occur
+        let matrix = WaveletMatrix::new([1u8, 0, 1]);
+        assert_eq!(matrix.width(), 1);
+        assert_eq!(matrix.rank(2, 3), 0);
+        assert_eq!(matrix.select(u16::MAX, 0), None);
+
+        // All zeros need no levels
+        let zeros = WaveletMatrix::new([0u8; 4]);
+        assert_eq!(zeros.width(), 0);
+        assert_eq!((zeros.access(3), zeros.rank(0, 3), zeros.select(0, 3)), (0, 3, Some(3)));
+        assert_eq!(zeros.select(0, 4), None);
+
+        let empty = WaveletMatrix::new(Vec::<u16>::new());
+        assert!(empty.is_empty());
+        assert_eq!(empty.rank(7, 0), 0);
+    }
+}
@@ -0,0 +1,154 @@
+//! ## Zero-run transform
+//! The RUNA/RUNB transform bzip2 runs after move-to-front, over fields of a
+//! fixed width read MSB first. Move-to-front leaves long runs of zero
+//! fields, and each run is written as its length in bijective base 2, least
+//! significant digit first, with RUNA for a digit of 1 and RUNB for 2. Every
+//! field is written one bit wider: RUNA is 0, RUNB is 1, and any other value
+//! is written as itself plus 1. A trailing field shorter than the width is
+//! passed through unchanged.
+
+use crate::symbols::{check_width, TailPolicy};
+use crate::{BitVec, BitVecError};
+
+/// RUNA, a digit of 1
+const RUNA: u64 = 0;
+/// RUNB, a digit of 2
+const RUNB: u64 = 1;
+
+/// Pushes a decoded run of zero fields, or returns an error if its length
+/// in bits would not fit in a usize
+fn push_zeros(out: &mut BitVec, run: u64, width: usize) -> Result<(), BitVecError> {
+    let bits = usize::try_from(run)
+        .ok()
+        .and_then(|run| run.checked_mul(width))
+        .filter(|&bits| out.len_bits().checked_add(bits).is_some())
+        .ok_or(BitVecError::InvalidEncoding)?;
+    out.extend(&BitVec::repeat_bit(false, bits));
+    Ok(())
+}
+
+/// Writes a run of zero fields as RUNA and RUNB digits
+fn push_run(out: &mut BitVec, mut run: u64, width: usize) {
+    while run > 0 {
+        let digit = if run % 2 == 1 { RUNA } else { RUNB };
+        out.push_int(digit, width as u32 + 1).unwrap();
+        run = (run - 1 - digit) / 2;
+    }
+}
+
+impl BitVec {
+    /// Returns the zero-run transform of the fields of `width` bits, each
+    /// written one bit wider. Panics if the width is not 1 to 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitvecs::BitVec;
+    ///
+    /// // Five zero bytes, a run written RUNA RUNB
+    /// let bv = BitVec::from(&[7, 0, 0, 0, 0, 0, 2]);
+    /// let encoded = bv.zero_run_encode(8);
+    /// assert_eq!(encoded.len_bits(), 4 * 9);
+    /// assert_eq!(encoded.zero_run_decode(8), Ok(bv));
+    /// ```
+    pub fn zero_run_encode(&self, width: usize) -> BitVec {
+        let (symbols, tail) = self.to_symbols(width, TailPolicy::Keep).unwrap();
+        let mut out = BitVec::with_capacity(self.len);
+        let mut run = 0;
+        for symbol in symbols {
+            if symbol == 0 {
+                run += 1;
+            } else {
+                push_run(&mut out, run, width);
+                run = 0;
+                out.push_int(symbol as u64 + 1, width as u32 + 1).unwrap();
+            }
+        }
+        push_run(&mut out, run, width);
+        out.extend(&tail);
+        out
+    }
+
+    /// Undoes `zero_run_encode` with the same width, or returns an error if
+    /// a field is too wide to decode, or a run is too long to count
+    pub fn zero_run_decode(&self, width: usize) -> Result<BitVec, BitVecError> {
+        check_width(width);
+        let mut out = BitVec::with_capacity(self.len);
+        let mut run: u64 = 0;
+        let mut place: u32 = 0;
+        // The encoded fields are a bit wider than a symbol can be
+        for symbol in self.chunks_exact(width + 1) {
+            let value = symbol.to_u64();
+            if value <= RUNB {
+                // A digit of 1 or 2 at the current place
+                let digit = (value + 1).checked_shl(place).filter(|_| place < 63);
+                run = digit.and_then(|digit| run.checked_add(digit)).ok_or(BitVecError::InvalidEncoding)?;
+                place += 1;
+                continue;
+            }
+
+            push_zeros(&mut out, run, width)?;
+            run = 0;
+            place = 0;
+            if value > 1 << width {
+                return Err(BitVecError::ValueTooWide { value: value - 1, width: width as u32 });
+            }
+            out.push_int(value - 1, width as u32).unwrap();
+        }
+        push_zeros(&mut out, run, width)?;
+        out.extend(&self.slice(self.len / (width + 1) * (width + 1)..).to_bitvec());
+        Ok(out)
+    }
+}
+
+// ############################################################################
+// Unit tests
+// ############################################################################
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_as_digits() {
+        // Runs of 1 to 4 as RUNA, RUNB, RUNA RUNA, RUNB RUNA
+        let expected = ["00", "01", "0000", "0100"];
+        for (run, digits) in (1..=4).zip(expected) {
+            let mut bv = BitVec::repeat_bit(false, run);
+            bv.push_bit(true);
+            let encoded = bv.zero_run_encode(1);
+            let mut want = digits.to_string();
+            want.push_str("10");
+            assert_eq!(encoded.to_bit_string(), want, "run of {}", run);
+            assert_eq!(encoded.zero_run_decode(1), Ok(bv));
+        }
+    }
+
+    #[test]
+    fn round_trips_after_mtf() {
+        let mut bv = BitVec::from(b"aaaaaaaaaaaaabbbbbbbbbbbbbaaaacccccccccccccccccccccc");
+        bv.push_bit(true);
+        let mtf = bv.mtf_encode(8);
+        let encoded = mtf.zero_run_encode(8);
+        assert!(encoded.len_bits() < mtf.len_bits() / 2);
+        assert_eq!(encoded.zero_run_decode(8).unwrap().mtf_decode(8), bv);
+
+        for width in [1, 3, 5, 12, 16] {
+            let zeros = BitVec::repeat_bit(false, 1000);
+            assert_eq!(zeros.zero_run_encode(width).zero_run_decode(width), Ok(zeros), "width {}", width);
+        }
+        assert_eq!(BitVec::new().zero_run_encode(8).zero_run_decode(8), Ok(BitVec::new()));
+    }
+
+    #[test]
+    fn bad_streams() {
+        // 3 bits for width 2, where 7 decodes to 6
+        let wide = BitVec::from_bit_str("111").unwrap();
+        assert_eq!(wide.zero_run_decode(2), Err(BitVecError::ValueTooWide { value: 6, width: 2 }));
+        let endless = BitVec::from_bit_str(&"01".repeat(70)).unwrap();
+        assert_eq!(endless.zero_run_decode(1), Err(BitVecError::InvalidEncoding));
+
+        // 62 RUNA digits count a run of 2^62 - 1 fields, too many bits for a usize
+        let huge = BitVec::repeat_bit(false, 62 * 17);
+        assert_eq!(huge.zero_run_decode(16), Err(BitVecError::InvalidEncoding));
+    }
+}